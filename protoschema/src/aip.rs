@@ -0,0 +1,443 @@
+use std::sync::Arc;
+
+use bon::Builder;
+use convert_case::{Case, Casing};
+use regex::Regex;
+
+use crate::{
+  api::{FieldBehavior, ResourceDescriptor, ResourceReference},
+  common::{FIELD_BEHAVIOR_PROTO_FILE, RESOURCE_PROTO_FILE, VALIDATE_PROTO_FILE},
+  field_type::ImportedItemPath,
+  fields::{Field, FieldBuilder, SetFieldType, SetName},
+  files::FileBuilder,
+  http::HttpRule,
+  messages::{MessageBuilder, MessageState, SetFields},
+  options::merge_options,
+  services::{ServiceBuilder, ServiceHandler, SetHandlers},
+  validators::{
+    message::build_message_validator_option, numeric::build_int32_validator_option,
+    string::build_string_validator_option,
+  },
+  FieldType, ProtoOption,
+};
+
+// The format for the ids chosen by the clients, described in AIP-122. The id can also be omitted, in which case it is chosen by the server.
+const RESOURCE_ID_PATTERN: &str = "^([a-z]([a-z0-9-]{0,61}[a-z0-9])?)?$";
+
+/// The standard methods described in [AIP-130](https://google.aip.dev/130).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardMethod {
+  Get,
+  List,
+  Create,
+  Update,
+  Delete,
+}
+
+impl StandardMethod {
+  /// All of the standard methods, in the order in which they are added to the service.
+  pub const ALL: [StandardMethod; 5] = [
+    StandardMethod::Get,
+    StandardMethod::List,
+    StandardMethod::Create,
+    StandardMethod::Update,
+    StandardMethod::Delete,
+  ];
+}
+
+/// The configuration for the resource service generated by [`FileBuilder::resource_service`].
+/// Only the name pattern of the resource is required. It can also be converted from a string directly.
+#[derive(Debug, Clone, Builder)]
+#[builder(start_fn = new)]
+pub struct ResourceConfig {
+  /// The pattern for the names of the resource, such as `publishers/{publisher}/books/{book}`.
+  /// It must end with a collection followed by a variable. The segments before those identify the parent of the resource.
+  #[builder(start_fn, into)]
+  pub pattern: Box<str>,
+  /// The type of the resource, such as `library.example.com/Book`.
+  /// Defaults to `<package>/<Message>`.
+  #[builder(into)]
+  pub resource_type: Option<Box<str>>,
+  /// The plural form of the resource's name in lower camel case, such as `books`.
+  /// Defaults to the singular form followed by an `s`.
+  #[builder(into)]
+  pub plural: Option<Box<str>>,
+  /// The name of the generated service.
+  /// Defaults to `<Message>Service`.
+  #[builder(into)]
+  pub service_name: Option<Box<str>>,
+  /// The version prefix used in the paths of the http bindings.
+  #[builder(into, default = "v1")]
+  pub version: Box<str>,
+  /// The maximum value allowed for the `page_size` field of the List request.
+  #[builder(default = 1000)]
+  pub max_page_size: i32,
+  /// The standard methods that should be generated. Defaults to all of them.
+  #[builder(into, default = StandardMethod::ALL)]
+  pub methods: Box<[StandardMethod]>,
+}
+
+impl From<&str> for ResourceConfig {
+  fn from(pattern: &str) -> Self {
+    ResourceConfig::new(pattern).build()
+  }
+}
+
+impl<S: resource_config_builder::IsComplete> From<ResourceConfigBuilder<S>> for ResourceConfig {
+  fn from(value: ResourceConfigBuilder<S>) -> Self {
+    value.build()
+  }
+}
+
+/// The items generated by [`FileBuilder::resource_service`].
+/// The messages are only present if the corresponding standard method was generated.
+pub struct ResourceService {
+  pub service: ServiceBuilder<SetHandlers>,
+  pub get_request: Option<MessageBuilder<SetFields>>,
+  pub list_request: Option<MessageBuilder<SetFields>>,
+  pub list_response: Option<MessageBuilder<SetFields>>,
+  pub create_request: Option<MessageBuilder<SetFields>>,
+  pub update_request: Option<MessageBuilder<SetFields>>,
+  pub delete_request: Option<MessageBuilder<SetFields>>,
+}
+
+type CompleteField = FieldBuilder<SetFieldType<SetName>>;
+
+fn field<T: Into<Arc<str>>>(name: T, field_type: FieldType) -> CompleteField {
+  Field::builder().name(name.into()).field_type(field_type)
+}
+
+// A string field that holds the name of a resource, such as `name` or `parent`
+fn name_field(name: &str, pattern: &str, reference: ProtoOption) -> CompleteField {
+  let regex = Regex::new(pattern).expect("The pattern for a resource name should be valid");
+
+  field(name, FieldType::String)
+    .add_option(FieldBehavior::Required.into())
+    .add_option(reference)
+    .add_option(build_string_validator_option(|v| {
+      v.required().pattern(regex)
+    }))
+    .add_import(FIELD_BEHAVIOR_PROTO_FILE.clone())
+    .add_import(RESOURCE_PROTO_FILE.clone())
+    .add_import(VALIDATE_PROTO_FILE.clone())
+}
+
+fn optional_field(name: &str, field_type: FieldType) -> CompleteField {
+  field(name, field_type)
+    .add_option(FieldBehavior::Optional.into())
+    .add_import(FIELD_BEHAVIOR_PROTO_FILE.clone())
+}
+
+// The id chosen by the client in a Create request
+fn resource_id_field(name: &str) -> CompleteField {
+  let regex = Regex::new(RESOURCE_ID_PATTERN).unwrap();
+
+  optional_field(name, FieldType::String)
+    .add_option(build_string_validator_option(|v| v.pattern(regex)))
+    .add_import(VALIDATE_PROTO_FILE.clone())
+}
+
+fn resource_field<S: MessageState>(name: &str, resource: &MessageBuilder<S>) -> CompleteField {
+  field(name, resource.get_type())
+    .add_option(FieldBehavior::Required.into())
+    .add_option(build_message_validator_option(|v| v.required()))
+    .add_import(resource.get_file())
+    .add_import(FIELD_BEHAVIOR_PROTO_FILE.clone())
+    .add_import(VALIDATE_PROTO_FILE.clone())
+}
+
+// The segments of a resource name pattern, split into the path template of the name, the path template of the parent (if there is one), the regex for the name, the regex for the parent and the collection id
+struct NamePattern {
+  name_template: String,
+  parent_template: Option<String>,
+  name_regex: String,
+  parent_regex: Option<String>,
+  collection: String,
+}
+
+#[track_caller]
+fn parse_pattern(pattern: &str) -> NamePattern {
+  let segments: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+
+  let is_variable = |segment: &str| segment.starts_with('{') && segment.ends_with('}');
+
+  if !segments.len().is_multiple_of(2)
+    || segments
+      .iter()
+      .enumerate()
+      .any(|(i, segment)| segment.is_empty() || is_variable(segment) != (i % 2 == 1))
+  {
+    panic!(
+      "Invalid resource pattern '{pattern}': it must be made of collections followed by variables, such as 'publishers/{{publisher}}/books/{{book}}'"
+    )
+  }
+
+  let template = |segments: &[&str]| -> String {
+    segments
+      .iter()
+      .map(|segment| if is_variable(segment) { "*" } else { segment })
+      .collect::<Vec<&str>>()
+      .join("/")
+  };
+
+  let regex = |segments: &[&str]| -> String {
+    let segments: Vec<String> = segments
+      .iter()
+      .map(|segment| {
+        if is_variable(segment) {
+          "[^/]+".to_string()
+        } else {
+          regex_syntax::escape(segment)
+        }
+      })
+      .collect();
+
+    format!("^{}$", segments.join("/"))
+  };
+
+  let parent = &segments[..segments.len() - 2];
+
+  NamePattern {
+    name_template: template(&segments),
+    parent_template: (!parent.is_empty()).then(|| template(parent)),
+    name_regex: regex(&segments),
+    parent_regex: (!parent.is_empty()).then(|| regex(parent)),
+    collection: segments[segments.len() - 2].to_string(),
+  }
+}
+
+fn empty_message() -> Arc<ImportedItemPath> {
+  ImportedItemPath {
+    full_name: "Empty".into(),
+    full_name_with_package: "google.protobuf.Empty".into(),
+    file: "google/protobuf/empty.proto".into(),
+    package: "google.protobuf".into(),
+  }
+  .into()
+}
+
+#[track_caller]
+pub(crate) fn resource_service<S: MessageState>(
+  file: &FileBuilder,
+  resource: &MessageBuilder<S>,
+  config: ResourceConfig,
+) -> ResourceService {
+  let pattern = parse_pattern(&config.pattern);
+
+  let full_name = resource.get_full_name();
+  let name = full_name
+    .rsplit('.')
+    .next()
+    .unwrap_or(&full_name)
+    .to_string();
+  let singular = name.to_case(Case::Camel);
+  let plural = config
+    .plural
+    .as_deref()
+    .map(str::to_string)
+    .unwrap_or_else(|| format!("{singular}s"));
+  let resource_type = config
+    .resource_type
+    .as_deref()
+    .map(str::to_string)
+    .unwrap_or_else(|| format!("{}/{name}", resource.get_package()));
+
+  let singular_field = singular.to_case(Case::Snake);
+  let plural_field = plural.to_case(Case::Snake);
+  let plural_name = plural.to_case(Case::Pascal);
+  let version = config.version.trim_matches('/');
+
+  let name_path = format!("/{version}/{{name={}}}", pattern.name_template);
+  let collection_path = match &pattern.parent_template {
+    Some(parent) => format!("/{version}/{{parent={parent}}}/{}", pattern.collection),
+    None => format!("/{version}/{}", pattern.collection),
+  };
+
+  {
+    let mut arena = resource.arena.borrow_mut();
+
+    arena.files[resource.file_id].conditionally_add_import(&RESOURCE_PROTO_FILE);
+    merge_options(
+      &mut arena.messages[resource.id].options,
+      [ResourceDescriptor::new(resource_type.as_str())
+        .pattern(config.pattern.as_ref())
+        .plural(plural.as_str())
+        .singular(singular.as_str())
+        .build()
+        .into()],
+    );
+  }
+
+  let parent_field = || {
+    pattern.parent_regex.as_ref().map(|regex| {
+      name_field(
+        "parent",
+        regex,
+        ResourceReference::ChildType(resource_type.as_str().into()).into(),
+      )
+    })
+  };
+
+  let mut handlers: Vec<ServiceHandler> = Vec::new();
+  let mut get_request = None;
+  let mut list_request = None;
+  let mut list_response = None;
+  let mut create_request = None;
+  let mut update_request = None;
+  let mut delete_request = None;
+
+  for method in config.methods.iter() {
+    match method {
+      StandardMethod::Get => {
+        let request = file.new_message(format!("Get{name}Request")).fields([(
+          1,
+          name_field(
+            "name",
+            &pattern.name_regex,
+            ResourceReference::Type(resource_type.as_str().into()).into(),
+          ),
+        )]);
+
+        handlers.push(
+          ServiceHandler::new(format!("Get{name}").into())
+            .request(&request)
+            .response(resource)
+            .options([HttpRule::get(name_path.as_str()).into()])
+            .build(),
+        );
+
+        get_request = Some(request);
+      }
+      StandardMethod::List => {
+        let max_page_size = config.max_page_size;
+        let page_size = optional_field("page_size", FieldType::Int32)
+          .add_option(build_int32_validator_option(|v| {
+            v.gte(0).lte(max_page_size)
+          }))
+          .add_import(VALIDATE_PROTO_FILE.clone());
+
+        let request_fields = parent_field()
+          .into_iter()
+          .chain([page_size, optional_field("page_token", FieldType::String)])
+          .enumerate()
+          .map(|(i, field)| (i as u32 + 1, field));
+
+        let request = file
+          .new_message(format!("List{plural_name}Request"))
+          .fields(request_fields);
+
+        let response = file
+          .new_message(format!("List{plural_name}Response"))
+          .fields([
+            (
+              1,
+              field(plural_field.as_str(), resource.get_type())
+                .repeated()
+                .add_import(resource.get_file()),
+            ),
+            (2, field("next_page_token", FieldType::String)),
+          ]);
+
+        handlers.push(
+          ServiceHandler::new(format!("List{plural_name}").into())
+            .request(&request)
+            .response(&response)
+            .options([HttpRule::get(collection_path.as_str()).into()])
+            .build(),
+        );
+
+        list_request = Some(request);
+        list_response = Some(response);
+      }
+      StandardMethod::Create => {
+        let resource_id = resource_id_field(&format!("{singular_field}_id"));
+
+        let request_fields = parent_field()
+          .into_iter()
+          .chain([resource_field(&singular_field, resource), resource_id])
+          .enumerate()
+          .map(|(i, field)| (i as u32 + 1, field));
+
+        let request = file
+          .new_message(format!("Create{name}Request"))
+          .fields(request_fields);
+
+        handlers.push(
+          ServiceHandler::new(format!("Create{name}").into())
+            .request(&request)
+            .response(resource)
+            .options([HttpRule::post(collection_path.as_str())
+              .body(singular_field.as_str())
+              .into()])
+            .build(),
+        );
+
+        create_request = Some(request);
+      }
+      StandardMethod::Update => {
+        let request = file.new_message(format!("Update{name}Request")).fields([
+          (1, resource_field(&singular_field, resource)),
+          (
+            2,
+            optional_field("update_mask", FieldType::FieldMask)
+              .add_import("google/protobuf/field_mask.proto"),
+          ),
+        ]);
+
+        handlers.push(
+          ServiceHandler::new(format!("Update{name}").into())
+            .request(&request)
+            .response(resource)
+            .options([HttpRule::patch(format!(
+              "/{version}/{{{singular_field}.name={}}}",
+              pattern.name_template
+            ))
+            .body(singular_field.as_str())
+            .into()])
+            .build(),
+        );
+
+        update_request = Some(request);
+      }
+      StandardMethod::Delete => {
+        let request = file.new_message(format!("Delete{name}Request")).fields([
+          (
+            1,
+            name_field(
+              "name",
+              &pattern.name_regex,
+              ResourceReference::Type(resource_type.as_str().into()).into(),
+            ),
+          ),
+          (2, optional_field("etag", FieldType::String)),
+        ]);
+
+        handlers.push(
+          ServiceHandler::new(format!("Delete{name}").into())
+            .request(&request)
+            .response_internal(empty_message())
+            .options([HttpRule::delete(name_path.as_str()).into()])
+            .build(),
+        );
+
+        delete_request = Some(request);
+      }
+    }
+  }
+
+  let service_name = config
+    .service_name
+    .as_deref()
+    .map(str::to_string)
+    .unwrap_or_else(|| format!("{name}Service"));
+
+  ResourceService {
+    service: file.new_service(service_name).handlers(handlers),
+    get_request,
+    list_request,
+    list_response,
+    create_request,
+    update_request,
+    delete_request,
+  }
+}
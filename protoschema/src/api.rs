@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use bon::Builder;
+
+#[cfg(feature = "api_types")]
+use crate::{
+  common::{FIELD_BEHAVIOR_PROTO_FILE, RESOURCE_PROTO_FILE},
+  fields::{FieldBuilder, State as FieldState},
+  messages::{MessageBuilder, MessageState},
+};
+use crate::{options::message_value, OptionValue, ProtoOption};
+
+/// The behaviors of a field, as defined in `google/api/field_behavior.proto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldBehavior {
+  Optional,
+  Required,
+  OutputOnly,
+  InputOnly,
+  Immutable,
+  UnorderedList,
+  NonEmptyDefault,
+  Identifier,
+}
+
+impl FieldBehavior {
+  /// Returns the name of the enum value for this behavior, such as `OUTPUT_ONLY`
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Optional => "OPTIONAL",
+      Self::Required => "REQUIRED",
+      Self::OutputOnly => "OUTPUT_ONLY",
+      Self::InputOnly => "INPUT_ONLY",
+      Self::Immutable => "IMMUTABLE",
+      Self::UnorderedList => "UNORDERED_LIST",
+      Self::NonEmptyDefault => "NON_EMPTY_DEFAULT",
+      Self::Identifier => "IDENTIFIER",
+    }
+  }
+}
+
+impl From<FieldBehavior> for ProtoOption {
+  fn from(behavior: FieldBehavior) -> Self {
+    ProtoOption {
+      name: "(google.api.field_behavior)".into(),
+      value: Arc::new(OptionValue::Enum(behavior.as_str().into())),
+    }
+  }
+}
+
+/// The `(google.api.resource_reference)` option, which marks a string field as the name of a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceReference {
+  /// The field holds the name of a resource of the given type.
+  Type(Box<str>),
+  /// The field holds the name of the parent of a resource of the given type, as in the `parent` field of a List request.
+  ChildType(Box<str>),
+}
+
+impl From<ResourceReference> for ProtoOption {
+  fn from(reference: ResourceReference) -> Self {
+    let value = match reference {
+      ResourceReference::Type(resource_type) => message_value([("type", resource_type.as_ref())]),
+      ResourceReference::ChildType(resource_type) => {
+        message_value([("child_type", resource_type.as_ref())])
+      }
+    };
+
+    ProtoOption {
+      name: "(google.api.resource_reference)".into(),
+      value: Arc::new(value),
+    }
+  }
+}
+
+/// The `(google.api.resource)` option, which describes the message of a resource.
+#[derive(Debug, Clone, Builder)]
+#[builder(start_fn = new)]
+pub struct ResourceDescriptor {
+  /// The type of the resource, such as `library.example.com/Book`.
+  #[builder(start_fn, into)]
+  pub resource_type: Box<str>,
+  /// The patterns for the names of the resource, such as `publishers/{publisher}/books/{book}`.
+  #[builder(field)]
+  pub patterns: Vec<Box<str>>,
+  /// The field that holds the name of the resource. Defaults to `name` when omitted.
+  #[builder(into)]
+  pub name_field: Option<Box<str>>,
+  /// The plural form of the resource's name in lower camel case, such as `books`.
+  #[builder(into)]
+  pub plural: Option<Box<str>>,
+  /// The singular form of the resource's name in lower camel case, such as `book`.
+  #[builder(into)]
+  pub singular: Option<Box<str>>,
+}
+
+impl<S: resource_descriptor_builder::State> ResourceDescriptorBuilder<S> {
+  /// Adds a pattern for the names of the resource
+  pub fn pattern<T: Into<Box<str>>>(mut self, pattern: T) -> Self {
+    self.patterns.push(pattern.into());
+    self
+  }
+}
+
+impl<S: resource_descriptor_builder::IsComplete> From<ResourceDescriptorBuilder<S>>
+  for ResourceDescriptor
+{
+  fn from(value: ResourceDescriptorBuilder<S>) -> Self {
+    value.build()
+  }
+}
+
+impl From<ResourceDescriptor> for ProtoOption {
+  fn from(descriptor: ResourceDescriptor) -> Self {
+    let mut values: Vec<(&str, OptionValue)> =
+      vec![("type", OptionValue::String(descriptor.resource_type))];
+
+    for pattern in descriptor.patterns {
+      values.push(("pattern", OptionValue::String(pattern)));
+    }
+
+    for (key, value) in [
+      ("name_field", descriptor.name_field),
+      ("plural", descriptor.plural),
+      ("singular", descriptor.singular),
+    ] {
+      if let Some(value) = value {
+        values.push((key, OptionValue::String(value)));
+      }
+    }
+
+    ProtoOption {
+      name: "(google.api.resource)".into(),
+      value: Arc::new(message_value(values)),
+    }
+  }
+}
+
+#[cfg(feature = "api_types")]
+impl<S: FieldState> FieldBuilder<S> {
+  /// Adds a `(google.api.field_behavior)` option to this field, and the import for it.
+  /// It can be called multiple times to give more than one behavior to the same field.
+  pub fn field_behavior(self, behavior: FieldBehavior) -> Self {
+    self
+      .add_option(behavior.into())
+      .add_import(FIELD_BEHAVIOR_PROTO_FILE.as_ref())
+  }
+
+  /// Adds the `(google.api.resource_reference)` option to this field, and the import for it.
+  pub fn resource_reference(self, reference: ResourceReference) -> Self {
+    self
+      .add_option(reference.into())
+      .add_import(RESOURCE_PROTO_FILE.as_ref())
+  }
+}
+
+#[cfg(feature = "api_types")]
+impl<S: MessageState> MessageBuilder<S> {
+  /// Adds the `(google.api.resource)` option to this message, and the import for it.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use protoschema::{api::ResourceDescriptor, message, string, Package};
+  ///
+  /// let my_pkg = Package::new("library.v1");
+  /// let my_file = my_pkg.new_file("library");
+  ///
+  /// let book_msg = message!(my_file.new_message("Book"), 1 => string!("name")).resource(
+  ///   ResourceDescriptor::new("library.example.com/Book")
+  ///     .pattern("publishers/{publisher}/books/{book}")
+  ///     .singular("book")
+  ///     .plural("books"),
+  /// );
+  /// ```
+  pub fn resource<R: Into<ResourceDescriptor>>(self, descriptor: R) -> MessageBuilder<S> {
+    self
+      .add_imports([RESOURCE_PROTO_FILE.as_ref()])
+      .add_options([descriptor.into().into()])
+  }
+}
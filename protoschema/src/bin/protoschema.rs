@@ -0,0 +1,517 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  process::{Command, ExitCode},
+};
+
+use askama::Template;
+use clap::{Parser, Subcommand, ValueEnum};
+use protoschema::{
+  errors::{LoadError, TemplatingError},
+  loader,
+  schema::Schema,
+  Package,
+};
+use serde_json::{json, Value};
+
+/// Renders, checks, lints, diffs and exports protobuf packages defined with protoschema.
+///
+/// A schema can be a YAML (.yaml, .yml), TOML (.toml) or JSON (.json) document in the format of the `protoschema::loader` module, or a JSON snapshot of a package, as produced by the `snapshot` command.
+#[derive(Parser)]
+#[command(name = "protoschema", version)]
+struct Cli {
+  /// The format of the output. With `json`, each command prints a single JSON object
+  #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+  format: Format,
+
+  #[command(subcommand)]
+  command: Commands,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+  Text,
+  Json,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+  /// Writes the proto files of a schema
+  Render {
+    schema: PathBuf,
+    /// The root directory of the proto files
+    #[arg(long, default_value = "proto")]
+    out: PathBuf,
+  },
+  /// Checks that the proto files in the output directory are up to date with a schema, without writing them
+  Check {
+    schema: PathBuf,
+    /// The root directory of the proto files
+    #[arg(long, default_value = "proto")]
+    out: PathBuf,
+  },
+  /// Reports the issues in the definition of a schema
+  Lint { schema: PathBuf },
+  /// Writes a JSON snapshot of a schema, which can be compared with the `diff` and `breaking` commands
+  Snapshot {
+    schema: PathBuf,
+    /// The file to write the snapshot to, instead of the standard output
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+  },
+  /// Lists the changes between two versions of a schema
+  Diff { old: PathBuf, new: PathBuf },
+  /// Lists the breaking changes between two versions of a schema, and fails if there are any
+  Breaking { old: PathBuf, new: PathBuf },
+  /// Exports a schema to another format
+  #[command(subcommand)]
+  Export(Export),
+}
+
+#[derive(Subcommand)]
+enum Export {
+  /// Writes a JSON Schema document for each top level message and enum
+  JsonSchema {
+    schema: PathBuf,
+    /// The directory where the documents are written
+    #[arg(long)]
+    out: PathBuf,
+  },
+  /// Writes a FileDescriptorSet with the files of a schema and their imports, using protoc
+  DescriptorSet {
+    schema: PathBuf,
+    /// The file to write the descriptor set to
+    #[arg(long, short)]
+    output: PathBuf,
+    /// The directories where protoc looks for the imported files, such as buf/validate/validate.proto
+    #[arg(long = "include", short = 'I')]
+    includes: Vec<PathBuf>,
+    /// The protoc executable. Defaults to the PROTOC environment variable, or to `protoc`
+    #[arg(long)]
+    protoc: Option<PathBuf>,
+  },
+  /// Writes a Rust module with the constants for the names and numbers of the messages, fields and enums
+  RustConstants {
+    schema: PathBuf,
+    /// The file to write the module to
+    #[arg(long, short)]
+    output: PathBuf,
+  },
+}
+
+// The error that stops a command, with the position in the schema where it was found, if there is one
+struct Failure {
+  message: String,
+  file: Option<PathBuf>,
+  line: Option<usize>,
+  column: Option<usize>,
+}
+
+impl Failure {
+  fn new(message: impl Into<String>) -> Self {
+    Failure {
+      message: message.into(),
+      file: None,
+      line: None,
+      column: None,
+    }
+  }
+
+  fn in_file(path: &Path, message: impl Into<String>) -> Self {
+    Failure {
+      file: Some(path.to_path_buf()),
+      ..Failure::new(message)
+    }
+  }
+
+  fn io(path: &Path, error: std::io::Error) -> Self {
+    Failure::in_file(path, error.to_string())
+  }
+
+  fn to_json(&self) -> Value {
+    json!({
+      "message": self.message,
+      "file": self.file.as_ref().map(|file| file.display().to_string()),
+      "line": self.line,
+      "column": self.column,
+    })
+  }
+
+  fn to_text(&self) -> String {
+    match (&self.file, self.line, self.column) {
+      (Some(file), Some(line), Some(column)) => {
+        format!("{}:{line}:{column}: {}", file.display(), self.message)
+      }
+      (Some(file), _, _) => format!("{}: {}", file.display(), self.message),
+      _ => self.message.clone(),
+    }
+  }
+}
+
+// The result of a command that ran to completion. If `failed` is true, the command found issues that should fail a CI job
+struct Outcome {
+  json: Value,
+  lines: Vec<String>,
+  failed: bool,
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let command = command_name(&cli.command);
+
+  match run(cli.command) {
+    Ok(outcome) => {
+      match cli.format {
+        Format::Json => {
+          let mut report = json!({ "command": command, "ok": !outcome.failed });
+          report
+            .as_object_mut()
+            .unwrap()
+            .extend(outcome.json.as_object().cloned().unwrap_or_default());
+          println!("{report}");
+        }
+        Format::Text => {
+          for line in outcome.lines {
+            println!("{line}");
+          }
+        }
+      };
+
+      if outcome.failed {
+        ExitCode::from(1)
+      } else {
+        ExitCode::SUCCESS
+      }
+    }
+    Err(failure) => {
+      match cli.format {
+        Format::Json => println!(
+          "{}",
+          json!({ "command": command, "ok": false, "error": failure.to_json() })
+        ),
+        Format::Text => eprintln!("error: {}", failure.to_text()),
+      };
+
+      ExitCode::from(2)
+    }
+  }
+}
+
+fn command_name(command: &Commands) -> &'static str {
+  match command {
+    Commands::Render { .. } => "render",
+    Commands::Check { .. } => "check",
+    Commands::Lint { .. } => "lint",
+    Commands::Snapshot { .. } => "snapshot",
+    Commands::Diff { .. } => "diff",
+    Commands::Breaking { .. } => "breaking",
+    Commands::Export(Export::JsonSchema { .. }) => "export json-schema",
+    Commands::Export(Export::DescriptorSet { .. }) => "export descriptor-set",
+    Commands::Export(Export::RustConstants { .. }) => "export rust-constants",
+  }
+}
+
+fn run(command: Commands) -> Result<Outcome, Failure> {
+  match command {
+    Commands::Render { schema, out } => render(&load(&schema)?, &out),
+    Commands::Check { schema, out } => check(&load(&schema)?, &out),
+    Commands::Lint { schema } => Ok(lint(&load(&schema)?)),
+    Commands::Snapshot { schema, output } => snapshot(&load(&schema)?, output.as_deref()),
+    Commands::Diff { old, new } => Ok(diff(&load(&old)?, &load(&new)?, false)),
+    Commands::Breaking { old, new } => Ok(diff(&load(&old)?, &load(&new)?, true)),
+    Commands::Export(Export::JsonSchema { schema, out }) => {
+      export_json_schemas(&load(&schema)?, &out)
+    }
+    Commands::Export(Export::DescriptorSet {
+      schema,
+      output,
+      includes,
+      protoc,
+    }) => export_descriptor_set(&load(&schema)?, &output, &includes, protoc),
+    Commands::Export(Export::RustConstants { schema, output }) => {
+      export_rust_constants(&load(&schema)?, &output)
+    }
+  }
+}
+
+// Builds a package from a document or a snapshot, depending on the extension of the file and, for JSON files, on their content
+fn load(path: &Path) -> Result<Package, Failure> {
+  let source = fs::read_to_string(path).map_err(|e| Failure::io(path, e))?;
+  let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+  let result = match extension {
+    "yaml" | "yml" => loader::from_yaml(&source),
+    "toml" => loader::from_toml(&source),
+    "json" => {
+      // The snapshots list all of the messages of the package at the top level, unlike the documents
+      let is_snapshot =
+        serde_json::from_str::<Value>(&source).is_ok_and(|value| value.get("messages").is_some());
+
+      if is_snapshot {
+        let schema: Schema = serde_json::from_str(&source)
+          .map_err(|e| Failure::in_file(path, format!("invalid snapshot: {e}")))?;
+
+        return Package::from_schema(&schema).map_err(|e| Failure::in_file(path, e.to_string()));
+      }
+
+      loader::from_json(&source)
+    }
+    _ => {
+      return Err(Failure::in_file(
+        path,
+        "unknown schema format, expected a .yaml, .yml, .toml or .json file",
+      ))
+    }
+  };
+
+  result.map_err(|error| match error {
+    LoadError::Invalid {
+      line,
+      column,
+      message,
+    } => Failure {
+      line: Some(line),
+      column: Some(column),
+      ..Failure::in_file(path, message)
+    },
+    LoadError::Unlocated(message) => Failure::in_file(path, message),
+  })
+}
+
+fn file_names(package: &Package) -> Vec<String> {
+  package
+    .schema()
+    .files
+    .iter()
+    .map(|file| file.name.to_string())
+    .collect()
+}
+
+fn render(package: &Package, out: &Path) -> Result<Outcome, Failure> {
+  match package.render_templates(out) {
+    Ok(()) => {}
+    Err(TemplatingError::InvalidSchema(errors)) => return Ok(lint_outcome(errors)),
+    Err(error) => return Err(Failure::new(error.to_string())),
+  }
+
+  let files: Vec<String> = file_names(package)
+    .iter()
+    .map(|name| out.join(name).display().to_string())
+    .collect();
+
+  Ok(Outcome {
+    lines: files.iter().map(|file| format!("wrote {file}")).collect(),
+    json: json!({ "files": files }),
+    failed: false,
+  })
+}
+
+fn check(package: &Package, out: &Path) -> Result<Outcome, Failure> {
+  let errors = package.check();
+
+  if !errors.is_empty() {
+    return Ok(lint_outcome(errors));
+  }
+
+  let mut stale = Vec::new();
+  let mut missing = Vec::new();
+
+  for template in package.build_templates() {
+    let path = out.join(template.name.as_ref());
+    let rendered = template.render().map_err(|e| Failure::new(e.to_string()))?;
+
+    match fs::read_to_string(&path) {
+      Ok(committed) if committed == rendered => {}
+      Ok(_) => stale.push(path.display().to_string()),
+      Err(_) => missing.push(path.display().to_string()),
+    }
+  }
+
+  let mut lines: Vec<String> = stale
+    .iter()
+    .map(|file| format!("stale: {file}"))
+    .chain(missing.iter().map(|file| format!("missing: {file}")))
+    .collect();
+
+  let failed = !lines.is_empty();
+
+  if failed {
+    lines
+      .push("the proto files are not up to date, run `protoschema render` to update them".into());
+  } else {
+    lines.push("the proto files are up to date".into());
+  }
+
+  Ok(Outcome {
+    json: json!({ "stale": stale, "missing": missing }),
+    lines,
+    failed,
+  })
+}
+
+fn lint_outcome(errors: Vec<protoschema::errors::SchemaError>) -> Outcome {
+  let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+
+  Outcome {
+    lines: errors.clone(),
+    json: json!({ "errors": errors }),
+    failed: !errors.is_empty(),
+  }
+}
+
+fn lint(package: &Package) -> Outcome {
+  lint_outcome(package.check())
+}
+
+fn snapshot(package: &Package, output: Option<&Path>) -> Result<Outcome, Failure> {
+  let snapshot = serde_json::to_string_pretty(&package.schema()).unwrap();
+
+  match output {
+    Some(path) => {
+      fs::write(path, snapshot + "\n").map_err(|e| Failure::io(path, e))?;
+
+      Ok(Outcome {
+        lines: vec![format!("wrote {}", path.display())],
+        json: json!({ "files": [path.display().to_string()] }),
+        failed: false,
+      })
+    }
+    None => Ok(Outcome {
+      json: json!({ "snapshot": serde_json::to_value(package.schema()).unwrap() }),
+      lines: vec![snapshot],
+      failed: false,
+    }),
+  }
+}
+
+fn diff(old: &Package, new: &Package, breaking_only: bool) -> Outcome {
+  let changes: Vec<_> = new
+    .schema()
+    .diff(&old.schema())
+    .into_iter()
+    .filter(|change| !breaking_only || change.is_breaking())
+    .collect();
+
+  let lines = changes
+    .iter()
+    .map(|change| {
+      if change.is_breaking() && !breaking_only {
+        format!("{change} (breaking)")
+      } else {
+        change.to_string()
+      }
+    })
+    .collect();
+
+  let json_changes: Vec<Value> = changes
+    .iter()
+    .map(|change| {
+      let mut value = serde_json::to_value(change).unwrap();
+      value["breaking"] = change.is_breaking().into();
+      value["message"] = change.kind.to_string().into();
+      value
+    })
+    .collect();
+
+  Outcome {
+    json: json!({ "changes": json_changes }),
+    lines,
+    failed: breaking_only && !changes.is_empty(),
+  }
+}
+
+fn export_json_schemas(package: &Package, out: &Path) -> Result<Outcome, Failure> {
+  fs::create_dir_all(out).map_err(|e| Failure::io(out, e))?;
+
+  let mut files = Vec::new();
+
+  for document in package.json_schemas() {
+    let id = document["$id"].as_str().unwrap_or_default();
+    let path = out.join(id);
+
+    fs::write(
+      &path,
+      serde_json::to_string_pretty(&document).unwrap() + "\n",
+    )
+    .map_err(|e| Failure::io(&path, e))?;
+
+    files.push(path.display().to_string());
+  }
+
+  Ok(Outcome {
+    lines: files.iter().map(|file| format!("wrote {file}")).collect(),
+    json: json!({ "files": files }),
+    failed: false,
+  })
+}
+
+fn export_rust_constants(package: &Package, output: &Path) -> Result<Outcome, Failure> {
+  package
+    .render_constants(output)
+    .map_err(|e| Failure::new(e.to_string()))?;
+
+  Ok(Outcome {
+    lines: vec![format!("wrote {}", output.display())],
+    json: json!({ "files": [output.display().to_string()] }),
+    failed: false,
+  })
+}
+
+fn export_descriptor_set(
+  package: &Package,
+  output: &Path,
+  includes: &[PathBuf],
+  protoc: Option<PathBuf>,
+) -> Result<Outcome, Failure> {
+  let errors = package.check();
+
+  if !errors.is_empty() {
+    return Ok(lint_outcome(errors));
+  }
+
+  let protoc = protoc
+    .or_else(|| std::env::var_os("PROTOC").map(PathBuf::from))
+    .unwrap_or_else(|| "protoc".into());
+
+  // The files are rendered in a temporary directory, which is used as the first include path
+  let proto_root = std::env::temp_dir().join(format!("protoschema-{}", std::process::id()));
+
+  package
+    .render_templates(&proto_root)
+    .map_err(|e| Failure::new(e.to_string()))?;
+
+  let mut command = Command::new(&protoc);
+
+  command
+    .arg("--include_imports")
+    .arg(format!("--descriptor_set_out={}", output.display()))
+    .arg(format!("--proto_path={}", proto_root.display()));
+
+  for include in includes {
+    command.arg(format!("--proto_path={}", include.display()));
+  }
+
+  let result = command.args(file_names(package)).output();
+
+  let _ = fs::remove_dir_all(&proto_root);
+
+  let result = result.map_err(|e| {
+    Failure::new(format!(
+      "could not run protoc at '{}': {e}",
+      protoc.display()
+    ))
+  })?;
+
+  if !result.status.success() {
+    return Err(Failure::new(format!(
+      "protoc failed: {}",
+      String::from_utf8_lossy(&result.stderr).trim()
+    )));
+  }
+
+  Ok(Outcome {
+    lines: vec![format!("wrote {}", output.display())],
+    json: json!({ "files": [output.display().to_string()] }),
+    failed: false,
+  })
+}
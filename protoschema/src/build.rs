@@ -0,0 +1,198 @@
+use std::{
+  env,
+  error::Error,
+  fs::{self, create_dir_all},
+  io,
+  path::{Path, PathBuf},
+};
+
+use askama::Template;
+
+use crate::{
+  errors::{BuildError, TemplatingError},
+  Package,
+};
+
+/// Starts the configuration for rendering a [`Package`] from a build script.
+///
+/// # Examples
+/// ```rust,no_run
+/// // build.rs
+/// use protoschema::{message, string, Package};
+///
+/// let package = Package::new("myapp.v1");
+/// let file = package.new_file("user");
+///
+/// message!(file.new_message("User"), 1 => string!("name"));
+///
+/// protoschema::build::configure(&package)
+///   .include("proto_deps")
+///   // Generating the Rust code with prost-build would look like this:
+///   // .compile(|files, includes| prost_build::compile_protos(files, includes))
+///   .compile(|_files, _includes| Ok::<(), std::io::Error>(()))
+///   .unwrap();
+/// ```
+pub fn configure(package: &Package) -> BuildConfig<'_> {
+  BuildConfig {
+    package,
+    out_dir: None,
+    includes: Vec::new(),
+    watched: Vec::new(),
+    build_script: "build.rs".into(),
+  }
+}
+
+/// The configuration for rendering a [`Package`] from a build script, created with [`configure`].
+///
+/// The files are rendered into `$OUT_DIR/proto` by default, or into the directory set with [`out_dir`](BuildConfig::out_dir), such as the proto root of the project.
+/// Only the files whose content has changed are written, so that their modification time is preserved and the code generators that depend on them are not triggered without a reason.
+pub struct BuildConfig<'a> {
+  package: &'a Package,
+  out_dir: Option<PathBuf>,
+  includes: Vec<PathBuf>,
+  watched: Vec<PathBuf>,
+  build_script: PathBuf,
+}
+
+/// The result of rendering a package with [`BuildConfig::render`] or [`BuildConfig::compile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedProtos {
+  /// The directory where the files were rendered
+  pub proto_root: PathBuf,
+  /// The paths of the rendered files
+  pub files: Vec<PathBuf>,
+  /// The directories where the imports of the files can be found, starting with the `proto_root`
+  pub includes: Vec<PathBuf>,
+  /// The paths that were emitted with `cargo:rerun-if-changed`
+  pub rerun_paths: Vec<PathBuf>,
+}
+
+impl<'a> BuildConfig<'a> {
+  /// Sets the directory where the files are rendered, instead of `$OUT_DIR/proto`.
+  pub fn out_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+    self.out_dir = Some(dir.into());
+    self
+  }
+
+  /// Adds a directory where the files imported by the package can be found, such as the directory that contains `buf/validate/validate.proto`.
+  /// It is passed to the code generator after the directory of the rendered files, and it is watched for changes.
+  pub fn include<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+    self.includes.push(dir.into());
+    self
+  }
+
+  /// Adds a file or directory that the build script should be run again for, when it changes, such as a document loaded with the [`loader`](crate::loader).
+  ///
+  /// The Rust files that define the package are already tracked by cargo if they are part of the build script (for example with a `#[path]` module), so they do not need to be added.
+  pub fn watch<P: Into<PathBuf>>(mut self, path: P) -> Self {
+    self.watched.push(path.into());
+    self
+  }
+
+  /// Sets the path of the build script, relative to the directory of the manifest, if it is not `build.rs` (such as when the `build` key of the manifest is set).
+  /// It is only watched when there are no other paths to watch.
+  pub fn build_script<P: Into<PathBuf>>(mut self, path: P) -> Self {
+    self.build_script = path.into();
+    self
+  }
+
+  /// Renders the files of the package, and emits the `cargo:rerun-if-changed` directives for the watched paths and the include directories.
+  ///
+  /// The rendered files are never watched, since they are written by the build script itself. If there are no other paths to watch, the [build script](BuildConfig::build_script) is emitted (inside of `CARGO_MANIFEST_DIR`), so that cargo does not run it again whenever a file of the crate changes.
+  ///
+  /// # Errors
+  /// If the package contains any of the issues reported by [`check`](Package::check), if `OUT_DIR` is not set and no output directory was given, or if a file could not be written.
+  pub fn render(&self) -> Result<RenderedProtos, BuildError> {
+    let proto_root = match &self.out_dir {
+      Some(dir) => dir.clone(),
+      None => env::var_os("OUT_DIR")
+        .map(|dir| Path::new(&dir).join("proto"))
+        .ok_or(BuildError::MissingOutDir)?,
+    };
+
+    let templates = self
+      .package
+      .checked_templates()
+      .map_err(TemplatingError::InvalidSchema)?;
+
+    let mut files = Vec::new();
+
+    for template in templates {
+      let path = proto_root.join(template.name.as_ref());
+      let content = template
+        .render()
+        .map_err(|e| TemplatingError::TemplateWritingFailure {
+          file: path.clone(),
+          source: io::Error::other(e),
+        })?;
+
+      write_if_changed(&path, &content)?;
+      files.push(path);
+    }
+
+    let mut rerun_paths: Vec<PathBuf> = self
+      .watched
+      .iter()
+      .chain(self.includes.iter())
+      .cloned()
+      .collect();
+
+    if rerun_paths.is_empty() {
+      rerun_paths.push(match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => Path::new(&dir).join(&self.build_script),
+        None => self.build_script.clone(),
+      });
+    }
+
+    for path in &rerun_paths {
+      println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    let includes = std::iter::once(proto_root.clone())
+      .chain(self.includes.iter().cloned())
+      .collect();
+
+    Ok(RenderedProtos {
+      proto_root,
+      files,
+      includes,
+      rerun_paths,
+    })
+  }
+
+  /// Renders the files of the package like [`render`](BuildConfig::render), and then passes the rendered files and the include directories to a code generator, such as `prost_build::compile_protos` or the `compile_protos` method of `tonic_build`.
+  ///
+  /// # Errors
+  /// If the files could not be rendered, or if the code generator returns an error.
+  pub fn compile<F, E>(&self, codegen: F) -> Result<RenderedProtos, BuildError>
+  where
+    F: FnOnce(&[PathBuf], &[PathBuf]) -> Result<(), E>,
+    E: Into<Box<dyn Error + Send + Sync>>,
+  {
+    let rendered = self.render()?;
+
+    codegen(&rendered.files, &rendered.includes).map_err(|e| BuildError::Codegen(e.into()))?;
+
+    Ok(rendered)
+  }
+}
+
+fn write_if_changed(path: &Path, content: &str) -> Result<(), TemplatingError> {
+  if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+    return Ok(());
+  }
+
+  let parent = path
+    .parent()
+    .ok_or_else(|| TemplatingError::MissingParentDirectory(path.to_path_buf()))?;
+
+  create_dir_all(parent).map_err(|e| TemplatingError::DirCreationFailure {
+    dir: parent.to_path_buf(),
+    source: e,
+  })?;
+
+  fs::write(path, content).map_err(|e| TemplatingError::TemplateWritingFailure {
+    file: path.to_path_buf(),
+    source: e,
+  })
+}
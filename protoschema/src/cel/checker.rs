@@ -0,0 +1,905 @@
+use std::fmt::Display;
+
+use super::parser::{BinaryOp, Expr, Literal, UnaryOp};
+use crate::{
+  fields::{FieldData, FieldKind},
+  packages::PackageData,
+  FieldType, MapKey,
+};
+
+/// The functions with a receiver whose overloads are checked, whereas the other ones (such as those of the Cel extensions that are not modeled here) are accepted with any arguments
+const KNOWN_METHODS: [&str; 37] = [
+  "size",
+  "contains",
+  "startsWith",
+  "endsWith",
+  "matches",
+  "isEmail",
+  "isHostname",
+  "isUri",
+  "isUriRef",
+  "isIp",
+  "isIpPrefix",
+  "isHostAndPort",
+  "lowerAscii",
+  "upperAscii",
+  "trim",
+  "replace",
+  "split",
+  "substring",
+  "indexOf",
+  "lastIndexOf",
+  "charAt",
+  "join",
+  "format",
+  "reverse",
+  "unique",
+  "isNan",
+  "isInf",
+  "getFullYear",
+  "getMonth",
+  "getDate",
+  "getDayOfMonth",
+  "getDayOfWeek",
+  "getDayOfYear",
+  "getHours",
+  "getMinutes",
+  "getSeconds",
+  "getMilliseconds",
+];
+
+/// The namespaces of the functions of the Cel extensions (such as `math.greatest`), which can be the root of a qualified name
+const EXTENSION_NAMESPACES: [&str; 6] = ["math", "strings", "base64", "lists", "sets", "optional"];
+
+/// The global functions of the extensions that are not modeled here, which are accepted with any arguments
+const EXTENSION_FUNCTIONS: [&str; 1] = ["getField"];
+
+/// The types of the values in Cel expressions
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CelType {
+  Bool,
+  Int,
+  Uint,
+  Double,
+  String,
+  Bytes,
+  Null,
+  Duration,
+  Timestamp,
+  List(Box<CelType>),
+  Map(Box<CelType>, Box<CelType>),
+  // The index of the message in the package
+  Message(usize),
+  // A value whose type cannot be determined statically, such as messages from other packages
+  Dyn,
+  Type,
+}
+
+impl CelType {
+  fn is_numeric(&self) -> bool {
+    matches!(self, CelType::Int | CelType::Uint | CelType::Double)
+  }
+
+  // Dyn matches any type, and lists and maps are compatible if their items are
+  fn is_assignable(&self, other: &CelType) -> bool {
+    match (self, other) {
+      (CelType::Dyn, _) | (_, CelType::Dyn) => true,
+      (CelType::List(a), CelType::List(b)) => a.is_assignable(b),
+      (CelType::Map(ka, va), CelType::Map(kb, vb)) => ka.is_assignable(kb) && va.is_assignable(vb),
+      (CelType::Null, CelType::Message(_)) | (CelType::Message(_), CelType::Null) => true,
+      (a, b) => a == b,
+    }
+  }
+
+  fn join(self, other: CelType) -> CelType {
+    if self == CelType::Dyn || self == CelType::Null {
+      other
+    } else {
+      self
+    }
+  }
+}
+
+/// The errors found while checking a Cel expression.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CheckError {
+  UnknownField(Box<str>),
+  Invalid(String),
+}
+
+impl Display for CheckError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CheckError::UnknownField(field) => write!(f, "undefined field '{field}'"),
+      CheckError::Invalid(error) => write!(f, "{error}"),
+    }
+  }
+}
+
+impl From<String> for CheckError {
+  fn from(value: String) -> Self {
+    CheckError::Invalid(value)
+  }
+}
+
+/// Checks the types of Cel expressions against the messages in a package
+pub(crate) struct Checker<'a> {
+  package: &'a PackageData,
+  variables: Vec<(Box<str>, CelType)>,
+}
+
+impl<'a> Checker<'a> {
+  /// Creates a new checker with the given type for `this`, and the optional type for `rule` (for predefined rules)
+  pub(crate) fn new(package: &'a PackageData, this: CelType, rule: Option<CelType>) -> Self {
+    let mut variables: Vec<(Box<str>, CelType)> =
+      vec![("this".into(), this), ("now".into(), CelType::Timestamp)];
+
+    if let Some(rule) = rule {
+      variables.push(("rule".into(), rule));
+      variables.push(("rules".into(), CelType::Dyn));
+    }
+
+    Checker { package, variables }
+  }
+
+  /// Returns the type of a field of a message
+  pub(crate) fn field_type(&self, field: &FieldData) -> CelType {
+    let value_type = self.value_type(&field.field_type);
+
+    match field.kind {
+      FieldKind::Repeated => CelType::List(Box::new(value_type)),
+      _ => value_type,
+    }
+  }
+
+  /// Returns the type of a single value of the given [`FieldType`]
+  pub(crate) fn value_type(&self, field_type: &FieldType) -> CelType {
+    match field_type {
+      FieldType::Double | FieldType::Float => CelType::Double,
+      FieldType::Int64
+      | FieldType::Int32
+      | FieldType::Sint32
+      | FieldType::Sint64
+      | FieldType::Sfixed32
+      | FieldType::Sfixed64
+      | FieldType::Enum(_) => CelType::Int,
+      FieldType::Uint64 | FieldType::Uint32 | FieldType::Fixed32 | FieldType::Fixed64 => {
+        CelType::Uint
+      }
+      FieldType::Bool => CelType::Bool,
+      FieldType::String => CelType::String,
+      FieldType::Bytes => CelType::Bytes,
+      FieldType::Duration => CelType::Duration,
+      FieldType::Timestamp => CelType::Timestamp,
+      FieldType::Message(path) => self
+        .package
+        .message_id(&path.full_name_with_package)
+        .map(CelType::Message)
+        .unwrap_or(CelType::Dyn),
+      FieldType::Map(key, value) => CelType::Map(
+        Box::new(map_key_type(key)),
+        Box::new(self.value_type(value)),
+      ),
+      _ => CelType::Dyn,
+    }
+  }
+
+  /// Returns the name of a type, to be used in error messages
+  pub(crate) fn type_name(&self, cel_type: &CelType) -> String {
+    match cel_type {
+      CelType::Bool => "bool".into(),
+      CelType::Int => "int".into(),
+      CelType::Uint => "uint".into(),
+      CelType::Double => "double".into(),
+      CelType::String => "string".into(),
+      CelType::Bytes => "bytes".into(),
+      CelType::Null => "null".into(),
+      CelType::Duration => "google.protobuf.Duration".into(),
+      CelType::Timestamp => "google.protobuf.Timestamp".into(),
+      CelType::List(item) => format!("list({})", self.type_name(item)),
+      CelType::Map(key, value) => {
+        format!("map({}, {})", self.type_name(key), self.type_name(value))
+      }
+      CelType::Message(id) => self.package.messages[*id]
+        .import_path
+        .full_name_with_package
+        .to_string(),
+      CelType::Dyn => "dyn".into(),
+      CelType::Type => "type".into(),
+    }
+  }
+
+  /// Checks a full rule expression, which must evaluate to a bool or to a string (the error message)
+  pub(crate) fn check_rule(&mut self, expr: &Expr) -> Result<(), CheckError> {
+    let result = self.check(expr)?;
+
+    match result {
+      CelType::Bool | CelType::String | CelType::Dyn => Ok(()),
+      other => Err(CheckError::Invalid(format!(
+        "the expression must evaluate to a bool or a string, but it evaluates to {}",
+        self.type_name(&other)
+      ))),
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Option<CelType> {
+    self
+      .variables
+      .iter()
+      .rev()
+      .find(|(var, _)| var.as_ref() == name)
+      .map(|(_, t)| t.clone())
+  }
+
+  // Checks if an expression is a qualified name that does not start with a variable, such as an enum value (`mypkg.Status.ACTIVE`) or the namespace of a function (`math`), which are resolved at runtime
+  fn is_qualified_name(&self, expr: &Expr) -> bool {
+    match expr {
+      Expr::Ident(name) => self.lookup(name).is_none() && self.is_known_root(name),
+      Expr::Member(target, _) => self.is_qualified_name(target),
+      _ => false,
+    }
+  }
+
+  // Checks if a name can be the root of a qualified name, because it is the namespace of an extension, the first segment of a known package or the name of a message or enum of this package
+  fn is_known_root(&self, name: &str) -> bool {
+    let is_root_of = |path: &str| path.split('.').next() == Some(name);
+
+    let messages = self.package.messages.iter();
+    let item_paths = messages
+      .clone()
+      .map(|msg| &msg.import_path)
+      .chain(self.package.enums.iter().map(|enum_| &enum_.import_path));
+    let field_type_paths = messages
+      .flat_map(|msg| {
+        msg
+          .fields
+          .iter()
+          .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()))
+      })
+      .filter_map(|(_, field)| match &field.field_type {
+        FieldType::Message(path) | FieldType::Enum(path) => Some(path),
+        _ => None,
+      });
+
+    name == "google"
+      || EXTENSION_NAMESPACES.contains(&name)
+      || is_root_of(&self.package.name)
+      || item_paths
+        .chain(field_type_paths)
+        .any(|path| is_root_of(&path.full_name) || is_root_of(&path.package))
+  }
+
+  fn mismatch(&self, op: &str, left: &CelType, right: &CelType) -> CheckError {
+    CheckError::Invalid(format!(
+      "no matching overload for '{op}' applied to ({}, {})",
+      self.type_name(left),
+      self.type_name(right)
+    ))
+  }
+
+  /// Returns the type of an expression
+  pub(crate) fn check(&mut self, expr: &Expr) -> Result<CelType, CheckError> {
+    match expr {
+      Expr::Literal(literal) => Ok(match literal {
+        Literal::Bool(_) => CelType::Bool,
+        Literal::Int(_) => CelType::Int,
+        Literal::Uint(_) => CelType::Uint,
+        Literal::Double(_) => CelType::Double,
+        Literal::String(_) => CelType::String,
+        Literal::Bytes(_) => CelType::Bytes,
+        Literal::Null => CelType::Null,
+      }),
+      Expr::Ident(name) => match self.lookup(name) {
+        Some(ident_type) => Ok(ident_type),
+        None => match name.as_ref() {
+          "int" | "uint" | "double" | "string" | "bytes" | "bool" | "list" | "map"
+          | "null_type" | "type" => Ok(CelType::Type),
+          _ => Err(CheckError::Invalid(format!(
+            "undeclared reference to '{name}'"
+          ))),
+        },
+      },
+      Expr::Member(target, _) if self.is_qualified_name(target) => Ok(CelType::Dyn),
+      Expr::Member(target, field) => {
+        let target_type = self.check(target)?;
+        self.member_type(&target_type, field)
+      }
+      Expr::Index(target, index) => {
+        let target_type = self.check(target)?;
+        let index_type = self.check(index)?;
+
+        match target_type {
+          CelType::List(item) => {
+            if !matches!(index_type, CelType::Int | CelType::Uint | CelType::Dyn) {
+              return Err(CheckError::Invalid(format!(
+                "a list cannot be indexed with a value of type {}",
+                self.type_name(&index_type)
+              )));
+            }
+            Ok(*item)
+          }
+          CelType::Map(key, value) => {
+            if !key.is_assignable(&index_type) {
+              return Err(self.mismatch("_[_]", &CelType::Map(key, value), &index_type));
+            }
+            Ok(*value)
+          }
+          CelType::Dyn => Ok(CelType::Dyn),
+          other => Err(CheckError::Invalid(format!(
+            "a value of type {} cannot be indexed",
+            self.type_name(&other)
+          ))),
+        }
+      }
+      Expr::Unary(op, operand) => {
+        let operand_type = self.check(operand)?;
+
+        match (op, &operand_type) {
+          (UnaryOp::Not, CelType::Bool | CelType::Dyn) => Ok(CelType::Bool),
+          (UnaryOp::Neg, CelType::Int | CelType::Double | CelType::Duration | CelType::Dyn) => {
+            Ok(operand_type)
+          }
+          (UnaryOp::Not, other) => Err(CheckError::Invalid(format!(
+            "no matching overload for '!' applied to {}",
+            self.type_name(other)
+          ))),
+          (UnaryOp::Neg, other) => Err(CheckError::Invalid(format!(
+            "no matching overload for '-' applied to {}",
+            self.type_name(other)
+          ))),
+        }
+      }
+      Expr::Binary(op, left, right) => {
+        let left_type = self.check(left)?;
+        let right_type = self.check(right)?;
+        self.binary_type(*op, left_type, right_type)
+      }
+      Expr::Conditional(condition, if_true, if_false) => {
+        let condition_type = self.check(condition)?;
+
+        if !CelType::Bool.is_assignable(&condition_type) {
+          return Err(CheckError::Invalid(format!(
+            "the condition of a ternary operator must be a bool, found {}",
+            self.type_name(&condition_type)
+          )));
+        }
+
+        let true_type = self.check(if_true)?;
+        let false_type = self.check(if_false)?;
+
+        if !true_type.is_assignable(&false_type) {
+          return Err(self.mismatch("_?_:_", &true_type, &false_type));
+        }
+
+        Ok(true_type.join(false_type))
+      }
+      Expr::List(items) => {
+        let mut item_type = CelType::Dyn;
+
+        for item in items {
+          let next = self.check(item)?;
+          item_type = if item_type == CelType::Dyn || item_type == next {
+            next
+          } else {
+            // Lists with mixed types are allowed, but their items become dyn
+            CelType::Dyn
+          };
+        }
+
+        Ok(CelType::List(Box::new(item_type)))
+      }
+      Expr::Map(entries) => {
+        let mut key_type = CelType::Dyn;
+        let mut value_type = CelType::Dyn;
+
+        for (i, (key, value)) in entries.iter().enumerate() {
+          let next_key = self.check(key)?;
+          let next_value = self.check(value)?;
+
+          if !matches!(
+            next_key,
+            CelType::Int | CelType::Uint | CelType::Bool | CelType::String | CelType::Dyn
+          ) {
+            return Err(CheckError::Invalid(format!(
+              "unsupported map key type {}",
+              self.type_name(&next_key)
+            )));
+          }
+
+          if i == 0 {
+            key_type = next_key;
+            value_type = next_value;
+          } else {
+            if key_type != next_key {
+              key_type = CelType::Dyn;
+            }
+            if value_type != next_value {
+              value_type = CelType::Dyn;
+            }
+          }
+        }
+
+        Ok(CelType::Map(Box::new(key_type), Box::new(value_type)))
+      }
+      Expr::Call { target, name, args } => self.call_type(target.as_deref(), name, args),
+      // The fields of the messages being constructed are not checked, but their values are
+      Expr::Struct { fields, .. } => {
+        for (_, value) in fields {
+          self.check(value)?;
+        }
+
+        Ok(CelType::Dyn)
+      }
+    }
+  }
+
+  fn member_type(&self, target_type: &CelType, field: &str) -> Result<CelType, CheckError> {
+    match target_type {
+      CelType::Message(id) => self
+        .package
+        .find_field(*id, field)
+        .map(|f| self.field_type(f))
+        .ok_or_else(|| CheckError::UnknownField(field.into())),
+      CelType::Map(key, value) if key.is_assignable(&CelType::String) => Ok(*value.clone()),
+      CelType::Dyn => Ok(CelType::Dyn),
+      other => Err(CheckError::Invalid(format!(
+        "a value of type {} does not have fields (selecting '{field}')",
+        self.type_name(other)
+      ))),
+    }
+  }
+
+  fn binary_type(
+    &self,
+    op: BinaryOp,
+    left: CelType,
+    right: CelType,
+  ) -> Result<CelType, CheckError> {
+    use CelType::*;
+
+    let is_dyn = left == Dyn || right == Dyn;
+
+    match op {
+      BinaryOp::And | BinaryOp::Or => {
+        if Bool.is_assignable(&left) && Bool.is_assignable(&right) {
+          Ok(Bool)
+        } else {
+          Err(self.mismatch(op.symbol(), &left, &right))
+        }
+      }
+      BinaryOp::Eq | BinaryOp::Ne => {
+        if left.is_assignable(&right) {
+          Ok(Bool)
+        } else {
+          Err(self.mismatch(op.symbol(), &left, &right))
+        }
+      }
+      BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+        let comparable = is_dyn
+          || (left.is_numeric() && right.is_numeric())
+          || (left == right && matches!(left, Bool | String | Bytes | Duration | Timestamp));
+
+        if comparable {
+          Ok(Bool)
+        } else {
+          Err(self.mismatch(op.symbol(), &left, &right))
+        }
+      }
+      BinaryOp::In => match &right {
+        List(item) if item.is_assignable(&left) => Ok(Bool),
+        Map(key, _) if key.is_assignable(&left) => Ok(Bool),
+        Dyn => Ok(Bool),
+        _ => Err(self.mismatch(op.symbol(), &left, &right)),
+      },
+      BinaryOp::Add => match (&left, &right) {
+        (Dyn, _) | (_, Dyn) => Ok(Dyn),
+        (Int, Int) | (Uint, Uint) | (Double, Double) | (String, String) | (Bytes, Bytes) => {
+          Ok(left)
+        }
+        (Duration, Duration) => Ok(Duration),
+        (Timestamp, Duration) | (Duration, Timestamp) => Ok(Timestamp),
+        (List(a), List(b)) => Ok(if a == b { left } else { List(Box::new(Dyn)) }),
+        _ => Err(self.mismatch(op.symbol(), &left, &right)),
+      },
+      BinaryOp::Sub => match (&left, &right) {
+        (Dyn, _) | (_, Dyn) => Ok(Dyn),
+        (Int, Int) | (Uint, Uint) | (Double, Double) => Ok(left),
+        (Duration, Duration) | (Timestamp, Timestamp) => Ok(Duration),
+        (Timestamp, Duration) => Ok(Timestamp),
+        _ => Err(self.mismatch(op.symbol(), &left, &right)),
+      },
+      BinaryOp::Mul | BinaryOp::Div => match (&left, &right) {
+        (Dyn, _) | (_, Dyn) => Ok(Dyn),
+        (Int, Int) | (Uint, Uint) | (Double, Double) => Ok(left),
+        _ => Err(self.mismatch(op.symbol(), &left, &right)),
+      },
+      BinaryOp::Rem => match (&left, &right) {
+        (Dyn, _) | (_, Dyn) => Ok(Dyn),
+        (Int, Int) | (Uint, Uint) => Ok(left),
+        _ => Err(self.mismatch(op.symbol(), &left, &right)),
+      },
+    }
+  }
+
+  fn expect_args(&self, name: &str, args: &[Expr], count: usize) -> Result<(), CheckError> {
+    if args.len() != count {
+      return Err(CheckError::Invalid(format!(
+        "'{name}' expects {count} argument(s), but {} were given",
+        args.len()
+      )));
+    }
+    Ok(())
+  }
+
+  fn check_args(
+    &mut self,
+    name: &str,
+    args: &[Expr],
+    expected: &[CelType],
+  ) -> Result<(), CheckError> {
+    self.expect_args(name, args, expected.len())?;
+
+    for (arg, expected_type) in args.iter().zip(expected) {
+      let arg_type = self.check(arg)?;
+
+      if !expected_type.is_assignable(&arg_type) {
+        return Err(CheckError::Invalid(format!(
+          "'{name}' expects an argument of type {}, found {}",
+          self.type_name(expected_type),
+          self.type_name(&arg_type)
+        )));
+      }
+    }
+
+    Ok(())
+  }
+
+  fn call_type(
+    &mut self,
+    target: Option<&Expr>,
+    name: &str,
+    args: &[Expr],
+  ) -> Result<CelType, CheckError> {
+    use CelType::*;
+
+    // Macros
+    if name == "has" && target.is_none() {
+      self.expect_args(name, args, 1)?;
+
+      return match &args[0] {
+        Expr::Member(target, field) => {
+          let target_type = self.check(target)?;
+          self.member_type(&target_type, field)?;
+          Ok(Bool)
+        }
+        _ => Err(CheckError::Invalid(
+          "the argument of 'has' must be a field selection".into(),
+        )),
+      };
+    }
+
+    if let Some(target) = target
+      && matches!(name, "all" | "exists" | "exists_one" | "map" | "filter")
+    {
+      return self.comprehension_type(target, name, args);
+    }
+
+    let Some(target) = target else {
+      return match name {
+        "size" => {
+          self.expect_args(name, args, 1)?;
+          let arg_type = self.check(&args[0])?;
+          self.size_type(&arg_type)
+        }
+        "int" | "uint" | "double" | "string" | "bytes" | "bool" | "dyn" | "duration"
+        | "timestamp" => {
+          self.expect_args(name, args, 1)?;
+          let arg_type = self.check(&args[0])?;
+          self.conversion_type(name, &arg_type)
+        }
+        "type" => {
+          self.expect_args(name, args, 1)?;
+          self.check(&args[0])?;
+          Ok(Type)
+        }
+        "matches" => {
+          self.check_args(name, args, &[String, String])?;
+          Ok(Bool)
+        }
+        _ if EXTENSION_FUNCTIONS.contains(&name) => self.unknown_call(args),
+        _ => Err(CheckError::Invalid(format!(
+          "undeclared reference to the function '{name}'"
+        ))),
+      };
+    };
+
+    // The functions in a namespace, such as `math.greatest` or `strings.quote`
+    if self.is_qualified_name(target) {
+      return self.unknown_call(args);
+    }
+
+    let target_type = self.check(target)?;
+
+    if target_type == Dyn {
+      for arg in args {
+        self.check(arg)?;
+      }
+
+      return Ok(match name {
+        "size" | "indexOf" | "lastIndexOf" => Int,
+        "format" | "reverse" | "lowerAscii" | "upperAscii" | "trim" => String,
+        "contains" | "startsWith" | "endsWith" | "matches" | "isEmail" | "isHostname" | "isIp"
+        | "isIpPrefix" | "isUri" | "isUriRef" | "isHostAndPort" | "unique" | "isNan" | "isInf" => {
+          Bool
+        }
+        _ => Dyn,
+      });
+    }
+
+    match (name, &target_type) {
+      ("size", _) => {
+        self.expect_args(name, args, 0)?;
+        self.size_type(&target_type)
+      }
+      ("contains" | "startsWith" | "endsWith" | "matches", String) => {
+        self.check_args(name, args, &[String])?;
+        Ok(Bool)
+      }
+      ("contains", Bytes) => {
+        self.check_args(name, args, &[Bytes])?;
+        Ok(Bool)
+      }
+      ("isEmail" | "isHostname" | "isUri" | "isUriRef", String) => {
+        self.check_args(name, args, &[])?;
+        Ok(Bool)
+      }
+      ("isIp" | "isIpPrefix", String) => {
+        if args.is_empty() {
+          Ok(Bool)
+        } else if args.len() == 1 {
+          self.check_args(name, args, &[Int])?;
+          Ok(Bool)
+        } else {
+          self.check_args(name, args, &[Int, Bool])?;
+          Ok(Bool)
+        }
+      }
+      ("isHostAndPort", String) => {
+        self.check_args(name, args, &[Bool])?;
+        Ok(Bool)
+      }
+      ("lowerAscii" | "upperAscii" | "trim", String) => {
+        self.check_args(name, args, &[])?;
+        Ok(String)
+      }
+      ("replace", String) => {
+        if args.len() == 3 {
+          self.check_args(name, args, &[String, String, Int])?;
+        } else {
+          self.check_args(name, args, &[String, String])?;
+        }
+        Ok(String)
+      }
+      ("split", String) => {
+        if args.len() == 2 {
+          self.check_args(name, args, &[String, Int])?;
+        } else {
+          self.check_args(name, args, &[String])?;
+        }
+        Ok(List(Box::new(String)))
+      }
+      ("substring", String) => {
+        if args.len() == 2 {
+          self.check_args(name, args, &[Int, Int])?;
+        } else {
+          self.check_args(name, args, &[Int])?;
+        }
+        Ok(String)
+      }
+      ("indexOf" | "lastIndexOf", String) => {
+        if args.len() == 2 {
+          self.check_args(name, args, &[String, Int])?;
+        } else {
+          self.check_args(name, args, &[String])?;
+        }
+        Ok(Int)
+      }
+      ("charAt", String) => {
+        self.check_args(name, args, &[Int])?;
+        Ok(String)
+      }
+      ("format", String) => {
+        self.expect_args(name, args, 1)?;
+
+        match self.check(&args[0])? {
+          List(_) | Dyn => Ok(String),
+          other => Err(CheckError::Invalid(format!(
+            "'format' expects a list of arguments, found {}",
+            self.type_name(&other)
+          ))),
+        }
+      }
+      ("reverse", String) => {
+        self.check_args(name, args, &[])?;
+        Ok(String)
+      }
+      ("join", List(item)) if String.is_assignable(item) => {
+        if args.is_empty() {
+          Ok(String)
+        } else {
+          self.check_args(name, args, &[String])?;
+          Ok(String)
+        }
+      }
+      ("unique", List(item)) if !matches!(**item, Message(_) | List(_) | Map(_, _)) => {
+        self.check_args(name, args, &[])?;
+        Ok(Bool)
+      }
+      ("isNan" | "isInf", Double) => {
+        if name == "isInf" && args.len() == 1 {
+          self.check_args(name, args, &[Int])?;
+        } else {
+          self.check_args(name, args, &[])?;
+        }
+        Ok(Bool)
+      }
+      (
+        "getFullYear" | "getMonth" | "getDate" | "getDayOfMonth" | "getDayOfWeek" | "getDayOfYear"
+        | "getHours" | "getMinutes" | "getSeconds" | "getMilliseconds",
+        Timestamp,
+      ) => {
+        if args.len() == 1 {
+          self.check_args(name, args, &[String])?;
+        } else {
+          self.check_args(name, args, &[])?;
+        }
+        Ok(Int)
+      }
+      ("getHours" | "getMinutes" | "getSeconds" | "getMilliseconds", Duration) => {
+        self.check_args(name, args, &[])?;
+        Ok(Int)
+      }
+      _ if KNOWN_METHODS.contains(&name) => Err(CheckError::Invalid(format!(
+        "no matching overload for '{name}' applied to {}",
+        self.type_name(&target_type)
+      ))),
+      _ => self.unknown_call(args),
+    }
+  }
+
+  // The result of a function that is not modeled by the checker, whose arguments are still checked
+  fn unknown_call(&mut self, args: &[Expr]) -> Result<CelType, CheckError> {
+    for arg in args {
+      self.check(arg)?;
+    }
+
+    Ok(CelType::Dyn)
+  }
+
+  fn size_type(&self, arg_type: &CelType) -> Result<CelType, CheckError> {
+    match arg_type {
+      CelType::String | CelType::Bytes | CelType::List(_) | CelType::Map(_, _) | CelType::Dyn => {
+        Ok(CelType::Int)
+      }
+      other => Err(CheckError::Invalid(format!(
+        "no matching overload for 'size' applied to {}",
+        self.type_name(other)
+      ))),
+    }
+  }
+
+  fn conversion_type(&self, name: &str, arg_type: &CelType) -> Result<CelType, CheckError> {
+    use CelType::*;
+
+    let (result, accepted): (CelType, &[CelType]) = match name {
+      "int" => (Int, &[Int, Uint, Double, String, Timestamp]),
+      "uint" => (Uint, &[Int, Uint, Double, String]),
+      "double" => (Double, &[Int, Uint, Double, String]),
+      "string" => (
+        String,
+        &[Int, Uint, Double, String, Bytes, Bool, Duration, Timestamp],
+      ),
+      "bytes" => (Bytes, &[String, Bytes]),
+      "bool" => (Bool, &[Bool, String]),
+      "duration" => (Duration, &[String, Duration]),
+      "timestamp" => (Timestamp, &[String, Int, Timestamp]),
+      _ => return Ok(Dyn),
+    };
+
+    if *arg_type == Dyn || accepted.contains(arg_type) {
+      Ok(result)
+    } else {
+      Err(CheckError::Invalid(format!(
+        "no matching overload for '{name}' applied to {}",
+        self.type_name(arg_type)
+      )))
+    }
+  }
+
+  fn comprehension_type(
+    &mut self,
+    target: &Expr,
+    name: &str,
+    args: &[Expr],
+  ) -> Result<CelType, CheckError> {
+    let target_type = self.check(target)?;
+
+    let item_type = match &target_type {
+      CelType::List(item) => *item.clone(),
+      CelType::Map(key, _) => *key.clone(),
+      CelType::Dyn => CelType::Dyn,
+      other => {
+        return Err(CheckError::Invalid(format!(
+          "'{name}' cannot be applied to a value of type {}",
+          self.type_name(other)
+        )))
+      }
+    };
+
+    if args.len() != 2 && !(name == "map" && args.len() == 3) {
+      return Err(CheckError::Invalid(format!(
+        "'{name}' expects 2 arguments, but {} were given",
+        args.len()
+      )));
+    }
+
+    let Expr::Ident(variable) = &args[0] else {
+      return Err(CheckError::Invalid(format!(
+        "the first argument of '{name}' must be an identifier"
+      )));
+    };
+
+    self.variables.push((variable.clone(), item_type));
+    let result = self.comprehension_body(&target_type, name, args);
+    self.variables.pop();
+
+    result
+  }
+
+  fn comprehension_body(
+    &mut self,
+    target_type: &CelType,
+    name: &str,
+    args: &[Expr],
+  ) -> Result<CelType, CheckError> {
+    let mut last_type = CelType::Dyn;
+
+    for (i, arg) in args[1..].iter().enumerate() {
+      let arg_type = self.check(arg)?;
+      // The three-arguments version of map has a filter predicate before the transform
+      let is_predicate = name != "map" || (args.len() == 3 && i == 0);
+
+      if is_predicate && !CelType::Bool.is_assignable(&arg_type) {
+        return Err(CheckError::Invalid(format!(
+          "the predicate of '{name}' must be a bool, found {}",
+          self.type_name(&arg_type)
+        )));
+      }
+
+      last_type = arg_type;
+    }
+
+    Ok(match name {
+      "map" => CelType::List(Box::new(last_type)),
+      "filter" => match target_type {
+        CelType::Map(key, _) => CelType::List(key.clone()),
+        other => other.clone(),
+      },
+      _ => CelType::Bool,
+    })
+  }
+}
+
+fn map_key_type(key: &MapKey) -> CelType {
+  match key {
+    MapKey::Int32
+    | MapKey::Int64
+    | MapKey::Sint32
+    | MapKey::Sint64
+    | MapKey::Sfixed32
+    | MapKey::Sfixed64 => CelType::Int,
+    MapKey::Uint32 | MapKey::Uint64 | MapKey::Fixed32 | MapKey::Fixed64 => CelType::Uint,
+    MapKey::Bool => CelType::Bool,
+    MapKey::String => CelType::String,
+  }
+}
@@ -0,0 +1,184 @@
+use std::{
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+  sync::LazyLock,
+};
+
+use regex::Regex;
+
+// The checks for the string formats supported by protovalidate, shared by the Cel functions (like `isEmail`) and the standard rules (like `string.email`)
+
+static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(
+    r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$",
+  )
+  .unwrap()
+});
+
+static URI_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*:[A-Za-z0-9\-._~!$&'()*+,;=:@/?#%\[\]]*$").unwrap()
+});
+
+static URI_REF_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^[A-Za-z0-9\-._~!$&'()*+,;=:@/?#%\[\]]*$").unwrap());
+
+static UUID_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+  Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$")
+    .unwrap()
+});
+
+static TUUID_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9a-fA-F]{32}$").unwrap());
+
+static HEADER_NAME_STRICT_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^:?[0-9a-zA-Z!#$%&'*+\-.^_|~`]+$").unwrap());
+
+static HEADER_VALUE_STRICT_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^[^\x00-\x08\x0A-\x1F\x7F]*$").unwrap());
+
+static HEADER_LOOSE_REGEX: LazyLock<Regex> =
+  LazyLock::new(|| Regex::new(r"^[^\x00\x0A\x0D]*$").unwrap());
+
+pub(crate) fn is_email(value: &str) -> bool {
+  EMAIL_REGEX.is_match(value)
+}
+
+pub(crate) fn is_hostname(value: &str) -> bool {
+  let value = value.strip_suffix('.').unwrap_or(value);
+
+  if value.is_empty() || value.len() > 253 {
+    return false;
+  }
+
+  let mut last_label = "";
+
+  for label in value.split('.') {
+    if label.is_empty()
+      || label.len() > 63
+      || label.starts_with('-')
+      || label.ends_with('-')
+      || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+      return false;
+    }
+
+    last_label = label;
+  }
+
+  // The top level domain cannot be entirely numeric
+  !last_label.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks if the value is an ip address. A version of 0 accepts both ipv4 and ipv6.
+pub(crate) fn is_ip(value: &str, version: i64) -> bool {
+  match version {
+    0 => value.parse::<IpAddr>().is_ok(),
+    4 => value.parse::<Ipv4Addr>().is_ok(),
+    6 => value.parse::<Ipv6Addr>().is_ok(),
+    _ => false,
+  }
+}
+
+/// Checks if the value is an ip address with a prefix length, such as `192.168.0.0/16`.
+/// If `strict` is true, the host bits must be set to zero.
+pub(crate) fn is_ip_prefix(value: &str, version: i64, strict: bool) -> bool {
+  let Some((address, prefix_length)) = value.split_once('/') else {
+    return false;
+  };
+
+  if prefix_length.is_empty() || !prefix_length.chars().all(|c| c.is_ascii_digit()) {
+    return false;
+  }
+
+  let Ok(prefix_length) = prefix_length.parse::<u32>() else {
+    return false;
+  };
+
+  let (bits, max_length): (u128, u32) = match address.parse::<IpAddr>() {
+    Ok(IpAddr::V4(ip)) if version == 0 || version == 4 => (u32::from(ip) as u128, 32),
+    Ok(IpAddr::V6(ip)) if version == 0 || version == 6 => (u128::from(ip), 128),
+    _ => return false,
+  };
+
+  if prefix_length > max_length {
+    return false;
+  }
+
+  if !strict {
+    return true;
+  }
+
+  let host_bits = max_length - prefix_length;
+  host_bits == 0 || bits & ((1u128 << host_bits) - 1) == 0
+}
+
+// A simplified check for absolute URIs, which verifies the scheme, the allowed characters and the percent-encoding
+pub(crate) fn is_uri(value: &str) -> bool {
+  URI_REGEX.is_match(value) && has_valid_percent_encoding(value)
+}
+
+pub(crate) fn is_uri_ref(value: &str) -> bool {
+  is_uri(value) || (URI_REF_REGEX.is_match(value) && has_valid_percent_encoding(value))
+}
+
+fn has_valid_percent_encoding(value: &str) -> bool {
+  let bytes = value.as_bytes();
+
+  bytes.iter().enumerate().all(|(i, byte)| {
+    *byte != b'%'
+      || (bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+        && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit))
+  })
+}
+
+pub(crate) fn is_host_and_port(value: &str, port_required: bool) -> bool {
+  if let Some(rest) = value.strip_prefix('[') {
+    let Some((ip, after)) = rest.split_once(']') else {
+      return false;
+    };
+
+    if !is_ip(ip, 6) {
+      return false;
+    }
+
+    return match after.strip_prefix(':') {
+      Some(port) => is_port(port),
+      None => after.is_empty() && !port_required,
+    };
+  }
+
+  match value.rsplit_once(':') {
+    Some((host, port)) => (is_hostname(host) || is_ip(host, 4)) && is_port(port),
+    None => !port_required && (is_hostname(value) || is_ip(value, 4)),
+  }
+}
+
+fn is_port(value: &str) -> bool {
+  !value.is_empty()
+    && value.len() <= 5
+    && value.chars().all(|c| c.is_ascii_digit())
+    && value.parse::<u32>().is_ok_and(|port| port <= 65535)
+}
+
+pub(crate) fn is_uuid(value: &str) -> bool {
+  UUID_REGEX.is_match(value)
+}
+
+pub(crate) fn is_tuuid(value: &str) -> bool {
+  TUUID_REGEX.is_match(value)
+}
+
+pub(crate) fn is_header_name(value: &str, strict: bool) -> bool {
+  !value.is_empty()
+    && if strict {
+      HEADER_NAME_STRICT_REGEX.is_match(value)
+    } else {
+      HEADER_LOOSE_REGEX.is_match(value)
+    }
+}
+
+pub(crate) fn is_header_value(value: &str, strict: bool) -> bool {
+  if strict {
+    HEADER_VALUE_STRICT_REGEX.is_match(value)
+  } else {
+    HEADER_LOOSE_REGEX.is_match(value)
+  }
+}
@@ -0,0 +1,1071 @@
+use std::{
+  cmp::Ordering,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use regex::Regex;
+
+use super::{
+  formats,
+  parser::{BinaryOp, Expr, Literal, UnaryOp},
+};
+use crate::{
+  fields::{FieldData, FieldKind},
+  json::{civil_from_days, format_duration, format_timestamp, parse_timestamp},
+  packages::PackageData,
+  Duration, FieldType, Timestamp,
+};
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The values produced by Cel expressions
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+  Null,
+  Bool(bool),
+  Int(i64),
+  Uint(u64),
+  Double(f64),
+  String(String),
+  Bytes(Vec<u8>),
+  // The duration in nanoseconds
+  Duration(i128),
+  // The nanoseconds since the unix epoch
+  Timestamp(i128),
+  List(Vec<Value>),
+  Map(Vec<(Value, Value)>),
+  Message(MessageValue),
+  Type(String),
+}
+
+/// A message instance, which only holds the fields that are set.
+/// The fields that are not set return their default value when accessed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct MessageValue {
+  // The index of the message in the package, if it is defined there
+  pub(crate) id: Option<usize>,
+  pub(crate) fields: Vec<(Box<str>, Value)>,
+}
+
+impl MessageValue {
+  pub(crate) fn get(&self, name: &str) -> Option<&Value> {
+    self
+      .fields
+      .iter()
+      .find(|(field, _)| field.as_ref() == name)
+      .map(|(_, value)| value)
+  }
+}
+
+type EvalResult = Result<Value, String>;
+
+impl Value {
+  pub(crate) fn type_name(&self) -> &'static str {
+    match self {
+      Value::Null => "null_type",
+      Value::Bool(_) => "bool",
+      Value::Int(_) => "int",
+      Value::Uint(_) => "uint",
+      Value::Double(_) => "double",
+      Value::String(_) => "string",
+      Value::Bytes(_) => "bytes",
+      Value::Duration(_) => "google.protobuf.Duration",
+      Value::Timestamp(_) => "google.protobuf.Timestamp",
+      Value::List(_) => "list",
+      Value::Map(_) => "map",
+      Value::Message(_) => "message",
+      Value::Type(_) => "type",
+    }
+  }
+
+  pub(crate) fn from_duration(duration: &Duration) -> Value {
+    Value::Duration(duration.seconds as i128 * NANOS_PER_SECOND + duration.nanos as i128)
+  }
+
+  pub(crate) fn from_timestamp(timestamp: &Timestamp) -> Value {
+    Value::Timestamp(timestamp.seconds as i128 * NANOS_PER_SECOND + timestamp.nanos as i128)
+  }
+
+  /// Returns true if this value is the protobuf zero value for its type
+  pub(crate) fn is_zero(&self) -> bool {
+    match self {
+      Value::Null => true,
+      Value::Bool(v) => !v,
+      Value::Int(v) => *v == 0,
+      Value::Uint(v) => *v == 0,
+      Value::Double(v) => *v == 0.0,
+      Value::String(v) => v.is_empty(),
+      Value::Bytes(v) => v.is_empty(),
+      Value::Duration(v) | Value::Timestamp(v) => *v == 0,
+      Value::List(v) => v.is_empty(),
+      Value::Map(v) => v.is_empty(),
+      Value::Message(_) | Value::Type(_) => false,
+    }
+  }
+}
+
+fn to_duration(nanos: i128) -> Duration {
+  Duration {
+    seconds: (nanos / NANOS_PER_SECOND) as i64,
+    nanos: (nanos % NANOS_PER_SECOND) as i32,
+  }
+}
+
+fn to_timestamp(nanos: i128) -> Timestamp {
+  Timestamp {
+    seconds: nanos.div_euclid(NANOS_PER_SECOND) as i64,
+    nanos: nanos.rem_euclid(NANOS_PER_SECOND) as i32,
+  }
+}
+
+/// Formats a value for error messages, using the Cel syntax for literals
+pub(crate) fn format_value(value: &Value) -> String {
+  match value {
+    Value::Null => "null".into(),
+    Value::Bool(v) => v.to_string(),
+    Value::Int(v) => v.to_string(),
+    Value::Uint(v) => v.to_string(),
+    Value::Double(v) => v.to_string(),
+    Value::String(v) => v.clone(),
+    Value::Bytes(v) => String::from_utf8_lossy(v).into_owned(),
+    Value::Duration(v) => format_duration(&to_duration(*v)),
+    Value::Timestamp(v) => format_timestamp(&to_timestamp(*v)),
+    Value::List(items) => format!(
+      "[{}]",
+      items
+        .iter()
+        .map(format_value)
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    Value::Map(entries) => format!(
+      "{{{}}}",
+      entries
+        .iter()
+        .map(|(k, v)| format!("{}: {}", format_value(k), format_value(v)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+    Value::Message(_) => "message".into(),
+    Value::Type(name) => name.clone(),
+  }
+}
+
+/// Compares two values with the Cel semantics, where numbers of different types are compared by their numeric value
+pub(crate) fn equals(left: &Value, right: &Value) -> bool {
+  match (left, right) {
+    (Value::List(a), Value::List(b)) => {
+      a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| equals(a, b))
+    }
+    (Value::Map(a), Value::Map(b)) => {
+      a.len() == b.len()
+        && a.iter().all(|(key, value)| {
+          b.iter()
+            .any(|(other_key, other_value)| equals(key, other_key) && equals(value, other_value))
+        })
+    }
+    (a, b) if is_number(a) && is_number(b) => compare(a, b) == Some(Ordering::Equal),
+    (a, b) => a == b,
+  }
+}
+
+fn is_number(value: &Value) -> bool {
+  matches!(value, Value::Int(_) | Value::Uint(_) | Value::Double(_))
+}
+
+/// Returns the ordering of two values, or `None` if they cannot be compared
+pub(crate) fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+  match (left, right) {
+    (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+    (Value::Uint(a), Value::Uint(b)) => Some(a.cmp(b)),
+    (Value::Int(a), Value::Uint(b)) => Some((*a as i128).cmp(&(*b as i128))),
+    (Value::Uint(a), Value::Int(b)) => Some((*a as i128).cmp(&(*b as i128))),
+    (Value::Double(a), Value::Double(b)) => a.partial_cmp(b),
+    (Value::Double(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+    (Value::Double(a), Value::Uint(b)) => a.partial_cmp(&(*b as f64)),
+    (Value::Int(a), Value::Double(b)) => (*a as f64).partial_cmp(b),
+    (Value::Uint(a), Value::Double(b)) => (*a as f64).partial_cmp(b),
+    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+    (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+    (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+    (Value::Duration(a), Value::Duration(b)) | (Value::Timestamp(a), Value::Timestamp(b)) => {
+      Some(a.cmp(b))
+    }
+    _ => None,
+  }
+}
+
+/// Returns the current time as a Cel timestamp
+pub(crate) fn now() -> Value {
+  let elapsed = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos() as i128)
+    .unwrap_or_default();
+
+  Value::Timestamp(elapsed)
+}
+
+/// Returns the default value of a field, which is used when the field is not set
+pub(crate) fn default_value(package: &PackageData, field: &FieldData) -> Value {
+  match field.kind {
+    FieldKind::Repeated => Value::List(Vec::new()),
+    _ => default_value_of(package, &field.field_type),
+  }
+}
+
+fn default_value_of(package: &PackageData, field_type: &FieldType) -> Value {
+  match field_type {
+    FieldType::Double | FieldType::Float => Value::Double(0.0),
+    FieldType::Int64
+    | FieldType::Int32
+    | FieldType::Sint32
+    | FieldType::Sint64
+    | FieldType::Sfixed32
+    | FieldType::Sfixed64
+    | FieldType::Enum(_) => Value::Int(0),
+    FieldType::Uint64 | FieldType::Uint32 | FieldType::Fixed32 | FieldType::Fixed64 => {
+      Value::Uint(0)
+    }
+    FieldType::Bool => Value::Bool(false),
+    FieldType::String => Value::String(String::new()),
+    FieldType::Bytes => Value::Bytes(Vec::new()),
+    FieldType::Duration => Value::Duration(0),
+    FieldType::Timestamp => Value::Timestamp(0),
+    FieldType::Map(_, _) => Value::Map(Vec::new()),
+    FieldType::Message(path) => Value::Message(MessageValue {
+      id: package.message_id(&path.full_name_with_package),
+      fields: Vec::new(),
+    }),
+    _ => Value::Message(MessageValue::default()),
+  }
+}
+
+/// Evaluates Cel expressions against the values of a message instance
+pub(crate) struct Interpreter<'a> {
+  package: &'a PackageData,
+  variables: Vec<(Box<str>, Value)>,
+}
+
+impl<'a> Interpreter<'a> {
+  /// Creates a new interpreter with the given value for `this`, and the optional value for `rule` (for predefined rules)
+  pub(crate) fn new(package: &'a PackageData, this: Value, rule: Option<Value>) -> Self {
+    let mut variables: Vec<(Box<str>, Value)> = vec![("this".into(), this), ("now".into(), now())];
+
+    if let Some(rule) = rule {
+      variables.push(("rule".into(), rule));
+      variables.push(("rules".into(), Value::Map(Vec::new())));
+    }
+
+    Interpreter { package, variables }
+  }
+
+  fn lookup(&self, name: &str) -> Option<&Value> {
+    self
+      .variables
+      .iter()
+      .rev()
+      .find(|(var, _)| var.as_ref() == name)
+      .map(|(_, value)| value)
+  }
+
+  /// Evaluates an expression
+  pub(crate) fn eval(&mut self, expr: &Expr) -> EvalResult {
+    match expr {
+      Expr::Literal(literal) => Ok(match literal {
+        Literal::Bool(v) => Value::Bool(*v),
+        Literal::Int(v) => Value::Int(*v),
+        Literal::Uint(v) => Value::Uint(*v),
+        Literal::Double(v) => Value::Double(*v),
+        Literal::String(v) => Value::String(v.to_string()),
+        Literal::Bytes(v) => Value::Bytes(v.to_vec()),
+        Literal::Null => Value::Null,
+      }),
+      Expr::Ident(name) => match name.as_ref() {
+        "int" | "uint" | "double" | "string" | "bytes" | "bool" | "list" | "map" | "null_type"
+        | "type" => Ok(Value::Type(name.to_string())),
+        _ => self
+          .lookup(name)
+          .cloned()
+          .ok_or_else(|| format!("undeclared reference to '{name}'")),
+      },
+      Expr::Member(target, field) => {
+        let target = self.eval(target)?;
+        self.member(&target, field)
+      }
+      Expr::Index(target, index) => {
+        let target = self.eval(target)?;
+        let index = self.eval(index)?;
+        self.index(&target, &index)
+      }
+      Expr::Call { target, name, args } => self.call(target.as_deref(), name, args),
+      Expr::Unary(op, operand) => {
+        let value = self.eval(operand)?;
+
+        match (op, value) {
+          (UnaryOp::Not, Value::Bool(v)) => Ok(Value::Bool(!v)),
+          (UnaryOp::Neg, Value::Int(v)) => v
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| "integer overflow".into()),
+          (UnaryOp::Neg, Value::Double(v)) => Ok(Value::Double(-v)),
+          (UnaryOp::Neg, Value::Duration(v)) => Ok(Value::Duration(-v)),
+          (op, value) => Err(format!(
+            "no matching overload for '{}' applied to {}",
+            if *op == UnaryOp::Not { "!" } else { "-" },
+            value.type_name()
+          )),
+        }
+      }
+      Expr::Binary(op, left, right) => self.binary(*op, left, right),
+      Expr::Conditional(condition, if_true, if_false) => match self.eval(condition)? {
+        Value::Bool(true) => self.eval(if_true),
+        Value::Bool(false) => self.eval(if_false),
+        other => Err(format!(
+          "the condition of a ternary expression must be a bool, found {}",
+          other.type_name()
+        )),
+      },
+      Expr::List(items) => Ok(Value::List(
+        items
+          .iter()
+          .map(|item| self.eval(item))
+          .collect::<Result<_, _>>()?,
+      )),
+      Expr::Map(entries) => {
+        let mut map: Vec<(Value, Value)> = Vec::new();
+
+        for (key, value) in entries {
+          let key = self.eval(key)?;
+          let value = self.eval(value)?;
+          map.push((key, value));
+        }
+
+        Ok(Value::Map(map))
+      }
+      Expr::Struct { name, .. } => Err(format!(
+        "the construction of messages ('{name}{{...}}') is not supported"
+      )),
+    }
+  }
+
+  fn member(&self, target: &Value, field: &str) -> EvalResult {
+    match target {
+      Value::Message(message) => {
+        if let Some(value) = message.get(field) {
+          return Ok(value.clone());
+        }
+
+        let definition = message
+          .id
+          .and_then(|id| self.package.find_field(id, field))
+          .ok_or_else(|| format!("no such field '{field}'"))?;
+
+        Ok(default_value(self.package, definition))
+      }
+      Value::Map(entries) => entries
+        .iter()
+        .find(|(key, _)| matches!(key, Value::String(key) if key == field))
+        .map(|(_, value)| value.clone())
+        .ok_or_else(|| format!("no such key: '{field}'")),
+      other => Err(format!(
+        "cannot select the field '{field}' from a value of type {}",
+        other.type_name()
+      )),
+    }
+  }
+
+  fn index(&self, target: &Value, index: &Value) -> EvalResult {
+    match target {
+      Value::List(items) => {
+        let position = match index {
+          Value::Int(i) => usize::try_from(*i).ok(),
+          Value::Uint(i) => usize::try_from(*i).ok(),
+          Value::Double(i) if i.fract() == 0.0 && *i >= 0.0 => Some(*i as usize),
+          other => {
+            return Err(format!(
+              "a list cannot be indexed with a value of type {}",
+              other.type_name()
+            ))
+          }
+        };
+
+        position
+          .and_then(|i| items.get(i))
+          .cloned()
+          .ok_or_else(|| format!("index out of range: {}", format_value(index)))
+      }
+      Value::Map(entries) => entries
+        .iter()
+        .find(|(key, _)| equals(key, index))
+        .map(|(_, value)| value.clone())
+        .ok_or_else(|| format!("no such key: {}", format_value(index))),
+      Value::Message(_) => match index {
+        Value::String(field) => self.member(target, field),
+        other => Err(format!(
+          "a message cannot be indexed with a value of type {}",
+          other.type_name()
+        )),
+      },
+      other => Err(format!(
+        "a value of type {} cannot be indexed",
+        other.type_name()
+      )),
+    }
+  }
+
+  fn eval_bool(&mut self, expr: &Expr) -> Result<bool, String> {
+    match self.eval(expr)? {
+      Value::Bool(v) => Ok(v),
+      other => Err(format!("expected a bool, found {}", other.type_name())),
+    }
+  }
+
+  fn binary(&mut self, op: BinaryOp, left: &Expr, right: &Expr) -> EvalResult {
+    if matches!(op, BinaryOp::And | BinaryOp::Or) {
+      // The value that determines the result regardless of the other operand
+      let absorbing = op == BinaryOp::Or;
+
+      let left = self.eval_bool(left);
+
+      if left == Ok(absorbing) {
+        return Ok(Value::Bool(absorbing));
+      }
+
+      let right = self.eval_bool(right);
+
+      return match (left, right) {
+        (_, Ok(v)) if v == absorbing => Ok(Value::Bool(absorbing)),
+        (Ok(_), Ok(v)) => Ok(Value::Bool(v)),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+      };
+    }
+
+    let left = self.eval(left)?;
+    let right = self.eval(right)?;
+
+    let mismatch = || {
+      format!(
+        "no matching overload for '{}' applied to ({}, {})",
+        op.symbol(),
+        left.type_name(),
+        right.type_name()
+      )
+    };
+
+    match op {
+      BinaryOp::Eq => Ok(Value::Bool(equals(&left, &right))),
+      BinaryOp::Ne => Ok(Value::Bool(!equals(&left, &right))),
+      BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+        let ordering = compare(&left, &right);
+
+        if ordering.is_none()
+          && !matches!(
+            (&left, &right),
+            (Value::Double(_), _) | (_, Value::Double(_))
+          )
+        {
+          return Err(mismatch());
+        }
+
+        Ok(Value::Bool(match (op, ordering) {
+          (_, None) => false,
+          (BinaryOp::Lt, Some(o)) => o == Ordering::Less,
+          (BinaryOp::Le, Some(o)) => o != Ordering::Greater,
+          (BinaryOp::Gt, Some(o)) => o == Ordering::Greater,
+          (_, Some(o)) => o != Ordering::Less,
+        }))
+      }
+      BinaryOp::In => match &right {
+        Value::List(items) => Ok(Value::Bool(items.iter().any(|item| equals(item, &left)))),
+        Value::Map(entries) => Ok(Value::Bool(
+          entries.iter().any(|(key, _)| equals(key, &left)),
+        )),
+        _ => Err(mismatch()),
+      },
+      BinaryOp::Add => match (&left, &right) {
+        (Value::Int(a), Value::Int(b)) => checked_int(a.checked_add(*b)),
+        (Value::Uint(a), Value::Uint(b)) => checked_uint(a.checked_add(*b)),
+        (Value::Double(a), Value::Double(b)) => Ok(Value::Double(a + b)),
+        (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{a}{b}"))),
+        (Value::Bytes(a), Value::Bytes(b)) => Ok(Value::Bytes([a.as_slice(), b].concat())),
+        (Value::List(a), Value::List(b)) => Ok(Value::List([a.as_slice(), b].concat())),
+        (Value::Duration(a), Value::Duration(b)) => Ok(Value::Duration(a + b)),
+        (Value::Timestamp(a), Value::Duration(b)) | (Value::Duration(b), Value::Timestamp(a)) => {
+          Ok(Value::Timestamp(a + b))
+        }
+        _ => Err(mismatch()),
+      },
+      BinaryOp::Sub => match (&left, &right) {
+        (Value::Int(a), Value::Int(b)) => checked_int(a.checked_sub(*b)),
+        (Value::Uint(a), Value::Uint(b)) => checked_uint(a.checked_sub(*b)),
+        (Value::Double(a), Value::Double(b)) => Ok(Value::Double(a - b)),
+        (Value::Duration(a), Value::Duration(b)) => Ok(Value::Duration(a - b)),
+        (Value::Timestamp(a), Value::Duration(b)) => Ok(Value::Timestamp(a - b)),
+        (Value::Timestamp(a), Value::Timestamp(b)) => Ok(Value::Duration(a - b)),
+        _ => Err(mismatch()),
+      },
+      BinaryOp::Mul => match (&left, &right) {
+        (Value::Int(a), Value::Int(b)) => checked_int(a.checked_mul(*b)),
+        (Value::Uint(a), Value::Uint(b)) => checked_uint(a.checked_mul(*b)),
+        (Value::Double(a), Value::Double(b)) => Ok(Value::Double(a * b)),
+        _ => Err(mismatch()),
+      },
+      BinaryOp::Div => match (&left, &right) {
+        (Value::Int(_), Value::Int(0)) | (Value::Uint(_), Value::Uint(0)) => {
+          Err("division by zero".into())
+        }
+        (Value::Int(a), Value::Int(b)) => checked_int(a.checked_div(*b)),
+        (Value::Uint(a), Value::Uint(b)) => checked_uint(a.checked_div(*b)),
+        (Value::Double(a), Value::Double(b)) => Ok(Value::Double(a / b)),
+        _ => Err(mismatch()),
+      },
+      BinaryOp::Rem => match (&left, &right) {
+        (Value::Int(_), Value::Int(0)) | (Value::Uint(_), Value::Uint(0)) => {
+          Err("modulus by zero".into())
+        }
+        (Value::Int(a), Value::Int(b)) => checked_int(a.checked_rem(*b)),
+        (Value::Uint(a), Value::Uint(b)) => checked_uint(a.checked_rem(*b)),
+        _ => Err(mismatch()),
+      },
+      BinaryOp::And | BinaryOp::Or => unreachable!(),
+    }
+  }
+
+  fn call(&mut self, target: Option<&Expr>, name: &str, args: &[Expr]) -> EvalResult {
+    // Macros
+    if name == "has" && target.is_none() {
+      let [Expr::Member(target, field)] = args else {
+        return Err("the argument of 'has' must be a field selection".into());
+      };
+
+      return match self.eval(target)? {
+        Value::Message(message) => Ok(Value::Bool(message.get(field).is_some())),
+        Value::Map(entries) => Ok(Value::Bool(entries.iter().any(
+          |(key, _)| matches!(key, Value::String(key) if key.as_str() == field.as_ref()),
+        ))),
+        other => Err(format!(
+          "'has' cannot be applied to a value of type {}",
+          other.type_name()
+        )),
+      };
+    }
+
+    if let Some(target) = target
+      && matches!(name, "all" | "exists" | "exists_one" | "map" | "filter")
+    {
+      return self.comprehension(target, name, args);
+    }
+
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(target) = target {
+      values.push(self.eval(target)?);
+    }
+
+    for arg in args {
+      values.push(self.eval(arg)?);
+    }
+
+    let receiver = if target.is_some() { "." } else { "" };
+    let no_overload = |values: &[Value]| {
+      format!(
+        "no matching overload for '{receiver}{name}' applied to ({})",
+        values
+          .iter()
+          .map(Value::type_name)
+          .collect::<Vec<_>>()
+          .join(", ")
+      )
+    };
+
+    use Value::*;
+
+    let result = match (name, values.as_slice()) {
+      ("size", [String(v)]) => Int(v.chars().count() as i64),
+      ("size", [Bytes(v)]) => Int(v.len() as i64),
+      ("size", [List(v)]) => Int(v.len() as i64),
+      ("size", [Map(v)]) => Int(v.len() as i64),
+      ("int" | "uint" | "double" | "string" | "bytes" | "bool" | "duration" | "timestamp", [v])
+        if target.is_none() =>
+      {
+        convert(name, v)?
+      }
+      ("dyn", [v]) => v.clone(),
+      ("type", [v]) => Type(v.type_name().into()),
+      ("matches", [String(v), String(pattern)]) => Bool(
+        Regex::new(pattern)
+          .map_err(|e| format!("invalid regex: {e}"))?
+          .is_match(v),
+      ),
+      ("contains", [String(v), String(sub)]) => Bool(v.contains(sub.as_str())),
+      ("contains", [Bytes(v), Bytes(sub)]) => {
+        Bool(sub.is_empty() || v.windows(sub.len()).any(|w| w == sub.as_slice()))
+      }
+      ("startsWith", [String(v), String(prefix)]) => Bool(v.starts_with(prefix.as_str())),
+      ("startsWith", [Bytes(v), Bytes(prefix)]) => Bool(v.starts_with(prefix)),
+      ("endsWith", [String(v), String(suffix)]) => Bool(v.ends_with(suffix.as_str())),
+      ("endsWith", [Bytes(v), Bytes(suffix)]) => Bool(v.ends_with(suffix)),
+      ("isEmail", [String(v)]) => Bool(formats::is_email(v)),
+      ("isHostname", [String(v)]) => Bool(formats::is_hostname(v)),
+      ("isIp", [String(v)]) => Bool(formats::is_ip(v, 0)),
+      ("isIp", [String(v), Int(version)]) => Bool(formats::is_ip(v, *version)),
+      ("isIpPrefix", [String(v)]) => Bool(formats::is_ip_prefix(v, 0, false)),
+      ("isIpPrefix", [String(v), Int(version)]) => Bool(formats::is_ip_prefix(v, *version, false)),
+      ("isIpPrefix", [String(v), Int(version), Bool(strict)]) => {
+        Bool(formats::is_ip_prefix(v, *version, *strict))
+      }
+      ("isUri", [String(v)]) => Bool(formats::is_uri(v)),
+      ("isUriRef", [String(v)]) => Bool(formats::is_uri_ref(v)),
+      ("isHostAndPort", [String(v), Bool(port_required)]) => {
+        Bool(formats::is_host_and_port(v, *port_required))
+      }
+      ("lowerAscii", [String(v)]) => String(v.to_ascii_lowercase()),
+      ("upperAscii", [String(v)]) => String(v.to_ascii_uppercase()),
+      ("trim", [String(v)]) => String(v.trim().into()),
+      ("replace", [String(v), String(from), String(to)]) => String(v.replace(from.as_str(), to)),
+      ("replace", [String(v), String(from), String(to), Int(n)]) => {
+        if *n < 0 {
+          String(v.replace(from.as_str(), to))
+        } else {
+          String(v.replacen(from.as_str(), to, *n as usize))
+        }
+      }
+      ("split", [String(v), String(separator)]) => List(
+        v.split(separator.as_str())
+          .map(|s| String(s.into()))
+          .collect(),
+      ),
+      ("split", [String(v), String(separator), Int(n)]) => match *n {
+        0 => List(Vec::new()),
+        n if n < 0 => List(
+          v.split(separator.as_str())
+            .map(|s| String(s.into()))
+            .collect(),
+        ),
+        n => List(
+          v.splitn(n as usize, separator.as_str())
+            .map(|s| String(s.into()))
+            .collect(),
+        ),
+      },
+      ("substring", [String(v), Int(start)]) => String(substring(v, *start, None)?),
+      ("substring", [String(v), Int(start), Int(end)]) => String(substring(v, *start, Some(*end))?),
+      ("indexOf", [String(v), String(sub)]) => Int(
+        v.find(sub.as_str())
+          .map(|i| v[..i].chars().count() as i64)
+          .unwrap_or(-1),
+      ),
+      ("lastIndexOf", [String(v), String(sub)]) => Int(
+        v.rfind(sub.as_str())
+          .map(|i| v[..i].chars().count() as i64)
+          .unwrap_or(-1),
+      ),
+      ("charAt", [String(v), Int(i)]) => {
+        let count = v.chars().count() as i64;
+
+        if *i < 0 || *i > count {
+          return Err(format!("index out of range: {i}"));
+        }
+
+        String(
+          v.chars()
+            .nth(*i as usize)
+            .map(|c| c.to_string())
+            .unwrap_or_default(),
+        )
+      }
+      ("format", [String(template), List(args)]) => String(format_string(template, args)?),
+      ("reverse", [String(v)]) => String(v.chars().rev().collect()),
+      ("join", [List(items)]) => String(join_strings(items, "")?),
+      ("join", [List(items), String(separator)]) => String(join_strings(items, separator)?),
+      ("unique", [List(items)]) => Bool(
+        items
+          .iter()
+          .enumerate()
+          .all(|(i, item)| !items[..i].iter().any(|other| equals(item, other))),
+      ),
+      ("isNan", [Double(v)]) => Bool(v.is_nan()),
+      ("isInf", [Double(v)]) => Bool(v.is_infinite()),
+      ("isInf", [Double(v), Int(sign)]) => Bool(match sign.cmp(&0) {
+        Ordering::Greater => *v == f64::INFINITY,
+        Ordering::Less => *v == f64::NEG_INFINITY,
+        Ordering::Equal => v.is_infinite(),
+      }),
+      (
+        "getFullYear" | "getMonth" | "getDate" | "getDayOfMonth" | "getDayOfWeek" | "getDayOfYear"
+        | "getHours" | "getMinutes" | "getSeconds" | "getMilliseconds",
+        [Timestamp(v), rest @ ..],
+      ) if rest.len() <= 1 => {
+        let offset = match rest {
+          [String(timezone)] => parse_utc_offset(timezone)?,
+          [] => 0,
+          _ => return Err(no_overload(&values)),
+        };
+
+        Int(timestamp_component(
+          name,
+          *v + offset as i128 * NANOS_PER_SECOND,
+        ))
+      }
+      ("getHours", [Duration(v)]) => Int((*v / (3600 * NANOS_PER_SECOND)) as i64),
+      ("getMinutes", [Duration(v)]) => Int((*v / (60 * NANOS_PER_SECOND)) as i64),
+      ("getSeconds", [Duration(v)]) => Int((*v / NANOS_PER_SECOND) as i64),
+      ("getMilliseconds", [Duration(v)]) => Int((*v / 1_000_000) as i64),
+      _ => return Err(no_overload(&values)),
+    };
+
+    Ok(result)
+  }
+
+  fn comprehension(&mut self, target: &Expr, name: &str, args: &[Expr]) -> EvalResult {
+    let items = match self.eval(target)? {
+      Value::List(items) => items,
+      Value::Map(entries) => entries.into_iter().map(|(key, _)| key).collect(),
+      other => {
+        return Err(format!(
+          "'{name}' cannot be applied to a value of type {}",
+          other.type_name()
+        ))
+      }
+    };
+
+    let (Some(Expr::Ident(variable)), true) = (
+      args.first(),
+      args.len() == 2 || (name == "map" && args.len() == 3),
+    ) else {
+      return Err(format!("invalid arguments for '{name}'"));
+    };
+
+    let mut results: Vec<Value> = Vec::new();
+    let mut matches: usize = 0;
+    let mut error: Option<String> = None;
+
+    for item in items {
+      self.variables.push((variable.clone(), item.clone()));
+      let result = self.comprehension_step(name, args);
+      self.variables.pop();
+
+      match (name, result) {
+        ("all", Ok(Some(false))) => return Ok(Value::Bool(false)),
+        ("exists", Ok(Some(true))) => return Ok(Value::Bool(true)),
+        ("all" | "exists", Err(e)) => error = Some(e),
+        (_, Err(e)) => return Err(e),
+        ("exists_one", Ok(Some(true))) => matches += 1,
+        ("filter", Ok(Some(true))) => results.push(item),
+        ("map", Ok(Some(true))) => {
+          self.variables.push((variable.clone(), item));
+          let transformed = self.eval(args.last().unwrap());
+          self.variables.pop();
+          results.push(transformed?);
+        }
+        _ => {}
+      }
+    }
+
+    if let Some(error) = error {
+      return Err(error);
+    }
+
+    Ok(match name {
+      "all" => Value::Bool(true),
+      "exists" => Value::Bool(false),
+      "exists_one" => Value::Bool(matches == 1),
+      _ => Value::List(results),
+    })
+  }
+
+  // Evaluates the predicate of a comprehension for the current item (`map` without a filter always matches)
+  fn comprehension_step(&mut self, name: &str, args: &[Expr]) -> Result<Option<bool>, String> {
+    if name == "map" && args.len() == 2 {
+      return Ok(Some(true));
+    }
+
+    self.eval_bool(&args[1]).map(Some)
+  }
+}
+
+fn checked_int(value: Option<i64>) -> EvalResult {
+  value
+    .map(Value::Int)
+    .ok_or_else(|| "integer overflow".into())
+}
+
+fn checked_uint(value: Option<u64>) -> EvalResult {
+  value
+    .map(Value::Uint)
+    .ok_or_else(|| "unsigned integer overflow".into())
+}
+
+fn substring(value: &str, start: i64, end: Option<i64>) -> Result<String, String> {
+  let count = value.chars().count() as i64;
+  let end = end.unwrap_or(count);
+
+  if start < 0 || end > count || start > end {
+    return Err(format!("substring index out of range: [{start}, {end})"));
+  }
+
+  Ok(
+    value
+      .chars()
+      .skip(start as usize)
+      .take((end - start) as usize)
+      .collect(),
+  )
+}
+
+fn join_strings(items: &[Value], separator: &str) -> Result<String, String> {
+  let strings = items
+    .iter()
+    .map(|item| match item {
+      Value::String(v) => Ok(v.as_str()),
+      other => Err(format!(
+        "'join' expects a list of strings, found {}",
+        other.type_name()
+      )),
+    })
+    .collect::<Result<Vec<&str>, String>>()?;
+
+  Ok(strings.join(separator))
+}
+
+// Implements the `format` function of the strings extension, with the %s, %d, %f, %e, %x, %X, %o and %b verbs
+fn format_string(template: &str, args: &[Value]) -> Result<String, String> {
+  let mut result = String::new();
+  let mut chars = template.chars().peekable();
+  let mut args = args.iter();
+
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      result.push(c);
+      continue;
+    }
+
+    if chars.peek() == Some(&'%') {
+      chars.next();
+      result.push('%');
+      continue;
+    }
+
+    let mut precision: Option<usize> = None;
+
+    if chars.peek() == Some(&'.') {
+      chars.next();
+      let mut digits = String::new();
+
+      while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+      }
+
+      precision = Some(
+        digits
+          .parse()
+          .map_err(|_| "invalid precision in format string")?,
+      );
+    }
+
+    let verb = chars.next().ok_or("unterminated format verb")?;
+    let arg = args
+      .next()
+      .ok_or_else(|| format!("missing argument for the format verb '%{verb}'"))?;
+    let invalid = || {
+      format!(
+        "the format verb '%{verb}' cannot be applied to {}",
+        arg.type_name()
+      )
+    };
+
+    let formatted = match (verb, arg) {
+      ('s', arg) => format_value(arg),
+      ('d', Value::Int(v)) => v.to_string(),
+      ('d', Value::Uint(v)) => v.to_string(),
+      ('f' | 'e', Value::Int(_) | Value::Uint(_) | Value::Double(_)) => {
+        let v = match arg {
+          Value::Int(v) => *v as f64,
+          Value::Uint(v) => *v as f64,
+          Value::Double(v) => *v,
+          _ => unreachable!(),
+        };
+        let precision = precision.unwrap_or(6);
+
+        if verb == 'f' {
+          format!("{v:.precision$}")
+        } else {
+          // Rust omits the sign and the padding of the exponent, which Cel always includes
+          let formatted = format!("{v:.precision$e}");
+          let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+          let (sign, digits) = match exponent.strip_prefix('-') {
+            Some(digits) => ('-', digits),
+            None => ('+', exponent),
+          };
+
+          format!("{mantissa}e{sign}{digits:0>2}")
+        }
+      }
+      ('x', Value::Int(v)) => format_signed(*v, |n| format!("{n:x}")),
+      ('X', Value::Int(v)) => format_signed(*v, |n| format!("{n:X}")),
+      ('o', Value::Int(v)) => format_signed(*v, |n| format!("{n:o}")),
+      ('b', Value::Int(v)) => format_signed(*v, |n| format!("{n:b}")),
+      ('x', Value::Uint(v)) => format!("{v:x}"),
+      ('X', Value::Uint(v)) => format!("{v:X}"),
+      ('o', Value::Uint(v)) => format!("{v:o}"),
+      ('b', Value::Uint(v)) => format!("{v:b}"),
+      ('x', Value::String(v)) => v.bytes().map(|b| format!("{b:02x}")).collect(),
+      ('X', Value::String(v)) => v.bytes().map(|b| format!("{b:02X}")).collect(),
+      ('x', Value::Bytes(v)) => v.iter().map(|b| format!("{b:02x}")).collect(),
+      ('X', Value::Bytes(v)) => v.iter().map(|b| format!("{b:02X}")).collect(),
+      ('b', Value::Bool(v)) => v.to_string(),
+      _ => return Err(invalid()),
+    };
+
+    result.push_str(&formatted);
+  }
+
+  Ok(result)
+}
+
+// Formats the absolute value of a signed integer, keeping its sign in front of it
+fn format_signed<F: Fn(u64) -> String>(value: i64, format: F) -> String {
+  let sign = if value < 0 { "-" } else { "" };
+
+  format!("{sign}{}", format(value.unsigned_abs()))
+}
+
+// Only the UTC offsets (like "+02:00") and "UTC" are supported as time zones
+fn parse_utc_offset(timezone: &str) -> Result<i64, String> {
+  if timezone == "UTC" || timezone == "Z" {
+    return Ok(0);
+  }
+
+  let invalid = || format!("unsupported time zone '{timezone}'");
+  let sign = match timezone.chars().next() {
+    Some('+') => 1,
+    Some('-') => -1,
+    _ => return Err(invalid()),
+  };
+
+  let (hours, minutes) = timezone[1..].split_once(':').ok_or_else(invalid)?;
+  let hours: i64 = hours.parse().map_err(|_| invalid())?;
+  let minutes: i64 = minutes.parse().map_err(|_| invalid())?;
+
+  Ok(sign * (hours * 3600 + minutes * 60))
+}
+
+fn timestamp_component(name: &str, nanos: i128) -> i64 {
+  let seconds = nanos.div_euclid(NANOS_PER_SECOND) as i64;
+  let days = seconds.div_euclid(86_400);
+  let seconds_of_day = seconds.rem_euclid(86_400);
+  let (year, month, day) = civil_from_days(days);
+
+  match name {
+    "getFullYear" => year,
+    "getMonth" => month as i64 - 1,
+    "getDate" => day as i64,
+    "getDayOfMonth" => day as i64 - 1,
+    // The 1st of January 1970 was a Thursday
+    "getDayOfWeek" => (days + 4).rem_euclid(7),
+    "getDayOfYear" => days - crate::json::days_from_civil(year, 1, 1),
+    "getHours" => seconds_of_day / 3600,
+    "getMinutes" => (seconds_of_day % 3600) / 60,
+    "getSeconds" => seconds_of_day % 60,
+    _ => (nanos.rem_euclid(NANOS_PER_SECOND) / 1_000_000) as i64,
+  }
+}
+
+fn convert(name: &str, value: &Value) -> EvalResult {
+  use Value::*;
+
+  let invalid = || {
+    format!(
+      "cannot convert {} ({}) to {name}",
+      format_value(value),
+      value.type_name()
+    )
+  };
+
+  let result = match (name, value) {
+    ("int", Int(v)) => Int(*v),
+    ("int", Uint(v)) => Int(i64::try_from(*v).map_err(|_| invalid())?),
+    ("int", Double(v)) if v.is_finite() && *v >= i64::MIN as f64 && *v < i64::MAX as f64 => {
+      Int(*v as i64)
+    }
+    ("int", String(v)) => Int(v.parse().map_err(|_| invalid())?),
+    ("int", Timestamp(v)) => Int(v.div_euclid(NANOS_PER_SECOND) as i64),
+    ("uint", Uint(v)) => Uint(*v),
+    ("uint", Int(v)) => Uint(u64::try_from(*v).map_err(|_| invalid())?),
+    ("uint", Double(v)) if v.is_finite() && *v >= 0.0 && *v < u64::MAX as f64 => Uint(*v as u64),
+    ("uint", String(v)) => Uint(v.parse().map_err(|_| invalid())?),
+    ("double", Double(v)) => Double(*v),
+    ("double", Int(v)) => Double(*v as f64),
+    ("double", Uint(v)) => Double(*v as f64),
+    ("double", String(v)) => Double(v.parse().map_err(|_| invalid())?),
+    ("string", String(v)) => String(v.clone()),
+    ("string", Bytes(v)) => {
+      String(std::string::String::from_utf8(v.clone()).map_err(|_| invalid())?)
+    }
+    ("string", Int(_) | Uint(_) | Double(_) | Bool(_) | Duration(_) | Timestamp(_)) => {
+      String(format_value(value))
+    }
+    ("bytes", Bytes(v)) => Bytes(v.clone()),
+    ("bytes", String(v)) => Bytes(v.as_bytes().to_vec()),
+    ("bool", Bool(v)) => Bool(*v),
+    ("bool", String(v)) => match v.as_str() {
+      "true" | "True" | "TRUE" | "t" | "1" => Bool(true),
+      "false" | "False" | "FALSE" | "f" | "0" => Bool(false),
+      _ => return Err(invalid()),
+    },
+    ("duration", Duration(v)) => Duration(*v),
+    ("duration", String(v)) => Duration(parse_cel_duration(v).ok_or_else(invalid)?),
+    ("timestamp", Timestamp(v)) => Timestamp(*v),
+    ("timestamp", Int(v)) => Timestamp(*v as i128 * NANOS_PER_SECOND),
+    ("timestamp", String(v)) => Value::from_timestamp(&parse_timestamp(v).ok_or_else(invalid)?),
+    _ => return Err(invalid()),
+  };
+
+  Ok(result)
+}
+
+/// Parses a Cel duration string, which is a sequence of decimal numbers with a unit (h, m, s, ms, us or ns), such as "1h30m" or "-1.5s"
+fn parse_cel_duration(input: &str) -> Option<i128> {
+  let (sign, mut rest) = match input.strip_prefix('-') {
+    Some(rest) => (-1, rest),
+    None => (1, input.strip_prefix('+').unwrap_or(input)),
+  };
+
+  if rest == "0" {
+    return Some(0);
+  }
+
+  if rest.is_empty() {
+    return None;
+  }
+
+  let mut total: f64 = 0.0;
+
+  while !rest.is_empty() {
+    let number_end = rest
+      .find(|c: char| !c.is_ascii_digit() && c != '.')
+      .filter(|i| *i > 0)?;
+    let (number, after_number) = rest.split_at(number_end);
+    let number: f64 = number.parse().ok()?;
+
+    let unit_end = after_number
+      .find(|c: char| c.is_ascii_digit() || c == '.')
+      .unwrap_or(after_number.len());
+    let (unit, after_unit) = after_number.split_at(unit_end);
+
+    let multiplier: f64 = match unit {
+      "h" => 3600e9,
+      "m" => 60e9,
+      "s" => 1e9,
+      "ms" => 1e6,
+      "us" | "µs" => 1e3,
+      "ns" => 1.0,
+      _ => return None,
+    };
+
+    total += number * multiplier;
+    rest = after_unit;
+  }
+
+  Some(sign * total.round() as i128)
+}
@@ -0,0 +1,363 @@
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+  Ident(Box<str>),
+  // Unsigned integer literals without the `u` suffix, which become ints after the sign is applied
+  Int(u64),
+  Uint(u64),
+  Double(f64),
+  String(Box<str>),
+  Bytes(Box<[u8]>),
+  LParen,
+  RParen,
+  LBracket,
+  RBracket,
+  LBrace,
+  RBrace,
+  Dot,
+  Comma,
+  Colon,
+  Question,
+  Not,
+  Minus,
+  Plus,
+  Star,
+  Slash,
+  Percent,
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  And,
+  Or,
+  In,
+}
+
+pub(crate) fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+  let chars: Vec<char> = input.chars().collect();
+  let mut tokens: Vec<Token> = Vec::new();
+  let mut i = 0;
+
+  while i < chars.len() {
+    let c = chars[i];
+
+    if c.is_whitespace() {
+      i += 1;
+      continue;
+    }
+
+    // Comments
+    if c == '/' && chars.get(i + 1) == Some(&'/') {
+      while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+      }
+      continue;
+    }
+
+    let two_chars: Option<Token> = match (c, chars.get(i + 1)) {
+      ('=', Some('=')) => Some(Token::Eq),
+      ('!', Some('=')) => Some(Token::Ne),
+      ('<', Some('=')) => Some(Token::Le),
+      ('>', Some('=')) => Some(Token::Ge),
+      ('&', Some('&')) => Some(Token::And),
+      ('|', Some('|')) => Some(Token::Or),
+      _ => None,
+    };
+
+    if let Some(token) = two_chars {
+      tokens.push(token);
+      i += 2;
+      continue;
+    }
+
+    let single_char: Option<Token> = match c {
+      '(' => Some(Token::LParen),
+      ')' => Some(Token::RParen),
+      '[' => Some(Token::LBracket),
+      ']' => Some(Token::RBracket),
+      '{' => Some(Token::LBrace),
+      '}' => Some(Token::RBrace),
+      ',' => Some(Token::Comma),
+      ':' => Some(Token::Colon),
+      '?' => Some(Token::Question),
+      '!' => Some(Token::Not),
+      '-' => Some(Token::Minus),
+      '+' => Some(Token::Plus),
+      '*' => Some(Token::Star),
+      '/' => Some(Token::Slash),
+      '%' => Some(Token::Percent),
+      '<' => Some(Token::Lt),
+      '>' => Some(Token::Gt),
+      '.' if !chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => Some(Token::Dot),
+      _ => None,
+    };
+
+    if let Some(token) = single_char {
+      tokens.push(token);
+      i += 1;
+      continue;
+    }
+
+    if c.is_ascii_digit() || c == '.' {
+      let (token, end) = lex_number(&chars, i)?;
+      tokens.push(token);
+      i = end;
+      continue;
+    }
+
+    if c.is_ascii_alphabetic() || c == '_' {
+      let start = i;
+      while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+        i += 1;
+      }
+      let ident: String = chars[start..i].iter().collect();
+
+      // String and bytes prefixes
+      if i < chars.len() && (chars[i] == '\'' || chars[i] == '"') {
+        let prefix = ident.to_ascii_lowercase();
+        let (is_raw, is_bytes) = match prefix.as_str() {
+          "r" => (true, false),
+          "b" => (false, true),
+          "rb" | "br" => (true, true),
+          _ => return Err(format!("unexpected string prefix '{ident}'")),
+        };
+
+        if is_bytes {
+          let (value, end) = lex_quoted::<Vec<u8>>(&chars, i, is_raw)?;
+          tokens.push(Token::Bytes(value.into_boxed_slice()));
+          i = end;
+        } else {
+          let (value, end) = lex_quoted::<String>(&chars, i, is_raw)?;
+          tokens.push(Token::String(value.into()));
+          i = end;
+        }
+        continue;
+      }
+
+      if ident == "in" {
+        tokens.push(Token::In);
+      } else {
+        tokens.push(Token::Ident(ident.into()));
+      }
+      continue;
+    }
+
+    if c == '\'' || c == '"' {
+      let (value, end) = lex_quoted::<String>(&chars, i, false)?;
+      tokens.push(Token::String(value.into()));
+      i = end;
+      continue;
+    }
+
+    return Err(format!("unexpected character '{c}'"));
+  }
+
+  Ok(tokens)
+}
+
+fn lex_number(chars: &[char], start: usize) -> Result<(Token, usize), String> {
+  let mut i = start;
+
+  if chars[i] == '0' && matches!(chars.get(i + 1), Some('x') | Some('X')) {
+    i += 2;
+    let digits_start = i;
+    while i < chars.len() && chars[i].is_ascii_hexdigit() {
+      i += 1;
+    }
+    let digits: String = chars[digits_start..i].iter().collect();
+    let value = u64::from_str_radix(&digits, 16)
+      .map_err(|_| format!("invalid hexadecimal literal '0x{digits}'"))?;
+
+    return Ok(int_token(chars, i, value));
+  }
+
+  let mut is_float = false;
+
+  while i < chars.len() && chars[i].is_ascii_digit() {
+    i += 1;
+  }
+
+  if i < chars.len() && chars[i] == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) {
+    is_float = true;
+    i += 1;
+    while i < chars.len() && chars[i].is_ascii_digit() {
+      i += 1;
+    }
+  }
+
+  if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+    let mut j = i + 1;
+    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+      j += 1;
+    }
+    if j < chars.len() && chars[j].is_ascii_digit() {
+      is_float = true;
+      i = j;
+      while i < chars.len() && chars[i].is_ascii_digit() {
+        i += 1;
+      }
+    }
+  }
+
+  let text: String = chars[start..i].iter().collect();
+
+  if is_float {
+    let value: f64 = text
+      .parse()
+      .map_err(|_| format!("invalid number literal '{text}'"))?;
+    return Ok((Token::Double(value), i));
+  }
+
+  let value: u64 = text
+    .parse()
+    .map_err(|_| format!("integer literal '{text}' is out of range"))?;
+
+  Ok(int_token(chars, i, value))
+}
+
+fn int_token(chars: &[char], end: usize, value: u64) -> (Token, usize) {
+  if end < chars.len() && (chars[end] == 'u' || chars[end] == 'U') {
+    (Token::Uint(value), end + 1)
+  } else {
+    (Token::Int(value), end)
+  }
+}
+
+/// The contents of a string or bytes literal, which only differ in how the escape sequences are stored
+trait QuotedBuffer: Default {
+  fn push_char(&mut self, c: char);
+
+  fn push_escape(&mut self, escape: Escape) -> Result<(), String>;
+}
+
+impl QuotedBuffer for String {
+  fn push_char(&mut self, c: char) {
+    self.push(c);
+  }
+
+  fn push_escape(&mut self, escape: Escape) -> Result<(), String> {
+    match escape {
+      Escape::Char(c) | Escape::Unicode(c) => self.push(c),
+      // In strings, the octets are interpreted as code points
+      Escape::Octet(octet) => self.push(char::from(octet)),
+    }
+
+    Ok(())
+  }
+}
+
+impl QuotedBuffer for Vec<u8> {
+  fn push_char(&mut self, c: char) {
+    self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+  }
+
+  fn push_escape(&mut self, escape: Escape) -> Result<(), String> {
+    match escape {
+      Escape::Char(c) => self.push_char(c),
+      Escape::Octet(octet) => self.push(octet),
+      Escape::Unicode(_) => return Err("unicode escapes are not allowed in bytes literals".into()),
+    }
+
+    Ok(())
+  }
+}
+
+fn lex_quoted<B: QuotedBuffer>(
+  chars: &[char],
+  start: usize,
+  is_raw: bool,
+) -> Result<(B, usize), String> {
+  let quote = chars[start];
+  let is_triple = chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+  let mut i = if is_triple { start + 3 } else { start + 1 };
+  let mut value = B::default();
+
+  loop {
+    let Some(&c) = chars.get(i) else {
+      return Err("unterminated string literal".into());
+    };
+
+    if is_triple {
+      if c == quote && chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote) {
+        return Ok((value, i + 3));
+      }
+    } else if c == quote {
+      return Ok((value, i + 1));
+    } else if c == '\n' {
+      return Err("unterminated string literal".into());
+    }
+
+    if c == '\\' && !is_raw {
+      let (escaped, end) = lex_escape(chars, i + 1)?;
+      value.push_escape(escaped)?;
+      i = end;
+      continue;
+    }
+
+    value.push_char(c);
+    i += 1;
+  }
+}
+
+enum Escape {
+  Char(char),
+  // The `\u` and `\U` escapes
+  Unicode(char),
+  // The hexadecimal and octal escapes, which are single octets in bytes literals
+  Octet(u8),
+}
+
+fn lex_escape(chars: &[char], start: usize) -> Result<(Escape, usize), String> {
+  let Some(&c) = chars.get(start) else {
+    return Err("unterminated escape sequence".into());
+  };
+
+  let simple = match c {
+    '\\' => Some('\\'),
+    '\'' => Some('\''),
+    '"' => Some('"'),
+    '`' => Some('`'),
+    '?' => Some('?'),
+    'a' => Some('\u{07}'),
+    'b' => Some('\u{08}'),
+    'f' => Some('\u{0C}'),
+    'n' => Some('\n'),
+    'r' => Some('\r'),
+    't' => Some('\t'),
+    'v' => Some('\u{0B}'),
+    _ => None,
+  };
+
+  if let Some(escaped) = simple {
+    return Ok((Escape::Char(escaped), start + 1));
+  }
+
+  let (radix, len, digits_start) = match c {
+    'x' | 'X' => (16, 2, start + 1),
+    'u' => (16, 4, start + 1),
+    'U' => (16, 8, start + 1),
+    '0'..='3' => (8, 3, start),
+    _ => return Err(format!("invalid escape sequence '\\{c}'")),
+  };
+
+  let digits: String = chars
+    .get(digits_start..digits_start + len)
+    .ok_or("unterminated escape sequence")?
+    .iter()
+    .collect();
+
+  let code = u32::from_str_radix(&digits, radix)
+    .map_err(|_| format!("invalid escape sequence '\\{c}{digits}'"))?;
+
+  let escaped = if matches!(c, 'u' | 'U') {
+    Escape::Unicode(
+      char::from_u32(code).ok_or_else(|| format!("invalid code point in '\\{c}{digits}'"))?,
+    )
+  } else {
+    // Two hexadecimal digits or three octal digits starting with 0-3 always fit in a byte
+    Escape::Octet(code as u8)
+  };
+
+  Ok((escaped, digits_start + len))
+}
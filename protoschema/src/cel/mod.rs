@@ -0,0 +1,15 @@
+// A small implementation of the Cel language, used to check the Cel rules defined in a schema and to evaluate them locally
+mod checker;
+#[cfg(feature = "serde")]
+pub(crate) mod formats;
+#[cfg(feature = "serde")]
+mod interpreter;
+mod lexer;
+mod parser;
+
+pub(crate) use checker::{CelType, CheckError, Checker};
+#[cfg(feature = "serde")]
+pub(crate) use interpreter::{
+  compare, default_value, equals, format_value, now, Interpreter, MessageValue, Value,
+};
+pub(crate) use parser::parse;
@@ -0,0 +1,410 @@
+use super::lexer::{tokenize, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Literal {
+  Bool(bool),
+  Int(i64),
+  Uint(u64),
+  Double(f64),
+  String(Box<str>),
+  Bytes(Box<[u8]>),
+  Null,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnaryOp {
+  Not,
+  Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+  Or,
+  And,
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  In,
+  Add,
+  Sub,
+  Mul,
+  Div,
+  Rem,
+}
+
+impl BinaryOp {
+  pub(crate) fn symbol(&self) -> &'static str {
+    match self {
+      BinaryOp::Or => "||",
+      BinaryOp::And => "&&",
+      BinaryOp::Eq => "==",
+      BinaryOp::Ne => "!=",
+      BinaryOp::Lt => "<",
+      BinaryOp::Le => "<=",
+      BinaryOp::Gt => ">",
+      BinaryOp::Ge => ">=",
+      BinaryOp::In => "in",
+      BinaryOp::Add => "+",
+      BinaryOp::Sub => "-",
+      BinaryOp::Mul => "*",
+      BinaryOp::Div => "/",
+      BinaryOp::Rem => "%",
+    }
+  }
+}
+
+/// The syntax tree of a Cel expression.
+/// Macros such as `has` or `all` are represented as regular calls.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+  Literal(Literal),
+  Ident(Box<str>),
+  Member(Box<Expr>, Box<str>),
+  Index(Box<Expr>, Box<Expr>),
+  Call {
+    target: Option<Box<Expr>>,
+    name: Box<str>,
+    args: Vec<Expr>,
+  },
+  Unary(UnaryOp, Box<Expr>),
+  Binary(BinaryOp, Box<Expr>, Box<Expr>),
+  Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+  List(Vec<Expr>),
+  Map(Vec<(Expr, Expr)>),
+  // Message construction (`google.protobuf.Duration{seconds: 5}`), which is parsed but neither type-checked nor evaluated
+  Struct {
+    name: Box<str>,
+    fields: Vec<(Box<str>, Expr)>,
+  },
+}
+
+/// Parses a Cel expression
+pub(crate) fn parse(input: &str) -> Result<Expr, String> {
+  let tokens = tokenize(input)?;
+  let mut parser = Parser { tokens, pos: 0 };
+
+  let expr = parser.expr()?;
+
+  if let Some(token) = parser.peek() {
+    return Err(format!("unexpected token {token:?}"));
+  }
+
+  Ok(expr)
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  pos: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    token
+  }
+
+  fn eat(&mut self, token: &Token) -> bool {
+    if self.peek() == Some(token) {
+      self.pos += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, token: &Token) -> Result<(), String> {
+    match self.next() {
+      Some(next) if &next == token => Ok(()),
+      Some(next) => Err(format!("expected {token:?}, found {next:?}")),
+      None => Err(format!(
+        "expected {token:?}, found the end of the expression"
+      )),
+    }
+  }
+
+  fn expr(&mut self) -> Result<Expr, String> {
+    let condition = self.or()?;
+
+    if self.eat(&Token::Question) {
+      let if_true = self.or()?;
+      self.expect(&Token::Colon)?;
+      let if_false = self.expr()?;
+
+      return Ok(Expr::Conditional(
+        Box::new(condition),
+        Box::new(if_true),
+        Box::new(if_false),
+      ));
+    }
+
+    Ok(condition)
+  }
+
+  fn or(&mut self) -> Result<Expr, String> {
+    let mut left = self.and()?;
+
+    while self.eat(&Token::Or) {
+      let right = self.and()?;
+      left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+    }
+
+    Ok(left)
+  }
+
+  fn and(&mut self) -> Result<Expr, String> {
+    let mut left = self.relation()?;
+
+    while self.eat(&Token::And) {
+      let right = self.relation()?;
+      left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+    }
+
+    Ok(left)
+  }
+
+  fn relation(&mut self) -> Result<Expr, String> {
+    let mut left = self.addition()?;
+
+    loop {
+      let op = match self.peek() {
+        Some(Token::Eq) => BinaryOp::Eq,
+        Some(Token::Ne) => BinaryOp::Ne,
+        Some(Token::Lt) => BinaryOp::Lt,
+        Some(Token::Le) => BinaryOp::Le,
+        Some(Token::Gt) => BinaryOp::Gt,
+        Some(Token::Ge) => BinaryOp::Ge,
+        Some(Token::In) => BinaryOp::In,
+        _ => return Ok(left),
+      };
+      self.pos += 1;
+
+      let right = self.addition()?;
+      left = Expr::Binary(op, Box::new(left), Box::new(right));
+    }
+  }
+
+  fn addition(&mut self) -> Result<Expr, String> {
+    let mut left = self.multiplication()?;
+
+    loop {
+      let op = match self.peek() {
+        Some(Token::Plus) => BinaryOp::Add,
+        Some(Token::Minus) => BinaryOp::Sub,
+        _ => return Ok(left),
+      };
+      self.pos += 1;
+
+      let right = self.multiplication()?;
+      left = Expr::Binary(op, Box::new(left), Box::new(right));
+    }
+  }
+
+  fn multiplication(&mut self) -> Result<Expr, String> {
+    let mut left = self.unary()?;
+
+    loop {
+      let op = match self.peek() {
+        Some(Token::Star) => BinaryOp::Mul,
+        Some(Token::Slash) => BinaryOp::Div,
+        Some(Token::Percent) => BinaryOp::Rem,
+        _ => return Ok(left),
+      };
+      self.pos += 1;
+
+      let right = self.unary()?;
+      left = Expr::Binary(op, Box::new(left), Box::new(right));
+    }
+  }
+
+  fn unary(&mut self) -> Result<Expr, String> {
+    if self.eat(&Token::Not) {
+      let operand = self.unary()?;
+      return Ok(Expr::Unary(UnaryOp::Not, Box::new(operand)));
+    }
+
+    if self.eat(&Token::Minus) {
+      // Negative number literals are folded, so that i64::MIN can be represented
+      match self.peek() {
+        Some(Token::Int(value)) => {
+          let value = *value;
+          self.pos += 1;
+
+          let negated = if value == i64::MIN.unsigned_abs() {
+            i64::MIN
+          } else {
+            -i64::try_from(value)
+              .map_err(|_| format!("integer literal '-{value}' is out of range"))?
+          };
+
+          return self.member_suffix(Expr::Literal(Literal::Int(negated)));
+        }
+        Some(Token::Double(value)) => {
+          let value = *value;
+          self.pos += 1;
+          return self.member_suffix(Expr::Literal(Literal::Double(-value)));
+        }
+        _ => {}
+      }
+
+      let operand = self.unary()?;
+      return Ok(Expr::Unary(UnaryOp::Neg, Box::new(operand)));
+    }
+
+    let primary = self.primary()?;
+    self.member_suffix(primary)
+  }
+
+  fn member_suffix(&mut self, mut expr: Expr) -> Result<Expr, String> {
+    loop {
+      if self.eat(&Token::Dot) {
+        let name = match self.next() {
+          Some(Token::Ident(name)) => name,
+          other => return Err(format!("expected a field or method name, found {other:?}")),
+        };
+
+        if self.eat(&Token::LParen) {
+          let args = self.list_items(&Token::RParen)?;
+          expr = Expr::Call {
+            target: Some(Box::new(expr)),
+            name,
+            args,
+          };
+        } else {
+          expr = Expr::Member(Box::new(expr), name);
+        }
+      } else if self.eat(&Token::LBracket) {
+        let index = self.expr()?;
+        self.expect(&Token::RBracket)?;
+        expr = Expr::Index(Box::new(expr), Box::new(index));
+      } else if self.peek() == Some(&Token::LBrace)
+        && let Some(name) = qualified_name(&expr)
+      {
+        self.pos += 1;
+        expr = self.struct_fields(name.into())?;
+      } else {
+        return Ok(expr);
+      }
+    }
+  }
+
+  // Parses the fields of a message construction, after the opening brace
+  fn struct_fields(&mut self, name: Box<str>) -> Result<Expr, String> {
+    let mut fields: Vec<(Box<str>, Expr)> = Vec::new();
+
+    while !self.eat(&Token::RBrace) {
+      let field = match self.next() {
+        Some(Token::Ident(field)) => field,
+        other => return Err(format!("expected a field name, found {other:?}")),
+      };
+      self.expect(&Token::Colon)?;
+      let value = self.expr()?;
+      fields.push((field, value));
+
+      if !self.eat(&Token::Comma) {
+        self.expect(&Token::RBrace)?;
+        break;
+      }
+    }
+
+    Ok(Expr::Struct { name, fields })
+  }
+
+  fn list_items(&mut self, closing: &Token) -> Result<Vec<Expr>, String> {
+    let mut items: Vec<Expr> = Vec::new();
+
+    if self.eat(closing) {
+      return Ok(items);
+    }
+
+    loop {
+      items.push(self.expr()?);
+
+      if self.eat(closing) {
+        return Ok(items);
+      }
+
+      self.expect(&Token::Comma)?;
+
+      // Trailing commas are allowed
+      if self.eat(closing) {
+        return Ok(items);
+      }
+    }
+  }
+
+  fn primary(&mut self) -> Result<Expr, String> {
+    let token = self.next().ok_or("unexpected end of the expression")?;
+
+    let expr = match token {
+      Token::Int(value) => Expr::Literal(Literal::Int(
+        i64::try_from(value).map_err(|_| format!("integer literal '{value}' is out of range"))?,
+      )),
+      Token::Uint(value) => Expr::Literal(Literal::Uint(value)),
+      Token::Double(value) => Expr::Literal(Literal::Double(value)),
+      Token::String(value) => Expr::Literal(Literal::String(value)),
+      Token::Bytes(value) => Expr::Literal(Literal::Bytes(value)),
+      Token::Ident(name) => match name.as_ref() {
+        "true" => Expr::Literal(Literal::Bool(true)),
+        "false" => Expr::Literal(Literal::Bool(false)),
+        "null" => Expr::Literal(Literal::Null),
+        _ => {
+          if self.eat(&Token::LParen) {
+            let args = self.list_items(&Token::RParen)?;
+            Expr::Call {
+              target: None,
+              name,
+              args,
+            }
+          } else if self.eat(&Token::LBrace) {
+            self.struct_fields(name)?
+          } else {
+            Expr::Ident(name)
+          }
+        }
+      },
+      Token::LParen => {
+        let expr = self.expr()?;
+        self.expect(&Token::RParen)?;
+        expr
+      }
+      Token::LBracket => Expr::List(self.list_items(&Token::RBracket)?),
+      Token::LBrace => {
+        let mut entries: Vec<(Expr, Expr)> = Vec::new();
+
+        while !self.eat(&Token::RBrace) {
+          let key = self.expr()?;
+          self.expect(&Token::Colon)?;
+          let value = self.expr()?;
+          entries.push((key, value));
+
+          if !self.eat(&Token::Comma) {
+            self.expect(&Token::RBrace)?;
+            break;
+          }
+        }
+
+        Expr::Map(entries)
+      }
+      other => return Err(format!("unexpected token {other:?}")),
+    };
+
+    Ok(expr)
+  }
+}
+
+// Returns the dotted name represented by a chain of field selections (i.e. `google.protobuf.Duration`)
+fn qualified_name(expr: &Expr) -> Option<String> {
+  match expr {
+    Expr::Ident(name) => Some(name.to_string()),
+    Expr::Member(target, field) => Some(format!("{}.{field}", qualified_name(target)?)),
+    _ => None,
+  }
+}
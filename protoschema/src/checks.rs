@@ -0,0 +1,697 @@
+use crate::{
+  cel::{parse, CelType, CheckError, Checker},
+  enums::EnumData,
+  errors::{ConflictError, SchemaError},
+  extensions::ExtensionKind,
+  fields::FieldData,
+  files::FileData,
+  http::{http_bindings, parse_template},
+  messages::MessageData,
+  packages::PackageData,
+  services::ServiceHandler,
+  FieldType, OptionValue, ProtoOption,
+};
+
+/// The names of the fields in protovalidate's `FieldRules.type` oneof
+const FIELD_RULES_TYPES: [&str; 21] = [
+  "float",
+  "double",
+  "int32",
+  "int64",
+  "uint32",
+  "uint64",
+  "sint32",
+  "sint64",
+  "fixed32",
+  "fixed64",
+  "sfixed32",
+  "sfixed64",
+  "bool",
+  "string",
+  "bytes",
+  "enum",
+  "repeated",
+  "map",
+  "any",
+  "duration",
+  "timestamp",
+];
+
+/// The options that can be defined multiple times, because their type is a repeated field.
+/// Other options can be declared with [`Package::allow_repeated_option`](crate::Package::allow_repeated_option).
+const REPEATED_OPTIONS: [&str; 1] = ["(google.api.field_behavior)"];
+
+// The numbers, names and reservations of the members of a message or an enum, used to detect the conflicts with the members added to it later
+pub(crate) struct Members {
+  item: Box<str>,
+  members: Vec<(i64, Box<str>)>,
+  reserved_numbers: Vec<i64>,
+  // The reserved ranges are inclusive
+  reserved_ranges: Vec<(i64, i64)>,
+  reserved_names: Vec<Box<str>>,
+  allow_alias: bool,
+}
+
+impl Members {
+  // Whether a number is already used by a member, or reserved
+  pub(crate) fn is_taken(&self, number: i64) -> bool {
+    self.members.iter().any(|(n, _)| *n == number) || self.is_reserved(number)
+  }
+
+  fn is_reserved(&self, number: i64) -> bool {
+    self.reserved_numbers.contains(&number)
+      || self
+        .reserved_ranges
+        .iter()
+        .any(|(start, end)| *start <= number && number <= *end)
+  }
+
+  // Checks the new members one at a time, so that the conflicts among them are also detected
+  pub(crate) fn check_members<'a, I>(mut self, new_members: I) -> Result<(), ConflictError>
+  where
+    I: IntoIterator<Item = (i64, &'a str)>,
+  {
+    for (number, name) in new_members {
+      if !self.allow_alias
+        && let Some((_, other)) = self.members.iter().find(|(n, _)| *n == number)
+      {
+        return Err(ConflictError::DuplicateNumber {
+          item: self.item,
+          number,
+          name: other.clone(),
+        });
+      }
+
+      if self.members.iter().any(|(_, n)| n.as_ref() == name) {
+        return Err(ConflictError::DuplicateName {
+          item: self.item,
+          name: name.into(),
+        });
+      }
+
+      if self.is_reserved(number) {
+        return Err(ConflictError::ReservedNumber {
+          item: self.item,
+          number,
+          name: name.into(),
+        });
+      }
+
+      if self.reserved_names.iter().any(|n| n.as_ref() == name) {
+        return Err(ConflictError::ReservedName {
+          item: self.item,
+          name: name.into(),
+        });
+      }
+
+      self.members.push((number, name.into()));
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn check_reservations(
+    mut self,
+    numbers: &[i64],
+    ranges: &[(i64, i64)],
+    names: &[Box<str>],
+  ) -> Result<(), ConflictError> {
+    self.reserved_numbers = numbers.to_vec();
+    self.reserved_ranges = ranges.to_vec();
+
+    if let Some((number, name)) = self
+      .members
+      .iter()
+      .find(|(number, _)| self.is_reserved(*number))
+    {
+      return Err(ConflictError::ReservedNumber {
+        item: self.item.clone(),
+        number: *number,
+        name: name.clone(),
+      });
+    }
+
+    if let Some(name) = names
+      .iter()
+      .find(|name| self.members.iter().any(|(_, n)| n == *name))
+    {
+      return Err(ConflictError::ReservedName {
+        item: self.item,
+        name: name.clone(),
+      });
+    }
+
+    Ok(())
+  }
+}
+
+impl MessageData {
+  pub(crate) fn members(&self) -> Members {
+    let oneof_fields = self.oneofs.iter().flat_map(|oneof| oneof.fields.iter());
+
+    Members {
+      item: self.import_path.full_name_with_package.as_ref().into(),
+      members: self
+        .fields
+        .iter()
+        .chain(oneof_fields)
+        .map(|(tag, field)| (*tag as i64, field.name.as_ref().into()))
+        .collect(),
+      reserved_numbers: self.reserved_numbers.iter().map(|n| *n as i64).collect(),
+      reserved_ranges: self
+        .reserved_ranges
+        .iter()
+        .map(|range| (range.start as i64, range.end as i64))
+        .collect(),
+      reserved_names: self.reserved_names.to_vec(),
+      allow_alias: false,
+    }
+  }
+}
+
+impl EnumData {
+  pub(crate) fn members(&self) -> Members {
+    Members {
+      item: self.import_path.full_name_with_package.as_ref().into(),
+      members: self
+        .variants
+        .iter()
+        .map(|(number, variant)| (*number as i64, variant.name.as_ref().into()))
+        .collect(),
+      reserved_numbers: self.reserved_numbers.iter().map(|n| *n as i64).collect(),
+      reserved_ranges: self
+        .reserved_ranges
+        .iter()
+        .map(|range| (range.start as i64, range.end as i64))
+        .collect(),
+      reserved_names: self.reserved_names.to_vec(),
+      allow_alias: self
+        .options
+        .iter()
+        .any(|option| &*option.name == "allow_alias" && *option.value == OptionValue::Bool(true)),
+    }
+  }
+}
+
+impl PackageData {
+  pub(crate) fn check(&self) -> Vec<SchemaError> {
+    let mut errors: Vec<SchemaError> = Vec::new();
+
+    for file in &self.files {
+      self.check_options(&file.name, &file.options, &mut errors);
+      self.check_predefined_cel_rules(file, &mut errors);
+    }
+
+    for (msg_id, msg) in self.messages.iter().enumerate() {
+      let msg_name = &msg.import_path.full_name_with_package;
+
+      self.check_options(msg_name, &msg.options, &mut errors);
+      check_message_members(msg, &mut errors);
+      check_message_oneof_rules(msg, &mut errors);
+      self.check_lineage(msg, &mut errors);
+      self.check_message_cel_rules(msg_id, &mut errors);
+
+      for (_, field) in msg.fields.iter() {
+        let field_name = format!("{}.{}", msg_name, field.name);
+
+        self.check_options(&field_name, &field.options, &mut errors);
+        self.check_required_rule(&field_name, field, &mut errors);
+      }
+
+      for oneof in &msg.oneofs {
+        self.check_options(
+          &format!("{}.{}", msg_name, oneof.name),
+          &oneof.options,
+          &mut errors,
+        );
+
+        for (_, field) in oneof.fields.iter() {
+          let field_name = format!("{}.{}", msg_name, field.name);
+
+          self.check_options(&field_name, &field.options, &mut errors);
+          self.check_required_rule(&field_name, field, &mut errors);
+        }
+      }
+    }
+
+    for enum_ in &self.enums {
+      self.check_options(
+        &enum_.import_path.full_name_with_package,
+        &enum_.options,
+        &mut errors,
+      );
+    }
+
+    for service in &self.services {
+      let service_name = format!("{}.{}", self.name, service.name);
+
+      self.check_options(&service_name, &service.options, &mut errors);
+
+      for handler in service.handlers.iter() {
+        let handler_name = format!("{}.{}", service_name, handler.name);
+
+        self.check_options(&handler_name, &handler.options, &mut errors);
+        self.check_http_bindings(&handler_name, handler, &mut errors);
+      }
+    }
+
+    errors
+  }
+
+  // Reports the derived messages whose source message was changed after the derivation
+  fn check_lineage(&self, msg: &MessageData, errors: &mut Vec<SchemaError>) {
+    if let Some(lineage) = &msg.lineage
+      && self.messages[lineage.source_id].fields != lineage.snapshot
+    {
+      errors.push(SchemaError::OutdatedDerivation {
+        item: msg.import_path.full_name_with_package.as_ref().into(),
+        source_message: lineage.source.full_name_with_package.as_ref().into(),
+      });
+    }
+  }
+
+  // Reports the fields marked as `REQUIRED` with `(google.api.field_behavior)` which do not have the `required` validation rule, if this check is enabled
+  fn check_required_rule(&self, item: &str, field: &FieldData, errors: &mut Vec<SchemaError>) {
+    if !self.enforce_required_rules {
+      return;
+    }
+
+    let is_required = field.options.iter().any(|option| {
+      &*option.name == "(google.api.field_behavior)"
+        && *option.value == OptionValue::Enum("REQUIRED".into())
+    });
+
+    let has_required_rule = field
+      .options
+      .iter()
+      .filter(|option| &*option.name == "(buf.validate.field)")
+      .any(|option| get_value(&option.value, "required") == Some(&OptionValue::Bool(true)));
+
+    if is_required && !has_required_rule {
+      errors.push(SchemaError::MissingRequiredRule { item: item.into() });
+    }
+  }
+
+  // Checks that the path variables and the body fields of the http bindings of a handler refer to existing fields
+  fn check_http_bindings(
+    &self,
+    item: &str,
+    handler: &ServiceHandler,
+    errors: &mut Vec<SchemaError>,
+  ) {
+    // The messages defined in other packages cannot be checked
+    let request_id = self.message_id(&handler.request.full_name_with_package);
+    let response_id = self.message_id(&handler.response.full_name_with_package);
+
+    for binding in http_bindings(&handler.options) {
+      let (_, variables) = parse_template(&binding.path);
+
+      let mut invalid_rule = |error: &str| {
+        errors.push(SchemaError::InvalidHttpRule {
+          item: item.into(),
+          rule: binding.path.clone(),
+          error: error.into(),
+        })
+      };
+
+      if binding.method.as_ref() == "get" && binding.body.is_some() {
+        invalid_rule("a GET binding cannot have a body");
+      }
+
+      for segments in variables.iter().filter_map(|v| v.segments.as_deref()) {
+        if segments
+          .split('/')
+          .rev()
+          .skip(1)
+          .any(|segment| segment == "**")
+        {
+          invalid_rule("the '**' wildcard can only be used as the last segment of a variable");
+        }
+      }
+
+      let request_fields = variables
+        .iter()
+        .map(|variable| variable.field_path.as_ref())
+        .chain(binding.body.as_deref().filter(|body| *body != "*"))
+        .filter_map(|field_path| request_id.map(|id| (id, field_path)));
+
+      let response_fields = binding
+        .response_body
+        .as_deref()
+        .and_then(|field_path| response_id.map(|id| (id, field_path)));
+
+      for (msg_id, field_path) in request_fields.chain(response_fields) {
+        if !self.has_field_path(msg_id, field_path) {
+          errors.push(SchemaError::UnknownField {
+            item: item.into(),
+            field: field_path.into(),
+            rule: format!("the http rule '{}'", binding.path).into(),
+          });
+        }
+      }
+    }
+  }
+
+  // Checks if a message contains a field with the given path, such as `book.name`
+  fn has_field_path(&self, msg_id: usize, field_path: &str) -> bool {
+    let mut msg_id = msg_id;
+    let mut segments = field_path.split('.').peekable();
+
+    while let Some(segment) = segments.next() {
+      let Some(field) = self.find_field(msg_id, segment) else {
+        return false;
+      };
+
+      if segments.peek().is_none() {
+        break;
+      }
+
+      let FieldType::Message(path) = &field.field_type else {
+        return false;
+      };
+
+      match self.message_id(&path.full_name_with_package) {
+        Some(id) => msg_id = id,
+        None => return true,
+      }
+    }
+
+    true
+  }
+}
+
+impl PackageData {
+  fn is_repeated_option(&self, name: &str) -> bool {
+    REPEATED_OPTIONS.contains(&name) || self.repeated_options.iter().any(|o| o.as_ref() == name)
+  }
+
+  fn check_options(&self, item: &str, options: &[ProtoOption], errors: &mut Vec<SchemaError>) {
+    for (i, option) in options.iter().enumerate() {
+      let first_occurrence = options[..i].iter().all(|o| o.name != option.name);
+      let is_duplicated = !self.is_repeated_option(&option.name)
+        && options[i + 1..].iter().any(|o| o.name == option.name);
+
+      if first_occurrence && is_duplicated {
+        errors.push(SchemaError::ConflictingOptions {
+          item: item.into(),
+          option: option.name.as_ref().into(),
+        });
+      }
+
+      if &*option.name == "(buf.validate.field)"
+        && let OptionValue::Message(values) = option.value.as_ref()
+      {
+        let types: Vec<Box<str>> = values
+          .iter()
+          .filter(|(name, _)| FIELD_RULES_TYPES.contains(&name.as_ref()))
+          .map(|(name, _)| name.clone())
+          .collect();
+
+        if types.len() > 1 {
+          errors.push(SchemaError::ConflictingRuleTypes {
+            item: item.into(),
+            types,
+          });
+        }
+      }
+    }
+  }
+}
+
+// Reports the fields (including those in the oneofs) whose tag or name is used by another field, or is reserved
+fn check_message_members(msg: &MessageData, errors: &mut Vec<SchemaError>) {
+  let mut members = msg.members();
+  let fields = std::mem::take(&mut members.members);
+
+  if let Err(error) = members.check_members(fields.iter().map(|(tag, name)| (*tag, name.as_ref())))
+  {
+    errors.push(SchemaError::MemberConflict(error));
+  }
+}
+
+fn check_message_oneof_rules(msg: &MessageData, errors: &mut Vec<SchemaError>) {
+  let msg_name = &msg.import_path.full_name_with_package;
+
+  let rules = msg
+    .options
+    .iter()
+    .filter(|o| &*o.name == "(buf.validate.message)")
+    .filter_map(|o| match o.value.as_ref() {
+      OptionValue::Message(values) => Some(values),
+      _ => None,
+    })
+    .flat_map(|values| values.iter())
+    .filter(|(name, _)| name.as_ref() == "oneof")
+    .filter_map(|(_, value)| match value {
+      OptionValue::List(rules) => Some(rules),
+      _ => None,
+    })
+    .flat_map(|rules| rules.iter());
+
+  for rule in rules {
+    let OptionValue::Message(rule_values) = rule else {
+      continue;
+    };
+
+    let fields: Vec<&str> = rule_values
+      .iter()
+      .filter(|(name, _)| name.as_ref() == "fields")
+      .filter_map(|(_, value)| match value {
+        OptionValue::List(fields) => Some(fields),
+        _ => None,
+      })
+      .flat_map(|fields| fields.iter())
+      .filter_map(|field| match field {
+        OptionValue::String(name) => Some(name.as_ref()),
+        _ => None,
+      })
+      .collect();
+
+    if fields.is_empty() {
+      errors.push(SchemaError::EmptyOneofRule {
+        item: msg_name.as_ref().into(),
+      });
+    }
+
+    for (i, field) in fields.iter().enumerate() {
+      let previous_occurrences = fields[..i].iter().filter(|f| *f == field).count();
+
+      if previous_occurrences > 0 {
+        // Only reported once per duplicated field
+        if previous_occurrences == 1 {
+          errors.push(SchemaError::DuplicateOneofRuleField {
+            item: msg_name.as_ref().into(),
+            field: (*field).into(),
+          });
+        }
+        continue;
+      }
+
+      let exists = msg.fields.iter().any(|(_, f)| f.name.as_ref() == *field)
+        || msg
+          .oneofs
+          .iter()
+          .flat_map(|oneof| oneof.fields.iter())
+          .any(|(_, f)| f.name.as_ref() == *field);
+
+      if !exists {
+        errors.push(SchemaError::UnknownField {
+          item: msg_name.as_ref().into(),
+          field: (*field).into(),
+          rule: "a message oneof rule".into(),
+        });
+      }
+    }
+  }
+}
+
+impl PackageData {
+  // Type-checks the Cel rules defined at the message level and in the rules of its fields
+  fn check_message_cel_rules(&self, msg_id: usize, errors: &mut Vec<SchemaError>) {
+    let msg = &self.messages[msg_id];
+    let msg_name = &msg.import_path.full_name_with_package;
+
+    for option in msg
+      .options
+      .iter()
+      .filter(|o| &*o.name == "(buf.validate.message)")
+    {
+      self.check_cel_rules(
+        msg_name,
+        &option.value,
+        CelType::Message(msg_id),
+        None,
+        errors,
+      );
+    }
+
+    let checker = Checker::new(self, CelType::Dyn, None);
+
+    let fields = msg
+      .fields
+      .iter()
+      .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()));
+
+    for (_, field) in fields {
+      let item = format!("{}.{}", msg_name, field.name);
+      let field_type = checker.field_type(field);
+
+      for option in field
+        .options
+        .iter()
+        .filter(|o| &*o.name == "(buf.validate.field)")
+      {
+        self.check_field_cel_rules(&item, &option.value, field_type.clone(), errors);
+      }
+    }
+  }
+
+  // Type-checks the Cel rules of predefined rules, where `this` is the value being validated and `rule` is the value of the rule
+  fn check_predefined_cel_rules(&self, file: &FileData, errors: &mut Vec<SchemaError>) {
+    let checker = Checker::new(self, CelType::Dyn, None);
+
+    for extension in file.extensions.iter() {
+      let Some(this) = rules_target_type(extension.kind) else {
+        continue;
+      };
+
+      for (_, field) in extension.fields.iter() {
+        let item = format!("{}.{}", self.name, field.name);
+        let rule = checker.field_type(field);
+
+        for option in field
+          .options
+          .iter()
+          .filter(|o| &*o.name == "(buf.validate.predefined)")
+        {
+          self.check_cel_rules(
+            &item,
+            &option.value,
+            this.clone(),
+            Some(rule.clone()),
+            errors,
+          );
+        }
+      }
+    }
+  }
+
+  fn check_field_cel_rules(
+    &self,
+    item: &str,
+    rules: &OptionValue,
+    this: CelType,
+    errors: &mut Vec<SchemaError>,
+  ) {
+    self.check_cel_rules(item, rules, this.clone(), None, errors);
+
+    // The rules for the items of lists and the keys and values of maps are also FieldRules
+    match this {
+      CelType::List(item_type) => {
+        if let Some(items) = get_value(rules, "repeated").and_then(|r| get_value(r, "items")) {
+          self.check_field_cel_rules(item, items, *item_type, errors);
+        }
+      }
+      CelType::Map(key_type, value_type) => {
+        let map_rules = get_value(rules, "map");
+
+        if let Some(keys) = map_rules.and_then(|r| get_value(r, "keys")) {
+          self.check_field_cel_rules(item, keys, *key_type, errors);
+        }
+
+        if let Some(values) = map_rules.and_then(|r| get_value(r, "values")) {
+          self.check_field_cel_rules(item, values, *value_type, errors);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn check_cel_rules(
+    &self,
+    item: &str,
+    rules: &OptionValue,
+    this: CelType,
+    rule: Option<CelType>,
+    errors: &mut Vec<SchemaError>,
+  ) {
+    let Some(OptionValue::List(cel_rules)) = get_value(rules, "cel") else {
+      return;
+    };
+
+    for cel_rule in cel_rules.iter() {
+      let get_string = |key: &str| match get_value(cel_rule, key) {
+        Some(OptionValue::String(value)) => Some(value.as_ref()),
+        _ => None,
+      };
+
+      let (Some(id), Some(expression)) = (get_string("id"), get_string("expression")) else {
+        continue;
+      };
+
+      let result = parse(expression)
+        .map_err(CheckError::Invalid)
+        .and_then(|expr| Checker::new(self, this.clone(), rule.clone()).check_rule(&expr));
+
+      match result {
+        Ok(()) => {}
+        Err(CheckError::UnknownField(field)) => errors.push(SchemaError::UnknownField {
+          item: item.into(),
+          field,
+          rule: format!("the cel rule '{id}'").into(),
+        }),
+        Err(CheckError::Invalid(error)) => errors.push(SchemaError::InvalidCelRule {
+          item: item.into(),
+          rule_id: id.into(),
+          error: error.into(),
+        }),
+      }
+    }
+  }
+}
+
+pub(crate) fn find_option<'a>(options: &'a [ProtoOption], name: &str) -> Option<&'a OptionValue> {
+  options
+    .iter()
+    .find(|option| &*option.name == name)
+    .map(|option| option.value.as_ref())
+}
+
+pub(crate) fn get_value<'a>(value: &'a OptionValue, key: &str) -> Option<&'a OptionValue> {
+  match value {
+    OptionValue::Message(values) => values
+      .iter()
+      .find(|(name, _)| name.as_ref() == key)
+      .map(|(_, value)| value),
+    _ => None,
+  }
+}
+
+// The type of the values validated by the rules messages that can be extended by predefined rules
+fn rules_target_type(kind: ExtensionKind) -> Option<CelType> {
+  let target = match kind {
+    ExtensionKind::FloatRules | ExtensionKind::DoubleRules => CelType::Double,
+    ExtensionKind::Int32Rules
+    | ExtensionKind::Int64Rules
+    | ExtensionKind::SInt32Rules
+    | ExtensionKind::SInt64Rules
+    | ExtensionKind::SFixed32Rules
+    | ExtensionKind::SFixed64Rules
+    | ExtensionKind::EnumRules => CelType::Int,
+    ExtensionKind::UInt32Rules
+    | ExtensionKind::UInt64Rules
+    | ExtensionKind::Fixed32Rules
+    | ExtensionKind::Fixed64Rules => CelType::Uint,
+    ExtensionKind::BoolRules => CelType::Bool,
+    ExtensionKind::StringRules => CelType::String,
+    ExtensionKind::BytesRules => CelType::Bytes,
+    ExtensionKind::RepeatedRules => CelType::List(Box::new(CelType::Dyn)),
+    ExtensionKind::MapRules => CelType::Map(Box::new(CelType::Dyn), Box::new(CelType::Dyn)),
+    ExtensionKind::AnyRules => CelType::Dyn,
+    ExtensionKind::DurationRules => CelType::Duration,
+    ExtensionKind::TimestampRules => CelType::Timestamp,
+    _ => return None,
+  };
+
+  Some(target)
+}
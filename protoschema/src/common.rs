@@ -10,10 +10,22 @@ pub static VALIDATE_PROTO_FILE: LazyLock<Arc<str>> =
 pub static DESCRIPTOR_PROTO_FILE: LazyLock<Arc<str>> =
   LazyLock::new(|| "google/protobuf/descriptor.proto".into());
 
+/// The path to the file that defines the `google.api.http` option.
+pub static ANNOTATIONS_PROTO_FILE: LazyLock<Arc<str>> =
+  LazyLock::new(|| "google/api/annotations.proto".into());
+
+/// The path to the file that defines the `google.api.field_behavior` option.
+pub static FIELD_BEHAVIOR_PROTO_FILE: LazyLock<Arc<str>> =
+  LazyLock::new(|| "google/api/field_behavior.proto".into());
+
+/// The path to the file that defines the `google.api.resource` and `google.api.resource_reference` options.
+pub static RESOURCE_PROTO_FILE: LazyLock<Arc<str>> =
+  LazyLock::new(|| "google/api/resource.proto".into());
+
 /// The allow_alias option for enums.
 pub fn allow_alias() -> ProtoOption {
   ProtoOption {
-    name: "allow_alias",
+    name: "allow_alias".into(),
     value: Arc::new(OptionValue::Bool(true)),
   }
 }
@@ -21,7 +33,15 @@ pub fn allow_alias() -> ProtoOption {
 /// A helper to create the [`ProtoOption`] that corresponds to 'deprecated = true'.
 pub fn deprecated() -> ProtoOption {
   ProtoOption {
-    name: "deprecated",
+    name: "deprecated".into(),
+    value: Arc::new(OptionValue::Bool(true)),
+  }
+}
+
+/// A helper to create the [`ProtoOption`] that corresponds to 'debug_redact = true', which hides the value of a field in the debug output.
+pub fn debug_redact() -> ProtoOption {
+  ProtoOption {
+    name: "debug_redact".into(),
     value: Arc::new(OptionValue::Bool(true)),
   }
 }
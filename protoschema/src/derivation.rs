@@ -0,0 +1,423 @@
+use std::sync::Arc;
+
+use crate::{
+  common::{FIELD_BEHAVIOR_PROTO_FILE, RESOURCE_PROTO_FILE, VALIDATE_PROTO_FILE},
+  errors::DerivationError,
+  field_type::ImportedItemPath,
+  fields::{Field, FieldBuilder, FieldData, IsComplete},
+  packages::PackageData,
+  OptionValue, ProtoOption,
+};
+
+/// The operations used to derive a message from the fields of another one, with [`MessageBuilder::derive_from`](crate::messages::MessageBuilder::derive_from).
+///
+/// The fields are always referred to with their name in the source message, even if they are renamed.
+/// By default, all of the fields of the source are copied with their tags, options and validators.
+/// The oneofs and the message options of the source are not copied.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{derivation::Derivation, message, string, uint32, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let user_msg = message!(
+///   my_file.new_message("User"),
+///   1 => uint32!("id"),
+///   2 => string!("name", |v| v.required().min_len(2)),
+///   3 => string!("email", |v| v.email()),
+/// );
+///
+/// let update_user_msg = my_file.new_message("UpdateUserRequest").derive_from(
+///   &user_msg,
+///   Derivation::new()
+///     .omit(["id"])
+///     .rename("email", "new_email")
+///     .without_required(["name"])
+///     .renumber()
+///     .add_field(10, string!("request_id")),
+/// );
+///
+/// assert_eq!(update_user_msg.get_lineage().unwrap().fields.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Derivation {
+  pub(crate) pick: Option<Vec<Box<str>>>,
+  pub(crate) omit: Vec<Box<str>>,
+  pub(crate) renames: Vec<(Box<str>, Box<str>)>,
+  pub(crate) tags: Vec<(Box<str>, u32)>,
+  pub(crate) renumber: bool,
+  pub(crate) validators: Vec<(Box<str>, Option<ProtoOption>)>,
+  pub(crate) without_required: Vec<Box<str>>,
+  pub(crate) added_fields: Vec<(u32, Field)>,
+}
+
+impl Derivation {
+  /// Creates a new derivation, which copies all of the fields of the source message
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Only copies the fields with the given names
+  pub fn pick<I, S>(mut self, names: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    self
+      .pick
+      .get_or_insert_default()
+      .extend(names.into_iter().map(|name| name.as_ref().into()));
+    self
+  }
+
+  /// Does not copy the fields with the given names
+  pub fn omit<I, S>(mut self, names: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    self
+      .omit
+      .extend(names.into_iter().map(|name| name.as_ref().into()));
+    self
+  }
+
+  /// Gives a new name to a field
+  pub fn rename<T: AsRef<str>, N: AsRef<str>>(mut self, name: T, new_name: N) -> Self {
+    self
+      .renames
+      .push((name.as_ref().into(), new_name.as_ref().into()));
+    self
+  }
+
+  /// Gives a new tag to a field. This takes precedence over [`renumber`](Derivation::renumber).
+  pub fn tag<T: AsRef<str>>(mut self, name: T, tag: u32) -> Self {
+    self.tags.push((name.as_ref().into(), tag));
+    self
+  }
+
+  /// Assigns sequential tags (starting from 1) to the copied fields, in the order of their original tags, instead of keeping the original ones.
+  /// The tags of the added fields are not affected.
+  pub fn renumber(mut self) -> Self {
+    self.renumber = true;
+    self
+  }
+
+  /// Replaces the validator of a field with a new one.
+  pub fn validator<T: AsRef<str>, V: Into<ProtoOption>>(mut self, name: T, validator: V) -> Self {
+    self
+      .validators
+      .push((name.as_ref().into(), Some(validator.into())));
+    self
+  }
+
+  /// Removes the validator of a field
+  pub fn without_validator<T: AsRef<str>>(mut self, name: T) -> Self {
+    self.validators.push((name.as_ref().into(), None));
+    self
+  }
+
+  /// Removes the `required` validation rule from the given fields, as well as the `REQUIRED` field behavior, if present.
+  /// This is useful for messages such as update requests, where all fields are optional.
+  pub fn without_required<I, S>(mut self, names: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    self
+      .without_required
+      .extend(names.into_iter().map(|name| name.as_ref().into()));
+    self
+  }
+
+  /// Adds a new field to the derived message
+  pub fn add_field<S: IsComplete>(mut self, tag: u32, field: FieldBuilder<S>) -> Self {
+    self.added_fields.push((tag, field.build()));
+    self
+  }
+}
+
+/// The information about the message from which a message was derived, returned by [`MessageBuilder::get_lineage`](crate::messages::MessageBuilder::get_lineage).
+#[derive(Debug, Clone)]
+pub struct Lineage {
+  /// The path to the source message
+  pub source: Arc<ImportedItemPath>,
+  /// The fields of the derived message that were copied from the source, as pairs of their current name and their name in the source
+  pub fields: Vec<(Box<str>, Box<str>)>,
+  pub(crate) source_id: usize,
+  pub(crate) file_id: usize,
+  pub(crate) derivation: Derivation,
+  // The fields of the source at the time of the derivation, used to detect later changes
+  pub(crate) snapshot: Box<[(u32, FieldData)]>,
+}
+
+fn without_required(options: &[ProtoOption]) -> Vec<ProtoOption> {
+  options
+    .iter()
+    .filter(|option| {
+      !(&*option.name == "(google.api.field_behavior)"
+        && *option.value == OptionValue::Enum("REQUIRED".into()))
+    })
+    .filter_map(|option| match option.value.as_ref() {
+      OptionValue::Message(values) if &*option.name == "(buf.validate.field)" => {
+        let values: Box<[(Box<str>, OptionValue)]> = values
+          .iter()
+          .filter(|(key, _)| key.as_ref() != "required")
+          .cloned()
+          .collect();
+
+        // The rules that only contained `required` have nothing left to validate
+        let is_empty = values
+          .iter()
+          .all(|(_, value)| matches!(value, OptionValue::Message(rules) if rules.is_empty()));
+
+        (!is_empty).then(|| ProtoOption {
+          name: option.name.clone(),
+          value: Arc::new(OptionValue::Message(values)),
+        })
+      }
+      _ => Some(option.clone()),
+    })
+    .collect()
+}
+
+// The imports needed by a field, based on its type and on the options whose files are known
+fn field_imports(field: &FieldData) -> Vec<Arc<str>> {
+  let mut imports: Vec<Arc<str>> = field.field_type.import_path().into_iter().collect();
+
+  for option in field.options.iter() {
+    if &*option.name == "(buf.validate.field)" {
+      imports.push(VALIDATE_PROTO_FILE.clone());
+    } else if &*option.name == "(google.api.field_behavior)" {
+      imports.push(FIELD_BEHAVIOR_PROTO_FILE.clone());
+    } else if &*option.name == "(google.api.resource_reference)" {
+      imports.push(RESOURCE_PROTO_FILE.clone());
+    }
+  }
+
+  imports
+}
+
+// The result of applying a derivation to the fields of a source message
+pub(crate) struct DerivedFields {
+  pub(crate) fields: Vec<(u32, FieldData)>,
+  pub(crate) imports: Vec<Arc<str>>,
+  pub(crate) lineage: Vec<(Box<str>, Box<str>)>,
+}
+
+impl Derivation {
+  pub(crate) fn apply(
+    &self,
+    item: &str,
+    source: &str,
+    source_fields: &[(u32, FieldData)],
+  ) -> Result<DerivedFields, DerivationError> {
+    let referenced_names = self
+      .pick
+      .iter()
+      .flatten()
+      .chain(self.omit.iter())
+      .chain(self.renames.iter().map(|(name, _)| name))
+      .chain(self.tags.iter().map(|(name, _)| name))
+      .chain(self.validators.iter().map(|(name, _)| name))
+      .chain(self.without_required.iter());
+
+    for name in referenced_names {
+      if !source_fields
+        .iter()
+        .any(|(_, field)| field.name.as_ref() == name.as_ref())
+      {
+        return Err(DerivationError::UnknownField {
+          item: item.into(),
+          field: name.clone(),
+          source_message: source.into(),
+        });
+      }
+    }
+
+    let mut result = DerivedFields {
+      fields: Vec::new(),
+      imports: Vec::new(),
+      lineage: Vec::new(),
+    };
+
+    let copied_fields = source_fields.iter().filter(|(_, field)| {
+      let name = field.name.as_ref();
+
+      self
+        .pick
+        .as_ref()
+        .is_none_or(|pick| pick.iter().any(|n| n.as_ref() == name))
+        && !self.omit.iter().any(|n| n.as_ref() == name)
+    });
+
+    for (i, (tag, field)) in copied_fields.enumerate() {
+      let source_name = field.name.as_ref();
+      let mut field = field.clone();
+
+      let mut tag = if self.renumber { i as u32 + 1 } else { *tag };
+
+      if let Some((_, new_tag)) = self.tags.iter().rfind(|(n, _)| n.as_ref() == source_name) {
+        tag = *new_tag;
+      }
+
+      if let Some((_, new_name)) = self
+        .renames
+        .iter()
+        .rfind(|(n, _)| n.as_ref() == source_name)
+      {
+        field.name = new_name.as_ref().into();
+      }
+
+      if let Some((_, validator)) = self
+        .validators
+        .iter()
+        .rfind(|(n, _)| n.as_ref() == source_name)
+      {
+        let mut options: Vec<ProtoOption> = field
+          .options
+          .iter()
+          .filter(|option| &*option.name != "(buf.validate.field)")
+          .cloned()
+          .collect();
+
+        options.extend(validator.clone());
+        field.options = options.into_boxed_slice();
+      }
+
+      if self
+        .without_required
+        .iter()
+        .any(|n| n.as_ref() == source_name)
+      {
+        field.options = without_required(&field.options).into_boxed_slice();
+      }
+
+      result.imports.extend(field_imports(&field));
+      result
+        .lineage
+        .push((field.name.as_ref().into(), source_name.into()));
+      result.fields.push((tag, field));
+    }
+
+    for (tag, field) in self.added_fields.iter().cloned() {
+      result.imports.extend(field.imports);
+      result.fields.push((
+        tag,
+        FieldData {
+          name: field.name,
+          options: field.options.into_boxed_slice(),
+          kind: field.kind,
+          field_type: field.field_type,
+        },
+      ));
+    }
+
+    Ok(result)
+  }
+
+  // Whether a field of the derived message was produced by this derivation, given the lineage that it recorded
+  fn produced(&self, lineage: &[(Box<str>, Box<str>)], name: &str) -> bool {
+    lineage.iter().any(|(n, _)| n.as_ref() == name)
+      || self
+        .added_fields
+        .iter()
+        .any(|(_, field)| field.name.as_ref() == name)
+  }
+}
+
+fn check_fields(item: &str, fields: &[(u32, FieldData)]) -> Result<(), DerivationError> {
+  for (i, (tag, field)) in fields.iter().enumerate() {
+    let previous = &fields[..i];
+
+    if previous.iter().any(|(t, _)| t == tag) {
+      return Err(DerivationError::DuplicateTag {
+        item: item.into(),
+        tag: *tag,
+      });
+    }
+
+    if previous.iter().any(|(_, f)| f.name == field.name) {
+      return Err(DerivationError::DuplicateName {
+        item: item.into(),
+        name: field.name.as_ref().into(),
+      });
+    }
+  }
+
+  Ok(())
+}
+
+impl PackageData {
+  // Applies the derivation of a message to the current fields of its source, and records the lineage.
+  // The kept fields are the ones that were added to the derived message separately, which are placed next to the derived ones.
+  pub(crate) fn derive_message(
+    &mut self,
+    msg_id: usize,
+    file_id: usize,
+    source_id: usize,
+    derivation: Derivation,
+    kept: Vec<(u32, FieldData)>,
+  ) -> Result<(), DerivationError> {
+    let source = self.messages[source_id].import_path.clone();
+    let snapshot = self.messages[source_id].fields.clone();
+    let item = &self.messages[msg_id].import_path.full_name_with_package;
+
+    let mut derived = derivation.apply(item, &source.full_name_with_package, &snapshot)?;
+
+    derived.fields.extend(kept);
+    check_fields(item, &derived.fields)?;
+    derived.fields.sort_by_key(|(tag, _)| *tag);
+
+    for import in derived.imports.iter() {
+      self.files[file_id].conditionally_add_import(import);
+    }
+
+    let msg = &mut self.messages[msg_id];
+
+    msg.fields = derived.fields.into_boxed_slice();
+    msg.lineage = Some(Lineage {
+      source,
+      fields: derived.lineage,
+      source_id,
+      file_id,
+      derivation,
+      snapshot,
+    });
+
+    Ok(())
+  }
+
+  // Re-applies all of the derivations, in order of definition, so that the messages derived from other derived messages are also updated
+  pub(crate) fn propagate_derivations(&mut self) -> Result<(), DerivationError> {
+    for msg_id in 0..self.messages.len() {
+      let Some(lineage) = &self.messages[msg_id].lineage else {
+        continue;
+      };
+
+      if self.messages[lineage.source_id].fields == lineage.snapshot {
+        continue;
+      }
+
+      let (file_id, source_id, derivation) = (
+        lineage.file_id,
+        lineage.source_id,
+        lineage.derivation.clone(),
+      );
+
+      // The fields added after the derivation, such as the ones from `add_fields`, are not affected by it
+      let kept = self.messages[msg_id]
+        .fields
+        .iter()
+        .filter(|(_, field)| !derivation.produced(&lineage.fields, &field.name))
+        .cloned()
+        .collect();
+
+      self.derive_message(msg_id, file_id, source_id, derivation, kept)?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,501 @@
+use std::{
+  collections::HashMap,
+  fmt::{self, Display},
+  ops::Range,
+  sync::Arc,
+};
+
+use crate::{
+  fields::FieldKind,
+  schema::{EnumInfo, FieldInfo, HandlerInfo, MessageInfo, Schema, ServiceInfo},
+  ProtoOption,
+};
+
+/// A difference between two [`Schema`]s, returned by [`diff_schemas`] and [`Schema::diff`].
+///
+/// The `item` is the fully qualified name of the element that changed (or the file name, for the changes to a file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Change {
+  pub item: Arc<str>,
+  #[cfg_attr(feature = "serde", serde(flatten))]
+  pub kind: ChangeKind,
+}
+
+/// The kinds of [`Change`] between two schemas.
+///
+/// The fields and the enum values are matched by their number, so a field whose tag has changed is reported as removed and added again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize),
+  serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum ChangeKind {
+  PackageRenamed {
+    old: Arc<str>,
+  },
+  FileAdded,
+  FileRemoved,
+  MessageAdded,
+  MessageRemoved,
+  EnumAdded,
+  EnumRemoved,
+  ServiceAdded,
+  ServiceRemoved,
+  FieldAdded {
+    tag: u32,
+  },
+  /// A field was removed. `reserved` tells whether its tag was reserved in the new schema.
+  FieldRemoved {
+    tag: u32,
+    reserved: bool,
+  },
+  /// The field with the same tag has a different name, which changes its representation in the JSON format.
+  FieldRenamed {
+    tag: u32,
+    old: Arc<str>,
+  },
+  FieldTypeChanged {
+    old: Arc<str>,
+    new: Arc<str>,
+  },
+  FieldKindChanged {
+    old: FieldKind,
+    new: FieldKind,
+  },
+  /// The field was moved into a oneof, out of a oneof, or into a different oneof.
+  FieldOneofChanged {
+    old: Option<Arc<str>>,
+    new: Option<Arc<str>>,
+  },
+  VariantAdded {
+    number: i32,
+  },
+  /// An enum value was removed. `reserved` tells whether its number was reserved in the new schema.
+  VariantRemoved {
+    number: i32,
+    reserved: bool,
+  },
+  VariantRenamed {
+    number: i32,
+    old: Arc<str>,
+  },
+  HandlerAdded,
+  HandlerRemoved,
+  RequestChanged {
+    old: Arc<str>,
+    new: Arc<str>,
+  },
+  ResponseChanged {
+    old: Arc<str>,
+    new: Arc<str>,
+  },
+  /// The options of the item have changed, including its validation rules.
+  OptionsChanged,
+}
+
+impl Change {
+  /// Whether this change breaks the compatibility with the clients and the data that use the old schema, either in the binary or in the JSON format.
+  ///
+  /// Removing an item, changing the type or the cardinality of a field and renaming a field or an enum value are breaking changes.
+  /// Removing a field or an enum value is not considered breaking if its number was reserved, since it cannot be reused with a different meaning.
+  pub fn is_breaking(&self) -> bool {
+    match &self.kind {
+      ChangeKind::FieldRemoved { reserved, .. } | ChangeKind::VariantRemoved { reserved, .. } => {
+        !reserved
+      }
+      ChangeKind::FileAdded
+      | ChangeKind::MessageAdded
+      | ChangeKind::EnumAdded
+      | ChangeKind::ServiceAdded
+      | ChangeKind::FieldAdded { .. }
+      | ChangeKind::VariantAdded { .. }
+      | ChangeKind::HandlerAdded
+      | ChangeKind::OptionsChanged => false,
+      _ => true,
+    }
+  }
+}
+
+impl Display for ChangeKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ChangeKind::PackageRenamed { old } => write!(f, "the package was renamed from '{old}'"),
+      ChangeKind::FileAdded => write!(f, "the file was added"),
+      ChangeKind::FileRemoved => write!(f, "the file was removed"),
+      ChangeKind::MessageAdded => write!(f, "the message was added"),
+      ChangeKind::MessageRemoved => write!(f, "the message was removed"),
+      ChangeKind::EnumAdded => write!(f, "the enum was added"),
+      ChangeKind::EnumRemoved => write!(f, "the enum was removed"),
+      ChangeKind::ServiceAdded => write!(f, "the service was added"),
+      ChangeKind::ServiceRemoved => write!(f, "the service was removed"),
+      ChangeKind::FieldAdded { tag } => write!(f, "the field was added with the tag {tag}"),
+      ChangeKind::FieldRemoved { tag, reserved } => {
+        write!(f, "the field with the tag {tag} was removed")?;
+
+        if !reserved {
+          write!(f, " without reserving its tag")?;
+        }
+
+        Ok(())
+      }
+      ChangeKind::FieldRenamed { tag, old } => {
+        write!(f, "the field with the tag {tag} was renamed from '{old}'")
+      }
+      ChangeKind::FieldTypeChanged { old, new } => {
+        write!(f, "the type of the field changed from '{old}' to '{new}'")
+      }
+      ChangeKind::FieldKindChanged { old, new } => {
+        write!(
+          f,
+          "the cardinality of the field changed from '{}' to '{}'",
+          kind_name(*old),
+          kind_name(*new)
+        )
+      }
+      ChangeKind::FieldOneofChanged { old, new } => {
+        write!(
+          f,
+          "the oneof of the field changed from {} to {}",
+          oneof_name(old.as_deref()),
+          oneof_name(new.as_deref())
+        )
+      }
+      ChangeKind::VariantAdded { number } => {
+        write!(f, "the enum value was added with the number {number}")
+      }
+      ChangeKind::VariantRemoved { number, reserved } => {
+        write!(f, "the enum value with the number {number} was removed")?;
+
+        if !reserved {
+          write!(f, " without reserving its number")?;
+        }
+
+        Ok(())
+      }
+      ChangeKind::VariantRenamed { number, old } => {
+        write!(
+          f,
+          "the enum value with the number {number} was renamed from '{old}'"
+        )
+      }
+      ChangeKind::HandlerAdded => write!(f, "the handler was added"),
+      ChangeKind::HandlerRemoved => write!(f, "the handler was removed"),
+      ChangeKind::RequestChanged { old, new } => {
+        write!(f, "the request changed from '{old}' to '{new}'")
+      }
+      ChangeKind::ResponseChanged { old, new } => {
+        write!(f, "the response changed from '{old}' to '{new}'")
+      }
+      ChangeKind::OptionsChanged => write!(f, "the options changed"),
+    }
+  }
+}
+
+impl Display for Change {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.item, self.kind)
+  }
+}
+
+fn kind_name(kind: FieldKind) -> &'static str {
+  match kind {
+    FieldKind::Normal => "singular",
+    FieldKind::Repeated => "repeated",
+    FieldKind::Optional => "optional",
+  }
+}
+
+fn oneof_name(oneof: Option<&str>) -> String {
+  match oneof {
+    Some(name) => format!("'{name}'"),
+    None => "none".to_string(),
+  }
+}
+
+/// Returns the changes between an old and a new version of a schema, such as two snapshots taken from different commits.
+///
+/// The files are matched by their name, and the messages, enums and services by their fully qualified name, so renaming one of them produces a removal and an addition.
+/// The fields and the enum values are matched by their number, and the handlers by their name.
+///
+/// The changes are returned in a stable order: the files come first, followed by the messages, the enums and the services. For each of them, the changes follow the order of the new schema, and the removed items come last.
+/// [`Change::is_breaking`] tells which changes are breaking.
+pub fn diff_schemas(old: &Schema, new: &Schema) -> Vec<Change> {
+  let mut changes = Vec::new();
+
+  if old.package != new.package {
+    changes.push(Change {
+      item: new.package.clone(),
+      kind: ChangeKind::PackageRenamed {
+        old: old.package.clone(),
+      },
+    });
+  }
+
+  diff_items(
+    &old.files,
+    &new.files,
+    |file| file.name.clone(),
+    &mut changes,
+    (ChangeKind::FileAdded, ChangeKind::FileRemoved),
+    |old_file, new_file, changes| {
+      diff_options(
+        &new_file.name,
+        &old_file.options,
+        &new_file.options,
+        changes,
+      );
+    },
+  );
+
+  diff_items(
+    &old.messages,
+    &new.messages,
+    |msg| msg.full_name.clone(),
+    &mut changes,
+    (ChangeKind::MessageAdded, ChangeKind::MessageRemoved),
+    diff_messages,
+  );
+
+  diff_items(
+    &old.enums,
+    &new.enums,
+    |enum_| enum_.full_name.clone(),
+    &mut changes,
+    (ChangeKind::EnumAdded, ChangeKind::EnumRemoved),
+    diff_enums,
+  );
+
+  diff_items(
+    &old.services,
+    &new.services,
+    |service| service.full_name.clone(),
+    &mut changes,
+    (ChangeKind::ServiceAdded, ChangeKind::ServiceRemoved),
+    diff_services,
+  );
+
+  changes
+}
+
+// Matches the items of the two lists by their key, and reports the additions and removals, calling `compare` for the items that are in both lists
+fn diff_items<T, K, C>(
+  old: &[T],
+  new: &[T],
+  key: K,
+  changes: &mut Vec<Change>,
+  (added, removed): (ChangeKind, ChangeKind),
+  compare: C,
+) where
+  K: Fn(&T) -> Arc<str>,
+  C: Fn(&T, &T, &mut Vec<Change>),
+{
+  let old_items: HashMap<Arc<str>, &T> = old.iter().map(|item| (key(item), item)).collect();
+  let new_keys: Vec<Arc<str>> = new.iter().map(&key).collect();
+
+  for (new_item, item) in new.iter().zip(new_keys.iter()) {
+    match old_items.get(item) {
+      Some(old_item) => compare(old_item, new_item, changes),
+      None => changes.push(Change {
+        item: item.clone(),
+        kind: added.clone(),
+      }),
+    }
+  }
+
+  for old_item in old {
+    let item = key(old_item);
+
+    if !new_keys.contains(&item) {
+      changes.push(Change {
+        item,
+        kind: removed.clone(),
+      });
+    }
+  }
+}
+
+fn diff_options(
+  item: &Arc<str>,
+  old: &[ProtoOption],
+  new: &[ProtoOption],
+  changes: &mut Vec<Change>,
+) {
+  if old != new {
+    changes.push(Change {
+      item: item.clone(),
+      kind: ChangeKind::OptionsChanged,
+    });
+  }
+}
+
+fn is_reserved<T: PartialOrd>(number: T, numbers: &[T], ranges: &[Range<T>]) -> bool {
+  numbers.contains(&number) || ranges.iter().any(|range| range.contains(&number))
+}
+
+fn diff_messages(old: &MessageInfo, new: &MessageInfo, changes: &mut Vec<Change>) {
+  diff_options(&new.full_name, &old.options, &new.options, changes);
+
+  let field_item =
+    |field: &FieldInfo| -> Arc<str> { format!("{}.{}", new.full_name, field.name).into() };
+
+  for new_field in &new.fields {
+    let item = field_item(new_field);
+
+    let Some(old_field) = old.fields.iter().find(|field| field.tag == new_field.tag) else {
+      changes.push(Change {
+        item,
+        kind: ChangeKind::FieldAdded { tag: new_field.tag },
+      });
+      continue;
+    };
+
+    let mut push = |kind: ChangeKind| {
+      changes.push(Change {
+        item: item.clone(),
+        kind,
+      })
+    };
+
+    if old_field.name != new_field.name {
+      push(ChangeKind::FieldRenamed {
+        tag: new_field.tag,
+        old: old_field.name.clone(),
+      });
+    }
+
+    if old_field.type_name != new_field.type_name {
+      push(ChangeKind::FieldTypeChanged {
+        old: old_field.type_name.clone(),
+        new: new_field.type_name.clone(),
+      });
+    }
+
+    if old_field.kind != new_field.kind {
+      push(ChangeKind::FieldKindChanged {
+        old: old_field.kind,
+        new: new_field.kind,
+      });
+    }
+
+    if old_field.oneof != new_field.oneof {
+      push(ChangeKind::FieldOneofChanged {
+        old: old_field.oneof.clone(),
+        new: new_field.oneof.clone(),
+      });
+    }
+
+    if old_field.options != new_field.options {
+      push(ChangeKind::OptionsChanged);
+    }
+  }
+
+  for old_field in &old.fields {
+    if !new.fields.iter().any(|field| field.tag == old_field.tag) {
+      changes.push(Change {
+        item: field_item(old_field),
+        kind: ChangeKind::FieldRemoved {
+          tag: old_field.tag,
+          reserved: is_reserved(old_field.tag, &new.reserved_numbers, &new.reserved_ranges),
+        },
+      });
+    }
+  }
+}
+
+fn diff_enums(old: &EnumInfo, new: &EnumInfo, changes: &mut Vec<Change>) {
+  diff_options(&new.full_name, &old.options, &new.options, changes);
+
+  let variant_item = |name: &str| -> Arc<str> { format!("{}.{}", new.full_name, name).into() };
+  let variant_options = |enum_: &EnumInfo, number: i32| {
+    enum_
+      .variant_options
+      .iter()
+      .find(|(n, _)| *n == number)
+      .map(|(_, options)| options.clone())
+      .unwrap_or_default()
+  };
+
+  for (number, name) in &new.variants {
+    let item = variant_item(name);
+
+    match old.variants.iter().find(|(n, _)| n == number) {
+      None => changes.push(Change {
+        item,
+        kind: ChangeKind::VariantAdded { number: *number },
+      }),
+      Some((_, old_name)) => {
+        if old_name != name {
+          changes.push(Change {
+            item: item.clone(),
+            kind: ChangeKind::VariantRenamed {
+              number: *number,
+              old: old_name.clone(),
+            },
+          });
+        }
+
+        diff_options(
+          &item,
+          &variant_options(old, *number),
+          &variant_options(new, *number),
+          changes,
+        );
+      }
+    }
+  }
+
+  for (number, name) in &old.variants {
+    if !new.variants.iter().any(|(n, _)| n == number) {
+      changes.push(Change {
+        item: variant_item(name),
+        kind: ChangeKind::VariantRemoved {
+          number: *number,
+          reserved: is_reserved(*number, &new.reserved_numbers, &new.reserved_ranges),
+        },
+      });
+    }
+  }
+}
+
+fn diff_services(old: &ServiceInfo, new: &ServiceInfo, changes: &mut Vec<Change>) {
+  diff_options(&new.full_name, &old.options, &new.options, changes);
+
+  let handler_item =
+    |handler: &HandlerInfo| -> Arc<str> { format!("{}.{}", new.full_name, handler.name).into() };
+
+  diff_items(
+    &old.handlers,
+    &new.handlers,
+    handler_item,
+    changes,
+    (ChangeKind::HandlerAdded, ChangeKind::HandlerRemoved),
+    |old_handler, new_handler, changes| {
+      let item = handler_item(new_handler);
+
+      if old_handler.request.full_name_with_package != new_handler.request.full_name_with_package {
+        changes.push(Change {
+          item: item.clone(),
+          kind: ChangeKind::RequestChanged {
+            old: old_handler.request.full_name_with_package.clone(),
+            new: new_handler.request.full_name_with_package.clone(),
+          },
+        });
+      }
+
+      if old_handler.response.full_name_with_package != new_handler.response.full_name_with_package
+      {
+        changes.push(Change {
+          item: item.clone(),
+          kind: ChangeKind::ResponseChanged {
+            old: old_handler.response.full_name_with_package.clone(),
+            new: new_handler.response.full_name_with_package.clone(),
+          },
+        });
+      }
+
+      diff_options(&item, &old_handler.options, &new_handler.options, changes);
+    },
+  );
+}
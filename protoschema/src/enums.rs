@@ -135,17 +135,16 @@ impl<S: EnumState> EnumBuilder<S> {
   }
 
   /// Sets the variants for this enum. Consumes the original builder and returns a new one.
+  ///
+  /// # Panics
+  /// If a variant has the same name as another variant (including those added with [`add_variants`](EnumBuilder::add_variants)), or the same number without the `allow_alias` option, or if its number or name are reserved.
+  #[track_caller]
   pub fn variants<I>(self, variants: I) -> EnumBuilder<SetVariants<S>>
   where
     S::Variants: IsUnset,
     I: IntoIterator<Item = (i32, EnumVariant)>,
   {
-    {
-      let mut arena = self.arena.borrow_mut();
-      let enum_ = &mut arena.enums[self.id];
-
-      enum_.variants = variants.into_iter().collect();
-    }
+    self.store_variants(variants.into_iter().collect());
 
     EnumBuilder {
       id: self.id,
@@ -188,7 +187,9 @@ impl<S: EnumState> EnumBuilder<S> {
       let enum_ = &mut arena.enums[self.id];
       let reserved_names: Vec<Box<str>> = names.into_iter().map(|n| n.as_ref().into()).collect();
 
-      enum_.reserved_names = reserved_names.into_boxed_slice()
+      enum_.reserved_names = [enum_.reserved_names.as_ref(), &reserved_names]
+        .concat()
+        .into()
     }
 
     EnumBuilder {
@@ -209,7 +210,12 @@ impl<S: EnumState> EnumBuilder<S> {
       let mut arena = self.arena.borrow_mut();
       let enum_ = &mut arena.enums[self.id];
 
-      enum_.reserved_numbers = numbers.into_iter().collect()
+      enum_.reserved_numbers = enum_
+        .reserved_numbers
+        .iter()
+        .copied()
+        .chain(numbers)
+        .collect()
     }
 
     EnumBuilder {
@@ -232,7 +238,12 @@ impl<S: EnumState> EnumBuilder<S> {
       let mut arena = self.arena.borrow_mut();
       let enum_ = &mut arena.enums[self.id];
 
-      enum_.reserved_ranges = ranges.into_iter().collect()
+      enum_.reserved_ranges = enum_
+        .reserved_ranges
+        .iter()
+        .cloned()
+        .chain(ranges)
+        .collect()
     }
 
     EnumBuilder {
@@ -244,6 +255,137 @@ impl<S: EnumState> EnumBuilder<S> {
   }
 }
 
+impl<S: EnumState> EnumBuilder<S> {
+  // Checks the new reservations against the existing variants
+  #[track_caller]
+  fn check_reservations(&self, numbers: &[i64], ranges: &[(i64, i64)], names: &[Box<str>]) {
+    let result = self.arena.borrow().enums[self.id]
+      .members()
+      .check_reservations(numbers, ranges, names);
+
+    if let Err(error) = result {
+      panic!("{error}")
+    }
+  }
+
+  /// Adds variants to this enum, in addition to the existing ones.
+  /// Unlike [`variants`](EnumBuilder::variants), this can be called any number of times, so that separate modules can contribute variants to the same enum.
+  ///
+  /// # Panics
+  /// If a variant has the same name as another variant, or the same number without the `allow_alias` option, or if its number or name are reserved.
+  #[track_caller]
+  pub fn add_variants<I>(self, variants: I) -> EnumBuilder<S>
+  where
+    I: IntoIterator<Item = (i32, EnumVariant)>,
+  {
+    self.store_variants(variants.into_iter().collect());
+    self
+  }
+
+  // Checks the new variants against the existing members of this enum, and adds them
+  #[track_caller]
+  fn store_variants(&self, variants: Vec<(i32, EnumVariant)>) {
+    let result = self.arena.borrow().enums[self.id].members().check_members(
+      variants
+        .iter()
+        .map(|(number, variant)| (*number as i64, variant.name.as_ref())),
+    );
+
+    if let Err(error) = result {
+      panic!("{error}")
+    }
+
+    let mut arena = self.arena.borrow_mut();
+    let enum_ = &mut arena.enums[self.id];
+
+    enum_.variants = [enum_.variants.as_ref(), &variants].concat().into();
+  }
+
+  /// Adds reserved numbers to this enum, in addition to the existing ones.
+  /// Unlike [`reserved_numbers`](EnumBuilder::reserved_numbers), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the numbers is used by a variant of this enum.
+  #[track_caller]
+  pub fn add_reserved<I>(self, numbers: I) -> EnumBuilder<S>
+  where
+    I: IntoIterator<Item = i32>,
+  {
+    let numbers: Vec<i32> = numbers.into_iter().collect();
+
+    self.check_reservations(
+      &numbers.iter().map(|n| *n as i64).collect::<Vec<_>>(),
+      &[],
+      &[],
+    );
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let enum_ = &mut arena.enums[self.id];
+
+      enum_.reserved_numbers = [enum_.reserved_numbers.as_ref(), &numbers].concat().into();
+    }
+
+    self
+  }
+
+  /// Adds reserved ranges to this enum, in addition to the existing ones. Just like in protobuf, ranges are considered to be inclusive.
+  /// Unlike [`reserved_ranges`](EnumBuilder::reserved_ranges), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the ranges includes the number of a variant of this enum.
+  #[track_caller]
+  pub fn add_reserved_ranges<I>(self, ranges: I) -> EnumBuilder<S>
+  where
+    I: IntoIterator<Item = Range<i32>>,
+  {
+    let ranges: Vec<Range<i32>> = ranges.into_iter().collect();
+
+    self.check_reservations(
+      &[],
+      &ranges
+        .iter()
+        .map(|range| (range.start as i64, range.end as i64))
+        .collect::<Vec<_>>(),
+      &[],
+    );
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let enum_ = &mut arena.enums[self.id];
+
+      enum_.reserved_ranges = [enum_.reserved_ranges.as_ref(), &ranges].concat().into();
+    }
+
+    self
+  }
+
+  /// Adds reserved names to this enum, in addition to the existing ones.
+  /// Unlike [`reserved_names`](EnumBuilder::reserved_names), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the names is used by a variant of this enum.
+  #[track_caller]
+  pub fn add_reserved_names<I, Str>(self, names: I) -> EnumBuilder<S>
+  where
+    I: IntoIterator<Item = Str>,
+    Str: AsRef<str>,
+  {
+    let names: Vec<Box<str>> = names.into_iter().map(|n| n.as_ref().into()).collect();
+
+    self.check_reservations(&[], &[], &names);
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let enum_ = &mut arena.enums[self.id];
+
+      enum_.reserved_names = [enum_.reserved_names.as_ref(), &names].concat().into();
+    }
+
+    self
+  }
+}
+
 #[doc(hidden)]
 pub trait EnumState: Sized {
   type Variants;
@@ -12,4 +12,213 @@ pub enum TemplatingError {
   FileCreationFailure { file: PathBuf, source: io::Error },
   #[error("Could not write the template to the file '{file}': {source:?}")]
   TemplateWritingFailure { file: PathBuf, source: io::Error },
+  #[error("The schema contains {} error(s): {}", .0.len(), display_errors(.0))]
+  InvalidSchema(Vec<SchemaError>),
+}
+
+fn display_errors(errors: &[SchemaError]) -> String {
+  errors
+    .iter()
+    .map(|e| e.to_string())
+    .collect::<Vec<_>>()
+    .join("; ")
+}
+
+/// The errors that can occur when rendering a package from a build script, with [`BuildConfig::render`](crate::build::BuildConfig::render) or [`BuildConfig::compile`](crate::build::BuildConfig::compile).
+#[derive(Error, Debug)]
+pub enum BuildError {
+  #[error(
+    "The OUT_DIR environment variable is not set, so the output directory must be set explicitly"
+  )]
+  MissingOutDir,
+  #[error(transparent)]
+  Templating(#[from] TemplatingError),
+  #[error("The code generation failed: {0}")]
+  Codegen(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// A diagnostic for an issue in the definition of a schema, which would cause the generated files to be rejected by protoc or by protovalidate.
+///
+/// The `item` is the fully qualified name of the element where the issue was found (or the file name, for file-level issues).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+  #[error("{item}: the option '{option}' is defined multiple times with conflicting values")]
+  ConflictingOptions { item: Box<str>, option: Box<str> },
+  #[error("{item}: the validation rules define more than one type of rules ({types:?})")]
+  ConflictingRuleTypes {
+    item: Box<str>,
+    types: Vec<Box<str>>,
+  },
+  #[error("{item}: the field '{field}' referenced in {rule} does not exist")]
+  UnknownField {
+    item: Box<str>,
+    field: Box<str>,
+    rule: Box<str>,
+  },
+  #[error("{item}: the cel rule '{rule_id}' is invalid: {error}")]
+  InvalidCelRule {
+    item: Box<str>,
+    rule_id: Box<str>,
+    error: Box<str>,
+  },
+  #[error("{item}: a message oneof rule must contain at least one field")]
+  EmptyOneofRule { item: Box<str> },
+  #[error("{item}: the field '{field}' is included more than once in the same message oneof rule")]
+  DuplicateOneofRuleField { item: Box<str>, field: Box<str> },
+  #[error(
+    "{item}: the field is marked as REQUIRED, but its validation rules do not include `required`"
+  )]
+  MissingRequiredRule { item: Box<str> },
+  #[error("{item}: the message '{source_message}' from which it was derived has changed since the derivation")]
+  OutdatedDerivation {
+    item: Box<str>,
+    source_message: Box<str>,
+  },
+  #[error("{item}: the http rule '{rule}' is invalid: {error}")]
+  InvalidHttpRule {
+    item: Box<str>,
+    rule: Box<str>,
+    error: Box<str>,
+  },
+  #[error("{item}: {message}")]
+  PolicyViolation { item: Box<str>, message: Box<str> },
+  #[error(transparent)]
+  MemberConflict(ConflictError),
+}
+
+/// The errors that can occur when deriving a message from another one, with [`MessageBuilder::derive_from`](crate::messages::MessageBuilder::derive_from) or [`Package::propagate_derivations`](crate::Package::propagate_derivations).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DerivationError {
+  #[error("{item}: the field '{field}' does not exist in the source message '{source_message}'")]
+  UnknownField {
+    item: Box<str>,
+    field: Box<str>,
+    source_message: Box<str>,
+  },
+  #[error("{item}: the tag {tag} is used by more than one field")]
+  DuplicateTag { item: Box<str>, tag: u32 },
+  #[error("{item}: the name '{name}' is used by more than one field")]
+  DuplicateName { item: Box<str>, name: Box<str> },
+}
+
+/// The collisions between the fields of a message, detected when its fields are set together with the [`Mixin`](crate::mixins::Mixin)s placed in it, and the placements that would give invalid tags to their fields.
+///
+/// `first` and `second` describe the two colliding fields, including the mixin that each of them came from.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MixinError {
+  #[error("{item}: the tag {tag} is used by both {first} and {second}")]
+  TagCollision {
+    item: Box<str>,
+    tag: u32,
+    first: Box<str>,
+    second: Box<str>,
+  },
+  #[error("{item}: the name '{name}' is used by both {first} and {second}")]
+  NameCollision {
+    item: Box<str>,
+    name: Box<str>,
+    first: Box<str>,
+    second: Box<str>,
+  },
+  #[error("{item}: the mixin '{mixin}' cannot be placed at the base tag {base}, since one of its tags would be 0, above the maximum field number or reserved by the implementation (19000-19999)")]
+  InvalidPlacement {
+    item: Box<str>,
+    mixin: Box<str>,
+    base: u32,
+  },
+  #[error("{item}: there are no free tags for the mixin '{mixin}' from the tag {band} up to the maximum field number")]
+  NoFreeTags {
+    item: Box<str>,
+    mixin: Box<str>,
+    band: u32,
+  },
+}
+
+/// The conflicts detected when adding fields, variants, handlers or reservations to an item that already contains some, with methods such as [`MessageBuilder::add_fields`](crate::messages::MessageBuilder::add_fields).
+///
+/// The numbers are the tags of the fields of a message, or the values of the variants of an enum.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConflictError {
+  #[error("{item}: the number {number} is already used by '{name}'")]
+  DuplicateNumber {
+    item: Box<str>,
+    number: i64,
+    name: Box<str>,
+  },
+  #[error("{item}: the name '{name}' is already in use")]
+  DuplicateName { item: Box<str>, name: Box<str> },
+  #[error("{item}: the number {number} is reserved, but it is used by '{name}'")]
+  ReservedNumber {
+    item: Box<str>,
+    number: i64,
+    name: Box<str>,
+  },
+  #[error("{item}: the name '{name}' is reserved, but it is in use")]
+  ReservedName { item: Box<str>, name: Box<str> },
+}
+
+/// The errors that can occur when rebuilding a package from a [`Schema`](crate::schema::Schema) snapshot, with [`Package::from_schema`](crate::Package::from_schema).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+  #[error("{item}: the item '{name}' is not defined in the schema")]
+  UnknownItem { item: Box<str>, name: Box<str> },
+  #[error("{item}: the item does not belong to the package '{package}'")]
+  ForeignItem { item: Box<str>, package: Box<str> },
+}
+
+/// The errors that can occur when building a package from a declarative document, with [`loader::from_yaml`](crate::loader::from_yaml), [`loader::from_toml`](crate::loader::from_toml) or [`loader::from_json`](crate::loader::from_json).
+///
+/// The line and column are 1-based, and they point to the position in the document where the issue was found.
+#[cfg(feature = "serde")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+  #[error("line {line}, column {column}: {message}")]
+  Invalid {
+    line: usize,
+    column: usize,
+    message: Box<str>,
+  },
+  #[error("{0}")]
+  Unlocated(Box<str>),
+}
+
+/// The errors that can occur when converting a `serde_json::Value` into an [`OptionValue`](crate::OptionValue).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum JsonConversionError {
+  #[error("Null values cannot be converted into option values")]
+  NullValue,
+  #[error("Unknown tag '{0}'")]
+  UnknownTag(Box<str>),
+  #[error("Invalid value for the tag '{tag}': {value}")]
+  InvalidTaggedValue { tag: Box<str>, value: String },
+}
+
+/// The errors that can occur when evaluating the validation rules of a message against an instance of it, with [`Package::evaluate`](crate::Package::evaluate).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EvaluationError {
+  #[error("The message '{0}' is not defined in this package")]
+  UnknownMessage(Box<str>),
+  #[error("Invalid value at '{field_path}': {error}")]
+  InvalidInstance {
+    field_path: Box<str>,
+    error: Box<str>,
+  },
+  #[error("The rule '{rule_id}' for '{field_path}' could not be evaluated: {error}")]
+  RuleEvaluationFailure {
+    field_path: Box<str>,
+    rule_id: Box<str>,
+    error: Box<str>,
+  },
+}
+
+/// The errors that can occur when generating example instances of a message, with [`Package::examples`](crate::Package::examples).
+#[cfg(feature = "serde")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExampleError {
+  #[error("The message '{0}' is not defined in this package")]
+  UnknownMessage(Box<str>),
+  #[error("The generated instance violates {} rule(s): {:#?}", .0.len(), .0)]
+  UnsatisfiedRules(Vec<crate::evaluation::Violation>),
+  #[error(transparent)]
+  Evaluation(#[from] EvaluationError),
 }
@@ -0,0 +1,1336 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use convert_case::{Case, Casing};
+use proto_types::protovalidate::Ignore;
+use regex::Regex;
+use serde_json::Value as Json;
+
+use crate::{
+  cel::{
+    compare, default_value, equals, format_value, formats, now, parse, Interpreter, MessageValue,
+    Value,
+  },
+  checks::{find_option, get_value},
+  errors::EvaluationError,
+  fields::{field_json_name, FieldData, FieldKind},
+  json::{parse_duration, parse_timestamp},
+  oneofs::OneofData,
+  packages::PackageData,
+  FieldType, MapKey, OptionValue,
+};
+
+/// A violation of a validation rule, with the same structure used by protovalidate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+  /// The path to the invalid field, such as `items[0].name` or `labels["key"]`.
+  /// It is empty for the rules defined at the message level.
+  pub field_path: Box<str>,
+  /// The id of the rule that was violated, such as `string.min_len`, or the id of a Cel rule.
+  pub rule_id: Box<str>,
+  /// The description of the violation.
+  pub message: Box<str>,
+  /// Whether the violation refers to the key of a map entry rather than to its value, as in protovalidate.
+  /// The key and the value of an entry share the same path.
+  pub for_key: bool,
+}
+
+impl Display for Violation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.field_path.is_empty() {
+      write!(f, "[{}] {}", self.rule_id, self.message)
+    } else if self.for_key {
+      write!(
+        f,
+        "{} (key): [{}] {}",
+        self.field_path, self.rule_id, self.message
+      )
+    } else {
+      write!(
+        f,
+        "{}: [{}] {}",
+        self.field_path, self.rule_id, self.message
+      )
+    }
+  }
+}
+
+type EvaluationResult<T> = Result<T, EvaluationError>;
+
+/// Validates an instance of a message, in the proto3 JSON format, against the rules defined in the schema
+pub(crate) fn evaluate_message(
+  package: &PackageData,
+  msg_id: usize,
+  instance: &Json,
+) -> EvaluationResult<Vec<Violation>> {
+  let evaluator = Evaluator { package };
+  let message = evaluator.message_value(msg_id, instance, "")?;
+
+  let mut violations: Vec<Violation> = Vec::new();
+  evaluator.validate_message(msg_id, &message, "", &mut violations)?;
+
+  Ok(violations)
+}
+
+struct Evaluator<'a> {
+  package: &'a PackageData,
+}
+
+fn join_path(path: &str, name: &str) -> String {
+  if path.is_empty() {
+    name.into()
+  } else {
+    format!("{path}.{name}")
+  }
+}
+
+fn key_path(path: &str, key: &Value) -> String {
+  match key {
+    Value::String(key) => format!("{path}[{key:?}]"),
+    other => format!("{path}[{}]", format_value(other)),
+  }
+}
+
+fn invalid(path: &str, error: impl Into<Box<str>>) -> EvaluationError {
+  EvaluationError::InvalidInstance {
+    field_path: path.into(),
+    error: error.into(),
+  }
+}
+
+fn is_scalar(field_type: &FieldType) -> bool {
+  matches!(
+    field_type,
+    FieldType::Double
+      | FieldType::Float
+      | FieldType::Int64
+      | FieldType::Uint64
+      | FieldType::Int32
+      | FieldType::Fixed64
+      | FieldType::Fixed32
+      | FieldType::Bool
+      | FieldType::String
+      | FieldType::Bytes
+      | FieldType::Uint32
+      | FieldType::Enum(_)
+      | FieldType::Sfixed32
+      | FieldType::Sfixed64
+      | FieldType::Sint32
+      | FieldType::Sint64
+  )
+}
+
+// Optional fields and singular message fields can tell apart an unset value from a zero value
+fn tracks_presence(field: &FieldData) -> bool {
+  match field.kind {
+    FieldKind::Optional => true,
+    FieldKind::Repeated => false,
+    FieldKind::Normal => {
+      !is_scalar(&field.field_type) && !matches!(field.field_type, FieldType::Map(_, _))
+    }
+  }
+}
+
+// Reads the `ignore` setting of the rules of a field
+pub(crate) fn ignore_setting(rules: &OptionValue) -> Ignore {
+  match get_value(rules, "ignore") {
+    Some(OptionValue::Enum(name)) if name.as_ref() == "IGNORE_ALWAYS" => Ignore::Always,
+    Some(OptionValue::Enum(name)) if name.as_ref() == "IGNORE_IF_ZERO_VALUE" => Ignore::IfZeroValue,
+    _ => Ignore::Unspecified,
+  }
+}
+
+pub(crate) fn entries(value: &OptionValue) -> &[(Box<str>, OptionValue)] {
+  match value {
+    OptionValue::Message(entries) => entries,
+    _ => &[],
+  }
+}
+
+/// Converts the value of an option into a Cel value
+fn option_to_value(value: &OptionValue) -> Value {
+  match value {
+    OptionValue::Bool(v) => Value::Bool(*v),
+    OptionValue::Int(v) => Value::Int(*v),
+    OptionValue::Uint(v) => Value::Uint(*v),
+    OptionValue::Float(v) => Value::Double(*v),
+    OptionValue::String(v) | OptionValue::Enum(v) => Value::String(v.to_string()),
+    OptionValue::List(items) => Value::List(items.iter().map(option_to_value).collect()),
+    OptionValue::Message(entries) => Value::Map(
+      entries
+        .iter()
+        .map(|(key, value)| (Value::String(key.to_string()), option_to_value(value)))
+        .collect(),
+    ),
+    OptionValue::Duration(v) => Value::from_duration(v),
+    OptionValue::Timestamp(v) => Value::from_timestamp(v),
+  }
+}
+
+// The values for the bytes rules are stored as the escaped contents of a protobuf string literal
+pub(crate) fn unescape_bytes(value: &str) -> Vec<u8> {
+  let bytes = value.as_bytes();
+  let mut result: Vec<u8> = Vec::new();
+  let mut i = 0;
+
+  while i < bytes.len() {
+    if bytes[i] == b'\\' && i + 1 < bytes.len() {
+      if bytes[i + 1] == b'x'
+        && let Some(byte) = value
+          .get(i + 2..i + 4)
+          .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+      {
+        result.push(byte);
+        i += 4;
+        continue;
+      }
+
+      result.push(bytes[i + 1]);
+      i += 2;
+    } else {
+      result.push(bytes[i]);
+      i += 1;
+    }
+  }
+
+  result
+}
+
+fn bytes_rule_value(value: &OptionValue) -> Value {
+  match value {
+    OptionValue::String(v) => Value::Bytes(unescape_bytes(v)),
+    OptionValue::List(items) => Value::List(items.iter().map(bytes_rule_value).collect()),
+    other => option_to_value(other),
+  }
+}
+
+pub(crate) fn decode_base64(input: &str) -> Option<Vec<u8>> {
+  let input = input.trim_end_matches('=');
+  let mut result: Vec<u8> = Vec::with_capacity(input.len() * 3 / 4);
+  let mut buffer: u32 = 0;
+  let mut bits: u32 = 0;
+
+  for c in input.bytes() {
+    let value = match c {
+      b'A'..=b'Z' => c - b'A',
+      b'a'..=b'z' => c - b'a' + 26,
+      b'0'..=b'9' => c - b'0' + 52,
+      b'+' | b'-' => 62,
+      b'/' | b'_' => 63,
+      _ => return None,
+    };
+
+    buffer = (buffer << 6) | value as u32;
+    bits += 6;
+
+    if bits >= 8 {
+      bits -= 8;
+      result.push((buffer >> bits) as u8);
+      buffer &= (1 << bits) - 1;
+    }
+  }
+
+  Some(result)
+}
+
+fn json_to_value(json: &Json) -> Value {
+  match json {
+    Json::Null => Value::Null,
+    Json::Bool(v) => Value::Bool(*v),
+    Json::Number(v) => {
+      if let Some(v) = v.as_i64() {
+        Value::Int(v)
+      } else if let Some(v) = v.as_u64() {
+        Value::Uint(v)
+      } else {
+        Value::Double(v.as_f64().unwrap_or_default())
+      }
+    }
+    Json::String(v) => Value::String(v.clone()),
+    Json::Array(items) => Value::List(items.iter().map(json_to_value).collect()),
+    Json::Object(entries) => Value::Map(
+      entries
+        .iter()
+        .map(|(key, value)| (Value::String(key.clone()), json_to_value(value)))
+        .collect(),
+    ),
+  }
+}
+
+fn format_list(value: &OptionValue) -> String {
+  format_value(&option_to_value(value))
+}
+
+pub(crate) fn as_u64(value: &OptionValue) -> u64 {
+  match value {
+    OptionValue::Uint(v) => *v,
+    OptionValue::Int(v) => *v as u64,
+    _ => 0,
+  }
+}
+
+impl Evaluator<'_> {
+  fn message_value(
+    &self,
+    msg_id: usize,
+    json: &Json,
+    path: &str,
+  ) -> EvaluationResult<MessageValue> {
+    let Json::Object(object) = json else {
+      return Err(invalid(path, "expected an object"));
+    };
+
+    let msg = &self.package.messages[msg_id];
+    let mut fields: Vec<(Box<str>, Value)> = Vec::new();
+
+    for (key, value) in object {
+      let field_path = join_path(path, key);
+
+      let (field, oneof) = msg
+        .fields
+        .iter()
+        .map(|(_, field)| (field, None))
+        .chain(msg.oneofs.iter().flat_map(|oneof| {
+          oneof
+            .fields
+            .iter()
+            .map(move |(_, field)| (field, Some(oneof)))
+        }))
+        .find(|(field, _)| field.name.as_ref() == key || field_json_name(field) == *key)
+        .ok_or_else(|| invalid(&field_path, "unknown field"))?;
+
+      if value.is_null() {
+        continue;
+      }
+
+      let converted = self.field_value(field, value, &field_path)?;
+
+      if oneof.is_none() && !tracks_presence(field) && converted.is_zero() {
+        continue;
+      }
+
+      if let Some(oneof) = oneof
+        && let Some((_, other)) = oneof.fields.iter().find(|(_, other)| {
+          other.name != field.name
+            && fields
+              .iter()
+              .any(|(name, _)| name.as_ref() == other.name.as_ref())
+        })
+      {
+        return Err(invalid(
+          &field_path,
+          format!(
+            "the fields '{}' and '{}' of the oneof '{}' cannot be set at the same time",
+            other.name, field.name, oneof.name
+          ),
+        ));
+      }
+
+      fields.push((field.name.as_ref().into(), converted));
+    }
+
+    Ok(MessageValue {
+      id: Some(msg_id),
+      fields,
+    })
+  }
+
+  fn field_value(&self, field: &FieldData, json: &Json, path: &str) -> EvaluationResult<Value> {
+    if field.kind != FieldKind::Repeated {
+      return self.convert_value(&field.field_type, json, path);
+    }
+
+    let Json::Array(items) = json else {
+      return Err(invalid(path, "expected an array"));
+    };
+
+    let items = items
+      .iter()
+      .enumerate()
+      .map(|(i, item)| self.convert_value(&field.field_type, item, &format!("{path}[{i}]")))
+      .collect::<EvaluationResult<Vec<Value>>>()?;
+
+    Ok(Value::List(items))
+  }
+
+  // Converts a JSON value into a Cel value, following the proto3 JSON mapping
+  fn convert_value(
+    &self,
+    field_type: &FieldType,
+    json: &Json,
+    path: &str,
+  ) -> EvaluationResult<Value> {
+    let value = match field_type {
+      FieldType::Double | FieldType::Float => match json {
+        Json::Number(v) => Value::Double(v.as_f64().unwrap_or_default()),
+        Json::String(v) => Value::Double(match v.as_str() {
+          "NaN" => f64::NAN,
+          "Infinity" => f64::INFINITY,
+          "-Infinity" => f64::NEG_INFINITY,
+          other => other
+            .parse()
+            .map_err(|_| invalid(path, "expected a number"))?,
+        }),
+        _ => return Err(invalid(path, "expected a number")),
+      },
+      FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => {
+        let value = int_value(json, path)?;
+
+        if i32::try_from(value).is_err() {
+          return Err(invalid(
+            path,
+            "the value is out of range for a 32-bit integer",
+          ));
+        }
+
+        Value::Int(value)
+      }
+      FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => {
+        Value::Int(int_value(json, path)?)
+      }
+      FieldType::Uint32 | FieldType::Fixed32 => {
+        let value = uint_value(json, path)?;
+
+        if u32::try_from(value).is_err() {
+          return Err(invalid(
+            path,
+            "the value is out of range for an unsigned 32-bit integer",
+          ));
+        }
+
+        Value::Uint(value)
+      }
+      FieldType::Uint64 | FieldType::Fixed64 => Value::Uint(uint_value(json, path)?),
+      FieldType::Bool => match json {
+        Json::Bool(v) => Value::Bool(*v),
+        _ => return Err(invalid(path, "expected a boolean")),
+      },
+      FieldType::String => match json {
+        Json::String(v) => Value::String(v.clone()),
+        _ => return Err(invalid(path, "expected a string")),
+      },
+      FieldType::Bytes => match json {
+        Json::String(v) => Value::Bytes(
+          decode_base64(v).ok_or_else(|| invalid(path, "expected a base64 encoded string"))?,
+        ),
+        _ => return Err(invalid(path, "expected a base64 encoded string")),
+      },
+      FieldType::Enum(enum_path) => match json {
+        Json::Number(_) => Value::Int(int_value(json, path)?),
+        Json::String(name) => {
+          let enum_ = self
+            .package
+            .enums
+            .iter()
+            .find(|e| e.import_path.full_name_with_package == enum_path.full_name_with_package)
+            .ok_or_else(|| {
+              invalid(
+                path,
+                format!(
+                  "the enum '{}' is not defined in this package",
+                  enum_path.full_name_with_package
+                ),
+              )
+            })?;
+
+          let prefix = enum_.name.to_case(Case::UpperSnake);
+
+          enum_
+            .variants
+            .iter()
+            .find(|(_, variant)| {
+              variant.name.as_ref() == name || format!("{}_{}", prefix, variant.name) == *name
+            })
+            .map(|(number, _)| Value::Int(*number as i64))
+            .ok_or_else(|| invalid(path, format!("unknown enum value '{name}'")))?
+        }
+        _ => return Err(invalid(path, "expected an enum name or number")),
+      },
+      FieldType::Duration => match json {
+        Json::String(v) => Value::from_duration(
+          &parse_duration(v).ok_or_else(|| invalid(path, "expected a duration such as '1.5s'"))?,
+        ),
+        _ => return Err(invalid(path, "expected a duration such as '1.5s'")),
+      },
+      FieldType::Timestamp => match json {
+        Json::String(v) => Value::from_timestamp(
+          &parse_timestamp(v).ok_or_else(|| invalid(path, "expected an RFC 3339 timestamp"))?,
+        ),
+        _ => return Err(invalid(path, "expected an RFC 3339 timestamp")),
+      },
+      FieldType::Map(key_type, value_type) => {
+        let Json::Object(object) = json else {
+          return Err(invalid(path, "expected an object"));
+        };
+
+        let mut entries: Vec<(Value, Value)> = Vec::new();
+
+        for (key, value) in object {
+          let key = map_key(key_type, key)
+            .ok_or_else(|| invalid(path, format!("the key '{key}' is not a valid {key_type}")))?;
+          let value = self.convert_value(value_type, value, &key_path(path, &key))?;
+
+          entries.push((key, value));
+        }
+
+        Value::Map(entries)
+      }
+      FieldType::Message(msg_path) => {
+        match self.package.message_id(&msg_path.full_name_with_package) {
+          Some(id) => Value::Message(self.message_value(id, json, path)?),
+          None => json_to_value(json),
+        }
+      }
+      FieldType::Any => {
+        let type_url = json
+          .get("@type")
+          .and_then(Json::as_str)
+          .ok_or_else(|| invalid(path, "an Any value must have a '@type' field"))?;
+
+        Value::Message(MessageValue {
+          id: None,
+          fields: vec![("type_url".into(), Value::String(type_url.into()))],
+        })
+      }
+      _ => json_to_value(json),
+    };
+
+    Ok(value)
+  }
+
+  fn validate_message(
+    &self,
+    msg_id: usize,
+    message: &MessageValue,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    let msg = &self.package.messages[msg_id];
+
+    if let Some(rules) = find_option(&msg.options, "(buf.validate.message)") {
+      if let Some(OptionValue::Bool(true)) = get_value(rules, "disabled") {
+        return Ok(());
+      }
+
+      self.apply_cel_rules(
+        rules,
+        &Value::Message(message.clone()),
+        None,
+        path,
+        violations,
+      )?;
+
+      if let Some(OptionValue::List(oneof_rules)) = get_value(rules, "oneof") {
+        for oneof_rule in oneof_rules.iter() {
+          check_message_oneof_rule(oneof_rule, message, path, violations);
+        }
+      }
+    }
+
+    for oneof in &msg.oneofs {
+      if is_required_oneof(oneof)
+        && !oneof
+          .fields
+          .iter()
+          .any(|(_, field)| message.get(&field.name).is_some())
+      {
+        violations.push(Violation {
+          field_path: join_path(path, &oneof.name).into(),
+          rule_id: "required".into(),
+          message: "exactly one field is required in oneof".into(),
+          for_key: false,
+        });
+      }
+    }
+
+    let fields = msg.fields.iter().map(|(_, field)| (field, false)).chain(
+      msg
+        .oneofs
+        .iter()
+        .flat_map(|oneof| oneof.fields.iter().map(|(_, field)| (field, true))),
+    );
+
+    for (field, in_oneof) in fields {
+      let field_path = join_path(path, &field.name);
+      let value = message.get(&field.name);
+
+      // Ignored fields skip the validation of the messages they contain as well
+      if find_option(&field.options, "(buf.validate.field)")
+        .is_some_and(|rules| ignore_setting(rules) == Ignore::Always)
+      {
+        continue;
+      }
+
+      self.validate_field(field, in_oneof, value, &field_path, violations)?;
+
+      if let Some(value) = value {
+        self.validate_nested(&field.field_type, value, &field_path, violations)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  // Validates the messages contained in a field, which can be a single message, a list of messages or a map with message values
+  fn validate_nested(
+    &self,
+    field_type: &FieldType,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    match (field_type, value) {
+      (FieldType::Message(_), Value::List(items)) => {
+        for (i, item) in items.iter().enumerate() {
+          self.validate_nested(field_type, item, &format!("{path}[{i}]"), violations)?;
+        }
+      }
+      (FieldType::Message(_), Value::Message(message)) => {
+        if let Some(id) = message.id {
+          self.validate_message(id, message, path, violations)?;
+        }
+      }
+      (FieldType::Map(_, value_type), Value::Map(entries)) => {
+        for (key, value) in entries {
+          self.validate_nested(value_type, value, &key_path(path, key), violations)?;
+        }
+      }
+      _ => {}
+    }
+
+    Ok(())
+  }
+
+  fn validate_field(
+    &self,
+    field: &FieldData,
+    in_oneof: bool,
+    value: Option<&Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    let Some(rules) = find_option(&field.options, "(buf.validate.field)") else {
+      return Ok(());
+    };
+
+    let value = match value {
+      Some(value) => value.clone(),
+      None if matches!(get_value(rules, "required"), Some(OptionValue::Bool(true))) => {
+        violations.push(Violation {
+          field_path: path.into(),
+          rule_id: "required".into(),
+          message: "value is required".into(),
+          for_key: false,
+        });
+
+        return Ok(());
+      }
+      // The rules are not applied to unset fields that track presence
+      None if in_oneof || tracks_presence(field) => return Ok(()),
+      None => default_value(self.package, field),
+    };
+
+    self.apply_field_rules(rules, Some(&field.field_type), &value, path, violations)
+  }
+
+  fn apply_field_rules(
+    &self,
+    rules: &OptionValue,
+    field_type: Option<&FieldType>,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    // The rules of list items and map entries can be ignored on their own
+    match ignore_setting(rules) {
+      Ignore::Always => return Ok(()),
+      Ignore::IfZeroValue if value.is_zero() => return Ok(()),
+      _ => {}
+    }
+
+    self.apply_cel_rules(rules, value, None, path, violations)?;
+
+    for (kind, type_rules) in entries(rules) {
+      if matches!(kind.as_ref(), "cel" | "required" | "ignore") {
+        continue;
+      }
+
+      self.apply_type_rules(kind, type_rules, field_type, value, path, violations)?;
+    }
+
+    Ok(())
+  }
+
+  fn apply_type_rules(
+    &self,
+    kind: &str,
+    rules: &OptionValue,
+    field_type: Option<&FieldType>,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    let mut violation = |rule_id: String, message: String| {
+      violations.push(Violation {
+        field_path: path.into(),
+        rule_id: rule_id.into(),
+        message: message.into(),
+        for_key: false,
+      })
+    };
+
+    if let Some((rule_id, message)) = range_violation(kind, rules, value) {
+      violation(rule_id, message);
+    }
+
+    for (name, rule) in entries(rules) {
+      if name.starts_with('[') {
+        continue;
+      }
+
+      let failure = match (kind, value) {
+        ("repeated", Value::List(items)) => repeated_rule(name, rule, items),
+        ("map", Value::Map(entries)) => map_rule(name, rule, entries),
+        ("string", Value::String(v)) => string_rule(name, rule, v, rules),
+        ("bytes", Value::Bytes(v)) => bytes_rule(name, rule, v),
+        ("enum", Value::Int(v)) => self.enum_rule(name, rule, *v, field_type),
+        ("any", Value::Message(any)) => any_rule(name, rule, any),
+        ("timestamp", Value::Timestamp(v)) => timestamp_rule(name, rule, *v),
+        (_, value) => common_rule(name, rule, value),
+      };
+
+      if let Some((rule_name, message)) =
+        failure.map_err(|error| EvaluationError::RuleEvaluationFailure {
+          field_path: path.into(),
+          rule_id: format!("{kind}.{name}").into(),
+          error: error.into(),
+        })?
+      {
+        violation(format!("{kind}.{rule_name}"), message);
+      }
+    }
+
+    let element_type = field_type.and_then(|t| match t {
+      FieldType::Map(_, value_type) => Some(value_type.as_ref()),
+      _ => None,
+    });
+
+    match (kind, value) {
+      ("repeated", Value::List(items)) => {
+        if let Some(item_rules) = get_value(rules, "items") {
+          for (i, item) in items.iter().enumerate() {
+            self.apply_field_rules(
+              item_rules,
+              field_type,
+              item,
+              &format!("{path}[{i}]"),
+              violations,
+            )?;
+          }
+        }
+      }
+      ("map", Value::Map(map_entries)) => {
+        for (key, item) in map_entries {
+          let item_path = key_path(path, key);
+
+          if let Some(key_rules) = get_value(rules, "keys") {
+            let first_key_violation = violations.len();
+
+            self.apply_field_rules(key_rules, None, key, &item_path, violations)?;
+
+            for violation in &mut violations[first_key_violation..] {
+              violation.for_key = true;
+            }
+          }
+
+          if let Some(value_rules) = get_value(rules, "values") {
+            self.apply_field_rules(value_rules, element_type, item, &item_path, violations)?;
+          }
+        }
+      }
+      _ => {}
+    }
+
+    for (name, rule) in entries(rules) {
+      if let Some(rule_name) = name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+        self.apply_predefined_rule(rule_name, rule, value, path, violations)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  // Applies the Cel rules of a predefined rule defined in this package
+  fn apply_predefined_rule(
+    &self,
+    name: &str,
+    rule: &OptionValue,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    let definition = self
+      .package
+      .files
+      .iter()
+      .flat_map(|file| file.extensions.iter())
+      .filter(|ext| ext.kind.is_validate_rules())
+      .flat_map(|ext| ext.fields.iter())
+      .find(|(_, field)| format!("{}.{}", self.package.name, field.name) == name);
+
+    let Some((_, field)) = definition else {
+      return Ok(());
+    };
+
+    if let Some(rules) = find_option(&field.options, "(buf.validate.predefined)") {
+      self.apply_cel_rules(rules, value, Some(&option_to_value(rule)), path, violations)?;
+    }
+
+    Ok(())
+  }
+
+  fn apply_cel_rules(
+    &self,
+    rules: &OptionValue,
+    this: &Value,
+    rule: Option<&Value>,
+    path: &str,
+    violations: &mut Vec<Violation>,
+  ) -> EvaluationResult<()> {
+    let Some(OptionValue::List(cel_rules)) = get_value(rules, "cel") else {
+      return Ok(());
+    };
+
+    for cel_rule in cel_rules.iter() {
+      let get_string = |key: &str| match get_value(cel_rule, key) {
+        Some(OptionValue::String(value)) => value.as_ref(),
+        _ => "",
+      };
+
+      let (id, message, expression) = (
+        get_string("id"),
+        get_string("message"),
+        get_string("expression"),
+      );
+
+      let failure = |error: String| EvaluationError::RuleEvaluationFailure {
+        field_path: path.into(),
+        rule_id: id.into(),
+        error: error.into(),
+      };
+
+      let expr = parse(expression).map_err(failure)?;
+      let result = Interpreter::new(self.package, this.clone(), rule.cloned())
+        .eval(&expr)
+        .map_err(failure)?;
+
+      let violation_message = match result {
+        Value::Bool(true) => continue,
+        Value::String(text) if text.is_empty() => continue,
+        Value::Bool(false) if message.is_empty() => format!("\"{expression}\" returned false"),
+        Value::Bool(false) => message.into(),
+        Value::String(text) => text,
+        other => {
+          return Err(failure(format!(
+            "the expression must evaluate to a bool or a string, found {}",
+            other.type_name()
+          )))
+        }
+      };
+
+      violations.push(Violation {
+        field_path: path.into(),
+        rule_id: id.into(),
+        message: violation_message.into(),
+        for_key: false,
+      });
+    }
+
+    Ok(())
+  }
+
+  fn enum_rule(
+    &self,
+    name: &str,
+    rule: &OptionValue,
+    value: i64,
+    field_type: Option<&FieldType>,
+  ) -> RuleResult {
+    if name != "defined_only" {
+      return common_rule(name, rule, &Value::Int(value));
+    }
+
+    let Some(FieldType::Enum(enum_path)) = field_type else {
+      return Ok(None);
+    };
+
+    let is_defined = self
+      .package
+      .enums
+      .iter()
+      .find(|e| e.import_path.full_name_with_package == enum_path.full_name_with_package)
+      .is_none_or(|e| e.variants.iter().any(|(number, _)| *number as i64 == value));
+
+    Ok((*rule == OptionValue::Bool(true) && !is_defined).then(|| {
+      (
+        "defined_only".into(),
+        "value must be one of the defined enum values".into(),
+      )
+    }))
+  }
+}
+
+// The name of the violated rule and the violation message, if the rule is not satisfied
+type RuleResult = Result<Option<(String, String)>, String>;
+
+fn check_message_oneof_rule(
+  rule: &OptionValue,
+  message: &MessageValue,
+  path: &str,
+  violations: &mut Vec<Violation>,
+) {
+  let names: Vec<&str> = match get_value(rule, "fields") {
+    Some(OptionValue::List(fields)) => fields
+      .iter()
+      .filter_map(|field| match field {
+        OptionValue::String(name) => Some(name.as_ref()),
+        _ => None,
+      })
+      .collect(),
+    _ => Vec::new(),
+  };
+  let required = matches!(get_value(rule, "required"), Some(OptionValue::Bool(true)));
+
+  let set_fields = names
+    .iter()
+    .filter(|name| message.get(name).is_some())
+    .count();
+
+  let violation_message = if set_fields > 1 {
+    format!("only one of {} can be set", names.join(", "))
+  } else if set_fields == 0 && required {
+    format!("one of {} must be set", names.join(", "))
+  } else {
+    return;
+  };
+
+  violations.push(Violation {
+    field_path: path.into(),
+    rule_id: "message.oneof".into(),
+    message: violation_message.into(),
+    for_key: false,
+  });
+}
+
+pub(crate) fn is_required_oneof(oneof: &OneofData) -> bool {
+  oneof.options.iter().any(|option| match &*option.name {
+    "(buf.validate.oneof).required" => *option.value == OptionValue::Bool(true),
+    "(buf.validate.oneof)" => matches!(
+      get_value(&option.value, "required"),
+      Some(OptionValue::Bool(true))
+    ),
+    _ => false,
+  })
+}
+
+fn int_value(json: &Json, path: &str) -> EvaluationResult<i64> {
+  match json {
+    Json::Number(v) => v
+      .as_i64()
+      .or_else(|| v.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+      .ok_or_else(|| invalid(path, "expected an integer")),
+    Json::String(v) => v.parse().map_err(|_| invalid(path, "expected an integer")),
+    _ => Err(invalid(path, "expected an integer")),
+  }
+}
+
+fn uint_value(json: &Json, path: &str) -> EvaluationResult<u64> {
+  match json {
+    Json::Number(v) => v
+      .as_u64()
+      .or_else(|| {
+        v.as_f64()
+          .filter(|f| f.fract() == 0.0 && *f >= 0.0)
+          .map(|f| f as u64)
+      })
+      .ok_or_else(|| invalid(path, "expected an unsigned integer")),
+    Json::String(v) => v
+      .parse()
+      .map_err(|_| invalid(path, "expected an unsigned integer")),
+    _ => Err(invalid(path, "expected an unsigned integer")),
+  }
+}
+
+fn map_key(key_type: &MapKey, key: &str) -> Option<Value> {
+  match key_type {
+    MapKey::String => Some(Value::String(key.into())),
+    MapKey::Bool => key.parse().ok().map(Value::Bool),
+    MapKey::Uint32 | MapKey::Uint64 | MapKey::Fixed32 | MapKey::Fixed64 => {
+      key.parse().ok().map(Value::Uint)
+    }
+    _ => key.parse().ok().map(Value::Int),
+  }
+}
+
+// Checks the bounds set with lt, lte, gt and gte, which are reported as a single rule when combined, as in protovalidate
+fn range_violation(kind: &str, rules: &OptionValue, value: &Value) -> Option<(String, String)> {
+  let bound = |name: &'static str| get_value(rules, name).map(|v| (name, option_to_value(v)));
+
+  let lower = bound("gt").or_else(|| bound("gte"));
+  let upper = bound("lt").or_else(|| bound("lte"));
+
+  let satisfies = |(name, limit): &(&str, Value)| {
+    let ordering = compare(value, limit);
+
+    match *name {
+      "gt" => ordering == Some(Ordering::Greater),
+      "gte" => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+      "lt" => ordering == Some(Ordering::Less),
+      _ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+  };
+
+  let describe = |(name, limit): &(&str, Value)| {
+    let relation = match *name {
+      "gt" => "greater than",
+      "gte" => "greater than or equal to",
+      "lt" => "less than",
+      _ => "less than or equal to",
+    };
+
+    format!("{relation} {}", format_value(limit))
+  };
+
+  let (rule_id, message) = match (&lower, &upper) {
+    (None, None) => return None,
+    (Some(lower), None) => {
+      if satisfies(lower) {
+        return None;
+      }
+
+      (
+        lower.0.to_string(),
+        format!("value must be {}", describe(lower)),
+      )
+    }
+    (None, Some(upper)) => {
+      if satisfies(upper) {
+        return None;
+      }
+
+      (
+        upper.0.to_string(),
+        format!("value must be {}", describe(upper)),
+      )
+    }
+    (Some(lower), Some(upper)) => {
+      let is_exclusive = compare(&upper.1, &lower.1) == Some(Ordering::Less);
+
+      if is_exclusive {
+        if satisfies(lower) || satisfies(upper) {
+          return None;
+        }
+
+        (
+          format!("{}_{}_exclusive", lower.0, upper.0),
+          format!("value must be {} or {}", describe(lower), describe(upper)),
+        )
+      } else {
+        if satisfies(lower) && satisfies(upper) {
+          return None;
+        }
+
+        (
+          format!("{}_{}", lower.0, upper.0),
+          format!("value must be {} and {}", describe(lower), describe(upper)),
+        )
+      }
+    }
+  };
+
+  Some((format!("{kind}.{rule_id}"), message))
+}
+
+// The rules shared by most types: const, in, not_in and finite
+fn common_rule(name: &str, rule: &OptionValue, value: &Value) -> RuleResult {
+  let expected = option_to_value(rule);
+
+  let failure = match name {
+    "const" if !equals(value, &expected) => format!("value must equal {}", format_value(&expected)),
+    "in" if !list_contains(&expected, value) => {
+      format!("value must be in list {}", format_list(rule))
+    }
+    "not_in" if list_contains(&expected, value) => {
+      format!("value must not be in list {}", format_list(rule))
+    }
+    "finite"
+      if expected == Value::Bool(true) && matches!(value, Value::Double(v) if !v.is_finite()) =>
+    {
+      "value must be finite".into()
+    }
+    _ => return Ok(None),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
+
+fn list_contains(list: &Value, value: &Value) -> bool {
+  match list {
+    Value::List(items) => items.iter().any(|item| equals(item, value)),
+    _ => false,
+  }
+}
+
+fn repeated_rule(name: &str, rule: &OptionValue, items: &[Value]) -> RuleResult {
+  let failure = match name {
+    "min_items" if (items.len() as u64) < as_u64(rule) => {
+      format!("value must contain at least {} item(s)", as_u64(rule))
+    }
+    "max_items" if (items.len() as u64) > as_u64(rule) => {
+      format!("value must contain no more than {} item(s)", as_u64(rule))
+    }
+    "unique"
+      if *rule == OptionValue::Bool(true)
+        && items
+          .iter()
+          .enumerate()
+          .any(|(i, item)| items[..i].iter().any(|other| equals(item, other))) =>
+    {
+      "repeated value must contain unique items".into()
+    }
+    _ => return Ok(None),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
+
+fn map_rule(name: &str, rule: &OptionValue, entries: &[(Value, Value)]) -> RuleResult {
+  let failure = match name {
+    "min_pairs" if (entries.len() as u64) < as_u64(rule) => {
+      format!("map must be at least {} entries", as_u64(rule))
+    }
+    "max_pairs" if (entries.len() as u64) > as_u64(rule) => {
+      format!("map must be at most {} entries", as_u64(rule))
+    }
+    _ => return Ok(None),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
+
+fn string_rule(name: &str, rule: &OptionValue, value: &str, rules: &OptionValue) -> RuleResult {
+  let text = match rule {
+    OptionValue::String(text) => text.as_ref(),
+    _ => "",
+  };
+  let is_set = *rule == OptionValue::Bool(true);
+  let chars = value.chars().count() as u64;
+  let bytes = value.len() as u64;
+
+  let failure = match name {
+    "len" if chars != as_u64(rule) => format!("value length must be {} characters", as_u64(rule)),
+    "min_len" if chars < as_u64(rule) => {
+      format!("value length must be at least {} characters", as_u64(rule))
+    }
+    "max_len" if chars > as_u64(rule) => {
+      format!("value length must be at most {} characters", as_u64(rule))
+    }
+    "len_bytes" if bytes != as_u64(rule) => format!("value length must be {} bytes", as_u64(rule)),
+    "min_bytes" if bytes < as_u64(rule) => {
+      format!("value length must be at least {} bytes", as_u64(rule))
+    }
+    "max_bytes" if bytes > as_u64(rule) => {
+      format!("value length must be at most {} bytes", as_u64(rule))
+    }
+    "pattern" => {
+      let regex = Regex::new(text).map_err(|e| format!("invalid regex: {e}"))?;
+
+      if regex.is_match(value) {
+        return Ok(None);
+      }
+
+      format!("value does not match regex pattern `{text}`")
+    }
+    "prefix" if !value.starts_with(text) => format!("value does not have prefix `{text}`"),
+    "suffix" if !value.ends_with(text) => format!("value does not have suffix `{text}`"),
+    "contains" if !value.contains(text) => format!("value does not contain substring `{text}`"),
+    "not_contains" if value.contains(text) => format!("value contains substring `{text}`"),
+    "well_known_regex" => {
+      let strict = !matches!(get_value(rules, "strict"), Some(OptionValue::Bool(false)));
+
+      let (is_valid, rule_name, description) = match rule {
+        OptionValue::Enum(regex) if regex.as_ref() == "KNOWN_REGEX_HTTP_HEADER_NAME" => (
+          formats::is_header_name(value, strict),
+          "well_known_regex.header_name",
+          "HTTP header name",
+        ),
+        OptionValue::Enum(regex) if regex.as_ref() == "KNOWN_REGEX_HTTP_HEADER_VALUE" => (
+          formats::is_header_value(value, strict),
+          "well_known_regex.header_value",
+          "HTTP header value",
+        ),
+        _ => return Ok(None),
+      };
+
+      if is_valid {
+        return Ok(None);
+      }
+
+      return Ok(Some((
+        rule_name.into(),
+        format!("value must be a valid {description}"),
+      )));
+    }
+    _ if is_set => {
+      let (is_valid, description) = match name {
+        "email" => (formats::is_email(value), "email address"),
+        "hostname" => (formats::is_hostname(value), "hostname"),
+        "ip" => (formats::is_ip(value, 0), "IP address"),
+        "ipv4" => (formats::is_ip(value, 4), "IPv4 address"),
+        "ipv6" => (formats::is_ip(value, 6), "IPv6 address"),
+        "uri" => (formats::is_uri(value), "URI"),
+        "uri_ref" => (formats::is_uri_ref(value), "URI Reference"),
+        "address" => (
+          formats::is_hostname(value) || formats::is_ip(value, 0),
+          "hostname, or ip address",
+        ),
+        "uuid" => (formats::is_uuid(value), "UUID"),
+        "tuuid" => (formats::is_tuuid(value), "trimmed UUID"),
+        "ip_with_prefixlen" => (formats::is_ip_prefix(value, 0, false), "IP prefix"),
+        "ipv4_with_prefixlen" => (
+          formats::is_ip_prefix(value, 4, false),
+          "IPv4 address with prefix length",
+        ),
+        "ipv6_with_prefixlen" => (
+          formats::is_ip_prefix(value, 6, false),
+          "IPv6 address with prefix length",
+        ),
+        "ip_prefix" => (formats::is_ip_prefix(value, 0, true), "IP prefix"),
+        "ipv4_prefix" => (formats::is_ip_prefix(value, 4, true), "IPv4 prefix"),
+        "ipv6_prefix" => (formats::is_ip_prefix(value, 6, true), "IPv6 prefix"),
+        "host_and_port" => (
+          formats::is_host_and_port(value, true),
+          "host (hostname or IP address) and port pair",
+        ),
+        _ => return common_rule(name, rule, &Value::String(value.into())),
+      };
+
+      if is_valid {
+        return Ok(None);
+      }
+
+      // Empty values are reported with a dedicated rule id, as in protovalidate
+      if value.is_empty() {
+        return Ok(Some((
+          format!("{name}_empty"),
+          format!("value is empty, which is not a valid {description}"),
+        )));
+      }
+
+      format!("value must be a valid {description}")
+    }
+    _ => return common_rule(name, rule, &Value::String(value.into())),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
+
+fn bytes_rule(name: &str, rule: &OptionValue, value: &[u8]) -> RuleResult {
+  let expected = bytes_rule_value(rule);
+  let expected_bytes: &[u8] = match &expected {
+    Value::Bytes(bytes) => bytes,
+    _ => &[],
+  };
+  let is_set = *rule == OptionValue::Bool(true);
+  let length = value.len() as u64;
+
+  let failure = match name {
+    "len" if length != as_u64(rule) => format!("value length must be {} bytes", as_u64(rule)),
+    "min_len" if length < as_u64(rule) => {
+      format!("value length must be at least {} bytes", as_u64(rule))
+    }
+    "max_len" if length > as_u64(rule) => {
+      format!("value length must be at most {} bytes", as_u64(rule))
+    }
+    "pattern" => {
+      let pattern = match rule {
+        OptionValue::String(pattern) => pattern.as_ref(),
+        _ => "",
+      };
+      let regex = Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?;
+      let text = std::str::from_utf8(value).map_err(|_| "the value must be valid UTF-8")?;
+
+      if regex.is_match(text) {
+        return Ok(None);
+      }
+
+      format!("value must match regex pattern `{pattern}`")
+    }
+    "prefix" if !value.starts_with(expected_bytes) => {
+      format!("value does not have prefix {}", format_value(&expected))
+    }
+    "suffix" if !value.ends_with(expected_bytes) => {
+      format!("value does not have suffix {}", format_value(&expected))
+    }
+    "contains"
+      if !expected_bytes.is_empty()
+        && !value
+          .windows(expected_bytes.len())
+          .any(|window| window == expected_bytes) =>
+    {
+      format!("value does not contain {}", format_value(&expected))
+    }
+    "ip" if is_set && value.len() != 4 && value.len() != 16 => {
+      "value must be a valid IP address".into()
+    }
+    "ipv4" if is_set && value.len() != 4 => "value must be a valid IPv4 address".into(),
+    "ipv6" if is_set && value.len() != 16 => "value must be a valid IPv6 address".into(),
+    "const" if !equals(&Value::Bytes(value.to_vec()), &expected) => {
+      format!("value must be {}", format_value(&expected))
+    }
+    "in" if !list_contains(&expected, &Value::Bytes(value.to_vec())) => {
+      format!("value must be in list {}", format_value(&expected))
+    }
+    "not_in" if list_contains(&expected, &Value::Bytes(value.to_vec())) => {
+      format!("value must not be in list {}", format_value(&expected))
+    }
+    _ => return Ok(None),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
+
+fn any_rule(name: &str, rule: &OptionValue, any: &MessageValue) -> RuleResult {
+  let type_url = any
+    .get("type_url")
+    .cloned()
+    .unwrap_or(Value::String(String::new()));
+  let list = option_to_value(rule);
+
+  let failure = match name {
+    "in" if !list_contains(&list, &type_url) => "type URL must be in the allow list",
+    "not_in" if list_contains(&list, &type_url) => "type URL must not be in the block list",
+    _ => return Ok(None),
+  };
+
+  Ok(Some((name.into(), failure.into())))
+}
+
+fn timestamp_rule(name: &str, rule: &OptionValue, value: i128) -> RuleResult {
+  let Value::Timestamp(now) = now() else {
+    return Ok(None);
+  };
+  let is_set = *rule == OptionValue::Bool(true);
+
+  let failure = match (name, option_to_value(rule)) {
+    ("lt_now", _) if is_set && value >= now => "value must be less than now".to_string(),
+    ("gt_now", _) if is_set && value <= now => "value must be greater than now".to_string(),
+    ("within", Value::Duration(within)) if (value - now).abs() > within => format!(
+      "value must be within {} of now",
+      format_value(&Value::Duration(within))
+    ),
+    ("lt_now" | "gt_now" | "within", _) => return Ok(None),
+    _ => return common_rule(name, rule, &Value::Timestamp(value)),
+  };
+
+  Ok(Some((name.into(), failure)))
+}
@@ -0,0 +1,1538 @@
+use convert_case::{Case, Casing};
+use proto_types::protovalidate::Ignore;
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde_json::{Map, Value as Json};
+
+#[cfg(feature = "proptest")]
+use proptest::{prelude::*, sample::Index};
+
+#[cfg(feature = "proptest")]
+use crate::Package;
+use crate::{
+  cel::{now, Value},
+  checks::{find_option, get_value},
+  errors::ExampleError,
+  evaluation::{
+    as_u64, decode_base64, entries, evaluate_message, ignore_setting, is_required_oneof,
+    unescape_bytes, Violation,
+  },
+  field_type::ImportedItemPath,
+  fields::{field_json_name, json_name, FieldData, FieldKind},
+  json::{format_duration, format_timestamp},
+  packages::PackageData,
+  Duration, FieldType, MapKey, OptionValue, Timestamp,
+};
+
+/// The example instances of a message, generated from its validation rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Examples {
+  /// An instance, in the proto3 JSON format, that satisfies all the validation rules of the message.
+  pub valid: Json,
+  /// The instances that violate exactly one validation rule each.
+  /// Each of them is obtained by changing a single value of the valid instance.
+  pub invalid: Vec<InvalidExample>,
+}
+
+/// An instance of a message that violates exactly one of its validation rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidExample {
+  /// The violation reported for this instance.
+  pub violation: Violation,
+  /// The instance, in the proto3 JSON format.
+  pub instance: Json,
+}
+
+type ExampleResult<T> = Result<T, ExampleError>;
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const NANOS_PER_DAY: i128 = 86_400 * NANOS_PER_SECOND;
+// 2024-01-01T00:00:00Z, used as the starting point for the timestamps without bounds
+const DEFAULT_TIMESTAMP: i128 = 1_704_067_200 * NANOS_PER_SECOND;
+const DEFAULT_ANY_TYPE: &str = "type.googleapis.com/google.protobuf.Empty";
+
+/// Generates the valid instance of a message and its counter-examples.
+///
+/// A seed of zero always picks the first available choice, so that the resulting examples are stable.
+pub(crate) fn generate_examples(
+  package: &PackageData,
+  msg_id: usize,
+  seed: u64,
+) -> ExampleResult<Examples> {
+  let mut generator = Generator::new(package, seed);
+  let object = generator.message(msg_id);
+  let valid = Json::Object(object.clone());
+
+  let violations = evaluate_message(package, msg_id, &valid)?;
+
+  if !violations.is_empty() {
+    return Err(ExampleError::UnsatisfiedRules(violations));
+  }
+
+  let mut invalid: Vec<InvalidExample> = Vec::new();
+
+  for instance in generator.message_counter_examples(msg_id, &object) {
+    // The candidates that break more than one rule (or none at all) are discarded
+    let Ok(mut violations) = evaluate_message(package, msg_id, &instance) else {
+      continue;
+    };
+
+    if violations.len() != 1 {
+      continue;
+    }
+
+    let violation = violations.remove(0);
+
+    if invalid.iter().any(|example| {
+      example.violation.field_path == violation.field_path
+        && example.violation.rule_id == violation.rule_id
+        && example.violation.for_key == violation.for_key
+    }) {
+      continue;
+    }
+
+    invalid.push(InvalidExample {
+      violation,
+      instance,
+    });
+  }
+
+  Ok(Examples { valid, invalid })
+}
+
+/// Returns a [`proptest`] strategy that produces valid instances of a message, in the proto3 JSON format.
+///
+/// The message can be referred to with its fully qualified name or with its name within the package, like in [`Package::examples`].
+#[cfg(feature = "proptest")]
+pub fn valid_instances(package: &Package, message: &str) -> ExampleResult<BoxedStrategy<Json>> {
+  let (data, _) = package.processed_data();
+  let msg_id = resolve_message(&data, message)?;
+
+  Ok(
+    any::<u64>()
+      .prop_filter_map(
+        "the generated instance does not satisfy the validation rules",
+        move |seed| {
+          let instance = Json::Object(Generator::new(&data, seed).message(msg_id));
+
+          matches!(evaluate_message(&data, msg_id, &instance), Ok(violations) if violations.is_empty())
+            .then_some(instance)
+        },
+      )
+      .boxed(),
+  )
+}
+
+/// Returns a [`proptest`] strategy that produces instances of a message that violate exactly one of its validation rules.
+///
+/// The message can be referred to with its fully qualified name or with its name within the package, like in [`Package::examples`].
+#[cfg(feature = "proptest")]
+pub fn invalid_instances(
+  package: &Package,
+  message: &str,
+) -> ExampleResult<BoxedStrategy<InvalidExample>> {
+  let (data, _) = package.processed_data();
+  let msg_id = resolve_message(&data, message)?;
+
+  Ok(
+    (any::<u64>(), any::<Index>())
+      .prop_filter_map(
+        "no counter-example could be generated",
+        move |(seed, index)| {
+          let mut examples = generate_examples(&data, msg_id, seed).ok()?;
+
+          if examples.invalid.is_empty() {
+            return None;
+          }
+
+          let index = index.index(examples.invalid.len());
+
+          Some(examples.invalid.swap_remove(index))
+        },
+      )
+      .boxed(),
+  )
+}
+
+// Finds a message and checks that a valid instance of it can be generated, so that a strategy does not reject all of its values
+#[cfg(feature = "proptest")]
+fn resolve_message(data: &PackageData, message: &str) -> ExampleResult<usize> {
+  let msg_id = data
+    .resolve_message(message)
+    .ok_or_else(|| ExampleError::UnknownMessage(message.into()))?;
+
+  generate_examples(data, msg_id, 0)?;
+
+  Ok(msg_id)
+}
+
+// A xorshift generator, used to vary the generated values
+struct Rng(u64);
+
+impl Rng {
+  fn below(&mut self, n: usize) -> usize {
+    if self.0 == 0 || n <= 1 {
+      return 0;
+    }
+
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+
+    (self.0 % n as u64) as usize
+  }
+}
+
+struct Generator<'a> {
+  package: &'a PackageData,
+  rng: Rng,
+  // The messages that are being generated, used to stop at recursive fields
+  stack: Vec<usize>,
+}
+
+// The rules that restrict the values of numbers, durations and timestamps, converted to integers
+struct Bounds {
+  const_: Option<i128>,
+  in_: Vec<i128>,
+  not_in: Vec<i128>,
+  lower: Option<i128>,
+  upper: Option<i128>,
+}
+
+impl Bounds {
+  fn new(
+    rules: Option<&OptionValue>,
+    step: i128,
+    convert: fn(&OptionValue) -> Option<i128>,
+  ) -> Self {
+    let rule = |name: &str| rules.and_then(|r| get_value(r, name)).and_then(convert);
+    let list = |name: &str| match rules.and_then(|r| get_value(r, name)) {
+      Some(OptionValue::List(items)) => items.iter().filter_map(convert).collect(),
+      _ => Vec::new(),
+    };
+
+    Self {
+      const_: rule("const"),
+      in_: list("in"),
+      not_in: list("not_in"),
+      lower: rule("gt").map(|v| v + step).or_else(|| rule("gte")),
+      upper: rule("lt").map(|v| v - step).or_else(|| rule("lte")),
+    }
+  }
+}
+
+fn integer_of(value: &OptionValue) -> Option<i128> {
+  match value {
+    OptionValue::Int(v) => Some(*v as i128),
+    OptionValue::Uint(v) => Some(*v as i128),
+    _ => None,
+  }
+}
+
+fn nanos_of(value: &OptionValue) -> Option<i128> {
+  match value {
+    OptionValue::Duration(v) => Some(v.seconds as i128 * NANOS_PER_SECOND + v.nanos as i128),
+    OptionValue::Timestamp(v) => Some(v.seconds as i128 * NANOS_PER_SECOND + v.nanos as i128),
+    _ => None,
+  }
+}
+
+fn float_of(value: &OptionValue) -> Option<f64> {
+  match value {
+    OptionValue::Float(v) => Some(*v),
+    OptionValue::Int(v) => Some(*v as f64),
+    OptionValue::Uint(v) => Some(*v as f64),
+    _ => None,
+  }
+}
+
+fn now_nanos() -> i128 {
+  match now() {
+    Value::Timestamp(nanos) => nanos,
+    _ => DEFAULT_TIMESTAMP,
+  }
+}
+
+fn duration_json(nanos: i128) -> Json {
+  Json::String(format_duration(&Duration {
+    seconds: (nanos / NANOS_PER_SECOND) as i64,
+    nanos: (nanos % NANOS_PER_SECOND) as i32,
+  }))
+}
+
+fn timestamp_json(nanos: i128) -> Json {
+  Json::String(format_timestamp(&Timestamp {
+    seconds: nanos.div_euclid(NANOS_PER_SECOND) as i64,
+    nanos: nanos.rem_euclid(NANOS_PER_SECOND) as i32,
+  }))
+}
+
+// The range of an integer type, and whether it uses 64 bits (which are encoded as strings in JSON)
+fn integer_range(field_type: &FieldType) -> Option<(i128, i128, bool)> {
+  let range = match field_type {
+    FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => {
+      (i32::MIN as i128, i32::MAX as i128, false)
+    }
+    FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => {
+      (i64::MIN as i128, i64::MAX as i128, true)
+    }
+    FieldType::Uint32 | FieldType::Fixed32 => (0, u32::MAX as i128, false),
+    FieldType::Uint64 | FieldType::Fixed64 => (0, u64::MAX as i128, true),
+    _ => return None,
+  };
+
+  Some(range)
+}
+
+fn integer_json(value: i128, is_64_bit: bool) -> Json {
+  if is_64_bit {
+    Json::String(value.to_string())
+  } else {
+    Json::from(value as i64)
+  }
+}
+
+fn key_type(key: &MapKey) -> FieldType {
+  match key {
+    MapKey::Int32 => FieldType::Int32,
+    MapKey::Int64 => FieldType::Int64,
+    MapKey::Uint32 => FieldType::Uint32,
+    MapKey::Uint64 => FieldType::Uint64,
+    MapKey::Sint32 => FieldType::Sint32,
+    MapKey::Sint64 => FieldType::Sint64,
+    MapKey::Fixed32 => FieldType::Fixed32,
+    MapKey::Fixed64 => FieldType::Fixed64,
+    MapKey::Sfixed32 => FieldType::Sfixed32,
+    MapKey::Sfixed64 => FieldType::Sfixed64,
+    MapKey::Bool => FieldType::Bool,
+    MapKey::String => FieldType::String,
+  }
+}
+
+// Map keys are always strings in JSON
+fn key_string(key: Json) -> String {
+  match key {
+    Json::String(key) => key,
+    other => other.to_string(),
+  }
+}
+
+// Whether a JSON value is the zero value of a type, which the rules with `IGNORE_IF_ZERO_VALUE` don't validate
+fn is_zero_json(field_type: &FieldType, value: &Json) -> bool {
+  match value {
+    Json::Null | Json::Bool(false) => true,
+    Json::Number(n) => n.as_f64() == Some(0.0),
+    Json::String(s) => match field_type {
+      FieldType::Duration => s == "0s",
+      FieldType::Timestamp => s == "1970-01-01T00:00:00Z",
+      FieldType::String | FieldType::Bytes => s.is_empty(),
+      FieldType::Enum(_) => false,
+      other => integer_range(other).is_some() && s == "0",
+    },
+    Json::Array(items) => items.is_empty(),
+    Json::Object(object) => object.is_empty() && !matches!(field_type, FieldType::Message(_)),
+    Json::Bool(true) => false,
+  }
+}
+
+// The rules for a specific type, such as the contents of `string` in `{ string: { min_len: 1 } }`
+fn type_rules(rules: &OptionValue) -> Option<&OptionValue> {
+  entries(rules)
+    .iter()
+    .find(|(name, _)| !matches!(name.as_ref(), "cel" | "required" | "ignore"))
+    .map(|(_, value)| value)
+}
+
+fn text_rule<'a>(rules: Option<&'a OptionValue>, name: &str) -> Option<&'a str> {
+  match rules.and_then(|r| get_value(r, name)) {
+    Some(OptionValue::String(value)) => Some(value),
+    _ => None,
+  }
+}
+
+fn text_list<'a>(rules: Option<&'a OptionValue>, name: &str) -> Vec<&'a str> {
+  match rules.and_then(|r| get_value(r, name)) {
+    Some(OptionValue::List(items)) => items
+      .iter()
+      .filter_map(|item| match item {
+        OptionValue::String(value) => Some(value.as_ref()),
+        _ => None,
+      })
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+fn length_rule(rules: Option<&OptionValue>, names: &[&str]) -> Option<usize> {
+  names
+    .iter()
+    .find_map(|name| rules.and_then(|r| get_value(r, name)))
+    .map(|value| as_u64(value) as usize)
+}
+
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+  let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+  for chunk in bytes.chunks(3) {
+    let buffer = chunk
+      .iter()
+      .enumerate()
+      .fold(0u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+
+    for i in 0..4 {
+      if i <= chunk.len() {
+        result.push(ALPHABET[(buffer >> (18 - 6 * i) & 0x3f) as usize] as char);
+      } else {
+        result.push('=');
+      }
+    }
+  }
+
+  result
+}
+
+// A value for the string formats supported by protovalidate, such as `email` or `uuid`
+fn well_known_string(rules: &OptionValue, index: usize) -> Option<String> {
+  entries(rules).iter().find_map(|(name, rule)| {
+    let value = match (name.as_ref(), rule) {
+      ("email", OptionValue::Bool(true)) => format!("user{}@example.com", suffix(index)),
+      ("hostname" | "address", OptionValue::Bool(true)) => match index {
+        0 => "example.com".into(),
+        _ => format!("host{index}.example.com"),
+      },
+      ("ip" | "ipv4", OptionValue::Bool(true)) => format!("192.168.0.{}", index % 254 + 1),
+      ("ipv6", OptionValue::Bool(true)) => format!("2001:db8::{:x}", index + 1),
+      ("uri", OptionValue::Bool(true)) => format!("https://example.com/{}", suffix(index)),
+      ("uri_ref", OptionValue::Bool(true)) => format!("/path/{}", suffix(index)),
+      ("uuid", OptionValue::Bool(true)) => format!("6f1e2c3a-8b4d-4e5f-9a0b-{:012x}", index + 1),
+      ("tuuid", OptionValue::Bool(true)) => format!("6f1e2c3a8b4d4e5f9a0b{:012x}", index + 1),
+      (
+        "ip_with_prefixlen" | "ip_prefix" | "ipv4_with_prefixlen" | "ipv4_prefix",
+        OptionValue::Bool(true),
+      ) => format!("192.168.{}.0/24", index % 256),
+      ("ipv6_with_prefixlen" | "ipv6_prefix", OptionValue::Bool(true)) => {
+        format!("2001:db8:{index:x}::/64")
+      }
+      ("host_and_port", OptionValue::Bool(true)) => format!("example.com:{}", 8080 + index),
+      ("well_known_regex", OptionValue::Enum(regex))
+        if regex.as_ref() == "KNOWN_REGEX_HTTP_HEADER_NAME" =>
+      {
+        format!("x-header{}", suffix(index))
+      }
+      ("well_known_regex", OptionValue::Enum(regex))
+        if regex.as_ref() == "KNOWN_REGEX_HTTP_HEADER_VALUE" =>
+      {
+        format!("value{}", suffix(index))
+      }
+      _ => return None,
+    };
+
+    Some(value)
+  })
+}
+
+fn suffix(index: usize) -> String {
+  match index {
+    0 => String::new(),
+    _ => index.to_string(),
+  }
+}
+
+/// Generates a short string that matches a regex.
+/// The repeated parts of the pattern are expanded until `extra` more characters are added, which is used to reach a minimum length or to make the strings unique.
+fn from_pattern(pattern: &str, extra: usize) -> Option<String> {
+  let hir = regex_syntax::parse(pattern).ok()?;
+  let mut result = String::new();
+  let mut extra = extra;
+
+  write_match(&hir, &mut extra, &mut result)?;
+
+  Some(result)
+}
+
+fn write_match(hir: &Hir, extra: &mut usize, output: &mut String) -> Option<()> {
+  match hir.kind() {
+    HirKind::Empty | HirKind::Look(_) => {}
+    HirKind::Literal(literal) => output.push_str(std::str::from_utf8(&literal.0).ok()?),
+    HirKind::Class(Class::Unicode(class)) => {
+      let ranges = class.ranges();
+      let preferred = ['a', '0', 'A', '-', '_']
+        .into_iter()
+        .find(|c| ranges.iter().any(|r| r.start() <= *c && *c <= r.end()));
+
+      output.push(preferred.or_else(|| ranges.first().map(|r| r.start()))?);
+    }
+    HirKind::Class(Class::Bytes(class)) => {
+      let byte = class.ranges().first()?.start();
+
+      output.push(char::from(byte).is_ascii().then_some(char::from(byte))?);
+    }
+    HirKind::Repetition(repetition) => {
+      for _ in 0..repetition.min {
+        write_match(&repetition.sub, extra, output)?;
+      }
+
+      let mut count = repetition.min;
+
+      while *extra > 0 && repetition.max.is_none_or(|max| count < max) {
+        let before = output.len();
+        write_match(&repetition.sub, extra, output)?;
+        let added = output.len() - before;
+
+        if added == 0 {
+          break;
+        }
+
+        *extra = extra.saturating_sub(added);
+        count += 1;
+      }
+    }
+    HirKind::Capture(capture) => write_match(&capture.sub, extra, output)?,
+    HirKind::Concat(items) => {
+      for item in items {
+        write_match(item, extra, output)?;
+      }
+    }
+    HirKind::Alternation(items) => write_match(items.first()?, extra, output)?,
+  }
+
+  Some(())
+}
+
+// Builds a string or a list of bytes from the prefix, contains and suffix rules, with a filler that is adjusted to respect the length rules
+fn fill(
+  parts: [&[u8]; 3],
+  filler: String,
+  min_len: Option<usize>,
+  max_len: Option<usize>,
+  length: impl Fn(&[u8]) -> usize,
+) -> Vec<u8> {
+  let [prefix, contains, suffix] = parts;
+  let fixed = length(prefix) + length(contains) + length(suffix);
+  let mut filler = filler.into_bytes();
+
+  if let Some(max_len) = max_len {
+    let available = max_len.saturating_sub(fixed);
+
+    // The end of the filler is kept, as it makes the values unique
+    if filler.len() > available {
+      filler.drain(..filler.len() - available);
+    }
+  }
+
+  if let Some(min_len) = min_len {
+    let missing = min_len.saturating_sub(fixed + filler.len());
+    filler.extend(std::iter::repeat_n(b'a', missing));
+  }
+
+  [prefix, &filler, contains, suffix].concat()
+}
+
+impl<'a> Generator<'a> {
+  fn new(package: &'a PackageData, seed: u64) -> Self {
+    Self {
+      package,
+      rng: Rng(seed),
+      stack: Vec::new(),
+    }
+  }
+
+  fn message(&mut self, msg_id: usize) -> Map<String, Json> {
+    let package = self.package;
+    let msg = &package.messages[msg_id];
+    let mut object: Map<String, Json> = Map::new();
+
+    self.stack.push(msg_id);
+
+    for (_, field) in msg.fields.iter() {
+      if let Some(value) = self.field(field) {
+        object.insert(field_json_name(field), value);
+      }
+    }
+
+    for oneof in &msg.oneofs {
+      let index = self.rng.below(oneof.fields.len());
+
+      if let Some((_, field)) = oneof.fields.get(index)
+        && let Some(value) = self.field(field)
+      {
+        object.insert(field_json_name(field), value);
+      }
+    }
+
+    // Only one of the fields in a message oneof rule can be set
+    if let Some(rules) = find_option(&msg.options, "(buf.validate.message)")
+      && let Some(OptionValue::List(oneof_rules)) = get_value(rules, "oneof")
+    {
+      for oneof_rule in oneof_rules.iter() {
+        let names = text_list(Some(oneof_rule), "fields");
+        let keep = self.rng.below(names.len());
+
+        for (i, name) in names.iter().enumerate() {
+          if i != keep {
+            object.remove(&json_key(package, msg_id, name));
+          }
+        }
+      }
+    }
+
+    self.stack.pop();
+
+    object
+  }
+
+  fn field(&mut self, field: &FieldData) -> Option<Json> {
+    let rules = find_option(&field.options, "(buf.validate.field)");
+
+    if field.kind == FieldKind::Repeated {
+      let list_rules = rules.and_then(|r| get_value(r, "repeated"));
+      let items_rules = list_rules.and_then(|r| get_value(r, "items"));
+      let count = self.collection_len(list_rules, "min_items", "max_items");
+
+      let items = (0..count)
+        .map(|i| self.value(&field.field_type, items_rules, i))
+        .collect::<Option<Vec<Json>>>()?;
+
+      return Some(Json::Array(items));
+    }
+
+    match &field.field_type {
+      FieldType::Map(key, value_type) => {
+        let map_rules = rules.and_then(|r| get_value(r, "map"));
+        let count = self.collection_len(map_rules, "min_pairs", "max_pairs");
+        let mut map: Map<String, Json> = Map::new();
+
+        for i in 0..count {
+          let key = self.value(
+            &key_type(key),
+            map_rules.and_then(|r| get_value(r, "keys")),
+            i,
+          )?;
+          let value = self.value(
+            value_type,
+            map_rules.and_then(|r| get_value(r, "values")),
+            i,
+          )?;
+
+          map.insert(key_string(key), value);
+        }
+
+        Some(Json::Object(map))
+      }
+      field_type => self.value(field_type, rules, 0),
+    }
+  }
+
+  fn collection_len(&mut self, rules: Option<&OptionValue>, min: &str, max: &str) -> usize {
+    let min = length_rule(rules, &[min]).unwrap_or_default();
+    let max = length_rule(rules, &[max]);
+    let len = min.max(1).min(max.unwrap_or(usize::MAX));
+    let extra = self
+      .rng
+      .below(max.unwrap_or(len + 3).saturating_sub(len).min(3) + 1);
+
+    len + extra
+  }
+
+  // Generates a value for a type, given the rules of a field (such as `{ string: { min_len: 1 } }`)
+  fn value(
+    &mut self,
+    field_type: &FieldType,
+    rules: Option<&OptionValue>,
+    index: usize,
+  ) -> Option<Json> {
+    let rules = rules.and_then(type_rules);
+    // The indexes are spread out when the values are randomized, so that the values in a list stay unique
+    let index = match self.rng.0 {
+      0 => index,
+      _ => index * 16 + self.rng.below(16),
+    };
+
+    let value = match field_type {
+      FieldType::String => Json::String(self.string(rules, index)),
+      FieldType::Bytes => Json::String(encode_base64(&self.bytes(rules, index))),
+      FieldType::Bool => Json::Bool(!matches!(
+        rules.and_then(|r| get_value(r, "const")),
+        Some(OptionValue::Bool(false))
+      )),
+      FieldType::Double | FieldType::Float => Json::from(self.float(rules, index)),
+      FieldType::Enum(path) => self.enum_value(path, rules, index)?,
+      FieldType::Duration => {
+        let bounds = Bounds::new(rules, NANOS_PER_SECOND, nanos_of);
+        let range = (
+          i64::MIN as i128 * NANOS_PER_SECOND,
+          i64::MAX as i128 * NANOS_PER_SECOND,
+        );
+
+        duration_json(pick(
+          bounds,
+          range,
+          NANOS_PER_SECOND,
+          NANOS_PER_SECOND,
+          index,
+        ))
+      }
+      FieldType::Timestamp => timestamp_json(self.timestamp(rules, index)),
+      FieldType::Any => {
+        let allowed = text_list(rules, "in");
+        let type_url = allowed
+          .get(index % allowed.len().max(1))
+          .copied()
+          .unwrap_or(DEFAULT_ANY_TYPE);
+
+        serde_json::json!({ "@type": type_url })
+      }
+      FieldType::Message(path) => {
+        let id = self.package.message_id(&path.full_name_with_package)?;
+
+        if self.stack.contains(&id) {
+          return None;
+        }
+
+        Json::Object(self.message(id))
+      }
+      FieldType::Empty | FieldType::Struct => Json::Object(Map::new()),
+      other => {
+        let (min, max, is_64_bit) = integer_range(other)?;
+        let bounds = Bounds::new(rules, 1, integer_of);
+
+        integer_json(pick(bounds, (min, max), 1, 1, index), is_64_bit)
+      }
+    };
+
+    Some(value)
+  }
+
+  fn string(&mut self, rules: Option<&OptionValue>, index: usize) -> String {
+    if let Some(value) = text_rule(rules, "const") {
+      return value.into();
+    }
+
+    let not_in = text_list(rules, "not_in");
+    let allowed: Vec<&str> = text_list(rules, "in")
+      .into_iter()
+      .filter(|value| !not_in.contains(value))
+      .collect();
+
+    if !allowed.is_empty() {
+      return allowed[index % allowed.len()].into();
+    }
+
+    if let Some(value) = rules.and_then(|r| well_known_string(r, index)) {
+      return value;
+    }
+
+    let min_len = length_rule(rules, &["len", "len_bytes", "min_len", "min_bytes"]);
+    let max_len = length_rule(rules, &["len", "len_bytes", "max_len", "max_bytes"]);
+
+    if let Some(pattern) = text_rule(rules, "pattern") {
+      let value = from_pattern(pattern, index).unwrap_or_default();
+      let missing = min_len
+        .unwrap_or_default()
+        .saturating_sub(value.chars().count());
+
+      if missing > 0 || max_len.is_some_and(|max| value.chars().count() > max) {
+        return from_pattern(pattern, missing).unwrap_or(value);
+      }
+
+      return value;
+    }
+
+    let parts = ["prefix", "contains", "suffix"]
+      .map(|name| text_rule(rules, name).unwrap_or_default().as_bytes());
+
+    let value = fill(
+      parts,
+      format!("value{}", suffix(index)),
+      min_len,
+      max_len,
+      |bytes| String::from_utf8_lossy(bytes).chars().count(),
+    );
+
+    String::from_utf8(value).unwrap_or_default()
+  }
+
+  fn bytes(&mut self, rules: Option<&OptionValue>, index: usize) -> Vec<u8> {
+    if let Some(value) = text_rule(rules, "const") {
+      return unescape_bytes(value);
+    }
+
+    let not_in: Vec<Vec<u8>> = text_list(rules, "not_in")
+      .into_iter()
+      .map(unescape_bytes)
+      .collect();
+    let allowed: Vec<Vec<u8>> = text_list(rules, "in")
+      .into_iter()
+      .map(unescape_bytes)
+      .filter(|value| !not_in.contains(value))
+      .collect();
+
+    if !allowed.is_empty() {
+      return allowed[index % allowed.len()].clone();
+    }
+
+    let is_set = |name: &str| {
+      matches!(
+        rules.and_then(|r| get_value(r, name)),
+        Some(OptionValue::Bool(true))
+      )
+    };
+
+    if is_set("ip") || is_set("ipv4") {
+      return vec![192, 168, 0, (index % 254 + 1) as u8];
+    }
+
+    if is_set("ipv6") {
+      let mut address = vec![0x20, 0x01, 0x0d, 0xb8];
+      address.extend([0; 11]);
+      address.push((index % 255 + 1) as u8);
+
+      return address;
+    }
+
+    let min_len = length_rule(rules, &["len", "min_len"]);
+    let max_len = length_rule(rules, &["len", "max_len"]);
+
+    if let Some(pattern) = text_rule(rules, "pattern") {
+      return from_pattern(pattern, min_len.unwrap_or_default())
+        .unwrap_or_default()
+        .into_bytes();
+    }
+
+    let parts = ["prefix", "contains", "suffix"].map(|name| {
+      text_rule(rules, name)
+        .map(unescape_bytes)
+        .unwrap_or_default()
+    });
+
+    fill(
+      [&parts[0], &parts[1], &parts[2]],
+      format!("value{}", suffix(index)),
+      min_len,
+      max_len,
+      <[u8]>::len,
+    )
+  }
+
+  fn float(&mut self, rules: Option<&OptionValue>, index: usize) -> f64 {
+    let rule = |name: &str| rules.and_then(|r| get_value(r, name)).and_then(float_of);
+    let list = |name: &str| match rules.and_then(|r| get_value(r, name)) {
+      Some(OptionValue::List(items)) => items.iter().filter_map(float_of).collect(),
+      _ => Vec::new(),
+    };
+
+    if let Some(value) = rule("const") {
+      return value;
+    }
+
+    let not_in: Vec<f64> = list("not_in");
+    let allowed: Vec<f64> = list("in")
+      .into_iter()
+      .filter(|value| !not_in.contains(value))
+      .collect();
+
+    if !allowed.is_empty() {
+      return allowed[index % allowed.len()];
+    }
+
+    let offset = index as f64;
+    let lower = rule("gt").or_else(|| rule("gte"));
+    let upper = rule("lt").or_else(|| rule("lte"));
+
+    let mut value = match (lower, upper) {
+      (Some(lower), Some(upper)) if lower <= upper => lower + (upper - lower) / (offset + 2.0),
+      (Some(lower), _) => lower + 1.0 + offset,
+      (None, Some(upper)) if upper > 1.0 + offset => 1.0 + offset,
+      (None, Some(upper)) => upper - 1.0 - offset,
+      (None, None) => 1.0 + offset,
+    };
+
+    while not_in.contains(&value) {
+      value += 0.5;
+    }
+
+    value
+  }
+
+  fn timestamp(&mut self, rules: Option<&OptionValue>, index: usize) -> i128 {
+    let mut bounds = Bounds::new(rules, NANOS_PER_SECOND, nanos_of);
+    let mut step = NANOS_PER_SECOND;
+    let now = now_nanos();
+    let is_set = |name: &str| {
+      matches!(
+        rules.and_then(|r| get_value(r, name)),
+        Some(OptionValue::Bool(true))
+      )
+    };
+
+    if is_set("lt_now") {
+      bounds.upper = Some(
+        bounds
+          .upper
+          .map_or(now - NANOS_PER_DAY, |u| u.min(now - NANOS_PER_DAY)),
+      );
+    }
+
+    if is_set("gt_now") {
+      bounds.lower = Some(
+        bounds
+          .lower
+          .map_or(now + NANOS_PER_DAY, |l| l.max(now + NANOS_PER_DAY)),
+      );
+    }
+
+    if let Some(within) = rules
+      .and_then(|r| get_value(r, "within"))
+      .and_then(nanos_of)
+    {
+      // The value stays well within the limits, since some time passes before it is validated
+      bounds.lower = Some(now - within / 2);
+      bounds.upper = Some(now + within / 2);
+      step = (within / 16).max(1);
+    }
+
+    let range = (
+      i64::MIN as i128 * NANOS_PER_SECOND,
+      i64::MAX as i128 * NANOS_PER_SECOND,
+    );
+
+    pick(bounds, range, step, DEFAULT_TIMESTAMP, index)
+  }
+
+  fn enum_value(
+    &mut self,
+    path: &ImportedItemPath,
+    rules: Option<&OptionValue>,
+    index: usize,
+  ) -> Option<Json> {
+    let enum_ = self
+      .package
+      .enums
+      .iter()
+      .find(|e| e.import_path.full_name_with_package == path.full_name_with_package)?;
+    let bounds = Bounds::new(rules, 1, integer_of);
+
+    let number = if let Some(value) = bounds.const_ {
+      value
+    } else {
+      let allowed: Vec<i128> = if bounds.in_.is_empty() {
+        // The variants other than the zero value are preferred, since it usually means that the value is unspecified
+        let mut variants: Vec<i128> = enum_
+          .variants
+          .iter()
+          .map(|(number, _)| *number as i128)
+          .collect();
+        variants.sort_by_key(|number| *number == 0);
+        variants
+      } else {
+        bounds.in_.clone()
+      };
+
+      let allowed: Vec<i128> = allowed
+        .into_iter()
+        .filter(|value| !bounds.not_in.contains(value))
+        .collect();
+
+      *allowed.get(index % allowed.len().max(1))?
+    };
+
+    Some(self.enum_json(path, number))
+  }
+
+  // Enum values are represented by the name of the variant, or by their number if they are not defined
+  fn enum_json(&self, path: &ImportedItemPath, number: i128) -> Json {
+    let variant = self
+      .package
+      .enums
+      .iter()
+      .find(|e| e.import_path.full_name_with_package == path.full_name_with_package)
+      .and_then(|e| {
+        e.variants
+          .iter()
+          .find(|(n, _)| *n as i128 == number)
+          .map(|(_, variant)| format!("{}_{}", e.name.to_case(Case::UpperSnake), variant.name))
+      });
+
+    match variant {
+      Some(name) => Json::String(name),
+      None => Json::from(number as i64),
+    }
+  }
+
+  /// The instances obtained by changing a single value of a valid instance of a message
+  fn message_counter_examples(&mut self, msg_id: usize, valid: &Map<String, Json>) -> Vec<Json> {
+    let package = self.package;
+    let msg = &package.messages[msg_id];
+    let mut result: Vec<Json> = Vec::new();
+
+    let mut replace = |field: &FieldData, value: Option<Json>| {
+      let mut object = valid.clone();
+
+      match value {
+        Some(value) => object.insert(field_json_name(field), value),
+        None => object.remove(&field_json_name(field)),
+      };
+
+      result.push(Json::Object(object));
+    };
+
+    let fields = msg
+      .fields
+      .iter()
+      .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()));
+
+    for (_, field) in fields {
+      let current = valid.get(&field_json_name(field));
+
+      for candidate in self.field_counter_examples(field, current) {
+        replace(field, candidate);
+      }
+    }
+
+    for oneof in &msg.oneofs {
+      if is_required_oneof(oneof) {
+        for (_, field) in oneof.fields.iter() {
+          replace(field, None);
+        }
+      }
+    }
+
+    if let Some(rules) = find_option(&msg.options, "(buf.validate.message)")
+      && let Some(OptionValue::List(oneof_rules)) = get_value(rules, "oneof")
+    {
+      for oneof_rule in oneof_rules.iter() {
+        let names = text_list(Some(oneof_rule), "fields");
+        let mut all_set = valid.clone();
+        let mut none_set = valid.clone();
+
+        for name in &names {
+          let key = json_key(package, msg_id, name);
+          none_set.remove(&key);
+
+          if let Some(field) = package.find_field(msg_id, name)
+            && !all_set.contains_key(&key)
+            && let Some(value) = self.field(field)
+          {
+            all_set.insert(key, value);
+          }
+        }
+
+        result.push(Json::Object(all_set));
+        result.push(Json::Object(none_set));
+      }
+    }
+
+    result
+  }
+
+  // The values that can replace the value of a field to make it invalid, where None means that the field is removed
+  fn field_counter_examples(
+    &mut self,
+    field: &FieldData,
+    current: Option<&Json>,
+  ) -> Vec<Option<Json>> {
+    let rules = find_option(&field.options, "(buf.validate.field)");
+    let mut result: Vec<Option<Json>> = Vec::new();
+
+    // The rules of ignored fields can never be violated
+    if rules.is_some_and(|r| ignore_setting(r) == Ignore::Always) {
+      return result;
+    }
+
+    if rules.is_some_and(|r| matches!(get_value(r, "required"), Some(OptionValue::Bool(true)))) {
+      result.push(None);
+    }
+
+    let Some(current) = current else {
+      return result;
+    };
+
+    match (current, &field.field_type) {
+      (Json::Array(items), field_type) if field.kind == FieldKind::Repeated => {
+        let list_rules = rules.and_then(|r| get_value(r, "repeated"));
+        let items_rules = list_rules.and_then(|r| get_value(r, "items"));
+
+        if let Some(min) = length_rule(list_rules, &["min_items"])
+          && min > 0
+          && items.len() >= min
+        {
+          result.push(Some(Json::Array(items[..min - 1].to_vec())));
+        }
+
+        if let Some(max) = length_rule(list_rules, &["max_items"]) {
+          let mut longer = items.clone();
+
+          while longer.len() <= max {
+            let Some(item) = self.value(field_type, items_rules, longer.len()) else {
+              break;
+            };
+
+            longer.push(item);
+          }
+
+          result.push(Some(Json::Array(longer)));
+        }
+
+        if list_rules
+          .is_some_and(|r| matches!(get_value(r, "unique"), Some(OptionValue::Bool(true))))
+          && let Some(first) = items.first()
+        {
+          let mut duplicated = items.clone();
+
+          match duplicated.get_mut(1) {
+            Some(second) => *second = first.clone(),
+            None => duplicated.push(first.clone()),
+          }
+
+          result.push(Some(Json::Array(duplicated)));
+        }
+
+        if let Some(first) = items.first() {
+          for candidate in self.value_counter_examples(field_type, items_rules, first) {
+            let mut changed = items.clone();
+            changed[0] = candidate;
+            result.push(Some(Json::Array(changed)));
+          }
+        }
+      }
+      (Json::Object(map), FieldType::Map(key, value_type)) => {
+        let map_rules = rules.and_then(|r| get_value(r, "map"));
+
+        if let Some(min) = length_rule(map_rules, &["min_pairs"])
+          && min > 0
+          && map.len() >= min
+        {
+          result.push(Some(Json::Object(
+            map.clone().into_iter().take(min - 1).collect(),
+          )));
+        }
+
+        if let Some(max) = length_rule(map_rules, &["max_pairs"]) {
+          let mut larger = map.clone();
+          let mut index = larger.len();
+
+          while larger.len() <= max && index < max + 32 {
+            let key = self.value(
+              &key_type(key),
+              map_rules.and_then(|r| get_value(r, "keys")),
+              index,
+            );
+            let value = self.value(
+              value_type,
+              map_rules.and_then(|r| get_value(r, "values")),
+              index,
+            );
+
+            if let (Some(key), Some(value)) = (key, value) {
+              larger.insert(key_string(key), value);
+            }
+
+            index += 1;
+          }
+
+          result.push(Some(Json::Object(larger)));
+        }
+
+        if let Some((first_key, first_value)) = map.iter().next() {
+          let key_json = match key {
+            MapKey::String => Json::String(first_key.clone()),
+            MapKey::Bool => Json::Bool(first_key == "true"),
+            _ => Json::String(first_key.clone()),
+          };
+
+          for candidate in self.value_counter_examples(
+            &key_type(key),
+            map_rules.and_then(|r| get_value(r, "keys")),
+            &key_json,
+          ) {
+            let mut changed = map.clone();
+            changed.remove(first_key);
+            changed.insert(key_string(candidate), first_value.clone());
+            result.push(Some(Json::Object(changed)));
+          }
+
+          for candidate in self.value_counter_examples(
+            value_type,
+            map_rules.and_then(|r| get_value(r, "values")),
+            first_value,
+          ) {
+            let mut changed = map.clone();
+            changed.insert(first_key.clone(), candidate);
+            result.push(Some(Json::Object(changed)));
+          }
+        }
+      }
+      (current, field_type) => {
+        result.extend(
+          self
+            .value_counter_examples(field_type, rules, current)
+            .into_iter()
+            .map(Some),
+        );
+      }
+    }
+
+    // The zero values are not validated at all, so they can't be counter-examples
+    if rules.is_some_and(|r| ignore_setting(r) == Ignore::IfZeroValue) {
+      result.retain(|candidate| {
+        candidate
+          .as_ref()
+          .is_none_or(|value| !is_zero_json(&field.field_type, value))
+      });
+    }
+
+    result
+  }
+
+  // The values that can replace a valid value to make it invalid, given the rules of a field
+  fn value_counter_examples(
+    &mut self,
+    field_type: &FieldType,
+    rules: Option<&OptionValue>,
+    current: &Json,
+  ) -> Vec<Json> {
+    let ignore = rules.map(ignore_setting).unwrap_or(Ignore::Unspecified);
+
+    if ignore == Ignore::Always {
+      return Vec::new();
+    }
+
+    let mut result = self.type_counter_examples(field_type, rules, current);
+
+    if ignore == Ignore::IfZeroValue {
+      result.retain(|value| !is_zero_json(field_type, value));
+    }
+
+    result
+  }
+
+  fn type_counter_examples(
+    &mut self,
+    field_type: &FieldType,
+    rules: Option<&OptionValue>,
+    current: &Json,
+  ) -> Vec<Json> {
+    if let (FieldType::Message(path), Json::Object(object)) = (field_type, current) {
+      return match self.package.message_id(&path.full_name_with_package) {
+        Some(id) => self.message_counter_examples(id, object),
+        None => Vec::new(),
+      };
+    }
+
+    let Some(rules) = rules.and_then(type_rules) else {
+      return Vec::new();
+    };
+
+    match field_type {
+      FieldType::String => {
+        let current = current.as_str().unwrap_or_default();
+
+        string_counter_examples(rules, current)
+          .into_iter()
+          .map(Json::String)
+          .collect()
+      }
+      FieldType::Bytes => {
+        let current = current.as_str().and_then(decode_base64).unwrap_or_default();
+
+        bytes_counter_examples(rules, &current)
+          .iter()
+          .map(|value| Json::String(encode_base64(value)))
+          .collect()
+      }
+      FieldType::Bool => match get_value(rules, "const") {
+        Some(OptionValue::Bool(value)) => vec![Json::Bool(!value)],
+        _ => Vec::new(),
+      },
+      FieldType::Double | FieldType::Float => float_counter_examples(rules),
+      FieldType::Enum(path) => {
+        let mut numbers = integer_counter_examples(rules, 1);
+
+        if matches!(
+          get_value(rules, "defined_only"),
+          Some(OptionValue::Bool(true))
+        ) {
+          let max = self
+            .package
+            .enums
+            .iter()
+            .find(|e| e.import_path.full_name_with_package == path.full_name_with_package)
+            .and_then(|e| e.variants.iter().map(|(number, _)| *number).max())
+            .unwrap_or_default();
+
+          numbers.push(max as i128 + 1);
+        }
+
+        numbers
+          .into_iter()
+          .map(|number| self.enum_json(path, number))
+          .collect()
+      }
+      FieldType::Duration => integer_counter_examples(rules, NANOS_PER_SECOND)
+        .into_iter()
+        .map(duration_json)
+        .collect(),
+      FieldType::Timestamp => {
+        let mut values = integer_counter_examples(rules, NANOS_PER_SECOND);
+        let now = now_nanos();
+
+        for (name, rule) in entries(rules) {
+          match (name.as_ref(), rule) {
+            ("lt_now", OptionValue::Bool(true)) => values.push(now + NANOS_PER_DAY),
+            ("gt_now", OptionValue::Bool(true)) => values.push(now - NANOS_PER_DAY),
+            ("within", within) => {
+              if let Some(within) = nanos_of(within) {
+                values.push(now - 2 * within - NANOS_PER_SECOND);
+              }
+            }
+            _ => {}
+          }
+        }
+
+        values.into_iter().map(timestamp_json).collect()
+      }
+      FieldType::Any => {
+        let mut type_urls: Vec<String> = Vec::new();
+
+        if get_value(rules, "in").is_some() {
+          type_urls.push("type.googleapis.com/invalid.Type".into());
+        }
+
+        if let Some(first) = text_list(Some(rules), "not_in").first() {
+          type_urls.push(first.to_string());
+        }
+
+        type_urls
+          .into_iter()
+          .map(|type_url| serde_json::json!({ "@type": type_url }))
+          .collect()
+      }
+      other => match integer_range(other) {
+        Some((_, _, is_64_bit)) => integer_counter_examples(rules, 1)
+          .into_iter()
+          .map(|value| integer_json(value, is_64_bit))
+          .collect(),
+        None => Vec::new(),
+      },
+    }
+  }
+}
+
+// Picks an integer that respects the bounds.
+// The index moves the value away from the starting point by the given step, so that the values in a list can be unique.
+fn pick(bounds: Bounds, range: (i128, i128), step: i128, origin: i128, index: usize) -> i128 {
+  if let Some(value) = bounds.const_ {
+    return value;
+  }
+
+  let allowed: Vec<i128> = bounds
+    .in_
+    .iter()
+    .copied()
+    .filter(|value| !bounds.not_in.contains(value))
+    .collect();
+
+  if !allowed.is_empty() {
+    return allowed[index % allowed.len()];
+  }
+
+  let (min, max) = range;
+  let mut low = bounds.lower.unwrap_or(min).max(min);
+  let mut high = bounds.upper.unwrap_or(max).min(max);
+
+  // A lower bound greater than the upper bound means that the valid values are outside of the range
+  if low > high && bounds.lower.is_some() && bounds.upper.is_some() {
+    high = max;
+  }
+
+  low = low.min(high);
+
+  let start = match (bounds.lower, bounds.upper) {
+    (Some(_), Some(_)) => low + (high - low) / 2,
+    (Some(_), None) => low,
+    _ => origin.clamp(low, high),
+  };
+
+  let offset = index as i128 * step;
+  let mut value = if start + offset <= high {
+    start + offset
+  } else if start - offset >= low {
+    start - offset
+  } else {
+    start
+  };
+
+  for _ in 0..64 {
+    if !bounds.not_in.contains(&value) {
+      break;
+    }
+
+    value = if value + step <= high {
+      value + step
+    } else {
+      value - step
+    };
+  }
+
+  value
+}
+
+fn integer_counter_examples(rules: &OptionValue, step: i128) -> Vec<i128> {
+  let convert = |value: &OptionValue| integer_of(value).or_else(|| nanos_of(value));
+
+  entries(rules)
+    .iter()
+    .filter_map(|(name, rule)| match name.as_ref() {
+      "const" => convert(rule).map(|v| v + step),
+      "in" => match rule {
+        OptionValue::List(items) => items.iter().filter_map(convert).max().map(|v| v + step),
+        _ => None,
+      },
+      "not_in" => match rule {
+        OptionValue::List(items) => items.iter().find_map(convert),
+        _ => None,
+      },
+      "gt" | "lt" => convert(rule),
+      "gte" => convert(rule).map(|v| v - step),
+      "lte" => convert(rule).map(|v| v + step),
+      _ => None,
+    })
+    .collect()
+}
+
+fn float_counter_examples(rules: &OptionValue) -> Vec<Json> {
+  entries(rules)
+    .iter()
+    .filter_map(|(name, rule)| match name.as_ref() {
+      "const" => float_of(rule).map(|v| Json::from(v + 1.0)),
+      "in" => match rule {
+        OptionValue::List(items) => items
+          .iter()
+          .filter_map(float_of)
+          .reduce(f64::max)
+          .map(|v| Json::from(v + 1.0)),
+        _ => None,
+      },
+      "not_in" => match rule {
+        OptionValue::List(items) => items.iter().find_map(float_of).map(Json::from),
+        _ => None,
+      },
+      "gt" | "lt" => float_of(rule).map(Json::from),
+      "gte" => float_of(rule).map(|v| Json::from(v - 1.0)),
+      "lte" => float_of(rule).map(|v| Json::from(v + 1.0)),
+      "finite" if *rule == OptionValue::Bool(true) => Some(Json::String("NaN".into())),
+      _ => None,
+    })
+    .collect()
+}
+
+fn string_counter_examples(rules: &OptionValue, current: &str) -> Vec<String> {
+  let chars = current.chars().count();
+  let mut result: Vec<String> = Vec::new();
+
+  for (name, rule) in entries(rules) {
+    let text = match rule {
+      OptionValue::String(text) => text.as_ref(),
+      _ => "",
+    };
+    let length = as_u64(rule) as usize;
+
+    match name.as_ref() {
+      "const" | "len" | "len_bytes" => result.push(format!("{current}x")),
+      "min_len" | "min_bytes" if length > 0 => {
+        result.push(current.chars().take(length - 1).collect())
+      }
+      "max_len" | "max_bytes" => result.push(format!(
+        "{current}{}",
+        "a".repeat((length + 1).saturating_sub(chars))
+      )),
+      "pattern" => result.extend(["".into(), "!".into()]),
+      // A single character is changed, so that the length rules are still satisfied
+      "prefix" => result.push(change_char(current, current.find(text))),
+      "suffix" => result.push(change_char(current, current.rfind(text))),
+      "contains" => result.push(current.replace(text, &change_char(text, Some(0)))),
+      "not_contains" => result.push(format!("{current}{text}")),
+      "in" => result.push(format!("{current}-other")),
+      "not_in" => result.extend(
+        text_list(Some(rules), "not_in")
+          .first()
+          .map(|v| v.to_string()),
+      ),
+      "well_known_regex" => result.push("invalid value\n".into()),
+      _ if *rule == OptionValue::Bool(true) => {
+        result.extend(["invalid value!".into(), String::new()])
+      }
+      _ => {}
+    }
+  }
+
+  result
+}
+
+// Replaces the character at the given byte position with a different one
+fn change_char(value: &str, position: Option<usize>) -> String {
+  let Some(position) = position else {
+    return value.into();
+  };
+
+  let mut chars: Vec<char> = value.chars().collect();
+  let index = value[..position].chars().count();
+
+  if let Some(c) = chars.get_mut(index) {
+    *c = if *c == 'x' { 'y' } else { 'x' };
+  }
+
+  chars.into_iter().collect()
+}
+
+fn bytes_counter_examples(rules: &OptionValue, current: &[u8]) -> Vec<Vec<u8>> {
+  let mut result: Vec<Vec<u8>> = Vec::new();
+
+  for (name, rule) in entries(rules) {
+    let text = match rule {
+      OptionValue::String(text) => unescape_bytes(text),
+      _ => Vec::new(),
+    };
+    let length = as_u64(rule) as usize;
+
+    match name.as_ref() {
+      "const" | "len" => result.push([current, b"x"].concat()),
+      "min_len" if length > 0 => result.push(current[..current.len().min(length - 1)].to_vec()),
+      "max_len" => {
+        let mut longer = current.to_vec();
+        longer.resize(longer.len().max(length + 1), b'a');
+        result.push(longer);
+      }
+      "pattern" => result.extend([Vec::new(), b"!".to_vec()]),
+      "prefix" | "suffix" | "contains" if !text.is_empty() => {
+        let position = current
+          .windows(text.len())
+          .position(|window| window == text.as_slice());
+
+        if let Some(i) = position {
+          let mut changed = current.to_vec();
+          changed[i] = !changed[i];
+          result.push(changed);
+        }
+      }
+      "in" => result.push([current, b"-other"].concat()),
+      "not_in" => result.extend(
+        text_list(Some(rules), "not_in")
+          .first()
+          .map(|v| unescape_bytes(v)),
+      ),
+      "ip" | "ipv4" | "ipv6" if *rule == OptionValue::Bool(true) => result.push(vec![1, 2, 3]),
+      _ => {}
+    }
+  }
+
+  result
+}
+
+// The key of a field in the proto3 JSON format, given its name
+fn json_key(package: &PackageData, msg_id: usize, name: &str) -> String {
+  package
+    .find_field(msg_id, name)
+    .map_or_else(|| json_name(name), field_json_name)
+}
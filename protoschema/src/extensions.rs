@@ -2,8 +2,10 @@ use bon::Builder;
 
 use crate::fields::{self, Field, FieldBuilder, FieldData};
 
-/// The kind of proto3 extension
-#[derive(Debug, Clone, Copy)]
+/// The kind of proto3 extension.
+/// The variants ending in `Rules` extend protovalidate's rules messages, and are used to define [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExtensionKind {
   MessageOptions,
   FieldOptions,
@@ -13,6 +15,27 @@ pub enum ExtensionKind {
   FileOptions,
   EnumOptions,
   EnumValueOptions,
+  FloatRules,
+  DoubleRules,
+  Int32Rules,
+  Int64Rules,
+  UInt32Rules,
+  UInt64Rules,
+  SInt32Rules,
+  SInt64Rules,
+  Fixed32Rules,
+  Fixed64Rules,
+  SFixed32Rules,
+  SFixed64Rules,
+  BoolRules,
+  StringRules,
+  BytesRules,
+  EnumRules,
+  RepeatedRules,
+  MapRules,
+  AnyRules,
+  DurationRules,
+  TimestampRules,
 }
 
 impl ExtensionKind {
@@ -27,8 +50,34 @@ impl ExtensionKind {
       ExtensionKind::FileOptions => "google.protobuf.FileOptions",
       ExtensionKind::EnumOptions => "google.protobuf.EnumOptions",
       ExtensionKind::EnumValueOptions => "google.protobuf.EnumValueOptions",
+      ExtensionKind::FloatRules => "buf.validate.FloatRules",
+      ExtensionKind::DoubleRules => "buf.validate.DoubleRules",
+      ExtensionKind::Int32Rules => "buf.validate.Int32Rules",
+      ExtensionKind::Int64Rules => "buf.validate.Int64Rules",
+      ExtensionKind::UInt32Rules => "buf.validate.UInt32Rules",
+      ExtensionKind::UInt64Rules => "buf.validate.UInt64Rules",
+      ExtensionKind::SInt32Rules => "buf.validate.SInt32Rules",
+      ExtensionKind::SInt64Rules => "buf.validate.SInt64Rules",
+      ExtensionKind::Fixed32Rules => "buf.validate.Fixed32Rules",
+      ExtensionKind::Fixed64Rules => "buf.validate.Fixed64Rules",
+      ExtensionKind::SFixed32Rules => "buf.validate.SFixed32Rules",
+      ExtensionKind::SFixed64Rules => "buf.validate.SFixed64Rules",
+      ExtensionKind::BoolRules => "buf.validate.BoolRules",
+      ExtensionKind::StringRules => "buf.validate.StringRules",
+      ExtensionKind::BytesRules => "buf.validate.BytesRules",
+      ExtensionKind::EnumRules => "buf.validate.EnumRules",
+      ExtensionKind::RepeatedRules => "buf.validate.RepeatedRules",
+      ExtensionKind::MapRules => "buf.validate.MapRules",
+      ExtensionKind::AnyRules => "buf.validate.AnyRules",
+      ExtensionKind::DurationRules => "buf.validate.DurationRules",
+      ExtensionKind::TimestampRules => "buf.validate.TimestampRules",
     }
   }
+
+  /// Returns true if the message being extended is one of protovalidate's rules messages (like `buf.validate.StringRules`), which is where predefined rules are defined.
+  pub fn is_validate_rules(&self) -> bool {
+    self.get_target().starts_with("buf.validate.")
+  }
 }
 
 /// A struct representing a protobuf extension
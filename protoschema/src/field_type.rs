@@ -5,6 +5,7 @@ pub use proto_types::{Duration, Timestamp};
 
 /// Protobuf map key types
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MapKey {
   Int32,
   Int64,
@@ -47,6 +48,7 @@ impl Display for MapKey {
 
 /// The import path information for a protobuf importable item, like an enum or a message
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImportedItemPath {
   pub full_name: Arc<str>,
   pub full_name_with_package: Arc<str>,
@@ -56,6 +58,7 @@ pub struct ImportedItemPath {
 
 /// The various types of protobuf fields, including some well known types such as [`any`](::proto_types::Any) or [`duration`](::proto_types::Duration)
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldType {
   Double,
   Float,
@@ -138,6 +141,58 @@ pub(crate) fn get_shortest_item_name(
 }
 
 impl FieldType {
+  /// Returns the file that must be imported to use this type, if there is one
+  pub(crate) fn import_path(&self) -> Option<Arc<str>> {
+    let path = match self {
+      FieldType::Message(path) | FieldType::Enum(path) => return Some(path.file.clone()),
+      FieldType::Map(_, value) => return value.import_path(),
+      FieldType::Duration => "google/protobuf/duration.proto",
+      FieldType::Timestamp => "google/protobuf/timestamp.proto",
+      FieldType::Any => "google/protobuf/any.proto",
+      FieldType::FieldMask => "google/protobuf/field_mask.proto",
+      FieldType::Empty => "google/protobuf/empty.proto",
+      FieldType::Struct => "google/protobuf/struct.proto",
+      FieldType::Money => "google/type/money.proto",
+      FieldType::Interval => "google/type/interval.proto",
+      FieldType::Color => "google/type/color.proto",
+      FieldType::Date => "google/type/date.proto",
+      FieldType::DateTime | FieldType::TimeZone => "google/type/datetime.proto",
+      FieldType::DayOfWeek => "google/type/dayofweek.proto",
+      FieldType::Decimal => "google/type/decimal.proto",
+      FieldType::Expr => "google/type/expr.proto",
+      FieldType::Fraction => "google/type/fraction.proto",
+      FieldType::LatLng => "google/type/latlng.proto",
+      FieldType::LocalizedText => "google/type/localized_text.proto",
+      FieldType::Month => "google/type/month.proto",
+      FieldType::PhoneNumber => "google/type/phone_number.proto",
+      FieldType::PostalAddress => "google/type/postal_address.proto",
+      FieldType::Quaternion => "google/type/quaternion.proto",
+      FieldType::TimeOfDay => "google/type/timeofday.proto",
+      FieldType::Status => "google/rpc/status.proto",
+      FieldType::Code => "google/rpc/code.proto",
+      FieldType::HttpRequest | FieldType::HttpResponse | FieldType::HttpHeader => {
+        "google/rpc/http.proto"
+      }
+      FieldType::ErrorInfo
+      | FieldType::RetryInfo
+      | FieldType::DebugInfo
+      | FieldType::QuotaFailure
+      | FieldType::QuotaFailureViolation
+      | FieldType::PreconditionFailure
+      | FieldType::PreconditionFailureViolation
+      | FieldType::BadRequest
+      | FieldType::FieldViolation
+      | FieldType::RequestInfo
+      | FieldType::ResourceInfo
+      | FieldType::Help
+      | FieldType::LocalizedMessage
+      | FieldType::Link => "google/rpc/error_details.proto",
+      _ => return None,
+    };
+
+    Some(path.into())
+  }
+
   pub(crate) fn render_name(&self, current_file: &str, current_package: &str) -> Arc<str> {
     match self {
       FieldType::Message(path) => get_shortest_item_name(path, current_file, current_package),
@@ -3,10 +3,11 @@ use std::{fmt::Display, sync::Arc};
 use bon::Builder;
 pub(crate) use field_builder::*;
 
-use crate::{FieldType, ProtoOption};
+use crate::{options::merge_options, FieldType, OptionValue, ProtoOption};
 
 /// The cardinality for a protobuf field.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldKind {
   #[default]
   Normal,
@@ -46,7 +47,7 @@ pub struct Field {
 }
 
 #[doc(hidden)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FieldData {
   pub options: Box<[ProtoOption]>,
   pub kind: FieldKind,
@@ -75,18 +76,20 @@ impl<S: field_builder::State> FieldBuilder<S> {
     self.field_type_internal(field_type)
   }
 
-  /// Adds an option to this field
+  /// Adds an option to this field.
+  /// If an identical option is already present, it is not added again, and if an option with a message value and the same name is present, the two are merged together.
+  /// This means that, for example, adding two validators to the same field will result in a single `(buf.validate.field)` option.
   pub fn add_option(mut self, option: ProtoOption) -> Self {
-    self.options.push(option);
+    merge_options(&mut self.options, [option]);
     self
   }
 
-  /// Adds multiple options to this field
+  /// Adds multiple options to this field, following the same merge rules as [`add_option`](FieldBuilder::add_option)
   pub fn add_options<I>(mut self, options: I) -> Self
   where
     I: IntoIterator<Item = ProtoOption>,
   {
-    self.options.extend(options);
+    merge_options(&mut self.options, options);
     self
   }
 
@@ -98,3 +101,82 @@ impl<S: field_builder::State> FieldBuilder<S> {
     self
   }
 }
+
+/// A reference to a field of a message, used to target it in message-level rules such as [`message_oneof_rule`](crate::messages::MessageBuilder::message_oneof_rule).
+/// It is implemented for field names and for complete [`FieldBuilder`]s, so that the same builder used to define a field can be used to refer to it.
+pub trait FieldReference {
+  /// Returns the name of the referenced field
+  fn field_name(&self) -> Arc<str>;
+}
+
+impl FieldReference for str {
+  fn field_name(&self) -> Arc<str> {
+    self.into()
+  }
+}
+
+impl FieldReference for String {
+  fn field_name(&self) -> Arc<str> {
+    self.as_str().into()
+  }
+}
+
+impl FieldReference for Arc<str> {
+  fn field_name(&self) -> Arc<str> {
+    self.clone()
+  }
+}
+
+impl FieldReference for Field {
+  fn field_name(&self) -> Arc<str> {
+    self.name.clone()
+  }
+}
+
+impl<S: IsComplete> FieldReference for FieldBuilder<S> {
+  fn field_name(&self) -> Arc<str> {
+    self.clone().build().name
+  }
+}
+
+impl<T: FieldReference + ?Sized> FieldReference for &T {
+  fn field_name(&self) -> Arc<str> {
+    (**self).field_name()
+  }
+}
+
+// The name of a field in the proto3 JSON format
+pub(crate) fn json_name(name: &str) -> String {
+  let mut result = String::new();
+  let mut capitalize = false;
+
+  for c in name.chars() {
+    if c == '_' {
+      capitalize = true;
+    } else if capitalize {
+      result.push(c.to_ascii_uppercase());
+      capitalize = false;
+    } else {
+      result.push(c);
+    }
+  }
+
+  result
+}
+
+// The name set with the `json_name` option, which replaces the default one
+pub(crate) fn explicit_json_name(field: &FieldData) -> Option<Box<str>> {
+  field
+    .options
+    .iter()
+    .find(|option| &*option.name == "json_name")
+    .and_then(|option| match option.value.as_ref() {
+      OptionValue::String(name) => Some(name.clone()),
+      _ => None,
+    })
+}
+
+// The name of a field in the proto3 JSON format, taking the `json_name` option into account
+pub(crate) fn field_json_name(field: &FieldData) -> String {
+  explicit_json_name(field).map_or_else(|| json_name(&field.name), String::from)
+}
@@ -1,15 +1,21 @@
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 
 use crate::{
-  common::DESCRIPTOR_PROTO_FILE,
+  aip::{ResourceConfig, ResourceService},
+  common::{DESCRIPTOR_PROTO_FILE, VALIDATE_PROTO_FILE},
   enums::{EnumBuilder, EnumData},
-  extensions::{Extension, ExtensionData},
+  extensions::{Extension, ExtensionData, ExtensionKind},
   field_type::ImportedItemPath,
-  fields::FieldData,
-  messages::{MessageBuilder, MessageData},
+  fields::{self, FieldBuilder, FieldData},
+  messages::{MessageBuilder, MessageData, MessageState},
+  options::merge_options,
   packages::Arena,
   rendering::FileTemplate,
   services::{ServiceBuilder, ServiceData},
+  validators::{
+    cel::CelRule,
+    predefined::{predefined_rule_option, PredefinedRule},
+  },
   ProtoOption,
 };
 
@@ -138,12 +144,13 @@ impl FileBuilder {
   }
 
   /// Adds the given options to this file's options. It does not consume the original builder and does not return a new one.
+  /// Identical options are only added once, and options with a message value and the same name are merged together.
   pub fn add_options<I>(&self, options: I)
   where
     I: IntoIterator<Item = ProtoOption>,
   {
     let file = &mut self.arena.borrow_mut().files[self.id];
-    file.options.extend(options)
+    merge_options(&mut file.options, options)
   }
 
   /// Adds the given extension to this file.
@@ -152,7 +159,11 @@ impl FileBuilder {
   pub fn add_extension(&self, extension: Extension) {
     let file = &mut self.arena.borrow_mut().files[self.id];
 
-    file.imports.insert(DESCRIPTOR_PROTO_FILE.clone());
+    if extension.kind.is_validate_rules() {
+      file.imports.insert(VALIDATE_PROTO_FILE.clone());
+    } else {
+      file.imports.insert(DESCRIPTOR_PROTO_FILE.clone());
+    }
 
     let mut built_fields: Vec<(u32, FieldData)> = extension
       .fields
@@ -184,6 +195,79 @@ impl FileBuilder {
     file.extensions.push(ext_data)
   }
 
+  /// Defines a new [`PredefinedRule`] in this file, and returns it so that it can be attached to the validators of the matching type.
+  /// The rule is added as an extension of the rules message indicated by `kind` (which must be one of the `Rules` variants of [`ExtensionKind`]), and the given Cel rules are placed in its `(buf.validate.predefined)` option.
+  /// In Cel expressions, `this` refers to the value of the field being validated, while `rule` refers to the value of the predefined rule.
+  /// The [`predefined_rule`](crate::predefined_rule) macro offers a shorter syntax for this.
+  #[track_caller]
+  pub fn new_predefined_rule<F, I>(
+    &self,
+    kind: ExtensionKind,
+    tag: u32,
+    field: FieldBuilder<F>,
+    rules: I,
+  ) -> PredefinedRule
+  where
+    F: fields::IsComplete,
+    I: IntoIterator<Item = CelRule>,
+  {
+    if !kind.is_validate_rules() {
+      panic!(
+        "Predefined rules can only extend protovalidate's rules messages, not {}",
+        kind.get_target()
+      )
+    }
+
+    let field = field.add_option(predefined_rule_option(rules));
+    let built_field = field.clone().build();
+
+    let name = format!("{}.{}", self.arena.borrow().name, built_field.name);
+
+    self.add_extension(
+      Extension::builder()
+        .kind(kind)
+        .fields([(tag, field)])
+        .build(),
+    );
+
+    PredefinedRule {
+      kind,
+      name: name.into(),
+      file: self.get_name(),
+      field_type: built_field.field_type,
+      field_kind: built_field.kind,
+    }
+  }
+
+  /// Generates the request and response messages, as well as the service, for the [standard methods](https://google.aip.dev/130) of the given resource, following the Google AIPs.
+  ///
+  /// The config can be a [`ResourceConfig`](crate::aip::ResourceConfig) or just the pattern for the names of the resource, such as `publishers/{publisher}/books/{book}`.
+  /// The resource receives the `(google.api.resource)` option, and the generated fields are annotated with `(google.api.field_behavior)`, `(google.api.resource_reference)` and the protovalidate rules that fit their role (such as the pattern of the resource name, or the range of the page size).
+  /// Each handler has a `(google.api.http)` binding, and the Delete handler returns `google.protobuf.Empty`.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use protoschema::{message, string, Package};
+  ///
+  /// let my_pkg = Package::new("library.v1");
+  /// let my_file = my_pkg.new_file("library");
+  ///
+  /// let book_msg = message!(my_file.new_message("Book"), 1 => string!("name"));
+  ///
+  /// let books = my_file.resource_service(&book_msg, "publishers/{publisher}/books/{book}");
+  ///
+  /// assert_eq!(books.service.get_name().as_ref(), "BookService");
+  /// assert!(my_pkg.check().is_empty());
+  /// ```
+  #[track_caller]
+  pub fn resource_service<S, C>(&self, resource: &MessageBuilder<S>, config: C) -> ResourceService
+  where
+    S: MessageState,
+    C: Into<ResourceConfig>,
+  {
+    crate::aip::resource_service(self, resource, config.into())
+  }
+
   /// Adds the given imports to this file.
   /// For the most common cases, this crate will automatically add the necessary imports, so make sure to use this only if you notice that an import is missing.
   /// It does not consume the original builder and does not return a new one.
@@ -0,0 +1,340 @@
+use std::{
+  marker::PhantomData,
+  rc::Rc,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
+
+use convert_case::{Case, Casing};
+
+use crate::{
+  fields::{Field, FieldBuilder, IsComplete, SetFieldType, SetName},
+  files::FileBuilder,
+  messages::{MessageBuilder, SetFields},
+  options::merge_options,
+  FieldType, ProtoOption,
+};
+
+type FieldFn = Rc<dyn Fn(&Instance) -> Field>;
+type NestedFn = Rc<dyn Fn(&Instance)>;
+
+// The source of the ids that tell apart the templates, even when they have the same name
+static NEXT_TEMPLATE_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_template_id() -> usize {
+  NEXT_TEMPLATE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A message with type parameters, such as `Page<T>`, which can be instantiated with different types to generate concrete messages.
+///
+/// The fields are defined with closures that receive the [`Instance`] being generated, which gives access to the types used for the parameters.
+/// Each instantiation generates a message named after its type arguments and the name of the template (such as `BookPage` for `Page<Book>`), with the imports for the types that it uses.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{generics::GenericMessage, message, proto_enum, string, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let page = GenericMessage::new("Page", ["T"])
+///   .nested(|page| {
+///     proto_enum!(page.message().new_enum("Order"), 0 => "ASC", 1 => "DESC");
+///   })
+///   .field(1, |page| page.field("T", "items").repeated())
+///   .field(2, |_| string!("next_page_token"))
+///   .field(3, |page| page.nested_field("Order", "order"));
+///
+/// let user_msg = message!(my_file.new_message("User"), 1 => string!("name"));
+///
+/// let user_page_msg = page.instantiate(&my_file, [user_msg.get_type()]);
+///
+/// assert_eq!(user_page_msg.get_full_name().as_ref(), "UserPage");
+/// ```
+#[derive(Clone)]
+pub struct GenericMessage {
+  // Changes whenever the template is modified, so that a modified clone does not share the instances of the original
+  id: usize,
+  name: Box<str>,
+  params: Box<[Box<str>]>,
+  fields: Vec<(u32, FieldFn)>,
+  nested: Vec<NestedFn>,
+  options: Vec<ProtoOption>,
+}
+
+impl std::fmt::Debug for GenericMessage {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("GenericMessage")
+      .field("id", &self.id)
+      .field("name", &self.name)
+      .field("params", &self.params)
+      .field("options", &self.options)
+      .finish_non_exhaustive()
+  }
+}
+
+impl GenericMessage {
+  /// Creates a new generic message with the given name and type parameters
+  pub fn new<T, I, S>(name: T, params: I) -> Self
+  where
+    T: AsRef<str>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    GenericMessage {
+      id: next_template_id(),
+      name: name.as_ref().into(),
+      params: params.into_iter().map(|p| p.as_ref().into()).collect(),
+      fields: Vec::new(),
+      nested: Vec::new(),
+      options: Vec::new(),
+    }
+  }
+
+  /// Adds a field to this message, which is generated by the given closure for each instance
+  pub fn field<F, S>(mut self, tag: u32, field_fn: F) -> Self
+  where
+    F: Fn(&Instance) -> FieldBuilder<S> + 'static,
+    S: IsComplete,
+  {
+    self
+      .fields
+      .push((tag, Rc::new(move |instance| field_fn(instance).build())));
+    self.id = next_template_id();
+    self
+  }
+
+  /// Adds a closure that is called for each instance before its fields are generated, which can be used to define nested enums and messages.
+  /// These can then be referred to in the fields with [`Instance::nested_field`].
+  pub fn nested<F>(mut self, nested_fn: F) -> Self
+  where
+    F: Fn(&Instance) + 'static,
+  {
+    self.nested.push(Rc::new(nested_fn));
+    self.id = next_template_id();
+    self
+  }
+
+  /// Adds options to this message, which are included in every instance
+  pub fn options<I>(mut self, options: I) -> Self
+  where
+    I: IntoIterator<Item = ProtoOption>,
+  {
+    merge_options(&mut self.options, options);
+    self.id = next_template_id();
+    self
+  }
+
+  /// Generates the concrete message for the given type arguments in the given file, or returns it if the same instance had already been generated.
+  ///
+  /// The message is named after the type arguments followed by the name of this template, so `Page<Book>` generates `BookPage`, and `Pair<string, Book>` generates `StringBookPair`.
+  ///
+  /// # Panics
+  /// If the number of arguments does not match the number of parameters, if the package already contains a different message with the same name, or if two of the generated fields have the same tag or name.
+  #[track_caller]
+  pub fn instantiate<I>(&self, file: &FileBuilder, args: I) -> MessageBuilder<SetFields>
+  where
+    I: IntoIterator<Item = FieldType>,
+  {
+    let args: Box<[FieldType]> = args.into_iter().collect();
+    let name: String = args
+      .iter()
+      .map(type_name)
+      .chain([self.name.to_string()])
+      .collect();
+
+    self.instantiate_as(file, name, args)
+  }
+
+  /// Same as [`instantiate`](GenericMessage::instantiate), but with a custom name for the generated message
+  #[track_caller]
+  pub fn instantiate_as<T, I>(
+    &self,
+    file: &FileBuilder,
+    name: T,
+    args: I,
+  ) -> MessageBuilder<SetFields>
+  where
+    T: AsRef<str>,
+    I: IntoIterator<Item = FieldType>,
+  {
+    let args: Box<[FieldType]> = args.into_iter().collect();
+
+    if args.len() != self.params.len() {
+      panic!(
+        "The generic message '{}' expects {} type argument(s), but {} were given",
+        self.name,
+        self.params.len(),
+        args.len()
+      )
+    }
+
+    let signature = (self.id, args.clone());
+
+    let existing = {
+      let arena = file.arena.borrow();
+      let full_name = format!("{}.{}", arena.name, name.as_ref());
+
+      arena.message_id(&full_name).map(|msg_id| {
+        if arena.messages[msg_id].instance_of.as_ref() != Some(&signature) {
+          panic!("The package already contains a message named '{full_name}'")
+        }
+
+        let file_id = arena
+          .files
+          .iter()
+          .position(|file| file.messages.contains(&msg_id))
+          .unwrap_or(file.id);
+
+        (msg_id, file_id)
+      })
+    };
+
+    if let Some((id, file_id)) = existing {
+      return MessageBuilder {
+        id,
+        arena: file.arena.clone(),
+        file_id,
+        _phantom: PhantomData,
+      };
+    }
+
+    let message = file.new_message(name).add_options(self.options.clone());
+
+    message.arena.borrow_mut().messages[message.id].instance_of = Some(signature);
+
+    let instance = Instance {
+      params: self.params.clone(),
+      args,
+      message,
+    };
+
+    for nested_fn in &self.nested {
+      nested_fn(&instance);
+    }
+
+    let fields: Vec<(u32, Field)> = self
+      .fields
+      .iter()
+      .map(|(tag, field_fn)| (*tag, field_fn(&instance)))
+      .collect();
+
+    instance.message.place_fields(fields);
+
+    let Instance { message, .. } = instance;
+
+    MessageBuilder {
+      id: message.id,
+      arena: message.arena,
+      file_id: message.file_id,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+// The name used for a type argument in the name of an instance
+fn type_name(field_type: &FieldType) -> String {
+  match field_type {
+    FieldType::Message(path) | FieldType::Enum(path) => path.full_name.replace('.', ""),
+    FieldType::Map(key, value) => format!(
+      "{}{}Map",
+      key.name().to_case(Case::Pascal),
+      type_name(value)
+    ),
+    other => other
+      .name()
+      .rsplit('.')
+      .next()
+      .unwrap_or_default()
+      .to_case(Case::Pascal),
+  }
+}
+
+/// A concrete instance of a [`GenericMessage`], which is passed to the closures that define its fields and nested items.
+#[derive(Debug)]
+pub struct Instance {
+  params: Box<[Box<str>]>,
+  args: Box<[FieldType]>,
+  message: MessageBuilder,
+}
+
+impl Instance {
+  /// Returns the type given for a type parameter
+  ///
+  /// # Panics
+  /// If the generic message does not have a parameter with this name.
+  #[track_caller]
+  pub fn arg(&self, param: &str) -> FieldType {
+    match self.params.iter().position(|p| p.as_ref() == param) {
+      Some(index) => self.args[index].clone(),
+      None => panic!("Unknown type parameter '{param}'"),
+    }
+  }
+
+  /// Creates a field with the type given for a type parameter, and with the import for it
+  #[track_caller]
+  pub fn field<T: Into<Arc<str>>>(
+    &self,
+    param: &str,
+    name: T,
+  ) -> FieldBuilder<SetFieldType<SetName>> {
+    let field_type = self.arg(param);
+    let import = field_type.import_path();
+
+    let field = Field::builder().name(name.into()).field_type(field_type);
+
+    match import {
+      Some(import) => field.add_import(import),
+      None => field,
+    }
+  }
+
+  /// Creates a field with the type of an enum or message defined inside of this instance
+  ///
+  /// # Panics
+  /// If this instance does not contain an enum or message with this name.
+  #[track_caller]
+  pub fn nested_field<T: Into<Arc<str>>>(
+    &self,
+    nested: &str,
+    name: T,
+  ) -> FieldBuilder<SetFieldType<SetName>> {
+    let field_type = {
+      let arena = self.message.arena.borrow();
+      let msg = &arena.messages[self.message.id];
+
+      let nested_enum = msg
+        .enums
+        .iter()
+        .map(|id| &arena.enums[*id])
+        .find(|e| e.name.as_ref() == nested)
+        .map(|e| FieldType::Enum(e.import_path.clone()));
+
+      let nested_message = || {
+        msg
+          .messages
+          .iter()
+          .map(|id| &arena.messages[*id])
+          .find(|m| m.name.as_ref() == nested)
+          .map(|m| FieldType::Message(m.import_path.clone()))
+      };
+
+      match nested_enum.or_else(nested_message) {
+        Some(field_type) => field_type,
+        None => panic!(
+          "The message '{}' does not contain an item named '{nested}'",
+          msg.import_path.full_name_with_package
+        ),
+      }
+    };
+
+    Field::builder().name(name.into()).field_type(field_type)
+  }
+
+  /// Returns the builder of the message being generated
+  pub fn message(&self) -> &MessageBuilder {
+    &self.message
+  }
+}
@@ -0,0 +1,290 @@
+use std::sync::Arc;
+
+use bon::Builder;
+
+use crate::{checks::get_value, options::message_value, OptionValue, ProtoOption};
+
+const HTTP_METHODS: [&str; 5] = ["get", "put", "post", "delete", "patch"];
+
+/// The HTTP method and path template of an [`HttpRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpPattern {
+  Get(Box<str>),
+  Put(Box<str>),
+  Post(Box<str>),
+  Delete(Box<str>),
+  Patch(Box<str>),
+  /// A method that is not included in the other variants, such as `HEAD`.
+  Custom {
+    kind: Box<str>,
+    path: Box<str>,
+  },
+}
+
+/// A builder for the `(google.api.http)` option, which maps a handler to a REST endpoint.
+///
+/// It can be converted into a [`ProtoOption`] and used in the options of a handler in the [`services`](crate::services) macro. The file containing the service will import `google/api/annotations.proto` automatically.
+/// The variables in the path template (such as `{name=users/*}`), as well as the `body` and `response_body` fields, are checked by [`Package::check`](crate::Package::check), which reports the ones that do not refer to an existing field of the request (or response) message.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{http::HttpRule, message, services, string, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let user_msg = message!(my_file.new_message("User"), 1 => string!("name"));
+/// let get_user_msg = message!(my_file.new_message("GetUserRequest"), 1 => string!("name"));
+///
+/// services!(
+///   my_file,
+///   UserService {
+///     GetUser(get_user_msg => user_msg) {
+///       [
+///         HttpRule::get("/v1/{name=users/*}")
+///           .additional_binding(HttpRule::get("/v1/me"))
+///           .into()
+///       ]
+///     },
+///   };
+/// );
+/// ```
+#[derive(Debug, Clone, Builder)]
+#[builder(start_fn(name = with_pattern, vis = ""))]
+pub struct HttpRule {
+  #[builder(start_fn)]
+  pub pattern: HttpPattern,
+  /// The other bindings for the same handler. These cannot have additional bindings themselves.
+  #[builder(field)]
+  pub additional_bindings: Vec<HttpRule>,
+  /// The name of the request field that is mapped to the HTTP body, or `*` for the whole request.
+  /// The fields that are not included in the path or in the body become query parameters.
+  #[builder(into)]
+  pub body: Option<Box<str>>,
+  /// The name of the response field that is mapped to the HTTP body. By default, the whole response is used.
+  #[builder(into)]
+  pub response_body: Option<Box<str>>,
+}
+
+impl HttpRule {
+  /// Creates a rule for a `GET` endpoint with the given path template.
+  pub fn get<T: Into<Box<str>>>(path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Get(path.into()))
+  }
+
+  /// Creates a rule for a `PUT` endpoint with the given path template.
+  pub fn put<T: Into<Box<str>>>(path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Put(path.into()))
+  }
+
+  /// Creates a rule for a `POST` endpoint with the given path template.
+  pub fn post<T: Into<Box<str>>>(path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Post(path.into()))
+  }
+
+  /// Creates a rule for a `DELETE` endpoint with the given path template.
+  pub fn delete<T: Into<Box<str>>>(path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Delete(path.into()))
+  }
+
+  /// Creates a rule for a `PATCH` endpoint with the given path template.
+  pub fn patch<T: Into<Box<str>>>(path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Patch(path.into()))
+  }
+
+  /// Creates a rule for an endpoint with a custom HTTP method, such as `HEAD`.
+  pub fn custom<K: Into<Box<str>>, T: Into<Box<str>>>(kind: K, path: T) -> HttpRuleBuilder {
+    Self::with_pattern(HttpPattern::Custom {
+      kind: kind.into(),
+      path: path.into(),
+    })
+  }
+}
+
+impl<S: http_rule_builder::State> HttpRuleBuilder<S> {
+  /// Adds another binding for the same handler.
+  pub fn additional_binding<R: Into<HttpRule>>(mut self, rule: R) -> Self {
+    self.additional_bindings.push(rule.into());
+    self
+  }
+}
+
+impl<S: http_rule_builder::IsComplete> From<HttpRuleBuilder<S>> for HttpRule {
+  fn from(value: HttpRuleBuilder<S>) -> Self {
+    value.build()
+  }
+}
+
+impl<S: http_rule_builder::IsComplete> From<HttpRuleBuilder<S>> for ProtoOption {
+  #[track_caller]
+  fn from(value: HttpRuleBuilder<S>) -> Self {
+    value.build().into()
+  }
+}
+
+impl From<HttpRule> for OptionValue {
+  #[track_caller]
+  fn from(rule: HttpRule) -> Self {
+    let mut values: Vec<(&str, OptionValue)> = Vec::new();
+
+    match rule.pattern {
+      HttpPattern::Get(path) => values.push(("get", OptionValue::String(path))),
+      HttpPattern::Put(path) => values.push(("put", OptionValue::String(path))),
+      HttpPattern::Post(path) => values.push(("post", OptionValue::String(path))),
+      HttpPattern::Delete(path) => values.push(("delete", OptionValue::String(path))),
+      HttpPattern::Patch(path) => values.push(("patch", OptionValue::String(path))),
+      HttpPattern::Custom { kind, path } => values.push((
+        "custom",
+        message_value([
+          ("kind", OptionValue::String(kind)),
+          ("path", OptionValue::String(path)),
+        ]),
+      )),
+    };
+
+    if let Some(body) = rule.body {
+      values.push(("body", OptionValue::String(body)));
+    }
+
+    if let Some(response_body) = rule.response_body {
+      values.push(("response_body", OptionValue::String(response_body)));
+    }
+
+    for binding in rule.additional_bindings {
+      if !binding.additional_bindings.is_empty() {
+        panic!("An additional binding cannot contain other additional bindings")
+      }
+
+      values.push(("additional_bindings", binding.into()));
+    }
+
+    message_value(values)
+  }
+}
+
+impl From<HttpRule> for ProtoOption {
+  #[track_caller]
+  fn from(rule: HttpRule) -> Self {
+    ProtoOption {
+      name: "(google.api.http)".into(),
+      value: Arc::new(rule.into()),
+    }
+  }
+}
+
+pub(crate) fn is_http_option(option: &ProtoOption) -> bool {
+  &*option.name == "(google.api.http)" || option.name.starts_with("(google.api.http).")
+}
+
+// A single binding of an http rule
+pub(crate) struct HttpBinding {
+  pub(crate) method: Box<str>,
+  pub(crate) path: Box<str>,
+  pub(crate) body: Option<Box<str>>,
+  pub(crate) response_body: Option<Box<str>>,
+}
+
+// Collects the bindings of the `(google.api.http)` option, which can be defined as a whole or with a separate option for each of its fields
+pub(crate) fn http_bindings(options: &[ProtoOption]) -> Vec<HttpBinding> {
+  let mut rule: Vec<(Box<str>, OptionValue)> = Vec::new();
+
+  for option in options {
+    if &*option.name == "(google.api.http)"
+      && let OptionValue::Message(values) = option.value.as_ref()
+    {
+      rule.extend(values.iter().cloned());
+    } else if let Some(key) = option.name.strip_prefix("(google.api.http).") {
+      rule.push((key.into(), option.value.as_ref().clone()));
+    }
+  }
+
+  let mut bindings: Vec<HttpBinding> = Vec::new();
+
+  if !rule.is_empty() {
+    collect_bindings(&OptionValue::Message(rule.into()), &mut bindings);
+  }
+
+  bindings
+}
+
+fn collect_bindings(rule: &OptionValue, bindings: &mut Vec<HttpBinding>) {
+  let get_string = |value: Option<&OptionValue>| match value {
+    Some(OptionValue::String(value)) if !value.is_empty() => Some(value.clone()),
+    _ => None,
+  };
+
+  let pattern = HTTP_METHODS
+    .iter()
+    .find_map(|method| get_string(get_value(rule, method)).map(|path| ((*method).into(), path)))
+    .or_else(|| {
+      let custom = get_value(rule, "custom")?;
+
+      get_string(get_value(custom, "kind"))
+        .map(|kind| kind.to_lowercase().into())
+        .zip(get_string(get_value(custom, "path")))
+    });
+
+  if let Some((method, path)) = pattern {
+    bindings.push(HttpBinding {
+      method,
+      path,
+      body: get_string(get_value(rule, "body")),
+      response_body: get_string(get_value(rule, "response_body")),
+    });
+  }
+
+  let OptionValue::Message(values) = rule else {
+    return;
+  };
+
+  for (key, value) in values.iter() {
+    if key.as_ref() != "additional_bindings" {
+      continue;
+    }
+
+    match value {
+      OptionValue::List(items) => {
+        for item in items.iter() {
+          collect_bindings(item, bindings);
+        }
+      }
+      other => collect_bindings(other, bindings),
+    }
+  }
+}
+
+// A variable of a path template, such as `{name=users/*}`
+pub(crate) struct PathVariable {
+  pub(crate) field_path: Box<str>,
+  pub(crate) segments: Option<Box<str>>,
+}
+
+// Splits a path template such as `/v1/{name=users/*}` into the path with the bare variables (`/v1/{name}`), and the list of the variables
+pub(crate) fn parse_template(template: &str) -> (String, Vec<PathVariable>) {
+  let mut path = String::new();
+  let mut variables: Vec<PathVariable> = Vec::new();
+  let mut rest = template;
+
+  while let Some(start) = rest.find('{')
+    && let Some(end) = rest[start..].find('}').map(|end| start + end)
+  {
+    let variable = &rest[start + 1..end];
+    let (field_path, segments) = match variable.split_once('=') {
+      Some((field_path, segments)) => (field_path, Some(segments.into())),
+      None => (variable, None),
+    };
+
+    path.push_str(&rest[..start]);
+    path.push_str(&format!("{{{field_path}}}"));
+    variables.push(PathVariable {
+      field_path: field_path.into(),
+      segments,
+    });
+
+    rest = &rest[end + 1..];
+  }
+
+  path.push_str(rest);
+
+  (path, variables)
+}
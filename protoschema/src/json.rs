@@ -0,0 +1,488 @@
+use std::{fmt, sync::Arc};
+
+use serde::{
+  de::{self, MapAccess, SeqAccess, Visitor},
+  Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{Map, Number, Value};
+
+use crate::{
+  errors::JsonConversionError,
+  field_type::{Duration, Timestamp},
+  OptionValue, ProtoOption,
+};
+
+const ENUM_TAG: &str = "$enum";
+const DURATION_TAG: &str = "$duration";
+const TIMESTAMP_TAG: &str = "$timestamp";
+const UINT_TAG: &str = "$uint";
+const FLOAT_TAG: &str = "$float";
+
+impl OptionValue {
+  /// Converts this value into a [`serde_json::Value`].
+  ///
+  /// Booleans, strings, lists and messages are mapped to their natural JSON counterparts.
+  /// Since JSON cannot represent them unambiguously, the values that cannot be told apart from others once converted are represented as single-key objects, with a key starting with `$` (which can never be a valid protobuf field name):
+  /// - Enum values become `{ "$enum": "MY_VALUE" }`
+  /// - Durations become `{ "$duration": "3600.5s" }`, using the proto3 JSON format
+  /// - Timestamps become `{ "$timestamp": "2025-01-01T00:00:00Z" }`, using the RFC 3339 format
+  /// - Unsigned integers that would also fit in an i64 become `{ "$uint": 5 }`
+  /// - Non-finite floats become `{ "$float": "NaN" }`, `{ "$float": "Infinity" }` or `{ "$float": "-Infinity" }`
+  ///
+  /// Converting the result back with [`from_json`](OptionValue::from_json) gives back the original value, except for NaN floats: they are restored as a NaN without their sign and payload, and since NaN never compares as equal to itself, neither does a value that contains it.
+  /// Since [`serde_json::Map`] sorts its keys unless the `preserve_order` feature of `serde_json` is enabled, the fields of messages may also come back in a different order. The [`Serialize`] and [`Deserialize`] implementations of [`OptionValue`] do not go through [`serde_json::Value`], and always keep the fields in their original order.
+  pub fn to_json(&self) -> Value {
+    match self {
+      Self::Bool(val) => Value::Bool(*val),
+      Self::Int(val) => Value::Number((*val).into()),
+      Self::Uint(val) => {
+        if i64::try_from(*val).is_ok() {
+          tagged(UINT_TAG, Value::Number((*val).into()))
+        } else {
+          Value::Number((*val).into())
+        }
+      }
+      Self::Float(val) => match Number::from_f64(*val) {
+        Some(num) => Value::Number(num),
+        None => {
+          let repr = if val.is_nan() {
+            "NaN"
+          } else if val.is_sign_positive() {
+            "Infinity"
+          } else {
+            "-Infinity"
+          };
+
+          tagged(FLOAT_TAG, Value::String(repr.into()))
+        }
+      },
+      Self::String(val) => Value::String(val.to_string()),
+      Self::List(list) => Value::Array(list.iter().map(OptionValue::to_json).collect()),
+      Self::Message(fields) => Value::Object(
+        fields
+          .iter()
+          .map(|(name, val)| (name.to_string(), val.to_json()))
+          .collect(),
+      ),
+      Self::Enum(val) => tagged(ENUM_TAG, Value::String(val.to_string())),
+      Self::Duration(val) => tagged(DURATION_TAG, Value::String(format_duration(val))),
+      Self::Timestamp(val) => tagged(TIMESTAMP_TAG, Value::String(format_timestamp(val))),
+    }
+  }
+
+  /// Builds an [`OptionValue`] from a [`serde_json::Value`], following the conventions described in [`to_json`](OptionValue::to_json).
+  ///
+  /// Plain JSON integers become [`OptionValue::Int`] if they fit into an i64, and [`OptionValue::Uint`] otherwise, whereas numbers with a fractional part become [`OptionValue::Float`].
+  /// Since integers are rendered in the same way regardless of their signedness, this makes it possible to use plain numbers when the values come from a configuration file, without having to use the `$uint` tag.
+  ///
+  /// Null values are not allowed, since they have no equivalent in protobuf options.
+  pub fn from_json(value: Value) -> Result<Self, JsonConversionError> {
+    let converted = match value {
+      Value::Null => return Err(JsonConversionError::NullValue),
+      Value::Bool(val) => Self::Bool(val),
+      Value::Number(num) => number_to_option_value(&num),
+      Value::String(val) => Self::String(val.into()),
+      Value::Array(items) => Self::List(
+        items
+          .into_iter()
+          .map(OptionValue::from_json)
+          .collect::<Result<Box<[OptionValue]>, JsonConversionError>>()?,
+      ),
+      Value::Object(map) => {
+        if map.len() == 1
+          && let Some((key, val)) = map.iter().next()
+          && key.starts_with('$')
+        {
+          return tagged_to_option_value(key, val);
+        }
+
+        Self::Message(
+          map
+            .into_iter()
+            .map(|(name, val)| Ok((name.into_boxed_str(), OptionValue::from_json(val)?)))
+            .collect::<Result<Box<[(Box<str>, OptionValue)]>, JsonConversionError>>()?,
+        )
+      }
+    };
+
+    Ok(converted)
+  }
+}
+
+fn tagged(tag: &str, value: Value) -> Value {
+  let mut map = Map::new();
+  map.insert(tag.to_string(), value);
+  Value::Object(map)
+}
+
+fn number_to_option_value(num: &Number) -> OptionValue {
+  if let Some(int) = num.as_i64() {
+    OptionValue::Int(int)
+  } else if let Some(uint) = num.as_u64() {
+    OptionValue::Uint(uint)
+  } else {
+    // Any number that is neither an i64 nor a u64 is always an f64
+    OptionValue::Float(num.as_f64().unwrap_or_default())
+  }
+}
+
+fn tagged_to_option_value(tag: &str, value: &Value) -> Result<OptionValue, JsonConversionError> {
+  let invalid = || JsonConversionError::InvalidTaggedValue {
+    tag: tag.into(),
+    value: value.to_string(),
+  };
+
+  match tag {
+    ENUM_TAG => value
+      .as_str()
+      .map(|v| OptionValue::Enum(v.into()))
+      .ok_or_else(invalid),
+    DURATION_TAG => value
+      .as_str()
+      .and_then(parse_duration)
+      .map(OptionValue::Duration)
+      .ok_or_else(invalid),
+    TIMESTAMP_TAG => value
+      .as_str()
+      .and_then(parse_timestamp)
+      .map(OptionValue::Timestamp)
+      .ok_or_else(invalid),
+    UINT_TAG => value.as_u64().map(OptionValue::Uint).ok_or_else(invalid),
+    FLOAT_TAG => match value {
+      Value::Number(num) => num.as_f64().map(OptionValue::Float).ok_or_else(invalid),
+      Value::String(repr) => match repr.as_str() {
+        "NaN" => Ok(OptionValue::Float(f64::NAN)),
+        "Infinity" => Ok(OptionValue::Float(f64::INFINITY)),
+        "-Infinity" => Ok(OptionValue::Float(f64::NEG_INFINITY)),
+        _ => Err(invalid()),
+      },
+      _ => Err(invalid()),
+    },
+    _ => Err(JsonConversionError::UnknownTag(tag.into())),
+  }
+}
+
+impl From<OptionValue> for Value {
+  fn from(value: OptionValue) -> Self {
+    value.to_json()
+  }
+}
+
+impl From<&OptionValue> for Value {
+  fn from(value: &OptionValue) -> Self {
+    value.to_json()
+  }
+}
+
+impl TryFrom<Value> for OptionValue {
+  type Error = JsonConversionError;
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    OptionValue::from_json(value)
+  }
+}
+
+impl Serialize for OptionValue {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    // Lists and messages are serialized directly rather than through `to_json`, so that the fields of messages keep their order
+    // regardless of the features of `serde_json` that are enabled
+    match self {
+      Self::List(list) => serializer.collect_seq(list.iter()),
+      Self::Message(fields) => serializer.collect_map(fields.iter().map(|(name, val)| (name, val))),
+      _ => self.to_json().serialize(serializer),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for OptionValue {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(OptionValueVisitor)
+  }
+}
+
+/// Builds an [`OptionValue`] directly from the deserializer, following the same conventions as [`OptionValue::from_json`],
+/// but keeping the fields of messages in the order in which they appear in the input
+struct OptionValueVisitor;
+
+impl<'de> Visitor<'de> for OptionValueVisitor {
+  type Value = OptionValue;
+
+  fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str("an option value")
+  }
+
+  fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+    Ok(OptionValue::Bool(v))
+  }
+
+  fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+    Ok(OptionValue::Int(v))
+  }
+
+  fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+    Ok(i64::try_from(v).map_or(OptionValue::Uint(v), OptionValue::Int))
+  }
+
+  fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+    Ok(OptionValue::Float(v))
+  }
+
+  fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+    Ok(OptionValue::String(v.into()))
+  }
+
+  fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+    Err(E::custom(JsonConversionError::NullValue))
+  }
+
+  fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+    self.visit_unit()
+  }
+
+  fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_any(self)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    let mut items = Vec::new();
+
+    while let Some(item) = seq.next_element::<OptionValue>()? {
+      items.push(item);
+    }
+
+    Ok(OptionValue::List(items.into()))
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+    let mut fields: Vec<(Box<str>, OptionValue)> = Vec::new();
+    // Tags are only recognized when they are the only key of the object, so the first one is kept aside until we know whether other keys follow
+    let mut pending_tag: Option<(String, Value)> = None;
+
+    while let Some(name) = map.next_key::<String>()? {
+      if let Some((tag, raw)) = pending_tag.take() {
+        let value = OptionValue::from_json(raw).map_err(de::Error::custom)?;
+        fields.push((tag.into_boxed_str(), value));
+      }
+
+      if fields.is_empty() && name.starts_with('$') {
+        pending_tag = Some((name, map.next_value()?));
+      } else {
+        fields.push((name.into_boxed_str(), map.next_value()?));
+      }
+    }
+
+    if let Some((tag, raw)) = pending_tag {
+      return tagged_to_option_value(&tag, &raw).map_err(de::Error::custom);
+    }
+
+    Ok(OptionValue::Message(fields.into()))
+  }
+}
+
+#[derive(Serialize)]
+struct SerializedOption<'a> {
+  name: &'a str,
+  value: &'a OptionValue,
+}
+
+#[derive(Deserialize)]
+struct DeserializedOption {
+  name: Box<str>,
+  value: OptionValue,
+}
+
+impl Serialize for ProtoOption {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    SerializedOption {
+      name: &self.name,
+      value: self.value.as_ref(),
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for ProtoOption {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let option = DeserializedOption::deserialize(deserializer)?;
+
+    Ok(ProtoOption {
+      name: option.name.into(),
+      value: Arc::new(option.value),
+    })
+  }
+}
+
+/// Formats a duration using the proto3 JSON format (i.e. "-3.5s")
+pub(crate) fn format_duration(duration: &Duration) -> String {
+  let sign = if duration.seconds < 0 || duration.nanos < 0 {
+    "-"
+  } else {
+    ""
+  };
+  let seconds = duration.seconds.unsigned_abs();
+  let nanos = duration.nanos.unsigned_abs();
+
+  format!("{}{}{}s", sign, seconds, format_nanos(nanos))
+}
+
+/// Parses a duration in the proto3 JSON format (i.e. "-3.5s")
+pub(crate) fn parse_duration(input: &str) -> Option<Duration> {
+  let input = input.strip_suffix('s')?;
+  let (is_negative, input) = match input.strip_prefix('-') {
+    Some(rest) => (true, rest),
+    None => (false, input),
+  };
+
+  // `i64::parse` also accepts a leading sign, which would allow inputs like "+3s" or "--3s"
+  if input.starts_with(['+', '-']) {
+    return None;
+  }
+
+  let (seconds, nanos) = match input.split_once('.') {
+    Some((seconds, fraction)) => (seconds.parse::<i64>().ok()?, parse_nanos(fraction)?),
+    None => (input.parse::<i64>().ok()?, 0),
+  };
+
+  if is_negative {
+    Some(Duration {
+      seconds: -seconds,
+      nanos: -nanos,
+    })
+  } else {
+    Some(Duration { seconds, nanos })
+  }
+}
+
+/// Formats a timestamp in the RFC 3339 format, always using the UTC offset (i.e. "2025-01-01T10:00:00.5Z")
+pub(crate) fn format_timestamp(timestamp: &Timestamp) -> String {
+  let days = timestamp.seconds.div_euclid(86_400);
+  let seconds_of_day = timestamp.seconds.rem_euclid(86_400);
+  let (year, month, day) = civil_from_days(days);
+
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+    year,
+    month,
+    day,
+    seconds_of_day / 3600,
+    (seconds_of_day % 3600) / 60,
+    seconds_of_day % 60,
+    format_nanos(timestamp.nanos.unsigned_abs())
+  )
+}
+
+/// Parses a timestamp in the RFC 3339 format (i.e. "2025-01-01T10:00:00.5+02:00")
+pub(crate) fn parse_timestamp(input: &str) -> Option<Timestamp> {
+  let (date, time) = input.split_once(['T', 't'])?;
+
+  let mut date_parts = date.splitn(3, '-');
+  let year: i64 = date_parts.next()?.parse().ok()?;
+  let month: u32 = date_parts.next()?.parse().ok()?;
+  let day: u32 = date_parts.next()?.parse().ok()?;
+
+  if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+    return None;
+  }
+
+  let (time, offset_seconds) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+    (time, 0)
+  } else {
+    let offset_start = time.rfind(['+', '-'])?;
+    let (time, offset) = time.split_at(offset_start);
+    let (offset_hours, offset_minutes) = offset[1..].split_once(':')?;
+    let offset_seconds =
+      offset_hours.parse::<i64>().ok()? * 3600 + offset_minutes.parse::<i64>().ok()? * 60;
+
+    if offset.starts_with('-') {
+      (time, -offset_seconds)
+    } else {
+      (time, offset_seconds)
+    }
+  };
+
+  let (time, nanos) = match time.split_once('.') {
+    Some((time, fraction)) => (time, parse_nanos(fraction)?),
+    None => (time, 0),
+  };
+
+  let mut time_parts = time.splitn(3, ':');
+  let hours: i64 = time_parts.next()?.parse().ok()?;
+  let minutes: i64 = time_parts.next()?.parse().ok()?;
+  let seconds: i64 = time_parts.next()?.parse().ok()?;
+
+  if hours > 23 || minutes > 59 || seconds > 60 {
+    return None;
+  }
+
+  let seconds = days_from_civil(year, month, day) * 86_400 + hours * 3600 + minutes * 60 + seconds
+    - offset_seconds;
+
+  Some(Timestamp { seconds, nanos })
+}
+
+fn format_nanos(nanos: u32) -> String {
+  if nanos == 0 {
+    String::new()
+  } else if nanos.is_multiple_of(1_000_000) {
+    format!(".{:03}", nanos / 1_000_000)
+  } else if nanos.is_multiple_of(1_000) {
+    format!(".{:06}", nanos / 1_000)
+  } else {
+    format!(".{:09}", nanos)
+  }
+}
+
+fn parse_nanos(fraction: &str) -> Option<i32> {
+  if fraction.is_empty() || fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+    return None;
+  }
+
+  let padded = format!("{:0<9}", fraction);
+  padded.parse().ok()
+}
+
+fn is_leap_year(year: i64) -> bool {
+  (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+  match month {
+    2 if is_leap_year(year) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+// The algorithms for converting between days and dates are taken from
+// http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let z = days + 719_468;
+  let era = (if z >= 0 { z } else { z - 146_096 }) / 146_097;
+  let day_of_era = z - era * 146_097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+  let month = (if month_index < 10 {
+    month_index + 3
+  } else {
+    month_index - 9
+  }) as u32;
+  let year = year_of_era + era * 400;
+
+  (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = (if year >= 0 { year } else { year - 399 }) / 400;
+  let year_of_era = year - era * 400;
+  let month = month as i64;
+  let day_of_year =
+    (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+  era * 146_097 + day_of_era - 719_468
+}
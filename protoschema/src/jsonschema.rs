@@ -0,0 +1,734 @@
+use std::collections::HashMap;
+
+use proto_types::protovalidate::Ignore;
+use serde_json::{json, Map, Value as Json};
+
+use crate::{
+  checks::{find_option, get_value},
+  evaluation::{entries, ignore_setting, is_required_oneof, unescape_bytes},
+  examples::encode_base64,
+  fields::{field_json_name, FieldData, FieldKind},
+  json::{format_duration, format_timestamp},
+  rendering::{EnumTemplate, FileTemplate, MessageTemplate},
+  FieldType, MapKey, OptionValue,
+};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+const INT_PATTERN: &str = "^-?[0-9]+$";
+const UINT_PATTERN: &str = "^[0-9]+$";
+const DURATION_PATTERN: &str = r"^-?[0-9]+(\.[0-9]{1,9})?s$";
+
+// The rules that apply to the integers and floats that are represented as JSON numbers
+const NUMBER_KINDS: [&str; 7] = [
+  "float", "double", "int32", "uint32", "sint32", "fixed32", "sfixed32",
+];
+// The rules that apply to the 64-bit integers, which are represented as strings in the proto3 JSON format
+const INT64_KINDS: [&str; 5] = ["int64", "uint64", "sint64", "fixed64", "sfixed64"];
+
+// The validation rules of a field, unless they are always ignored
+pub(crate) fn field_rules(field: &FieldData) -> Option<&OptionValue> {
+  find_option(&field.options, "(buf.validate.field)").filter(|rules| not_ignored(rules))
+}
+
+fn not_ignored(rules: &OptionValue) -> bool {
+  ignore_setting(rules) != Ignore::Always
+}
+
+/// Returns the `$id` of the JSON Schema document of a message or enum, given its fully qualified name.
+pub fn schema_id(full_name: &str) -> String {
+  format!("{full_name}.schema.json")
+}
+
+/// Turns each top level message and enum of the given file templates into a JSON Schema document (draft 2020-12), which describes its instances in the proto3 JSON format.
+///
+/// Each document has its own `$id`, built with [`schema_id`], and the nested messages and enums are defined in its `$defs`, with their own `$id`.
+/// The fields that refer to another message or enum use a `$ref` to its `$id`, so the documents for the other messages in the package should be registered along with it.
+/// Like the proto3 JSON parsers, the documents accept each field under its JSON name (taken from the `json_name` option, when it is set) as well as under its original name.
+///
+/// The validation rules that have a JSON Schema equivalent are translated (for example, `min_len` becomes `minLength`, `in` becomes `enum` and `email` becomes `format: email`), while the others (such as the Cel rules) are left out.
+/// The bounds of the 64-bit integers only apply to the values written as JSON numbers, and the bounds of the durations and timestamps are left out as well.
+/// The rules of the fields with `IGNORE_ALWAYS` are not translated.
+pub fn json_schemas(files: &[FileTemplate]) -> Vec<Json> {
+  let exporter = Exporter::new(files, References::Ids);
+
+  let mut documents: Vec<Json> = Vec::new();
+
+  for file in files {
+    let schemas = file
+      .messages
+      .iter()
+      .map(|msg| exporter.message_schema(&format!("{}.{}", file.package, msg.name), msg))
+      .chain(
+        file
+          .enums
+          .iter()
+          .map(|e| exporter.enum_schema(&format!("{}.{}", file.package, e.name), e)),
+      );
+
+    for mut schema in schemas {
+      schema.insert("$schema".into(), DRAFT.into());
+      documents.push(Json::Object(schema));
+    }
+  }
+
+  documents
+}
+
+// How the schemas of the messages and enums refer to each other
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum References {
+  // Each schema has its own `$id`, and the nested items are defined in its `$defs`
+  Ids,
+  // All the schemas are defined in the `components` of an OpenAPI document
+  Components,
+}
+
+pub(crate) struct Exporter<'a> {
+  // The enums defined in the templates, by their fully qualified name
+  enums: HashMap<String, &'a EnumTemplate>,
+  references: References,
+}
+
+impl<'a> Exporter<'a> {
+  pub(crate) fn new(files: &'a [FileTemplate], references: References) -> Self {
+    let mut exporter = Exporter {
+      enums: HashMap::new(),
+      references,
+    };
+
+    for file in files {
+      exporter.collect_enums(&file.package, &file.messages, &file.enums);
+    }
+
+    exporter
+  }
+
+  // The schemas of all the messages and enums (including the nested ones), by their fully qualified name
+  pub(crate) fn components(&self, files: &[FileTemplate]) -> Map<String, Json> {
+    let mut components = Map::new();
+
+    for file in files {
+      self.collect_components(&file.package, &file.messages, &file.enums, &mut components);
+    }
+
+    components
+  }
+
+  fn collect_components(
+    &self,
+    prefix: &str,
+    messages: &[MessageTemplate],
+    enums: &[EnumTemplate],
+    components: &mut Map<String, Json>,
+  ) {
+    for e in enums {
+      let full_name = format!("{prefix}.{}", e.name);
+      let schema = self.enum_schema(&full_name, e);
+      components.insert(full_name, schema.into());
+    }
+
+    for msg in messages {
+      let full_name = format!("{prefix}.{}", msg.name);
+
+      self.collect_components(&full_name, &msg.messages, &msg.enums, components);
+
+      let schema = self.message_schema(&full_name, msg);
+      components.insert(full_name, schema.into());
+    }
+  }
+
+  pub(crate) fn reference(&self, full_name: &str) -> Json {
+    match self.references {
+      References::Ids => json!({ "$ref": schema_id(full_name) }),
+      References::Components => json!({ "$ref": format!("#/components/schemas/{full_name}") }),
+    }
+  }
+
+  fn collect_enums(
+    &mut self,
+    prefix: &str,
+    messages: &'a [MessageTemplate],
+    enums: &'a [EnumTemplate],
+  ) {
+    for e in enums {
+      self.enums.insert(format!("{prefix}.{}", e.name), e);
+    }
+
+    for msg in messages {
+      self.collect_enums(&format!("{prefix}.{}", msg.name), &msg.messages, &msg.enums);
+    }
+  }
+
+  fn message_schema(&self, full_name: &str, msg: &MessageTemplate) -> Map<String, Json> {
+    let mut schema = Map::new();
+
+    if self.references == References::Ids {
+      schema.insert("$id".into(), schema_id(full_name).into());
+    }
+
+    schema.insert("title".into(), msg.name.as_ref().into());
+    schema.insert("type".into(), "object".into());
+
+    let message_rules = find_option(&msg.options, "(buf.validate.message)");
+    let disabled = matches!(
+      message_rules.and_then(|rules| get_value(rules, "disabled")),
+      Some(OptionValue::Bool(true))
+    );
+
+    let mut properties = Map::new();
+    let mut required: Vec<Json> = Vec::new();
+    // The required fields that can be set with either of their names
+    let mut required_any: Vec<Json> = Vec::new();
+
+    let fields = msg
+      .fields
+      .iter()
+      .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()));
+
+    for (_, field) in fields {
+      let rules = field_rules(field).filter(|_| !disabled);
+      let names = accepted_names(field);
+
+      if matches!(
+        rules.and_then(|rules| get_value(rules, "required")),
+        Some(OptionValue::Bool(true))
+      ) {
+        match names.as_slice() {
+          [name] => required.push(name.as_str().into()),
+          _ => required_any.push(presence(&names)),
+        }
+      }
+
+      let field_schema = self.field_schema(field, rules);
+
+      for name in names {
+        properties.insert(name, field_schema.clone());
+      }
+    }
+
+    schema.insert("properties".into(), properties.into());
+
+    if !required.is_empty() {
+      schema.insert("required".into(), required.into());
+    }
+
+    schema.insert("additionalProperties".into(), false.into());
+
+    let mut constraints: Vec<Json> = required_any;
+
+    constraints.extend(msg.oneofs.iter().map(|oneof| {
+      let names: Vec<Vec<String>> = oneof
+        .fields
+        .iter()
+        .map(|(_, field)| accepted_names(field))
+        .collect();
+
+      oneof_constraint(&names, !disabled && is_required_oneof(oneof))
+    }));
+
+    if !disabled
+      && let Some(OptionValue::List(oneof_rules)) =
+        message_rules.and_then(|rules| get_value(rules, "oneof"))
+    {
+      let all_fields: Vec<&FieldData> = msg
+        .fields
+        .iter()
+        .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()))
+        .map(|(_, field)| field)
+        .collect();
+
+      for rule in oneof_rules.iter() {
+        let names: Vec<Vec<String>> = match get_value(rule, "fields") {
+          Some(OptionValue::List(fields)) => fields
+            .iter()
+            .filter_map(|name| {
+              all_fields
+                .iter()
+                .find(|field| matches!(name, OptionValue::String(name) if **name == *field.name))
+                .map(|field| accepted_names(field))
+            })
+            .collect(),
+          _ => continue,
+        };
+
+        let is_required = matches!(get_value(rule, "required"), Some(OptionValue::Bool(true)));
+
+        constraints.push(oneof_constraint(&names, is_required));
+      }
+    }
+
+    match constraints.len() {
+      0 => {}
+      1 => {
+        if let Some(Json::Object(constraint)) = constraints.pop() {
+          schema.extend(constraint);
+        }
+      }
+      _ => {
+        schema.insert("allOf".into(), constraints.into());
+      }
+    }
+
+    // In the OpenAPI components, the nested items are defined separately
+    if self.references == References::Components {
+      return schema;
+    }
+
+    let mut defs = Map::new();
+
+    for nested in &msg.messages {
+      defs.insert(
+        nested.name.to_string(),
+        self
+          .message_schema(&format!("{full_name}.{}", nested.name), nested)
+          .into(),
+      );
+    }
+
+    for e in &msg.enums {
+      defs.insert(
+        e.name.to_string(),
+        self
+          .enum_schema(&format!("{full_name}.{}", e.name), e)
+          .into(),
+      );
+    }
+
+    if !defs.is_empty() {
+      schema.insert("$defs".into(), defs.into());
+    }
+
+    schema
+  }
+
+  fn enum_schema(&self, full_name: &str, e: &EnumTemplate) -> Map<String, Json> {
+    let mut schema = Map::new();
+
+    // The proto3 JSON parsers accept both the name and the number of a variant
+    let values: Vec<Json> = e
+      .variants
+      .iter()
+      .map(|(_, variant)| Json::from(variant.name.as_ref()))
+      .chain(e.variants.iter().map(|(number, _)| Json::from(*number)))
+      .collect();
+
+    if self.references == References::Ids {
+      schema.insert("$id".into(), schema_id(full_name).into());
+    }
+
+    schema.insert("title".into(), e.name.as_ref().into());
+    schema.insert("type".into(), json!(["string", "integer"]));
+    schema.insert("enum".into(), values.into());
+
+    schema
+  }
+
+  pub(crate) fn field_schema(&self, field: &FieldData, rules: Option<&OptionValue>) -> Json {
+    let mut schema = if field.kind == FieldKind::Repeated {
+      let repeated_rules = rules.and_then(|rules| get_value(rules, "repeated"));
+      let item_rules = repeated_rules.and_then(|rules| get_value(rules, "items"));
+
+      let mut schema = Map::new();
+      schema.insert("type".into(), "array".into());
+      schema.insert(
+        "items".into(),
+        self.value_schema(&field.field_type, item_rules).into(),
+      );
+
+      if let Some(repeated_rules) = repeated_rules {
+        self.apply_rules("repeated", repeated_rules, &field.field_type, &mut schema);
+      }
+
+      schema
+    } else {
+      self.value_schema(&field.field_type, rules)
+    };
+
+    if find_option(&field.options, "deprecated") == Some(&OptionValue::Bool(true)) {
+      schema.insert("deprecated".into(), true.into());
+    }
+
+    schema.into()
+  }
+
+  // The schema for a single value of a field, with its rules
+  fn value_schema(&self, field_type: &FieldType, rules: Option<&OptionValue>) -> Map<String, Json> {
+    let rules = rules.filter(|rules| not_ignored(rules));
+    let mut schema = self.type_schema(field_type, rules);
+
+    for (kind, type_rules) in rules.map(entries).unwrap_or_default() {
+      if matches!(kind.as_ref(), "cel" | "required" | "ignore") {
+        continue;
+      }
+
+      self.apply_rules(kind, type_rules, field_type, &mut schema);
+    }
+
+    schema
+  }
+
+  fn type_schema(&self, field_type: &FieldType, rules: Option<&OptionValue>) -> Map<String, Json> {
+    let schema = match field_type {
+      FieldType::Double | FieldType::Float => json!({ "type": "number" }),
+      FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => json!({
+        "type": "integer",
+        "minimum": i32::MIN,
+        "maximum": i32::MAX,
+      }),
+      FieldType::Uint32 | FieldType::Fixed32 => json!({
+        "type": "integer",
+        "minimum": 0,
+        "maximum": u32::MAX,
+      }),
+      FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => {
+        json!({ "type": "string", "pattern": INT_PATTERN })
+      }
+      FieldType::Uint64 | FieldType::Fixed64 => {
+        json!({ "type": "string", "pattern": UINT_PATTERN })
+      }
+      FieldType::Bool => json!({ "type": "boolean" }),
+      FieldType::String | FieldType::FieldMask => json!({ "type": "string" }),
+      FieldType::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+      FieldType::Enum(path) => match self.enums.get(path.full_name_with_package.as_ref()) {
+        Some(_) => {
+          let reference = self.reference(&path.full_name_with_package);
+
+          let defined_only = matches!(
+            rules
+              .and_then(|rules| get_value(rules, "enum"))
+              .and_then(|rules| get_value(rules, "defined_only")),
+            Some(OptionValue::Bool(true))
+          );
+
+          // Without defined_only, the unknown enum numbers are accepted as well
+          if defined_only {
+            reference
+          } else {
+            json!({ "anyOf": [reference, { "type": "integer" }] })
+          }
+        }
+        None => json!({ "type": ["string", "integer"] }),
+      },
+      FieldType::Code | FieldType::DayOfWeek | FieldType::Month => {
+        json!({ "type": ["string", "integer"] })
+      }
+      FieldType::Message(path) => self.reference(&path.full_name_with_package),
+      FieldType::Duration => json!({ "type": "string", "pattern": DURATION_PATTERN }),
+      FieldType::Timestamp => json!({ "type": "string", "format": "date-time" }),
+      FieldType::Any => json!({
+        "type": "object",
+        "properties": { "@type": { "type": "string" } },
+        "required": ["@type"],
+      }),
+      FieldType::Empty => json!({ "type": "object", "additionalProperties": false }),
+      FieldType::Map(key_type, value_type) => {
+        let map_rules = rules.and_then(|rules| get_value(rules, "map"));
+
+        json!({
+          "type": "object",
+          "propertyNames": self.key_schema(key_type, map_rules.and_then(|rules| get_value(rules, "keys"))),
+          "additionalProperties": self.value_schema(value_type, map_rules.and_then(|rules| get_value(rules, "values"))),
+        })
+      }
+      _ => json!({ "type": "object" }),
+    };
+
+    match schema {
+      Json::Object(schema) => schema,
+      _ => Map::new(),
+    }
+  }
+
+  // The keys of a map are always strings in the proto3 JSON format
+  fn key_schema(&self, key_type: &MapKey, rules: Option<&OptionValue>) -> Json {
+    match key_type {
+      MapKey::String => {
+        let mut schema = self.value_schema(&FieldType::String, rules);
+        schema.remove("type");
+        schema.into()
+      }
+      MapKey::Bool => json!({ "enum": ["true", "false"] }),
+      MapKey::Uint32 | MapKey::Uint64 | MapKey::Fixed32 | MapKey::Fixed64 => {
+        json!({ "pattern": UINT_PATTERN })
+      }
+      _ => json!({ "pattern": INT_PATTERN }),
+    }
+  }
+
+  fn apply_rules(
+    &self,
+    kind: &str,
+    rules: &OptionValue,
+    field_type: &FieldType,
+    schema: &mut Map<String, Json>,
+  ) {
+    let mut patterns: Vec<String> = Vec::new();
+    let mut negated: Vec<Json> = Vec::new();
+    let mut alternatives: Vec<Json> = Vec::new();
+
+    for (name, rule) in entries(rules) {
+      let is_set = *rule == OptionValue::Bool(true);
+
+      match (kind, name.as_ref()) {
+        ("any", "in") => {
+          schema.insert(
+            "properties".into(),
+            json!({ "@type": { "type": "string", "enum": self.rule_values(kind, rule, field_type) } }),
+          );
+        }
+        ("any", "not_in") => negated.push(json!({
+          "properties": { "@type": { "enum": self.rule_values(kind, rule, field_type) } },
+        })),
+        ("enum", "const") => {
+          schema.insert(
+            "enum".into(),
+            self.rule_values(kind, rule, field_type).into(),
+          );
+        }
+        (_, "const") => {
+          schema.insert("const".into(), self.rule_value(kind, rule));
+        }
+        (_, "in") => {
+          schema.insert(
+            "enum".into(),
+            self.rule_values(kind, rule, field_type).into(),
+          );
+        }
+        (_, "not_in") => negated.push(json!({ "enum": self.rule_values(kind, rule, field_type) })),
+        // The bounds of the durations and timestamps are not emitted,
+        // since JSON Schema cannot compare the strings that represent them
+        (kind, "gt" | "gte" | "lt" | "lte") if NUMBER_KINDS.contains(&kind) => {
+          schema.insert(bound_keyword(name).into(), self.rule_value(kind, rule));
+        }
+        // The 64-bit integers can also be written as JSON numbers, which are the only form that the bounds can check
+        (kind, "gt" | "gte" | "lt" | "lte") if INT64_KINDS.contains(&kind) => {
+          let bound = match rule {
+            OptionValue::Int(value) => Json::from(*value),
+            OptionValue::Uint(value) => Json::from(*value),
+            _ => continue,
+          };
+
+          schema.insert("type".into(), json!(["string", "integer"]));
+          schema.insert(bound_keyword(name).into(), bound);
+        }
+        ("string", "len") => {
+          schema.insert("minLength".into(), self.rule_value(kind, rule));
+          schema.insert("maxLength".into(), self.rule_value(kind, rule));
+        }
+        ("string", "min_len") => {
+          schema.insert("minLength".into(), self.rule_value(kind, rule));
+        }
+        ("string", "max_len") => {
+          schema.insert("maxLength".into(), self.rule_value(kind, rule));
+        }
+        ("string", "pattern" | "prefix" | "suffix" | "contains" | "not_contains") => {
+          let OptionValue::String(text) = rule else {
+            continue;
+          };
+
+          let escaped = regex_syntax::escape(text);
+
+          match name.as_ref() {
+            "pattern" => patterns.push(text.to_string()),
+            "prefix" => patterns.push(format!("^{escaped}")),
+            "suffix" => patterns.push(format!("{escaped}$")),
+            "contains" => patterns.push(escaped),
+            _ => negated.push(json!({ "pattern": escaped })),
+          }
+        }
+        ("string", "tuuid") if is_set => patterns.push("^[0-9a-fA-F]{32}$".into()),
+        ("string", "ip") if is_set => {
+          alternatives.push(json!([{ "format": "ipv4" }, { "format": "ipv6" }]));
+        }
+        ("string", "address") if is_set => {
+          alternatives.push(json!([
+            { "format": "hostname" },
+            { "format": "ipv4" },
+            { "format": "ipv6" },
+          ]));
+        }
+        ("string", format) if is_set => {
+          let format = match format {
+            "email" => "email",
+            "hostname" => "hostname",
+            "ipv4" => "ipv4",
+            "ipv6" => "ipv6",
+            "uri" => "uri",
+            "uri_ref" => "uri-reference",
+            "uuid" => "uuid",
+            _ => continue,
+          };
+
+          schema.insert("format".into(), format.into());
+        }
+        ("repeated", "min_items") => {
+          schema.insert("minItems".into(), self.rule_value(kind, rule));
+        }
+        ("repeated", "max_items") => {
+          schema.insert("maxItems".into(), self.rule_value(kind, rule));
+        }
+        ("repeated", "unique") if is_set => {
+          schema.insert("uniqueItems".into(), true.into());
+        }
+        ("map", "min_pairs") => {
+          schema.insert("minProperties".into(), self.rule_value(kind, rule));
+        }
+        ("map", "max_pairs") => {
+          schema.insert("maxProperties".into(), self.rule_value(kind, rule));
+        }
+        _ => {}
+      }
+    }
+
+    match patterns.len() {
+      0 => {}
+      1 => {
+        schema.insert("pattern".into(), patterns.remove(0).into());
+      }
+      _ => {
+        push_all_of(
+          schema,
+          patterns
+            .into_iter()
+            .map(|pattern| json!({ "pattern": pattern })),
+        );
+      }
+    }
+
+    match negated.len() {
+      0 => {}
+      1 => {
+        schema.insert("not".into(), negated.remove(0));
+      }
+      _ => {
+        schema.insert("not".into(), json!({ "anyOf": negated }));
+      }
+    }
+
+    match alternatives.len() {
+      0 => {}
+      1 => {
+        schema.insert("anyOf".into(), alternatives.remove(0));
+      }
+      _ => {
+        push_all_of(
+          schema,
+          alternatives
+            .into_iter()
+            .map(|alternative| json!({ "anyOf": alternative })),
+        );
+      }
+    }
+  }
+
+  // The values of a list rule (or of a single value, for the enums), in the proto3 JSON format
+  fn rule_values(&self, kind: &str, rule: &OptionValue, field_type: &FieldType) -> Vec<Json> {
+    let values: &[OptionValue] = match rule {
+      OptionValue::List(values) => values,
+      value => std::slice::from_ref(value),
+    };
+
+    if kind != "enum" {
+      return values
+        .iter()
+        .map(|value| self.rule_value(kind, value))
+        .collect();
+    }
+
+    let enum_template = match field_type {
+      FieldType::Enum(path) => self.enums.get(path.full_name_with_package.as_ref()),
+      _ => None,
+    };
+
+    // An enum value can be given with either its name or its number
+    values
+      .iter()
+      .flat_map(|value| {
+        let number = match value {
+          OptionValue::Int(number) => *number,
+          OptionValue::Uint(number) => *number as i64,
+          other => return vec![self.rule_value(kind, other)],
+        };
+
+        let name = enum_template.and_then(|e| {
+          e.variants
+            .iter()
+            .find(|(variant_number, _)| *variant_number as i64 == number)
+            .map(|(_, variant)| Json::from(variant.name.as_ref()))
+        });
+
+        name.into_iter().chain([Json::from(number)]).collect()
+      })
+      .collect()
+  }
+
+  fn rule_value(&self, kind: &str, rule: &OptionValue) -> Json {
+    match rule {
+      OptionValue::Bool(value) => (*value).into(),
+      OptionValue::Int(value) if INT64_KINDS.contains(&kind) => value.to_string().into(),
+      OptionValue::Uint(value) if INT64_KINDS.contains(&kind) => value.to_string().into(),
+      OptionValue::Int(value) => (*value).into(),
+      OptionValue::Uint(value) => (*value).into(),
+      OptionValue::Float(value) => (*value).into(),
+      OptionValue::String(value) if kind == "bytes" => encode_base64(&unescape_bytes(value)).into(),
+      OptionValue::String(value) | OptionValue::Enum(value) => value.as_ref().into(),
+      OptionValue::List(values) => values
+        .iter()
+        .map(|value| self.rule_value(kind, value))
+        .collect(),
+      OptionValue::Message(_) => Json::Null,
+      OptionValue::Duration(value) => format_duration(value).into(),
+      OptionValue::Timestamp(value) => format_timestamp(value).into(),
+    }
+  }
+}
+
+fn bound_keyword(rule: &str) -> &'static str {
+  match rule {
+    "gt" => "exclusiveMinimum",
+    "gte" => "minimum",
+    "lt" => "exclusiveMaximum",
+    _ => "maximum",
+  }
+}
+
+// The constraint for a group of mutually exclusive fields, like the ones in a oneof
+// The names that can be used for a field in the proto3 JSON format, which are its JSON name and its original name, if they differ
+fn accepted_names(field: &FieldData) -> Vec<String> {
+  let json_name = field_json_name(field);
+
+  if json_name == *field.name {
+    vec![json_name]
+  } else {
+    vec![json_name, field.name.to_string()]
+  }
+}
+
+// The constraint that requires a field to be set with any of its names
+fn presence(names: &[String]) -> Json {
+  match names {
+    [name] => json!({ "required": [name] }),
+    _ => {
+      json!({ "anyOf": names.iter().map(|name| json!({ "required": [name] })).collect::<Vec<Json>>() })
+    }
+  }
+}
+
+fn oneof_constraint(names: &[Vec<String>], is_required: bool) -> Json {
+  let mut options: Vec<Json> = names.iter().map(|names| presence(names)).collect();
+
+  if !is_required {
+    options.push(json!({ "not": { "anyOf": options.clone() } }));
+  }
+
+  json!({ "oneOf": options })
+}
+
+fn push_all_of(schema: &mut Map<String, Json>, items: impl Iterator<Item = Json>) {
+  if let Json::Array(all_of) = schema
+    .entry("allOf")
+    .or_insert_with(|| Json::Array(Vec::new()))
+  {
+    all_of.extend(items);
+  }
+}
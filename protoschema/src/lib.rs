@@ -15,23 +15,64 @@ pub use crate::{
   packages::Package,
 };
 
+/// The generation of the standard methods of a resource, following the Google AIPs
+pub mod aip;
+/// The typed options from the `google.api` package, such as `(google.api.field_behavior)` and `(google.api.resource)`.
+/// The options are always available, since the [`aip`] scaffolding relies on them, while the methods that add them to the field and message builders require the `api_types` feature
+pub mod api;
+/// The rendering of a package from a build script, followed by the generation of the code for its files
+pub mod build;
 /// A collection of common protobuf items, such as the [`ProtoOption`]s for 'deprecated' or 'allow_alias'
 pub mod common;
+/// The derivation of new messages from the fields of existing ones
+pub mod derivation;
+/// The comparison of two versions of a schema, used to detect breaking changes
+pub mod diff;
 pub mod enums;
 pub mod errors;
+/// The local evaluation of the validation rules defined in a schema, used to test them without a protovalidate runtime
+#[cfg(feature = "serde")]
+pub mod evaluation;
+/// The generation of example instances of a message, which satisfy or violate its validation rules
+#[cfg(feature = "serde")]
+pub mod examples;
 pub mod extensions;
 pub mod field_type;
 pub mod fields;
 pub mod files;
+/// Generic message templates, which are instantiated with different types
+pub mod generics;
+/// The `(google.api.http)` option, which maps the handlers of a service to REST endpoints
+pub mod http;
+/// The export of the messages and enums of a schema as JSON Schema documents, following the proto3 JSON mapping
+#[cfg(feature = "serde")]
+pub mod jsonschema;
+/// The loading of a package from a declarative YAML, TOML or JSON document
+#[cfg(feature = "serde")]
+pub mod loader;
 pub mod messages;
+/// Named blocks of reusable fields, which are placed in messages with relative tags
+pub mod mixins;
 pub mod oneofs;
+/// The export of the services of a schema, and of their HTTP bindings, as an OpenAPI document
+#[cfg(feature = "serde")]
+pub mod openapi;
 pub mod packages;
+/// Passes that inspect and modify the items of a package before its templates are built, used to apply organization-wide policies
+pub mod passes;
 pub mod rendering;
+/// A read-only view over the items of a package, used to write custom generators and checks
+pub mod schema;
 pub mod services;
 pub mod validators;
 #[macro_use]
 pub mod options;
 
+mod cel;
+mod checks;
+#[cfg(feature = "serde")]
+mod json;
+
 #[macro_use]
 pub mod macros;
 
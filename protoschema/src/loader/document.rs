@@ -0,0 +1,534 @@
+use std::{
+  cell::{Cell, RefCell},
+  fmt,
+  marker::PhantomData,
+  ops::Deref,
+  str::FromStr,
+};
+
+use serde::{
+  de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+  Deserialize, Deserializer,
+};
+
+use crate::{
+  loader::rules::RulesDef, validators::cel::CelRule, FieldType, MapKey, OptionValue, ProtoOption,
+};
+
+thread_local! {
+  // The number of names read so far while parsing a document, which gives each name a stable id
+  static NAMES_READ: Cell<usize> = const { Cell::new(0) };
+  // The name that should be rejected while parsing, so that the parser reports the location of an error found while building the package
+  static REJECTED_NAME: RefCell<Option<Failure>> = const { RefCell::new(None) };
+}
+
+/// An error found while building a package from a document, which is reported at the position of the name with the given id
+#[derive(Debug, Clone)]
+pub(crate) struct Failure {
+  pub(crate) name: usize,
+  pub(crate) message: String,
+}
+
+impl Failure {
+  pub(crate) fn new<T: fmt::Display>(name: &Name, message: T) -> Self {
+    Failure {
+      name: name.id,
+      message: message.to_string(),
+    }
+  }
+}
+
+/// Parses a document, and rejects the name of the given failure (if there is one) so that the error is reported at its position
+pub(crate) fn parse_document<F, E>(parse: F, failure: Option<&Failure>) -> Result<PackageDef, E>
+where
+  F: FnOnce() -> Result<PackageDef, E>,
+{
+  NAMES_READ.set(0);
+  REJECTED_NAME.set(failure.cloned());
+
+  let result = parse();
+
+  REJECTED_NAME.set(None);
+  result
+}
+
+/// The name of an item of the document
+#[derive(Debug, Clone)]
+pub(crate) struct Name {
+  value: Box<str>,
+  id: usize,
+}
+
+impl Name {
+  fn read<E: de::Error>(value: &str) -> Result<Self, E> {
+    let id = NAMES_READ.get();
+    NAMES_READ.set(id + 1);
+
+    let rejection = REJECTED_NAME.with_borrow(|rejected| match rejected {
+      Some(failure) if failure.name == id => Some(failure.message.clone()),
+      _ => None,
+    });
+
+    match rejection {
+      Some(message) => Err(E::custom(message)),
+      None => Ok(Name {
+        value: value.into(),
+        id,
+      }),
+    }
+  }
+}
+
+impl Deref for Name {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    &self.value
+  }
+}
+
+impl fmt::Display for Name {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.value)
+  }
+}
+
+impl<'de> Deserialize<'de> for Name {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct NameVisitor;
+
+    impl Visitor<'_> for NameVisitor {
+      type Value = Name;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a name")
+      }
+
+      fn visit_str<E: de::Error>(self, value: &str) -> Result<Name, E> {
+        Name::read(value)
+      }
+    }
+
+    deserializer.deserialize_str(NameVisitor)
+  }
+}
+
+/// The root of a document
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PackageDef {
+  pub(crate) package: Name,
+  #[serde(default)]
+  pub(crate) blocks: Vec<BlockDef>,
+  #[serde(default)]
+  pub(crate) files: Vec<FileDef>,
+}
+
+/// A named block of fields, which becomes a [`Mixin`](crate::mixins::Mixin)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BlockDef {
+  pub(crate) name: Name,
+  pub(crate) fields: Vec<FieldDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FileDef {
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) imports: Vec<String>,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  #[serde(default)]
+  pub(crate) messages: Vec<MessageDef>,
+  #[serde(default)]
+  pub(crate) enums: Vec<EnumDef>,
+  #[serde(default)]
+  pub(crate) services: Vec<ServiceDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MessageDef {
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  #[serde(default)]
+  pub(crate) fields: Vec<FieldDef>,
+  #[serde(default)]
+  pub(crate) include: Vec<IncludeDef>,
+  pub(crate) mixin_band: Option<u32>,
+  #[serde(default)]
+  pub(crate) oneofs: Vec<OneofDef>,
+  #[serde(default)]
+  pub(crate) messages: Vec<MessageDef>,
+  #[serde(default)]
+  pub(crate) enums: Vec<EnumDef>,
+  #[serde(default)]
+  pub(crate) reserved: Vec<ReservedDef<u32>>,
+  #[serde(default)]
+  pub(crate) reserved_names: Vec<String>,
+  pub(crate) rules: Option<MessageRulesDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FieldDef {
+  pub(crate) tag: u32,
+  pub(crate) name: Name,
+  #[serde(rename = "type")]
+  pub(crate) type_: TypeDef,
+  #[serde(default)]
+  pub(crate) repeated: bool,
+  #[serde(default)]
+  pub(crate) optional: bool,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  pub(crate) rules: Option<RulesDef>,
+}
+
+/// The inclusion of a block in a message, written as the name of the block (to place it automatically) or as `{ block, at }`
+#[derive(Debug)]
+pub(crate) struct IncludeDef {
+  pub(crate) block: Name,
+  pub(crate) at: Option<u32>,
+}
+
+impl<'de> Deserialize<'de> for IncludeDef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct PlacedBlock {
+      block: Name,
+      at: Option<u32>,
+    }
+
+    struct IncludeVisitor;
+
+    impl<'de> Visitor<'de> for IncludeVisitor {
+      type Value = IncludeDef;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the name of a block, or a map with the `block` and `at` keys")
+      }
+
+      fn visit_str<E: de::Error>(self, value: &str) -> Result<IncludeDef, E> {
+        Ok(IncludeDef {
+          block: Name::read(value)?,
+          at: None,
+        })
+      }
+
+      fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<IncludeDef, A::Error> {
+        let placed = PlacedBlock::deserialize(MapAccessDeserializer::new(map))?;
+
+        Ok(IncludeDef {
+          block: placed.block,
+          at: placed.at,
+        })
+      }
+    }
+
+    deserializer.deserialize_any(IncludeVisitor)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct OneofDef {
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) required: bool,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  pub(crate) fields: Vec<FieldDef>,
+}
+
+/// The protovalidate rules of a message
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MessageRulesDef {
+  #[serde(default)]
+  pub(crate) cel: Vec<CelRule>,
+  #[serde(default)]
+  pub(crate) oneof: Vec<OneofRuleDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct OneofRuleDef {
+  pub(crate) fields: Vec<String>,
+  #[serde(default)]
+  pub(crate) required: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EnumDef {
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  pub(crate) variants: Vec<VariantDef>,
+  #[serde(default)]
+  pub(crate) reserved: Vec<ReservedDef<i32>>,
+  #[serde(default)]
+  pub(crate) reserved_names: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct VariantDef {
+  pub(crate) number: i32,
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ServiceDef {
+  pub(crate) name: Name,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+  #[serde(default)]
+  pub(crate) handlers: Vec<HandlerDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct HandlerDef {
+  pub(crate) name: Name,
+  pub(crate) request: String,
+  pub(crate) response: String,
+  #[serde(default)]
+  pub(crate) options: OptionsDef,
+}
+
+/// A reserved number, or a range written as `"start..end"` (with an exclusive end, like in the [`message`](crate::message) macro)
+///
+/// The numbers are `u32` for the tags of messages and `i32` for the values of enums.
+#[derive(Debug, Clone)]
+pub(crate) enum ReservedDef<T> {
+  Number(T),
+  Range(T, T),
+}
+
+impl<'de, T> Deserialize<'de> for ReservedDef<T>
+where
+  T: TryFrom<i64> + TryFrom<u64> + FromStr + PartialOrd,
+{
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct ReservedVisitor<T>(PhantomData<T>);
+
+    impl<T> Visitor<'_> for ReservedVisitor<T>
+    where
+      T: TryFrom<i64> + TryFrom<u64> + FromStr + PartialOrd,
+    {
+      type Value = ReservedDef<T>;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, or a range such as \"5..10\"")
+      }
+
+      fn visit_u64<E: de::Error>(self, value: u64) -> Result<ReservedDef<T>, E> {
+        T::try_from(value)
+          .map(ReservedDef::Number)
+          .map_err(|_| E::custom(format!("the number {value} is out of range")))
+      }
+
+      fn visit_i64<E: de::Error>(self, value: i64) -> Result<ReservedDef<T>, E> {
+        T::try_from(value)
+          .map(ReservedDef::Number)
+          .map_err(|_| E::custom(format!("the number {value} is out of range")))
+      }
+
+      fn visit_str<E: de::Error>(self, value: &str) -> Result<ReservedDef<T>, E> {
+        let range = value
+          .split_once("..")
+          .and_then(|(start, end)| Some((start.trim().parse().ok()?, end.trim().parse().ok()?)));
+
+        match range {
+          Some((start, end)) if start < end => Ok(ReservedDef::Range(start, end)),
+          _ => Err(E::custom(format!("invalid range '{value}'"))),
+        }
+      }
+    }
+
+    deserializer.deserialize_any(ReservedVisitor(PhantomData))
+  }
+}
+
+/// A map of options, where the keys are the names of the options and the values follow the conventions of [`OptionValue::from_json`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OptionsDef(pub(crate) Vec<ProtoOption>);
+
+impl<'de> Deserialize<'de> for OptionsDef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct OptionsVisitor;
+
+    impl<'de> Visitor<'de> for OptionsVisitor {
+      type Value = OptionsDef;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of options")
+      }
+
+      // The options are read one by one rather than through a `serde_json::Map`, so that they keep the order of the document
+      fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OptionsDef, A::Error> {
+        let mut options = Vec::new();
+
+        while let Some(name) = map.next_key::<String>()? {
+          let value = map.next_value::<OptionValue>().map_err(|e| {
+            de::Error::custom(format!("invalid value for the option '{name}': {e}"))
+          })?;
+
+          options.push(ProtoOption {
+            name: name.into(),
+            value: value.into(),
+          });
+        }
+
+        Ok(OptionsDef(options))
+      }
+    }
+
+    deserializer.deserialize_map(OptionsVisitor)
+  }
+}
+
+/// The type of a field, as it would be written in a proto file.
+/// The names of the messages and enums of the document are resolved when the package is built.
+#[derive(Debug, Clone)]
+pub(crate) enum TypeDef {
+  Builtin(FieldType),
+  Named(Box<str>),
+  Map(MapKey, Box<TypeDef>),
+}
+
+// The types that are identified by their name alone
+const BUILTIN_TYPES: &[FieldType] = &[
+  FieldType::Double,
+  FieldType::Float,
+  FieldType::Int64,
+  FieldType::Uint64,
+  FieldType::Int32,
+  FieldType::Fixed64,
+  FieldType::Fixed32,
+  FieldType::Bool,
+  FieldType::String,
+  FieldType::Bytes,
+  FieldType::Uint32,
+  FieldType::Sfixed32,
+  FieldType::Sfixed64,
+  FieldType::Sint32,
+  FieldType::Sint64,
+  FieldType::Duration,
+  FieldType::Timestamp,
+  FieldType::Any,
+  FieldType::FieldMask,
+  FieldType::Empty,
+  FieldType::Struct,
+  FieldType::Money,
+  FieldType::Interval,
+  FieldType::Color,
+  FieldType::Date,
+  FieldType::DateTime,
+  FieldType::TimeZone,
+  FieldType::DayOfWeek,
+  FieldType::Decimal,
+  FieldType::Expr,
+  FieldType::Fraction,
+  FieldType::LatLng,
+  FieldType::LocalizedText,
+  FieldType::Month,
+  FieldType::PhoneNumber,
+  FieldType::PostalAddress,
+  FieldType::Quaternion,
+  FieldType::TimeOfDay,
+  FieldType::Status,
+  FieldType::Code,
+  FieldType::HttpRequest,
+  FieldType::HttpResponse,
+  FieldType::HttpHeader,
+  FieldType::ErrorInfo,
+  FieldType::RetryInfo,
+  FieldType::DebugInfo,
+  FieldType::QuotaFailure,
+  FieldType::QuotaFailureViolation,
+  FieldType::PreconditionFailure,
+  FieldType::PreconditionFailureViolation,
+  FieldType::BadRequest,
+  FieldType::FieldViolation,
+  FieldType::RequestInfo,
+  FieldType::ResourceInfo,
+  FieldType::Help,
+  FieldType::Link,
+  FieldType::LocalizedMessage,
+];
+
+const MAP_KEYS: [MapKey; 12] = [
+  MapKey::Int32,
+  MapKey::Int64,
+  MapKey::Uint32,
+  MapKey::Uint64,
+  MapKey::Sint32,
+  MapKey::Sint64,
+  MapKey::Fixed32,
+  MapKey::Fixed64,
+  MapKey::Sfixed32,
+  MapKey::Sfixed64,
+  MapKey::Bool,
+  MapKey::String,
+];
+
+impl TypeDef {
+  fn parse(input: &str) -> Result<Self, String> {
+    let input = input.trim();
+
+    if let Some(params) = input
+      .strip_prefix("map<")
+      .and_then(|rest| rest.strip_suffix('>'))
+    {
+      let (key, value) = params
+        .split_once(',')
+        .ok_or_else(|| format!("invalid map type '{input}'"))?;
+
+      let key = MAP_KEYS
+        .into_iter()
+        .find(|k| k.name() == key.trim())
+        .ok_or_else(|| format!("invalid map key type '{}'", key.trim()))?;
+
+      let value = TypeDef::parse(value)?;
+
+      if matches!(value, TypeDef::Map(..)) {
+        return Err("the values of a map cannot be maps".into());
+      }
+
+      return Ok(TypeDef::Map(key, Box::new(value)));
+    }
+
+    if let Some(builtin) = BUILTIN_TYPES.iter().find(|t| t.name() == input) {
+      return Ok(TypeDef::Builtin(builtin.clone()));
+    }
+
+    let is_valid_name = !input.is_empty()
+      && input.trim_start_matches('.').split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+          && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+      });
+
+    if is_valid_name {
+      Ok(TypeDef::Named(input.into()))
+    } else {
+      Err(format!("invalid type '{input}'"))
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for TypeDef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let input = String::deserialize(deserializer)?;
+    TypeDef::parse(&input).map_err(de::Error::custom)
+  }
+}
@@ -0,0 +1,662 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+  checks::Members,
+  common::VALIDATE_PROTO_FILE,
+  enums::{EnumBuilder, EnumVariant},
+  errors::LoadError,
+  fields::{Field, FieldBuilder, FieldKind, SetFieldType, SetName},
+  loader::document::{
+    parse_document, EnumDef, Failure, FieldDef, MessageDef, Name, PackageDef, ReservedDef,
+    ServiceDef, TypeDef,
+  },
+  messages::MessageBuilder,
+  mixins::{resolve_mixins, Mixin, MixinPlacement, DEFAULT_MIXIN_BAND},
+  oneofs::Oneof,
+  services::ServiceHandler,
+  FieldType, Package,
+};
+
+mod document;
+mod rules;
+
+type CompleteField = FieldBuilder<SetFieldType<SetName>>;
+
+/// Builds a [`Package`] from a YAML document.
+///
+/// The document describes the files of the package, with their messages, enums and services, and it is mapped onto the same builders used by the macros, so the result is identical to a package defined in Rust.
+/// Its structure is the following:
+/// - `package`: the name of the package
+/// - `blocks`: a list of named blocks of fields (with `name` and `fields`), whose tags are relative to the position where they are placed, like a [`Mixin`]
+/// - `files`: a list of files, each with a `name` and optionally `imports`, `options`, `messages`, `enums` and `services`
+///
+/// The items of a file are defined as follows:
+/// - Messages have a `name`, and optionally `options`, `fields`, `oneofs` (with `name`, `fields`, `options` and `required`), nested `messages` and `enums`, `reserved` numbers (or ranges like `"10..20"`), `reserved_names`, `rules` (with `cel` rules, each with an `id`, a `message` and an `expression`, and `oneof` rules, each with the `fields` and whether one of them is `required`) and `include`, which places blocks in the message either automatically (with the name of the block) or at a given tag (with `{ block, at }`)
+/// - Fields have a `tag`, a `name` and a `type`, written as in a proto file (such as `string`, `google.protobuf.Timestamp`, `map<string, int32>` or the name of a message or enum of the document), and optionally `repeated`, `optional`, `options` and `rules`
+/// - Enums have a `name` and a list of `variants` (with `number`, `name` and optionally `options`), and optionally `options`, `reserved` and `reserved_names`. Like in the [`proto_enum`](crate::proto_enum) macro, the variant names are prefixed with the name of the enum automatically
+/// - Services have a `name`, and optionally `options` and `handlers`, each with a `name`, a `request` and a `response` message, and optionally `options`
+///
+/// Options are maps from the name of the option to its value, which follows the conventions of [`OptionValue::from_json`](crate::OptionValue::from_json).
+/// The validation rules of a field follow the structure of protovalidate's `FieldRules`: `required`, `ignore` (`if_zero_value` or `always`) and `cel` apply to the field as a whole, whereas the other rules are defined under the key for the type of the field (such as `string`, `int32`, `enum`, `repeated` or `map`). For string fields, the well known formats are set with `well_known` (such as `well_known: email`).
+///
+/// The names of messages and enums are resolved like in protobuf, starting from the scope where they are used and moving outwards, and they can also be fully qualified.
+///
+/// # Errors
+/// If the document is not valid, or if it describes an invalid schema (such as two fields with the same tag, or rules that do not match the type of a field).
+/// The error contains the line and column of the issue, which for the issues found after parsing is the position of the name of the item where it was found.
+///
+/// # Examples
+/// ```rust
+/// let package = protoschema::loader::from_yaml(
+///   r#"
+/// package: myapp.v1
+/// blocks:
+///   - name: timestamps
+///     fields:
+///       - { tag: 0, name: created_at, type: google.protobuf.Timestamp }
+///       - { tag: 1, name: updated_at, type: google.protobuf.Timestamp }
+/// files:
+///   - name: user
+///     messages:
+///       - name: User
+///         include: [timestamps]
+///         fields:
+///           - { tag: 1, name: id, type: uint64 }
+///           - tag: 2
+///             name: email
+///             type: string
+///             rules: { required: true, string: { well_known: email } }
+///           - { tag: 3, name: status, type: Status }
+///     enums:
+///       - name: Status
+///         variants:
+///           - { number: 0, name: UNSPECIFIED }
+///           - { number: 1, name: ACTIVE }
+/// "#,
+/// )
+/// .unwrap();
+///
+/// let schema = package.schema();
+/// let user_msg = schema.find_message("User").unwrap();
+///
+/// assert_eq!(user_msg.fields.len(), 5);
+/// assert_eq!(user_msg.fields[3].name.as_ref(), "created_at");
+/// assert_eq!(user_msg.fields[3].tag, 1000);
+/// ```
+#[cfg(feature = "yaml")]
+pub fn from_yaml(source: &str) -> Result<Package, LoadError> {
+  load(|| serde_yaml_ng::from_str(source).map_err(yaml_error))
+}
+
+/// Builds a [`Package`] from a TOML document, with the same structure described in [`from_yaml`].
+///
+/// # Errors
+/// If the document is not valid, or if it describes an invalid schema.
+#[cfg(feature = "toml")]
+pub fn from_toml(source: &str) -> Result<Package, LoadError> {
+  load(|| toml::from_str(source).map_err(|e| toml_error(source, &e)))
+}
+
+/// Builds a [`Package`] from a JSON document, with the same structure described in [`from_yaml`].
+///
+/// # Errors
+/// If the document is not valid, or if it describes an invalid schema.
+pub fn from_json(source: &str) -> Result<Package, LoadError> {
+  load(|| serde_json::from_str(source).map_err(|e| json_error(&e)))
+}
+
+fn load<F>(parse: F) -> Result<Package, LoadError>
+where
+  F: Fn() -> Result<PackageDef, LoadError>,
+{
+  let document = parse_document(&parse, None)?;
+
+  document.build().map_err(|failure| {
+    // The document is parsed again, rejecting the name of the item where the error was found, so that the parser reports its position
+    match parse_document(&parse, Some(&failure)) {
+      Err(error) => error,
+      Ok(_) => LoadError::Unlocated(failure.message.into()),
+    }
+  })
+}
+
+// Removes the position appended to the message by the parsers
+fn strip_position(message: String, line: usize, column: usize) -> Box<str> {
+  let suffix = format!(" at line {line} column {column}");
+
+  match message.strip_suffix(&suffix) {
+    Some(stripped) => stripped.into(),
+    None => message.into(),
+  }
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_error(error: serde_yaml_ng::Error) -> LoadError {
+  match error.location() {
+    Some(location) => LoadError::Invalid {
+      line: location.line(),
+      column: location.column(),
+      message: strip_position(error.to_string(), location.line(), location.column()),
+    },
+    None => LoadError::Unlocated(error.to_string().into()),
+  }
+}
+
+#[cfg(feature = "toml")]
+fn toml_error(source: &str, error: &toml::de::Error) -> LoadError {
+  match error.span() {
+    Some(span) => {
+      let before = &source[..span.start.min(source.len())];
+      let line = before.matches('\n').count() + 1;
+      let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+
+      LoadError::Invalid {
+        line,
+        column,
+        message: error.message().into(),
+      }
+    }
+    None => LoadError::Unlocated(error.message().into()),
+  }
+}
+
+fn json_error(error: &serde_json::Error) -> LoadError {
+  if error.line() == 0 {
+    return LoadError::Unlocated(error.to_string().into());
+  }
+
+  LoadError::Invalid {
+    line: error.line(),
+    column: error.column(),
+    message: strip_position(error.to_string(), error.line(), error.column()),
+  }
+}
+
+// The items declared in a document, indexed by their name relative to the package
+#[derive(Default)]
+struct Declarations<'d> {
+  package: Box<str>,
+  types: HashMap<String, FieldType>,
+  messages: Vec<(String, MessageBuilder, &'d MessageDef)>,
+  message_ids: HashMap<String, usize>,
+  enums: Vec<(EnumBuilder, &'d EnumDef)>,
+  mixins: HashMap<&'d str, Mixin>,
+}
+
+// Joins a name to the scope where it is defined
+fn scoped(scope: Option<&str>, name: &str) -> String {
+  match scope {
+    Some(scope) => format!("{scope}.{name}"),
+    None => name.to_string(),
+  }
+}
+
+// Splits a list of reserved numbers and ranges
+fn reservations<T: Copy>(reserved: &[ReservedDef<T>]) -> (Vec<T>, Vec<Range<T>>) {
+  let mut numbers = Vec::new();
+  let mut ranges = Vec::new();
+
+  for item in reserved {
+    match item {
+      ReservedDef::Number(number) => numbers.push(*number),
+      ReservedDef::Range(start, end) => ranges.push(*start..*end),
+    }
+  }
+
+  (numbers, ranges)
+}
+
+// Adds the new members of a message or enum one at a time, and reports the first one that conflicts with the existing members or with the ones before it
+fn check_members<F>(members: F, new_members: &[(i64, &str, &Name)]) -> Result<(), Failure>
+where
+  F: Fn() -> Members,
+{
+  for (i, (_, _, name)) in new_members.iter().enumerate() {
+    let result = members().check_members(
+      new_members[..=i]
+        .iter()
+        .map(|(number, member, _)| (*number, *member)),
+    );
+
+    if let Err(error) = result {
+      return Err(Failure::new(name, error));
+    }
+  }
+
+  Ok(())
+}
+
+impl PackageDef {
+  fn build(&self) -> Result<Package, Failure> {
+    let package = Package::new(&*self.package);
+    let mut declarations = Declarations {
+      package: (*self.package).into(),
+      ..Default::default()
+    };
+    let mut files = Vec::new();
+
+    // All of the messages and enums are created first, so that the fields can refer to them regardless of the order
+    for file_def in &self.files {
+      if self
+        .files
+        .iter()
+        .take_while(|other| !std::ptr::eq(*other, file_def))
+        .any(|other| *other.name == *file_def.name)
+      {
+        return Err(Failure::new(
+          &file_def.name,
+          format!("the file '{}' is defined more than once", file_def.name),
+        ));
+      }
+
+      let file = package.new_file(&*file_def.name);
+
+      file.add_imports(&file_def.imports);
+      file.add_options(file_def.options.0.clone());
+
+      for message_def in &file_def.messages {
+        declarations.declare_message(file.new_message(&*message_def.name), message_def, None)?;
+      }
+
+      for enum_def in &file_def.enums {
+        declarations.declare_enum(file.new_enum(&*enum_def.name), enum_def, None)?;
+      }
+
+      files.push((file, file_def));
+    }
+
+    for block in &self.blocks {
+      if declarations.mixins.contains_key(&*block.name) {
+        return Err(Failure::new(
+          &block.name,
+          format!("the block '{}' is defined more than once", block.name),
+        ));
+      }
+
+      let mut mixin = Mixin::new(&*block.name);
+
+      for field_def in &block.fields {
+        mixin = mixin.field(field_def.tag, declarations.field(field_def, None)?);
+      }
+
+      declarations.mixins.insert(&block.name, mixin);
+    }
+
+    for (builder, enum_def) in &declarations.enums {
+      define_enum(builder, enum_def)?;
+    }
+
+    for (name, builder, message_def) in &declarations.messages {
+      declarations.define_message(name, builder, message_def)?;
+    }
+
+    for (file, file_def) in &files {
+      for service_def in &file_def.services {
+        declarations.define_service(file.new_service(&*service_def.name), service_def)?;
+      }
+    }
+
+    Ok(package)
+  }
+}
+
+impl<'d> Declarations<'d> {
+  fn declare(
+    &mut self,
+    name: &Name,
+    full_name: &str,
+    field_type: FieldType,
+  ) -> Result<(), Failure> {
+    if self.types.contains_key(full_name) {
+      return Err(Failure::new(
+        name,
+        format!("the name '{full_name}' is already used by another item of the package"),
+      ));
+    }
+
+    self.types.insert(full_name.to_string(), field_type);
+    Ok(())
+  }
+
+  fn declare_message(
+    &mut self,
+    builder: MessageBuilder,
+    def: &'d MessageDef,
+    scope: Option<&str>,
+  ) -> Result<(), Failure> {
+    let full_name = scoped(scope, &def.name);
+
+    self.declare(&def.name, &full_name, builder.get_type())?;
+
+    for nested in &def.messages {
+      self.declare_message(builder.new_message(&*nested.name), nested, Some(&full_name))?;
+    }
+
+    for nested in &def.enums {
+      self.declare_enum(builder.new_enum(&*nested.name), nested, Some(&full_name))?;
+    }
+
+    self
+      .message_ids
+      .insert(full_name.clone(), self.messages.len());
+    self.messages.push((full_name, builder, def));
+    Ok(())
+  }
+
+  fn declare_enum(
+    &mut self,
+    builder: EnumBuilder,
+    def: &'d EnumDef,
+    scope: Option<&str>,
+  ) -> Result<(), Failure> {
+    self.declare(&def.name, &scoped(scope, &def.name), builder.get_type())?;
+    self.enums.push((builder, def));
+    Ok(())
+  }
+
+  // Finds the name of an item, starting from the given scope and moving outwards, like protoc does
+  fn lookup<'a, T>(
+    &self,
+    name: &str,
+    scope: Option<&str>,
+    items: &'a HashMap<String, T>,
+  ) -> Option<&'a T> {
+    let package_prefix = format!("{}.", self.package);
+
+    if let Some(absolute) = name.strip_prefix('.') {
+      return absolute
+        .strip_prefix(&package_prefix)
+        .and_then(|relative| items.get(relative));
+    }
+
+    let mut scope = scope;
+
+    while let Some(current) = scope {
+      if let Some(item) = items.get(&format!("{current}.{name}")) {
+        return Some(item);
+      }
+
+      scope = current.rsplit_once('.').map(|(parent, _)| parent);
+    }
+
+    items.get(name).or_else(|| {
+      name
+        .strip_prefix(&package_prefix)
+        .and_then(|relative| items.get(relative))
+    })
+  }
+
+  fn resolve(&self, type_def: &TypeDef, scope: Option<&str>) -> Result<FieldType, String> {
+    match type_def {
+      TypeDef::Builtin(field_type) => Ok(field_type.clone()),
+      TypeDef::Map(key, value) => Ok(FieldType::Map(
+        key.clone(),
+        Box::new(self.resolve(value, scope)?),
+      )),
+      TypeDef::Named(name) => self
+        .lookup(name, scope, &self.types)
+        .cloned()
+        .ok_or_else(|| format!("the type '{name}' is not defined in the package")),
+    }
+  }
+
+  fn field(&self, def: &FieldDef, scope: Option<&str>) -> Result<CompleteField, Failure> {
+    let fail = |message: String| Failure::new(&def.name, message);
+
+    let field_type = self.resolve(&def.type_, scope).map_err(fail)?;
+
+    let kind = match (def.repeated, def.optional) {
+      (true, true) => return Err(fail("a field cannot be both repeated and optional".into())),
+      (true, false) => FieldKind::Repeated,
+      (false, true) => FieldKind::Optional,
+      (false, false) => FieldKind::Normal,
+    };
+
+    if matches!(field_type, FieldType::Map(..)) && kind != FieldKind::Normal {
+      return Err(fail("a map field cannot be repeated or optional".into()));
+    }
+
+    let mut field = Field::builder()
+      .name((*def.name).into())
+      .field_type(field_type.clone())
+      .add_options(def.options.0.clone());
+
+    match kind {
+      FieldKind::Repeated => field = field.repeated(),
+      FieldKind::Optional => field = field.optional(),
+      FieldKind::Normal => {}
+    };
+
+    if let Some(import) = field_type.import_path() {
+      field = field.add_import(import);
+    }
+
+    if let Some(rules) = &def.rules {
+      let option = rules.to_option(&field_type, kind).map_err(fail)?;
+
+      field = field
+        .add_option(option)
+        .add_import(VALIDATE_PROTO_FILE.clone());
+    }
+
+    Ok(field)
+  }
+
+  fn define_message(
+    &self,
+    full_name: &str,
+    builder: &MessageBuilder,
+    def: &MessageDef,
+  ) -> Result<(), Failure> {
+    let fail = |message: String| Failure::new(&def.name, message);
+
+    let (numbers, ranges) = reservations(&def.reserved);
+    let mut message = builder
+      .clone()
+      .add_options(def.options.0.clone())
+      .add_reserved(numbers)
+      .add_reserved_ranges(ranges)
+      .add_reserved_names(&def.reserved_names);
+
+    let fields = def
+      .fields
+      .iter()
+      .map(|field_def| Ok((field_def.tag, self.field(field_def, Some(full_name))?)))
+      .collect::<Result<Vec<(u32, CompleteField)>, Failure>>()?;
+
+    let mixins = def
+      .include
+      .iter()
+      .map(|include| {
+        let mixin = self.mixins.get(&*include.block).ok_or_else(|| {
+          Failure::new(
+            &include.block,
+            format!("the block '{}' is not defined", include.block),
+          )
+        })?;
+        let placement = include.at.map_or(MixinPlacement::Auto, MixinPlacement::At);
+
+        Ok((mixin.clone(), placement))
+      })
+      .collect::<Result<Vec<(Mixin, MixinPlacement)>, Failure>>()?;
+
+    let oneofs = def
+      .oneofs
+      .iter()
+      .map(|oneof_def| {
+        let fields = oneof_def
+          .fields
+          .iter()
+          .map(|field_def| {
+            if field_def.repeated || field_def.optional {
+              return Err(Failure::new(
+                &field_def.name,
+                "the fields of a oneof cannot be repeated or optional",
+              ));
+            }
+
+            Ok((field_def.tag, self.field(field_def, Some(full_name))?))
+          })
+          .collect::<Result<Vec<(u32, CompleteField)>, Failure>>()?;
+
+        Ok((oneof_def, fields))
+      })
+      .collect::<Result<Vec<_>, Failure>>()?;
+
+    // The conflicts are checked in advance, since the builders panic when they find them.
+    // The fields of the message and of its oneofs are checked first, so that the errors point to the field that causes them
+    let mut members: Vec<(i64, &str, &Name)> = def
+      .fields
+      .iter()
+      .chain(oneofs.iter().flat_map(|(oneof_def, _)| &oneof_def.fields))
+      .map(|field_def| (field_def.tag as i64, &*field_def.name, &field_def.name))
+      .collect();
+
+    let message_members = || builder.arena.borrow().messages[builder.id].members();
+
+    check_members(message_members, &members)?;
+
+    // The oneofs are added before the fields, so the automatic placements avoid their tags as well
+    let existing = message_members();
+    let oneof_tags: Vec<u32> = oneofs
+      .iter()
+      .flat_map(|(oneof_def, _)| oneof_def.fields.iter().map(|field_def| field_def.tag))
+      .collect();
+
+    let resolved = resolve_mixins(
+      &builder.get_full_name_with_package(),
+      fields
+        .iter()
+        .map(|(tag, field)| (*tag, field.clone().build()))
+        .collect(),
+      &mixins,
+      def.mixin_band.unwrap_or(DEFAULT_MIXIN_BAND),
+      |tag| existing.is_taken(tag as i64) || oneof_tags.contains(&tag),
+    )
+    .map_err(|e| fail(e.to_string()))?;
+
+    // The fields that come from the blocks are reported at the name of the message
+    members.extend(
+      resolved.fields[def.fields.len()..]
+        .iter()
+        .map(|(tag, field)| (*tag as i64, field.name.as_ref(), &def.name)),
+    );
+
+    check_members(message_members, &members)?;
+
+    for (mixin, placement) in &mixins {
+      message = message.mixin(mixin, *placement);
+    }
+
+    if let Some(band) = def.mixin_band {
+      message = message.mixin_band(band);
+    }
+
+    let message = message.add_oneofs(oneofs.into_iter().map(|(oneof_def, fields)| {
+      let oneof = Oneof::builder()
+        .name((*oneof_def.name).into())
+        .add_options(oneof_def.options.0.clone());
+
+      if oneof_def.required {
+        oneof.required().fields(fields).build()
+      } else {
+        oneof.fields(fields).build()
+      }
+    }));
+
+    let mut message = message.fields(fields);
+
+    if let Some(rules) = &def.rules {
+      if !rules.cel.is_empty() {
+        message = message.cel_rules(rules.cel.clone());
+      }
+
+      for oneof_rule in &rules.oneof {
+        message = message.message_oneof_rule(&oneof_rule.fields, oneof_rule.required);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn define_service(
+    &self,
+    builder: crate::services::ServiceBuilder,
+    def: &ServiceDef,
+  ) -> Result<(), Failure> {
+    let mut handlers = Vec::new();
+
+    for (i, handler_def) in def.handlers.iter().enumerate() {
+      let fail = |message: String| Failure::new(&handler_def.name, message);
+
+      if def.handlers[..i]
+        .iter()
+        .any(|other| *other.name == *handler_def.name)
+      {
+        return Err(fail(format!(
+          "the handler '{}' is defined more than once",
+          handler_def.name
+        )));
+      }
+
+      let find_message = |name: &str| {
+        self
+          .lookup(name, None, &self.message_ids)
+          .map(|id| &self.messages[*id].1)
+          .ok_or_else(|| format!("the message '{name}' is not defined in the package"))
+      };
+
+      let request = find_message(&handler_def.request).map_err(fail)?;
+      let response = find_message(&handler_def.response).map_err(fail)?;
+
+      handlers.push(
+        ServiceHandler::new((*handler_def.name).into())
+          .request(request)
+          .response(response)
+          .options(handler_def.options.0.clone())
+          .build(),
+      );
+    }
+
+    builder
+      .options(def.options.0.clone())
+      .add_handlers(handlers);
+
+    Ok(())
+  }
+}
+
+fn define_enum(builder: &EnumBuilder, def: &EnumDef) -> Result<(), Failure> {
+  let (numbers, ranges) = reservations(&def.reserved);
+
+  // The options are set first, since they determine whether the variants can share the same number
+  let enum_builder = builder
+    .clone()
+    .options(def.options.0.clone())
+    .add_reserved(numbers)
+    .add_reserved_ranges(ranges)
+    .add_reserved_names(&def.reserved_names);
+
+  let members: Vec<(i64, &str, &Name)> = def
+    .variants
+    .iter()
+    .map(|variant| (variant.number as i64, &*variant.name, &variant.name))
+    .collect();
+
+  check_members(
+    || builder.arena.borrow().enums[builder.id].members(),
+    &members,
+  )?;
+
+  enum_builder.add_variants(def.variants.iter().map(|variant| {
+    (
+      variant.number,
+      EnumVariant::builder()
+        .name(&*variant.name)
+        .options(variant.options.0.clone())
+        .build(),
+    )
+  }));
+
+  Ok(())
+}
@@ -0,0 +1,693 @@
+use std::{fmt::Debug, hash::Hash};
+
+use proto_types::protovalidate::Ignore;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{
+  field_type::{Duration, Timestamp},
+  fields::FieldKind,
+  json::{parse_duration, parse_timestamp},
+  validators::{
+    any::AnyValidator,
+    bool::BoolValidator,
+    bytes::{self, BytesValidator},
+    cel::CelRule,
+    check_comparables,
+    duration::DurationValidator,
+    enums::EnumValidator,
+    map::MapValidator,
+    message::MessageValidator,
+    numeric::*,
+    repeated::RepeatedValidator,
+    string::{self, StringValidator},
+    timestamp::{check_now_rules, TimestampValidator},
+    validate_lists,
+  },
+  FieldType, MapKey, ProtoOption,
+};
+
+/// The protovalidate rules of a field, which are converted into the `(buf.validate.field)` option with the validators of the [`validators`](crate::validators) module.
+///
+/// The `required`, `ignore` and `cel` rules apply to the field as a whole, whereas the rules that are specific to a type are defined under the key of that type (such as `string` or `repeated`), like in protovalidate's `FieldRules`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RulesDef {
+  required: Option<bool>,
+  ignore: Option<IgnoreDef>,
+  cel: Option<Vec<CelRule>>,
+  string: Option<StringRulesDef>,
+  bytes: Option<BytesRulesDef>,
+  bool: Option<BoolRulesDef>,
+  int32: Option<NumericRulesDef<i32>>,
+  int64: Option<NumericRulesDef<i64>>,
+  sint32: Option<NumericRulesDef<i32>>,
+  sint64: Option<NumericRulesDef<i64>>,
+  sfixed32: Option<NumericRulesDef<i32>>,
+  sfixed64: Option<NumericRulesDef<i64>>,
+  uint32: Option<NumericRulesDef<u32>>,
+  uint64: Option<NumericRulesDef<u64>>,
+  fixed32: Option<NumericRulesDef<u32>>,
+  fixed64: Option<NumericRulesDef<u64>>,
+  float: Option<NumericRulesDef<f32>>,
+  double: Option<NumericRulesDef<f64>>,
+  #[serde(rename = "enum")]
+  enum_: Option<EnumRulesDef>,
+  duration: Option<DurationRulesDef>,
+  timestamp: Option<TimestampRulesDef>,
+  any: Option<AnyRulesDef>,
+  repeated: Option<Box<RepeatedRulesDef>>,
+  map: Option<Box<MapRulesDef>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IgnoreDef {
+  IfZeroValue,
+  Always,
+}
+
+impl From<IgnoreDef> for Ignore {
+  fn from(value: IgnoreDef) -> Self {
+    match value {
+      IgnoreDef::IfZeroValue => Ignore::IfZeroValue,
+      IgnoreDef::Always => Ignore::Always,
+    }
+  }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StringRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<String>,
+  len: Option<u64>,
+  min_len: Option<u64>,
+  max_len: Option<u64>,
+  len_bytes: Option<u64>,
+  min_bytes: Option<u64>,
+  max_bytes: Option<u64>,
+  pattern: Option<String>,
+  prefix: Option<String>,
+  suffix: Option<String>,
+  contains: Option<String>,
+  not_contains: Option<String>,
+  #[serde(rename = "in")]
+  in_: Option<Vec<String>>,
+  not_in: Option<Vec<String>>,
+  well_known: Option<string::WellKnown>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BytesRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<String>,
+  len: Option<u64>,
+  min_len: Option<u64>,
+  max_len: Option<u64>,
+  pattern: Option<String>,
+  prefix: Option<String>,
+  suffix: Option<String>,
+  contains: Option<String>,
+  #[serde(rename = "in")]
+  in_: Option<Vec<String>>,
+  not_in: Option<Vec<String>>,
+  well_known: Option<bytes::WellKnown>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoolRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NumericRulesDef<T> {
+  #[serde(rename = "const")]
+  const_: Option<T>,
+  lt: Option<T>,
+  lte: Option<T>,
+  gt: Option<T>,
+  gte: Option<T>,
+  #[serde(rename = "in")]
+  in_: Option<Vec<T>>,
+  not_in: Option<Vec<T>>,
+  // Only used by float and double
+  finite: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnumRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<i32>,
+  defined_only: Option<bool>,
+  #[serde(rename = "in")]
+  in_: Option<Vec<i32>>,
+  not_in: Option<Vec<i32>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DurationRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<DurationDef>,
+  lt: Option<DurationDef>,
+  lte: Option<DurationDef>,
+  gt: Option<DurationDef>,
+  gte: Option<DurationDef>,
+  #[serde(rename = "in")]
+  in_: Option<Vec<DurationDef>>,
+  not_in: Option<Vec<DurationDef>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimestampRulesDef {
+  #[serde(rename = "const")]
+  const_: Option<TimestampDef>,
+  lt: Option<TimestampDef>,
+  lte: Option<TimestampDef>,
+  gt: Option<TimestampDef>,
+  gte: Option<TimestampDef>,
+  lt_now: Option<bool>,
+  gt_now: Option<bool>,
+  within: Option<DurationDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnyRulesDef {
+  #[serde(rename = "in")]
+  in_: Option<Vec<String>>,
+  not_in: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RepeatedRulesDef {
+  min_items: Option<u64>,
+  max_items: Option<u64>,
+  unique: Option<bool>,
+  items: Option<RulesDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MapRulesDef {
+  min_pairs: Option<u64>,
+  max_pairs: Option<u64>,
+  keys: Option<RulesDef>,
+  values: Option<RulesDef>,
+}
+
+/// A duration in the proto3 JSON format (i.e. "3.5s")
+#[derive(Debug, Clone, Copy)]
+struct DurationDef(Duration);
+
+impl<'de> Deserialize<'de> for DurationDef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let input = String::deserialize(deserializer)?;
+
+    parse_duration(&input)
+      .map(DurationDef)
+      .ok_or_else(|| de::Error::custom(format!("invalid duration '{input}'")))
+  }
+}
+
+/// A timestamp in the RFC 3339 format (i.e. "2025-01-01T10:00:00Z")
+#[derive(Debug, Clone, Copy)]
+struct TimestampDef(Timestamp);
+
+impl<'de> Deserialize<'de> for TimestampDef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let input = String::deserialize(deserializer)?;
+
+    parse_timestamp(&input)
+      .map(TimestampDef)
+      .ok_or_else(|| de::Error::custom(format!("invalid timestamp '{input}'")))
+  }
+}
+
+// The rules that apply to a field as a whole, regardless of its type
+struct CommonRules {
+  cel: Option<Box<[CelRule]>>,
+  required: Option<bool>,
+  ignore: Option<Ignore>,
+}
+
+fn check_lists<T>(in_list: Option<&[T]>, not_in_list: Option<&[T]>) -> Result<(), String>
+where
+  T: Eq + Hash + Debug + Clone,
+{
+  validate_lists(in_list, not_in_list).map_err(|invalid| {
+    format!("the following values are present inside of 'in' and 'not_in': {invalid:?}")
+  })
+}
+
+// Floats are compared by their bits, like in the validators
+fn check_float_lists<T: Into<f64> + Copy>(
+  in_list: Option<&[T]>,
+  not_in_list: Option<&[T]>,
+) -> Result<(), String> {
+  let to_bits = |list: &[T]| -> Vec<u64> { list.iter().map(|v| (*v).into().to_bits()).collect() };
+
+  validate_lists(
+    in_list.map(to_bits).as_deref(),
+    not_in_list.map(to_bits).as_deref(),
+  )
+  .map_err(|invalid| {
+    format!(
+      "the following values are present inside of 'in' and 'not_in': {:?}",
+      invalid
+        .into_iter()
+        .map(f64::from_bits)
+        .collect::<Vec<f64>>()
+    )
+  })
+}
+
+fn parse_pattern(pattern: Option<&str>) -> Result<Option<Regex>, String> {
+  pattern
+    .map(|pattern| Regex::new(pattern).map_err(|e| format!("invalid pattern '{pattern}': {e}")))
+    .transpose()
+}
+
+fn boxed<T: Clone>(list: &Option<Vec<T>>) -> Option<Box<[T]>> {
+  list.as_ref().map(|list| list.clone().into_boxed_slice())
+}
+
+macro_rules! integer_option {
+  ($rules:expr, $common:expr, $validator:ident) => {{
+    let default = NumericRulesDef::default();
+    let rules = $rules.as_ref().unwrap_or(&default);
+
+    if rules.finite.is_some() {
+      return Err("the 'finite' rule can only be used for float and double fields".into());
+    }
+
+    check_comparables(rules.lt, rules.lte, rules.gt, rules.gte)?;
+    check_lists(rules.in_.as_deref(), rules.not_in.as_deref())?;
+
+    $validator {
+      predefined: Vec::new(),
+      const_: rules.const_,
+      lt: rules.lt,
+      lte: rules.lte,
+      gt: rules.gt,
+      gte: rules.gte,
+      in_: boxed(&rules.in_),
+      not_in: boxed(&rules.not_in),
+      cel: $common.cel,
+      required: $common.required,
+      ignore: $common.ignore,
+    }
+    .into()
+  }};
+}
+
+macro_rules! float_option {
+  ($rules:expr, $common:expr, $validator:ident) => {{
+    let default = NumericRulesDef::default();
+    let rules = $rules.as_ref().unwrap_or(&default);
+
+    check_comparables(rules.lt, rules.lte, rules.gt, rules.gte)?;
+    check_float_lists(rules.in_.as_deref(), rules.not_in.as_deref())?;
+
+    $validator {
+      predefined: Vec::new(),
+      const_: rules.const_,
+      lt: rules.lt,
+      lte: rules.lte,
+      gt: rules.gt,
+      gte: rules.gte,
+      in_: boxed(&rules.in_),
+      not_in: boxed(&rules.not_in),
+      finite: rules.finite,
+      cel: $common.cel,
+      required: $common.required,
+      ignore: $common.ignore,
+    }
+    .into()
+  }};
+}
+
+fn map_key_type(key: &MapKey) -> FieldType {
+  match key {
+    MapKey::Int32 => FieldType::Int32,
+    MapKey::Int64 => FieldType::Int64,
+    MapKey::Uint32 => FieldType::Uint32,
+    MapKey::Uint64 => FieldType::Uint64,
+    MapKey::Sint32 => FieldType::Sint32,
+    MapKey::Sint64 => FieldType::Sint64,
+    MapKey::Fixed32 => FieldType::Fixed32,
+    MapKey::Fixed64 => FieldType::Fixed64,
+    MapKey::Sfixed32 => FieldType::Sfixed32,
+    MapKey::Sfixed64 => FieldType::Sfixed64,
+    MapKey::Bool => FieldType::Bool,
+    MapKey::String => FieldType::String,
+  }
+}
+
+impl RulesDef {
+  // The types of rules that are defined, which should be at most one
+  fn rule_types(&self) -> Vec<&'static str> {
+    [
+      ("string", self.string.is_some()),
+      ("bytes", self.bytes.is_some()),
+      ("bool", self.bool.is_some()),
+      ("int32", self.int32.is_some()),
+      ("int64", self.int64.is_some()),
+      ("sint32", self.sint32.is_some()),
+      ("sint64", self.sint64.is_some()),
+      ("sfixed32", self.sfixed32.is_some()),
+      ("sfixed64", self.sfixed64.is_some()),
+      ("uint32", self.uint32.is_some()),
+      ("uint64", self.uint64.is_some()),
+      ("fixed32", self.fixed32.is_some()),
+      ("fixed64", self.fixed64.is_some()),
+      ("float", self.float.is_some()),
+      ("double", self.double.is_some()),
+      ("enum", self.enum_.is_some()),
+      ("duration", self.duration.is_some()),
+      ("timestamp", self.timestamp.is_some()),
+      ("any", self.any.is_some()),
+      ("repeated", self.repeated.is_some()),
+      ("map", self.map.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, is_set)| *is_set)
+    .map(|(name, _)| name)
+    .collect()
+  }
+
+  /// Converts these rules into the `(buf.validate.field)` option of a field with the given type and cardinality.
+  ///
+  /// Returns an error if the rules do not apply to this type of field, or if they are not valid (such as a `lt` rule that is smaller than `gt`).
+  pub(crate) fn to_option(
+    &self,
+    field_type: &FieldType,
+    kind: FieldKind,
+  ) -> Result<ProtoOption, String> {
+    let rule_types = self.rule_types();
+
+    if rule_types.len() > 1 {
+      return Err(format!(
+        "the rules define more than one type of rules ({})",
+        rule_types.join(", ")
+      ));
+    }
+
+    let expected = match (kind, field_type) {
+      (FieldKind::Repeated, _) => "repeated",
+      (_, FieldType::Map(..)) => "map",
+      (_, FieldType::Enum(_)) => "enum",
+      (_, other) => other.name(),
+    };
+
+    if let Some(rule_type) = rule_types.first()
+      && *rule_type != expected
+    {
+      return Err(format!(
+        "the '{rule_type}' rules cannot be used for a field of type '{}'",
+        if kind == FieldKind::Repeated {
+          format!("repeated {}", field_type.name())
+        } else {
+          field_type.name().to_string()
+        }
+      ));
+    }
+
+    let common = CommonRules {
+      cel: self.cel.clone().map(Vec::into_boxed_slice),
+      required: self.required,
+      ignore: self.ignore.map(Ignore::from),
+    };
+
+    if kind == FieldKind::Repeated {
+      let default = RepeatedRulesDef::default();
+      let rules = self.repeated.as_deref().unwrap_or(&default);
+
+      let items = rules
+        .items
+        .as_ref()
+        .map(|items| items.to_option(field_type, FieldKind::Normal))
+        .transpose()
+        .map_err(|e| format!("invalid rules for the items: {e}"))?;
+
+      return Ok(
+        RepeatedValidator {
+          predefined: Vec::new(),
+          items,
+          min_items: rules.min_items,
+          max_items: rules.max_items,
+          unique: rules.unique,
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into(),
+      );
+    }
+
+    let option = match field_type {
+      FieldType::Map(key, value) => {
+        let default = MapRulesDef::default();
+        let rules = self.map.as_deref().unwrap_or(&default);
+
+        let keys = rules
+          .keys
+          .as_ref()
+          .map(|keys| keys.to_option(&map_key_type(key), FieldKind::Normal))
+          .transpose()
+          .map_err(|e| format!("invalid rules for the keys: {e}"))?;
+
+        let values = rules
+          .values
+          .as_ref()
+          .map(|values| values.to_option(value, FieldKind::Normal))
+          .transpose()
+          .map_err(|e| format!("invalid rules for the values: {e}"))?;
+
+        MapValidator {
+          predefined: Vec::new(),
+          keys,
+          values,
+          min_pairs: rules.min_pairs,
+          max_pairs: rules.max_pairs,
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      FieldType::String => {
+        let default = StringRulesDef::default();
+        let rules = self.string.as_ref().unwrap_or(&default);
+
+        check_lists(rules.in_.as_deref(), rules.not_in.as_deref())?;
+
+        StringValidator {
+          predefined: Vec::new(),
+          len: rules.len,
+          min_len: rules.min_len,
+          max_len: rules.max_len,
+          len_bytes: rules.len_bytes,
+          min_bytes: rules.min_bytes,
+          max_bytes: rules.max_bytes,
+          pattern: parse_pattern(rules.pattern.as_deref())?,
+          prefix: rules.prefix.as_deref(),
+          suffix: rules.suffix.as_deref(),
+          contains: rules.contains.as_deref(),
+          not_contains: rules.not_contains.as_deref(),
+          in_: rules
+            .in_
+            .as_ref()
+            .map(|list| list.iter().map(String::as_str).collect()),
+          not_in: rules
+            .not_in
+            .as_ref()
+            .map(|list| list.iter().map(String::as_str).collect()),
+          well_known: rules.well_known,
+          const_: rules.const_.as_deref(),
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      FieldType::Bytes => {
+        let default = BytesRulesDef::default();
+        let rules = self.bytes.as_ref().unwrap_or(&default);
+
+        check_lists(rules.in_.as_deref(), rules.not_in.as_deref())?;
+
+        BytesValidator {
+          predefined: Vec::new(),
+          len: rules.len,
+          min_len: rules.min_len,
+          max_len: rules.max_len,
+          pattern: parse_pattern(rules.pattern.as_deref())?,
+          prefix: rules.prefix.as_deref().map(str::as_bytes),
+          suffix: rules.suffix.as_deref().map(str::as_bytes),
+          contains: rules.contains.as_deref().map(str::as_bytes),
+          in_: rules
+            .in_
+            .as_ref()
+            .map(|list| list.iter().map(String::as_bytes).collect()),
+          not_in: rules
+            .not_in
+            .as_ref()
+            .map(|list| list.iter().map(String::as_bytes).collect()),
+          well_known: rules.well_known,
+          const_: rules.const_.as_deref().map(str::as_bytes),
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      FieldType::Bool => {
+        if common.cel.is_some() || common.ignore.is_some() {
+          return Err("the 'cel' and 'ignore' rules cannot be used for bool fields".into());
+        }
+
+        BoolValidator {
+          predefined: Vec::new(),
+          const_: self.bool.as_ref().and_then(|rules| rules.const_),
+          required: common.required,
+        }
+        .into()
+      }
+      FieldType::Int32 => integer_option!(self.int32, common, Int32Validator),
+      FieldType::Int64 => integer_option!(self.int64, common, Int64Validator),
+      FieldType::Sint32 => integer_option!(self.sint32, common, Sint32Validator),
+      FieldType::Sint64 => integer_option!(self.sint64, common, Sint64Validator),
+      FieldType::Sfixed32 => {
+        integer_option!(self.sfixed32, common, Sfixed32Validator)
+      }
+      FieldType::Sfixed64 => {
+        integer_option!(self.sfixed64, common, Sfixed64Validator)
+      }
+      FieldType::Uint32 => integer_option!(self.uint32, common, Uint32Validator),
+      FieldType::Uint64 => integer_option!(self.uint64, common, Uint64Validator),
+      FieldType::Fixed32 => {
+        integer_option!(self.fixed32, common, Fixed32Validator)
+      }
+      FieldType::Fixed64 => {
+        integer_option!(self.fixed64, common, Fixed64Validator)
+      }
+      FieldType::Float => float_option!(self.float, common, FloatValidator),
+      FieldType::Double => float_option!(self.double, common, DoubleValidator),
+      FieldType::Enum(_) => {
+        let default = EnumRulesDef::default();
+        let rules = self.enum_.as_ref().unwrap_or(&default);
+
+        check_lists(rules.in_.as_deref(), rules.not_in.as_deref())?;
+
+        EnumValidator {
+          predefined: Vec::new(),
+          in_: boxed(&rules.in_),
+          not_in: boxed(&rules.not_in),
+          const_: rules.const_,
+          defined_only: rules.defined_only,
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      FieldType::Duration => {
+        let default = DurationRulesDef::default();
+        let rules = self.duration.as_ref().unwrap_or(&default);
+        let list = |list: &Option<Vec<DurationDef>>| -> Option<Box<[Duration]>> {
+          list.as_ref().map(|list| list.iter().map(|d| d.0).collect())
+        };
+        let (in_, not_in) = (list(&rules.in_), list(&rules.not_in));
+        let (lt, lte, gt, gte) = (
+          rules.lt.map(|d| d.0),
+          rules.lte.map(|d| d.0),
+          rules.gt.map(|d| d.0),
+          rules.gte.map(|d| d.0),
+        );
+
+        check_comparables(lt, lte, gt, gte)?;
+        check_lists(in_.as_deref(), not_in.as_deref())?;
+
+        DurationValidator {
+          predefined: Vec::new(),
+          in_,
+          not_in,
+          const_: rules.const_.map(|d| d.0),
+          lt,
+          lte,
+          gt,
+          gte,
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      FieldType::Timestamp => {
+        let default = TimestampRulesDef::default();
+        let rules = self.timestamp.as_ref().unwrap_or(&default);
+
+        let validator = TimestampValidator {
+          predefined: Vec::new(),
+          const_: rules.const_.map(|t| t.0),
+          lt: rules.lt.map(|t| t.0),
+          lte: rules.lte.map(|t| t.0),
+          lt_now: rules.lt_now,
+          gt: rules.gt.map(|t| t.0),
+          gte: rules.gte.map(|t| t.0),
+          gt_now: rules.gt_now,
+          within: rules.within.map(|d| d.0),
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        };
+
+        check_comparables(validator.lt, validator.lte, validator.gt, validator.gte)?;
+        check_now_rules(&validator)?;
+
+        validator.into()
+      }
+      FieldType::Any => {
+        let default = AnyRulesDef::default();
+        let rules = self.any.as_ref().unwrap_or(&default);
+        let in_: Option<Vec<&str>> = rules
+          .in_
+          .as_ref()
+          .map(|list| list.iter().map(String::as_str).collect());
+        let not_in: Option<Vec<&str>> = rules
+          .not_in
+          .as_ref()
+          .map(|list| list.iter().map(String::as_str).collect());
+
+        check_lists(in_.as_deref(), not_in.as_deref())?;
+
+        AnyValidator {
+          predefined: Vec::new(),
+          in_: in_.as_deref(),
+          not_in: not_in.as_deref(),
+          cel: common.cel,
+          required: common.required,
+          ignore: common.ignore,
+        }
+        .into()
+      }
+      _ => MessageValidator {
+        cel: common.cel,
+        required: common.required,
+        ignore: common.ignore,
+      }
+      .into(),
+    };
+
+    Ok(option)
+  }
+}
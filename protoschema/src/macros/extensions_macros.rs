@@ -45,3 +45,51 @@ macro_rules! extension {
     }
   };
 }
+
+/// A macro that defines a [`PredefinedRule`](crate::validators::predefined::PredefinedRule) in a [`FileBuilder`](crate::files::FileBuilder) and returns it.
+///
+/// The first argument is the ident for the [`FileBuilder`](crate::files::FileBuilder) where the rule will be defined.
+/// The second argument is the ident of the protovalidate rules message being extended, which will be matched with a variant of the [`ExtensionKind`](crate::extensions::ExtensionKind) enum (i.e. StringRules, Int32Rules, etc).
+/// Then comes the extension field, defined as `$field_number:literal => $field:expr`, followed by the Cel rules for the predefined rule, which use the same syntax as the `cel` rules in the [`message`](crate::message) macro.
+/// In the Cel expressions, `this` refers to the value of the field being validated, and `rule` refers to the value of the predefined rule.
+/// # Examples
+/// ```
+/// use protoschema::{Package, bool, message, predefined_rule, string};
+///
+/// let package = Package::new("mypkg");
+/// let rules_file = package.new_file("rules");
+///
+/// let slug = predefined_rule!(
+///   rules_file,
+///   StringRules,
+///   1161 => bool!("slug"),
+///   cel = [
+///     {
+///       id = "string.slug",
+///       msg = "value must be a slug",
+///       expr = "!rule || this.matches('^[a-z0-9]+(?:-[a-z0-9]+)*$')"
+///     }
+///   ]
+/// );
+///
+/// let file = package.new_file("myfile");
+///
+/// message!(
+///   file.new_message("Post"),
+///   1 => string!("url_slug", |v| v.predefined(&slug, true)),
+/// );
+/// ```
+#[macro_export]
+macro_rules! predefined_rule {
+  ($file:ident, $extendee:ident, $tag:literal => $field:expr, cel = [ $($rules:tt)* ] $(,)?) => {
+    $file.new_predefined_rule(
+      $crate::extensions::ExtensionKind::$extendee,
+      $tag,
+      $field,
+      $crate::cel_rules!(
+        @rules()
+        @rest($($rules)*)
+      ),
+    )
+  };
+}
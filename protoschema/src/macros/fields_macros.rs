@@ -93,6 +93,34 @@ macro_rules! reusable_fields {
   };
 }
 
+/// Defines a named [`Mixin`](crate::mixins::Mixin), a block of reusable fields whose tags are relative to the position where it is placed in a message.
+///
+/// # Examples
+/// ```
+/// use protoschema::{mixin, message, timestamp, uint64, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let timestamps = mixin!("timestamps",
+///   0 => timestamp!("created_at"),
+///   1 => timestamp!("updated_at")
+/// );
+///
+/// message!(my_file.new_message("my_msg").mixin(&timestamps, 100),
+///   1 => uint64!("id")
+/// );
+/// ```
+#[macro_export]
+macro_rules! mixin {
+  ($name:expr, $($tag:literal => $field:expr),+ $(,)?) => {
+    $crate::mixins::Mixin::new($name)
+    $(
+      .field($tag, $field)
+    )+
+  };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! repeated_field {
@@ -85,12 +85,20 @@ macro_rules! cel_rules {
 ///   // Options can only be defined at the very top
 ///   options  = [ my_opt.clone() ], // Or `options = my_list_of_options.clone()`
 ///   // reserved_names must always be followed by a comma because it's an expression, even if it's the last item in the list
-///   reserved_names = [ "abc", "deg" ],
+///   reserved_names = [ "ghi", "jkl" ],
 ///   // Accepts both numbers and ranges
 ///   reserved = [ 5, 12, 23..29 ],
+///   // Only one of these fields can be set (protovalidate's message oneof rule).
+///   // The fields can be referred to by name or with their builders.
+///   // Use `oneof_rule(required)` to require exactly one of them to be set.
+///   oneof_rule = [ "abc", "def" ],
+///   // Sets the message-level rules with a closure, like for field validators.
+///   // Must be followed by a comma, even if last
+///   validate = |v| v.disabled(),
 ///
 ///   // Single field
 ///   10 => string!("abc"),
+///   11 => string!("def"),
 ///   // Included reusable fields
 ///   include(my_common_fields),
 /// );
@@ -138,6 +146,7 @@ macro_rules! message {
       @reserved()
       @reserved_names()
       @cel()
+      @rules()
       @input($($tokens)*)
     }.add_options($options)
   };
@@ -152,6 +161,7 @@ macro_rules! message {
       @reserved()
       @reserved_names()
       @cel()
+      @rules()
       @input($($tokens)*)
     }
   };
@@ -170,6 +180,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($names:expr)?)
     @cel($($cel_rules:expr)?)
+    @rules($($rules:tt)*)
     @input($(,)?)
   ) => {
     {
@@ -180,23 +191,27 @@ macro_rules! _internal_message_body {
 
       let oneofs_list = [ $($oneofs)* ];
 
-      let mut new_msg = $builder
-        .fields(fields_list)
-        .add_oneofs(oneofs_list)
-      $(
-        .cel_rules($cel_rules)
-      )?
-
+      let new_msg = $builder
       $(
         .reserved_names($names)
       )?;
 
-      $crate::parse_reserved! {
+      let new_msg = $crate::parse_reserved! {
         @builder(new_msg)
         @ranges()
         @numbers()
         @rest($($reserved)*)
-      }
+      };
+
+      // The oneofs and the reservations are set before the fields, so that the mixins placed among the fields avoid them
+      new_msg
+        .add_oneofs(oneofs_list)
+        .fields(fields_list)
+      $(
+        .cel_rules($cel_rules)
+      )?
+
+      $($rules)*
     }
   };
 
@@ -210,6 +225,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? include_oneof($oneof:expr) $($rest:tt)*)
   ) => {
     $crate::_internal_message_body! {
@@ -221,6 +237,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -235,6 +252,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? include($block:expr) $($rest:tt)*)
   ) => {
     $crate::_internal_message_body! {
@@ -246,6 +264,101 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
+      @input($($rest)*)
+    }
+  };
+
+  // Required message oneof rule
+  (
+    @builder($builder:expr)
+    @fields($($fields:tt)*)
+    @fields_blocks($($fields_blocks:tt)*)
+    @oneofs($($oneofs:tt)*)
+    @enums($($enums:tt)*)
+    @reserved($($reserved:tt)*)
+    @reserved_names($($reserved_names:tt)*)
+    @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
+    @input($(,)? oneof_rule(required) = [ $($oneof_rule_field:expr),* $(,)? ] $($rest:tt)*)
+  ) => {
+    $crate::_internal_message_body! {
+      @builder($builder)
+      @fields($($fields)*)
+      @fields_blocks($($fields_blocks)*)
+      @oneofs($($oneofs)*)
+      @enums($($enums)*)
+      @reserved($($reserved)*)
+      @reserved_names($($reserved_names)*)
+      @cel($($cel)*)
+      @rules(
+        $($rules)*
+        .message_oneof_rule(
+          [ $($crate::fields::FieldReference::field_name(&$oneof_rule_field)),* ],
+          true,
+        )
+      )
+      @input($($rest)*)
+    }
+  };
+
+  // Message oneof rule
+  (
+    @builder($builder:expr)
+    @fields($($fields:tt)*)
+    @fields_blocks($($fields_blocks:tt)*)
+    @oneofs($($oneofs:tt)*)
+    @enums($($enums:tt)*)
+    @reserved($($reserved:tt)*)
+    @reserved_names($($reserved_names:tt)*)
+    @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
+    @input($(,)? oneof_rule = [ $($oneof_rule_field:expr),* $(,)? ] $($rest:tt)*)
+  ) => {
+    $crate::_internal_message_body! {
+      @builder($builder)
+      @fields($($fields)*)
+      @fields_blocks($($fields_blocks)*)
+      @oneofs($($oneofs)*)
+      @enums($($enums)*)
+      @reserved($($reserved)*)
+      @reserved_names($($reserved_names)*)
+      @cel($($cel)*)
+      @rules(
+        $($rules)*
+        .message_oneof_rule(
+          [ $($crate::fields::FieldReference::field_name(&$oneof_rule_field)),* ],
+          false,
+        )
+      )
+      @input($($rest)*)
+    }
+  };
+
+  // Message rules
+  (
+    @builder($builder:expr)
+    @fields($($fields:tt)*)
+    @fields_blocks($($fields_blocks:tt)*)
+    @oneofs($($oneofs:tt)*)
+    @enums($($enums:tt)*)
+    @reserved($($reserved:tt)*)
+    @reserved_names($($reserved_names:tt)*)
+    @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
+    // Expr must be followed by a comma
+    @input($(,)? validate = $validate:expr, $($rest:tt)*)
+  ) => {
+    $crate::_internal_message_body! {
+      @builder($builder)
+      @fields($($fields)*)
+      @fields_blocks($($fields_blocks)*)
+      @oneofs($($oneofs)*)
+      @enums($($enums)*)
+      @reserved($($reserved)*)
+      @reserved_names($($reserved_names)*)
+      @cel($($cel)*)
+      @rules($($rules)* .validate($validate))
       @input($($rest)*)
     }
   };
@@ -260,6 +373,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel()
+    @rules($($rules:tt)*)
     @input($(,)? cel = [ $($items:tt)* ] $($rest:tt)*)
   ) => {
     $crate::_internal_message_body! {
@@ -274,6 +388,7 @@ macro_rules! _internal_message_body {
         @rules()
         @rest($($items)*)
       ))
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -288,6 +403,7 @@ macro_rules! _internal_message_body {
     @reserved()
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? reserved = [ $($items:tt)* ] $($rest:tt)*)
   ) => {
     $crate::_internal_message_body! {
@@ -299,6 +415,7 @@ macro_rules! _internal_message_body {
       @reserved($($items)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -313,6 +430,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names()
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     // Expr must be followed by a comma
     @input($(,)? reserved_names = $reserved_names:expr, $($rest:tt)*)
   ) => {
@@ -325,6 +443,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($reserved_names)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -339,6 +458,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? enum $name:literal { $($tokens:tt)* } $($rest:tt)* )
   ) => {
     $crate::_internal_message_body! {
@@ -350,6 +470,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -364,6 +485,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? oneof $name:literal { $($oneof_body:tt)* } $($rest:tt)* )
   ) => {
     $crate::_internal_message_body! {
@@ -378,6 +500,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -392,6 +515,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? $tag:literal => $field:expr, $($rest:tt)* )
   ) => {
     $crate::_internal_message_body! {
@@ -403,6 +527,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input($($rest)*)
     }
   };
@@ -417,6 +542,7 @@ macro_rules! _internal_message_body {
     @reserved($($reserved:tt)*)
     @reserved_names($($reserved_names:tt)*)
     @cel($($cel:tt)*)
+    @rules($($rules:tt)*)
     @input($(,)? $tag:literal => $field:expr)
   ) => {
     $crate::_internal_message_body! {
@@ -428,6 +554,7 @@ macro_rules! _internal_message_body {
       @reserved($($reserved)*)
       @reserved_names($($reserved_names)*)
       @cel($($cel)*)
+      @rules($($rules)*)
       @input()
     }
   };
@@ -1,16 +1,22 @@
-use std::{marker::PhantomData, ops::Range, sync::Arc};
+use std::{marker::PhantomData, ops::Range, rc::Rc, sync::Arc};
 
 use crate::{
   common::VALIDATE_PROTO_FILE,
+  derivation::{Derivation, Lineage},
   enums::{EnumBuilder, EnumData},
   field_type::ImportedItemPath,
-  fields::{self, FieldBuilder, FieldData},
+  fields::{self, Field, FieldBuilder, FieldData, FieldReference},
+  mixins::{resolve_mixins, AppliedMixin, Mixin, MixinPlacement, DEFAULT_MIXIN_BAND},
   oneofs::{Oneof, OneofData},
+  options::merge_options,
   packages::Arena,
   rendering::MessageTemplate,
   sealed,
-  validators::cel::CelRule,
-  Empty, FieldType, IsSet, IsUnset, OptionValue, ProtoOption, Set, Unset,
+  validators::{
+    cel::CelRule,
+    message_rules::{self, MessageRules, MessageRulesBuilder},
+  },
+  Empty, FieldType, IsSet, IsUnset, ProtoOption, Set, Unset,
 };
 
 /// The builder for a protobuf Message. Its methods are used to collect and store the data for a given message.
@@ -36,30 +42,55 @@ pub struct MessageData {
   pub enums: Vec<usize>,
   pub messages: Vec<usize>,
   pub imports: Vec<Arc<str>>,
+  pub lineage: Option<Lineage>,
+  // The id of the generic message and the type arguments that this message is an instance of
+  pub(crate) instance_of: Option<(usize, Box<[FieldType]>)>,
+  // The mixins that are placed when the fields are set
+  pub(crate) pending_mixins: Vec<(Mixin, MixinPlacement)>,
+  pub(crate) mixin_band: Option<u32>,
+  pub mixins: Vec<AppliedMixin>,
 }
 
 impl<S: MessageState> MessageBuilder<S> {
+  /// Sets the protovalidate rules for this message, such as Cel rules, oneof rules and the `disabled` flag.
+  /// Rules added with multiple calls to this method (or to [`cel_rules`](MessageBuilder::cel_rules) and [`message_oneof_rule`](MessageBuilder::message_oneof_rule)) are merged into a single `(buf.validate.message)` option.
+  pub fn validate<F, R>(self, config_fn: F) -> MessageBuilder<S>
+  where
+    F: FnOnce(MessageRulesBuilder) -> MessageRulesBuilder<R>,
+    R: message_rules::IsComplete,
+  {
+    let rules = config_fn(MessageRules::builder()).build();
+    self.add_message_rules(rules)
+  }
+
   /// Sets the Cel rules for this message to be used with protovalidate.
   /// Cel rules can be easily defined with the [`cel_rule`](crate::cel_rule) macro, or directly within the [`message`](crate::message!) macro.
   pub fn cel_rules<I>(self, rules: I) -> MessageBuilder<S>
   where
     I: IntoIterator<Item = CelRule>,
   {
+    let rules: Vec<CelRule> = rules.into_iter().collect();
+    self.add_message_rules(MessageRules::builder().cel(rules).build())
+  }
+
+  /// Adds a protovalidate oneof rule to this message, which ensures that at most one of the given fields is set (or exactly one, if `required` is true).
+  /// Unlike a regular oneof, this can be applied to any of the message's fields, including repeated and map fields.
+  /// The fields can be referred to by name or with their [`FieldBuilder`], and they must be among the fields defined for this message, which is checked when the templates are rendered.
+  pub fn message_oneof_rule<I, F>(self, fields: I, required: bool) -> MessageBuilder<S>
+  where
+    I: IntoIterator<Item = F>,
+    F: FieldReference,
+  {
+    self.add_message_rules(MessageRules::builder().oneof(fields, required).build())
+  }
+
+  fn add_message_rules(self, rules: MessageRules) -> MessageBuilder<S> {
     {
       let mut arena = self.arena.borrow_mut();
       let msg = &mut arena.messages[self.id];
       msg.imports.push(VALIDATE_PROTO_FILE.clone());
 
-      let rules: Vec<OptionValue> = rules.into_iter().map(|r| r.into()).collect();
-      let option = ProtoOption {
-        name: "(buf.validate.message)",
-        value: OptionValue::Message(
-          vec![("cel".into(), OptionValue::List(rules.into_boxed_slice()))].into(),
-        )
-        .into(),
-      };
-
-      msg.options.push(option);
+      merge_options(&mut msg.options, [rules.into()]);
     }
 
     MessageBuilder {
@@ -70,6 +101,27 @@ impl<S: MessageState> MessageBuilder<S> {
     }
   }
 
+  /// Validates an instance of this message, given in the proto3 JSON format, against its validation rules (including the ones added by the passes of the package).
+  /// See [`Package::evaluate`](crate::Package::evaluate) for more information.
+  #[cfg(feature = "serde")]
+  pub fn evaluate(
+    &self,
+    instance: &serde_json::Value,
+  ) -> Result<Vec<crate::evaluation::Violation>, crate::errors::EvaluationError> {
+    let (package, _) = self.arena.borrow().processed();
+
+    crate::evaluation::evaluate_message(&package, self.id, instance)
+  }
+
+  /// Generates a valid instance of this message and a list of instances that violate exactly one of its validation rules.
+  /// See [`Package::examples`](crate::Package::examples) for more information.
+  #[cfg(feature = "serde")]
+  pub fn examples(&self) -> Result<crate::examples::Examples, crate::errors::ExampleError> {
+    let (package, _) = self.arena.borrow().processed();
+
+    crate::examples::generate_examples(&package, self.id, 0)
+  }
+
   #[doc(hidden)]
   pub fn get_type(&self) -> FieldType {
     FieldType::Message(self.get_import_path())
@@ -225,43 +277,161 @@ impl<S: MessageState> MessageBuilder<S> {
     }
   }
 
-  /// Sets the fields for this message
+  /// Sets the fields for this message, and places the mixins that were added with [`mixin`](MessageBuilder::mixin) among them.
+  ///
+  /// # Panics
+  /// If two fields (including those of the mixins and those added with [`add_fields`](MessageBuilder::add_fields)) have the same tag or the same name.
+  #[track_caller]
   pub fn fields<I, F>(self, fields: I) -> MessageBuilder<SetFields<S>>
   where
     S::Fields: IsUnset,
     I: IntoIterator<Item = (u32, FieldBuilder<F>)>,
     F: fields::IsComplete,
   {
-    {
-      let mut arena = self.arena.borrow_mut();
+    self.place_fields(
+      fields
+        .into_iter()
+        .map(|(tag, field)| (tag, field.build()))
+        .collect(),
+    );
 
-      let mut final_fields: Vec<(u32, FieldData)> = fields
+    MessageBuilder {
+      id: self.id,
+      arena: self.arena,
+      file_id: self.file_id,
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Adds fields to this message, in addition to the existing ones, and places the mixins that were added with [`mixin`](MessageBuilder::mixin) among them.
+  /// Unlike [`fields`](MessageBuilder::fields), this can be called any number of times, so that separate modules can contribute fields to the same message.
+  ///
+  /// # Panics
+  /// If a field has the same tag or name as another field of the message (including those in its oneofs), or if its tag or name are reserved.
+  #[track_caller]
+  pub fn add_fields<I, F>(self, fields: I) -> MessageBuilder<S>
+  where
+    I: IntoIterator<Item = (u32, FieldBuilder<F>)>,
+    F: fields::IsComplete,
+  {
+    self.place_fields(
+      fields
         .into_iter()
-        .map(|(tag, field)| {
-          let field = field.build();
-          let file_id = self.file_id;
+        .map(|(tag, field)| (tag, field.build()))
+        .collect(),
+    );
 
-          for import in field.imports {
-            arena.files[file_id].conditionally_add_import(&import);
-          }
+    self
+  }
 
-          (
-            tag,
-            FieldData {
-              name: field.name,
-              options: field.options.into_boxed_slice(),
-              kind: field.kind,
-              field_type: field.field_type,
-            },
-          )
-        })
-        .collect();
+  // Places the pending mixins among the new fields, checks them against the existing members of the message and adds all of them
+  #[track_caller]
+  pub(crate) fn place_fields(&self, fields: Vec<(u32, Field)>) {
+    let (item, mixins, band, members) = {
+      let mut arena = self.arena.borrow_mut();
+      let msg = &mut arena.messages[self.id];
+
+      (
+        msg.import_path.full_name_with_package.clone(),
+        std::mem::take(&mut msg.pending_mixins),
+        msg.mixin_band.unwrap_or(DEFAULT_MIXIN_BAND),
+        msg.members(),
+      )
+    };
+
+    let resolved = match resolve_mixins(&item, fields, &mixins, band, |tag| {
+      members.is_taken(tag as i64)
+    }) {
+      Ok(resolved) => resolved,
+      Err(error) => panic!("{error}"),
+    };
+
+    // Checks the conflicts with the existing fields, such as the ones added before with `add_fields`
+    let result = members.check_members(
+      resolved
+        .fields
+        .iter()
+        .map(|(tag, field)| (*tag as i64, field.name.as_ref())),
+    );
+
+    if let Err(error) = result {
+      panic!("{error}")
+    }
+
+    self.set_fields(resolved.fields);
+    self.arena.borrow_mut().messages[self.id]
+      .mixins
+      .extend(resolved.mixins);
+  }
+
+  // Adds the given fields to the existing ones, and adds their imports to the file of this message
+  fn set_fields<I>(&self, fields: I)
+  where
+    I: IntoIterator<Item = (u32, Field)>,
+  {
+    let mut arena = self.arena.borrow_mut();
+    let existing_fields = std::mem::take(&mut arena.messages[self.id].fields);
+
+    let mut final_fields: Vec<(u32, FieldData)> = fields
+      .into_iter()
+      .map(|(tag, field)| {
+        for import in field.imports {
+          arena.files[self.file_id].conditionally_add_import(&import);
+        }
+
+        (
+          tag,
+          FieldData {
+            name: field.name,
+            options: field.options.into_boxed_slice(),
+            kind: field.kind,
+            field_type: field.field_type,
+          },
+        )
+      })
+      .collect();
 
-      final_fields.sort_by_key(|t| t.0);
+    final_fields.extend(existing_fields);
+    final_fields.sort_by_key(|t| t.0);
 
-      arena.messages[self.id].fields = final_fields.into_boxed_slice()
+    arena.messages[self.id].fields = final_fields.into_boxed_slice()
+  }
+
+  /// Sets the fields of this message by deriving them from the fields of another message, as described by the given [`Derivation`].
+  ///
+  /// The lineage of the derived message is recorded, so that [`Package::check`](crate::Package::check) can report the derived messages whose source has changed since the derivation, and [`Package::propagate_derivations`](crate::Package::propagate_derivations) can apply the derivations again to the current fields of their source.
+  ///
+  /// # Panics
+  /// If the source belongs to another package, if the derivation refers to a field that does not exist in the source, or if two of the resulting fields have the same name or tag.
+  #[track_caller]
+  pub fn derive_from<SS: MessageState>(
+    self,
+    source: &MessageBuilder<SS>,
+    derivation: Derivation,
+  ) -> MessageBuilder<SetFields<S>>
+  where
+    S::Fields: IsUnset,
+  {
+    assert!(
+      Rc::ptr_eq(&self.arena, &source.arena),
+      "The source of a derivation must belong to the same package as the derived message"
+    );
+
+    let result = self.arena.borrow_mut().derive_message(
+      self.id,
+      self.file_id,
+      source.id,
+      derivation,
+      Vec::new(),
+    );
+
+    if let Err(error) = result {
+      panic!("{error}")
     }
 
+    // The pending mixins are placed among the derived fields
+    self.place_fields(Vec::new());
+
     MessageBuilder {
       id: self.id,
       arena: self.arena,
@@ -270,12 +440,63 @@ impl<S: MessageState> MessageBuilder<S> {
     }
   }
 
+  /// Adds a [`Mixin`] to this message, which is placed among the fields of the message by the next call to [`fields`](MessageBuilder::fields), [`add_fields`](MessageBuilder::add_fields) or [`derive_from`](MessageBuilder::derive_from).
+  ///
+  /// The placement can be a base tag (such as `100`, so that the relative tag 0 becomes 100, 1 becomes 101 and so on), or [`MixinPlacement::Auto`] to use the first free spot in the mixin band.
+  pub fn mixin<P: Into<MixinPlacement>>(self, mixin: &Mixin, placement: P) -> MessageBuilder<S>
+  where
+    S::Fields: IsUnset,
+  {
+    self.arena.borrow_mut().messages[self.id]
+      .pending_mixins
+      .push((mixin.clone(), placement.into()));
+    self
+  }
+
+  /// Sets the tag where the mixins with [`MixinPlacement::Auto`] start being placed in this message. Defaults to [`DEFAULT_MIXIN_BAND`].
+  pub fn mixin_band(self, start: u32) -> MessageBuilder<S>
+  where
+    S::Fields: IsUnset,
+  {
+    self.arena.borrow_mut().messages[self.id].mixin_band = Some(start);
+    self
+  }
+
+  /// Returns the mixins placed in this message, with the tags given to their fields
+  pub fn get_mixins(&self) -> Vec<AppliedMixin> {
+    self.arena.borrow().messages[self.id].mixins.clone()
+  }
+
+  /// Returns the [`Lineage`] of this message, if it was derived from another message with [`derive_from`](MessageBuilder::derive_from)
+  pub fn get_lineage(&self) -> Option<Lineage> {
+    self.arena.borrow().messages[self.id].lineage.clone()
+  }
+
   /// Adds the given oneofs to this message
+  ///
+  /// # Panics
+  /// If a field of the oneofs has the same tag or name as another field of the message (including those in the other oneofs), or if its tag or name are reserved.
+  #[track_caller]
   pub fn add_oneofs<I>(self, oneofs: I) -> MessageBuilder<SetOneofs<S>>
   where
     S::Oneofs: IsUnset,
     I: IntoIterator<Item = Oneof>,
   {
+    let oneofs: Vec<Oneof> = oneofs.into_iter().collect();
+
+    let result = self.arena.borrow().messages[self.id]
+      .members()
+      .check_members(
+        oneofs
+          .iter()
+          .flat_map(|oneof| oneof.fields.iter())
+          .map(|(tag, field)| (*tag as i64, field.name.as_ref())),
+      );
+
+    if let Err(error) = result {
+      panic!("{error}")
+    }
+
     {
       let mut arena = self.arena.borrow_mut();
 
@@ -327,7 +548,8 @@ impl<S: MessageState> MessageBuilder<S> {
     }
   }
 
-  /// Adds the given options to the message's list of options
+  /// Adds the given options to the message's list of options.
+  /// Identical options are only added once, and options with a message value and the same name are merged together.
   pub fn add_options<I>(self, options: I) -> MessageBuilder<S>
   where
     I: IntoIterator<Item = ProtoOption>,
@@ -336,7 +558,7 @@ impl<S: MessageState> MessageBuilder<S> {
       let mut arena = self.arena.borrow_mut();
       let msg = &mut arena.messages[self.id];
 
-      msg.options.extend(options)
+      merge_options(&mut msg.options, options)
     }
 
     MessageBuilder {
@@ -359,7 +581,9 @@ impl<S: MessageState> MessageBuilder<S> {
       let msg = &mut arena.messages[self.id];
       let reserved_names: Vec<Box<str>> = names.into_iter().map(|n| n.as_ref().into()).collect();
 
-      msg.reserved_names = reserved_names.into_boxed_slice();
+      msg.reserved_names = [msg.reserved_names.as_ref(), &reserved_names]
+        .concat()
+        .into();
     }
 
     MessageBuilder {
@@ -380,7 +604,12 @@ impl<S: MessageState> MessageBuilder<S> {
       let mut arena = self.arena.borrow_mut();
       let msg = &mut arena.messages[self.id];
 
-      msg.reserved_numbers = numbers.into_iter().collect()
+      msg.reserved_numbers = msg
+        .reserved_numbers
+        .iter()
+        .copied()
+        .chain(numbers)
+        .collect()
     }
 
     MessageBuilder {
@@ -402,7 +631,7 @@ impl<S: MessageState> MessageBuilder<S> {
       let mut arena = self.arena.borrow_mut();
       let msg = &mut arena.messages[self.id];
 
-      msg.reserved_ranges = ranges.into_iter().collect()
+      msg.reserved_ranges = msg.reserved_ranges.iter().cloned().chain(ranges).collect()
     }
 
     MessageBuilder {
@@ -414,6 +643,104 @@ impl<S: MessageState> MessageBuilder<S> {
   }
 }
 
+impl<S: MessageState> MessageBuilder<S> {
+  // Checks the new reservations against the existing fields
+  #[track_caller]
+  fn check_reservations(&self, numbers: &[i64], ranges: &[(i64, i64)], names: &[Box<str>]) {
+    let result = self.arena.borrow().messages[self.id]
+      .members()
+      .check_reservations(numbers, ranges, names);
+
+    if let Err(error) = result {
+      panic!("{error}")
+    }
+  }
+
+  /// Adds reserved numbers to this message, in addition to the existing ones.
+  /// Unlike [`reserved_numbers`](MessageBuilder::reserved_numbers), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the numbers is used by a field of this message.
+  #[track_caller]
+  pub fn add_reserved<I>(self, numbers: I) -> MessageBuilder<S>
+  where
+    I: IntoIterator<Item = u32>,
+  {
+    let numbers: Vec<u32> = numbers.into_iter().collect();
+
+    self.check_reservations(
+      &numbers.iter().map(|n| *n as i64).collect::<Vec<_>>(),
+      &[],
+      &[],
+    );
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let msg = &mut arena.messages[self.id];
+
+      msg.reserved_numbers = [msg.reserved_numbers.as_ref(), &numbers].concat().into();
+    }
+
+    self
+  }
+
+  /// Adds reserved ranges to this message, in addition to the existing ones. As in protobuf, the ranges are considered to be inclusive.
+  /// Unlike [`reserved_ranges`](MessageBuilder::reserved_ranges), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the ranges includes the tag of a field of this message.
+  #[track_caller]
+  pub fn add_reserved_ranges<I>(self, ranges: I) -> MessageBuilder<S>
+  where
+    I: IntoIterator<Item = Range<u32>>,
+  {
+    let ranges: Vec<Range<u32>> = ranges.into_iter().collect();
+
+    self.check_reservations(
+      &[],
+      &ranges
+        .iter()
+        .map(|range| (range.start as i64, range.end as i64))
+        .collect::<Vec<_>>(),
+      &[],
+    );
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let msg = &mut arena.messages[self.id];
+
+      msg.reserved_ranges = [msg.reserved_ranges.as_ref(), &ranges].concat().into();
+    }
+
+    self
+  }
+
+  /// Adds reserved names to this message, in addition to the existing ones.
+  /// Unlike [`reserved_names`](MessageBuilder::reserved_names), this can be called any number of times.
+  ///
+  /// # Panics
+  /// If one of the names is used by a field of this message.
+  #[track_caller]
+  pub fn add_reserved_names<I, Str>(self, names: I) -> MessageBuilder<S>
+  where
+    I: IntoIterator<Item = Str>,
+    Str: AsRef<str>,
+  {
+    let names: Vec<Box<str>> = names.into_iter().map(|n| n.as_ref().into()).collect();
+
+    self.check_reservations(&[], &[], &names);
+
+    {
+      let mut arena = self.arena.borrow_mut();
+      let msg = &mut arena.messages[self.id];
+
+      msg.reserved_names = [msg.reserved_names.as_ref(), &names].concat().into();
+    }
+
+    self
+  }
+}
+
 #[allow(non_camel_case_types)]
 mod members {
   pub struct fields;
@@ -0,0 +1,260 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+  errors::MixinError,
+  fields::{Field, FieldBuilder, IsComplete},
+};
+
+/// The tag where the automatic placement of mixins starts, unless a different one is set with [`MessageBuilder::mixin_band`](crate::messages::MessageBuilder::mixin_band).
+pub const DEFAULT_MIXIN_BAND: u32 = 1000;
+
+/// The highest field number allowed by protobuf
+pub const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+// The tags reserved by the protobuf implementation, which cannot be used by fields
+const IMPLEMENTATION_RESERVED_TAGS: RangeInclusive<u32> = 19000..=19999;
+
+/// A named block of reusable fields, whose tags are relative to the position where the block is placed in a message.
+///
+/// Unlike the blocks created with [`reusable_fields`](crate::reusable_fields), which have fixed tags, a mixin is placed with [`MessageBuilder::mixin`](crate::messages::MessageBuilder::mixin) either at a given base tag or in the first free spot of the tag band for mixins.
+/// The collisions between the tags or names of the fields of a message are detected when the fields are set, and they report the mixin that each field came from.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{message, mixin, mixins::MixinPlacement, string, timestamp, uint64, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let timestamps = mixin!("timestamps", 0 => timestamp!("created_at"), 1 => timestamp!("updated_at"));
+/// let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+///
+/// let user_msg = message!(
+///   my_file
+///     .new_message("User")
+///     .mixin(&timestamps, 100)
+///     .mixin(&audit, MixinPlacement::Auto),
+///   1 => uint64!("id"),
+/// );
+///
+/// let mixins = user_msg.get_mixins();
+///
+/// assert_eq!(mixins[0].fields, vec![(100, "created_at".into()), (101, "updated_at".into())]);
+/// assert_eq!(mixins[1].fields, vec![(1000, "created_by".into()), (1001, "updated_by".into())]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mixin {
+  pub(crate) name: Box<str>,
+  pub(crate) fields: Vec<(u32, Field)>,
+}
+
+impl Mixin {
+  /// Creates a new empty mixin with the given name
+  pub fn new<T: AsRef<str>>(name: T) -> Self {
+    Mixin {
+      name: name.as_ref().into(),
+      fields: Vec::new(),
+    }
+  }
+
+  /// Adds a field to this mixin, with a tag relative to the base tag where the mixin is placed (starting from 0)
+  pub fn field<S: IsComplete>(mut self, relative_tag: u32, field: FieldBuilder<S>) -> Self {
+    self.fields.push((relative_tag, field.build()));
+    self
+  }
+
+  /// Returns the name of this mixin
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Returns the number of tags occupied by this mixin, from its base tag up to its last field
+  pub fn width(&self) -> u32 {
+    self
+      .fields
+      .iter()
+      .map(|(tag, _)| tag + 1)
+      .max()
+      .unwrap_or(0)
+  }
+}
+
+/// The position of a [`Mixin`] in a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixinPlacement {
+  /// Places the mixin at the given base tag, so that the field with the relative tag 0 gets this tag
+  At(u32),
+  /// Places the mixin at the lowest base tag of the mixin band where none of its tags are already in use.
+  /// Since the mixins are placed in order of inclusion, changing this order changes their tags.
+  Auto,
+}
+
+impl From<u32> for MixinPlacement {
+  fn from(value: u32) -> Self {
+    MixinPlacement::At(value)
+  }
+}
+
+/// A mixin that was placed in a message, returned by [`MessageBuilder::get_mixins`](crate::messages::MessageBuilder::get_mixins).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMixin {
+  /// The name of the mixin
+  pub mixin: Box<str>,
+  /// The base tag where the mixin was placed
+  pub base: u32,
+  /// The tags and names of the fields that came from the mixin
+  pub fields: Vec<(u32, Box<str>)>,
+}
+
+// The fields of a message after placing its mixins, and the tags given to them
+pub(crate) struct ResolvedFields {
+  pub(crate) fields: Vec<(u32, Field)>,
+  pub(crate) mixins: Vec<AppliedMixin>,
+}
+
+// Describes the origin of a field, for the error messages
+fn describe(name: &str, mixin: Option<&str>) -> Box<str> {
+  match mixin {
+    Some(mixin) => format!("the field '{name}' (from the mixin '{mixin}')").into(),
+    None => format!("the field '{name}'").into(),
+  }
+}
+
+// Returns the tags of the fields of a mixin placed at the given base tag, or None if one of them is above the maximum field number
+fn placed_tags(mixin: &Mixin, base: u32) -> Option<Vec<u32>> {
+  mixin
+    .fields
+    .iter()
+    .map(|(tag, _)| {
+      base
+        .checked_add(*tag)
+        .filter(|tag| *tag <= MAX_FIELD_NUMBER)
+    })
+    .collect()
+}
+
+fn is_valid_tag(tag: u32) -> bool {
+  tag != 0 && !IMPLEMENTATION_RESERVED_TAGS.contains(&tag)
+}
+
+// Places the mixins among the other fields of a message, and checks that all of the tags and names are unique.
+// The automatic placements also avoid the tags for which `is_taken` returns true, such as the reserved ones and those of the existing fields and oneofs.
+pub(crate) fn resolve_mixins(
+  item: &str,
+  fields: Vec<(u32, Field)>,
+  mixins: &[(Mixin, MixinPlacement)],
+  band: u32,
+  is_taken: impl Fn(u32) -> bool,
+) -> Result<ResolvedFields, MixinError> {
+  let mut all_fields: Vec<(u32, Field, Option<&str>)> = fields
+    .into_iter()
+    .map(|(tag, field)| (tag, field, None))
+    .collect();
+
+  let mut bases: Vec<Option<u32>> = mixins
+    .iter()
+    .map(|(_, placement)| match placement {
+      MixinPlacement::At(base) => Some(*base),
+      MixinPlacement::Auto => None,
+    })
+    .collect();
+
+  // The fixed placements are known in advance, so the automatic ones are placed around them
+  let mut used_tags: Vec<u32> = all_fields.iter().map(|(tag, _, _)| *tag).collect();
+
+  for ((mixin, _), base) in mixins.iter().zip(bases.iter()) {
+    if let Some(base) = base {
+      let tags = placed_tags(mixin, *base)
+        .filter(|tags| tags.iter().all(|tag| is_valid_tag(*tag)))
+        .ok_or_else(|| MixinError::InvalidPlacement {
+          item: item.into(),
+          mixin: mixin.name.clone(),
+          base: *base,
+        })?;
+
+      used_tags.extend(tags);
+    }
+  }
+
+  for ((mixin, _), base) in mixins.iter().zip(bases.iter_mut()) {
+    if base.is_some() {
+      continue;
+    }
+
+    // The tag 0 is not a valid field number
+    let mut candidate = band.max(1);
+
+    loop {
+      // Once the last tag of the mixin goes past the maximum field number, none of the next candidates can be used
+      let Some(tags) = placed_tags(mixin, candidate) else {
+        return Err(MixinError::NoFreeTags {
+          item: item.into(),
+          mixin: mixin.name.clone(),
+          band,
+        });
+      };
+
+      let is_free = tags
+        .iter()
+        .all(|tag| is_valid_tag(*tag) && !used_tags.contains(tag) && !is_taken(*tag));
+
+      if is_free {
+        used_tags.extend(tags);
+        *base = Some(candidate);
+        break;
+      }
+
+      candidate += 1;
+    }
+  }
+
+  let mut applied = Vec::new();
+
+  for ((mixin, _), base) in mixins.iter().zip(bases) {
+    let base = base.unwrap_or(band);
+
+    applied.push(AppliedMixin {
+      mixin: mixin.name.clone(),
+      base,
+      fields: mixin
+        .fields
+        .iter()
+        .map(|(tag, field)| (base + tag, field.name.as_ref().into()))
+        .collect(),
+    });
+
+    for (tag, field) in mixin.fields.iter() {
+      all_fields.push((base + tag, field.clone(), Some(mixin.name.as_ref())));
+    }
+  }
+
+  for (i, (tag, field, mixin)) in all_fields.iter().enumerate() {
+    let previous = &all_fields[..i];
+
+    if let Some((_, other, other_mixin)) = previous.iter().find(|(t, _, _)| t == tag) {
+      return Err(MixinError::TagCollision {
+        item: item.into(),
+        tag: *tag,
+        first: describe(&other.name, *other_mixin),
+        second: describe(&field.name, *mixin),
+      });
+    }
+
+    if let Some((_, other, other_mixin)) = previous.iter().find(|(_, f, _)| f.name == field.name) {
+      return Err(MixinError::NameCollision {
+        item: item.into(),
+        name: field.name.as_ref().into(),
+        first: describe(&other.name, *other_mixin),
+        second: describe(&field.name, *mixin),
+      });
+    }
+  }
+
+  Ok(ResolvedFields {
+    fields: all_fields
+      .into_iter()
+      .map(|(tag, field, _)| (tag, field))
+      .collect(),
+    mixins: applied,
+  })
+}
@@ -6,6 +6,7 @@ pub(crate) use oneof_builder::*;
 use crate::{
   common::VALIDATE_PROTO_FILE,
   fields::{self, Field, FieldBuilder, FieldData},
+  options::merge_options,
   OptionValue, ProtoOption,
 };
 
@@ -35,7 +36,7 @@ impl<S: oneof_builder::State> OneofBuilder<S> {
     self
       .add_imports([VALIDATE_PROTO_FILE.clone()])
       .add_options([ProtoOption {
-        name: "(buf.validate.oneof).required",
+        name: "(buf.validate.oneof).required".into(),
         value: Arc::new(OptionValue::Bool(true)),
       }])
   }
@@ -68,11 +69,12 @@ impl<S: oneof_builder::State> OneofBuilder<S> {
   }
 
   /// Adds the given options to this oneof.
+  /// Identical options are only added once, and options with a message value and the same name are merged together.
   pub fn add_options<I>(mut self, options: I) -> OneofBuilder<S>
   where
     I: IntoIterator<Item = ProtoOption>,
   {
-    self.options.extend(options);
+    merge_options(&mut self.options, options);
     self
   }
 }
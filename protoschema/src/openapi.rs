@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::{
+  checks::{find_option, get_value},
+  fields::{field_json_name, FieldData, FieldKind},
+  http::{http_bindings, parse_template, HttpBinding},
+  jsonschema::{field_rules, Exporter, References},
+  rendering::{FileTemplate, MessageTemplate},
+  services::{ServiceData, ServiceHandler},
+  FieldType, OptionValue,
+};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+const OPERATION_METHODS: [&str; 8] = [
+  "get", "put", "post", "delete", "patch", "head", "options", "trace",
+];
+
+/// Builds an OpenAPI 3.1 document for the services defined in the given file templates.
+///
+/// Each handler with a `(google.api.http)` option (see [`HttpRule`](crate::http::HttpRule)) becomes an operation (one for each of its bindings, including the additional ones), identified by `<Service>_<Handler>`.
+/// The request and response bodies refer to the schemas in `components.schemas`, which are generated from all the messages and enums of the templates like in [`json_schemas`](crate::jsonschema::json_schemas), so they include the constraints of the validation rules.
+///
+/// The variables in the path template (such as `{name=users/*}`) become path parameters, with the schema of the field that they refer to.
+/// When the body is not `*`, the fields of the request that are not bound to the path or to the body become query parameters.
+pub fn openapi_document(files: &[FileTemplate], title: &str, version: &str) -> Json {
+  let exporter = Exporter::new(files, References::Components);
+  let mut messages: HashMap<String, &MessageTemplate> = HashMap::new();
+
+  for file in files {
+    collect_messages(&file.package, &file.messages, &mut messages);
+  }
+
+  let builder = OperationBuilder {
+    exporter: &exporter,
+    messages,
+  };
+
+  let mut paths = Map::new();
+
+  for service in files.iter().flat_map(|file| file.services.iter()) {
+    for handler in service.handlers.iter() {
+      let bindings = http_bindings(&handler.options);
+
+      // The custom methods that are not supported by OpenAPI are left out
+      let bindings = bindings
+        .iter()
+        .filter(|binding| OPERATION_METHODS.contains(&binding.method.as_ref()));
+
+      for (i, binding) in bindings.enumerate() {
+        let operation_id = if i == 0 {
+          format!("{}_{}", service.name, handler.name)
+        } else {
+          format!("{}_{}{}", service.name, handler.name, i)
+        };
+
+        let (path, operation) = builder.operation(service, handler, binding, operation_id);
+
+        if let Json::Object(path_item) = paths.entry(path).or_insert_with(|| json!({})) {
+          path_item.insert(binding.method.to_string(), operation);
+        }
+      }
+    }
+  }
+
+  json!({
+    "openapi": OPENAPI_VERSION,
+    "info": { "title": title, "version": version },
+    "paths": paths,
+    "components": { "schemas": exporter.components(files) },
+  })
+}
+
+fn collect_messages<'a>(
+  prefix: &str,
+  messages: &'a [MessageTemplate],
+  result: &mut HashMap<String, &'a MessageTemplate>,
+) {
+  for msg in messages {
+    let full_name = format!("{prefix}.{}", msg.name);
+
+    collect_messages(&full_name, &msg.messages, result);
+    result.insert(full_name, msg);
+  }
+}
+
+fn message_fields(msg: &MessageTemplate) -> impl Iterator<Item = &FieldData> {
+  msg
+    .fields
+    .iter()
+    .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()))
+    .map(|(_, field)| field)
+}
+
+fn segments_pattern(segments: &str) -> String {
+  let segments: Vec<String> = segments
+    .split('/')
+    .map(|segment| match segment {
+      "*" => "[^/]+".to_string(),
+      "**" => ".+".to_string(),
+      literal => regex_syntax::escape(literal),
+    })
+    .collect();
+
+  format!("^{}$", segments.join("/"))
+}
+
+// Query parameters can only hold single values or lists of them
+fn is_query_type(field_type: &FieldType) -> bool {
+  matches!(
+    field_type,
+    FieldType::Double
+      | FieldType::Float
+      | FieldType::Int64
+      | FieldType::Uint64
+      | FieldType::Int32
+      | FieldType::Fixed64
+      | FieldType::Fixed32
+      | FieldType::Bool
+      | FieldType::String
+      | FieldType::Bytes
+      | FieldType::Uint32
+      | FieldType::Enum(_)
+      | FieldType::Sfixed32
+      | FieldType::Sfixed64
+      | FieldType::Sint32
+      | FieldType::Sint64
+      | FieldType::Duration
+      | FieldType::Timestamp
+      | FieldType::FieldMask
+  )
+}
+
+struct OperationBuilder<'a> {
+  exporter: &'a Exporter<'a>,
+  // The messages defined in the templates, by their fully qualified name
+  messages: HashMap<String, &'a MessageTemplate>,
+}
+
+impl OperationBuilder<'_> {
+  fn operation(
+    &self,
+    service: &ServiceData,
+    handler: &ServiceHandler,
+    binding: &HttpBinding,
+    operation_id: String,
+  ) -> (String, Json) {
+    let request = handler.request.full_name_with_package.as_ref();
+    let response = handler.response.full_name_with_package.as_ref();
+
+    let (path, variables) = parse_template(&binding.path);
+    let mut parameters: Vec<Json> = Vec::new();
+
+    for variable in &variables {
+      let mut schema = self
+        .field_schema(request, &variable.field_path)
+        .unwrap_or_else(|| json!({ "type": "string" }));
+
+      if let (Some(segments), Json::Object(schema)) = (&variable.segments, &mut schema) {
+        schema.insert("pattern".into(), segments_pattern(segments).into());
+      }
+
+      parameters.push(json!({
+        "name": variable.field_path.as_ref(),
+        "in": "path",
+        "required": true,
+        "schema": schema,
+      }));
+    }
+
+    let mut operation = Map::new();
+
+    operation.insert("operationId".into(), operation_id.into());
+    operation.insert("tags".into(), json!([service.name.as_ref()]));
+
+    if find_option(&handler.options, "deprecated") == Some(&OptionValue::Bool(true)) {
+      operation.insert("deprecated".into(), true.into());
+    }
+
+    let body_schema = match binding.body.as_deref() {
+      Some("*") => Some(self.message_schema(request)),
+      body => {
+        let excluded: Vec<&str> = variables
+          .iter()
+          .map(|variable| variable.field_path.as_ref())
+          .chain(body)
+          .collect();
+
+        self.query_parameters(request, "", "", &excluded, &mut Vec::new(), &mut parameters);
+
+        body.and_then(|body| self.field_schema(request, body))
+      }
+    };
+
+    if !parameters.is_empty() {
+      operation.insert("parameters".into(), parameters.into());
+    }
+
+    if let Some(body_schema) = body_schema {
+      operation.insert(
+        "requestBody".into(),
+        json!({
+          "required": true,
+          "content": { "application/json": { "schema": body_schema } },
+        }),
+      );
+    }
+
+    let response_schema = binding
+      .response_body
+      .as_deref()
+      .and_then(|response_body| self.field_schema(response, response_body))
+      .unwrap_or_else(|| self.message_schema(response));
+
+    operation.insert(
+      "responses".into(),
+      json!({
+        "200": {
+          "description": "A successful response.",
+          "content": { "application/json": { "schema": response_schema } },
+        },
+      }),
+    );
+
+    (path, operation.into())
+  }
+
+  // The schema of a request or response, which refers to its component unless it is `google.protobuf.Empty`
+  fn message_schema(&self, message: &str) -> Json {
+    if message == "google.protobuf.Empty" {
+      json!({ "type": "object", "additionalProperties": false })
+    } else {
+      self.exporter.reference(message)
+    }
+  }
+
+  // Finds a field of a message, given its path (such as `book.name`)
+  fn find_field(&self, message: &str, field_path: &str) -> Option<&FieldData> {
+    let mut msg = *self.messages.get(message)?;
+    let mut segments = field_path.split('.').peekable();
+
+    loop {
+      let segment = segments.next()?;
+      let field = message_fields(msg).find(|field| field.name.as_ref() == segment)?;
+
+      if segments.peek().is_none() {
+        return Some(field);
+      }
+
+      let FieldType::Message(path) = &field.field_type else {
+        return None;
+      };
+
+      msg = self.messages.get(path.full_name_with_package.as_ref())?;
+    }
+  }
+
+  fn field_schema(&self, message: &str, field_path: &str) -> Option<Json> {
+    let field = self.find_field(message, field_path)?;
+
+    Some(self.exporter.field_schema(field, field_rules(field)))
+  }
+
+  // Adds the fields that are not bound to the path or to the body as query parameters, flattening the nested messages with dotted names
+  fn query_parameters(
+    &self,
+    message: &str,
+    prefix: &str,
+    json_prefix: &str,
+    excluded: &[&str],
+    visited: &mut Vec<String>,
+    parameters: &mut Vec<Json>,
+  ) {
+    let Some(msg) = self.messages.get(message) else {
+      return;
+    };
+
+    // Recursive messages cannot be flattened
+    if visited.iter().any(|name| name == message) {
+      return;
+    }
+
+    visited.push(message.to_string());
+
+    for field in message_fields(msg) {
+      let (field_path, json_path) = if prefix.is_empty() {
+        (field.name.to_string(), field_json_name(field))
+      } else {
+        (
+          format!("{prefix}.{}", field.name),
+          format!("{json_prefix}.{}", field_json_name(field)),
+        )
+      };
+
+      if excluded.contains(&field_path.as_str()) {
+        continue;
+      }
+
+      match &field.field_type {
+        FieldType::Message(path) if field.kind != FieldKind::Repeated => self.query_parameters(
+          &path.full_name_with_package,
+          &field_path,
+          &json_path,
+          excluded,
+          visited,
+          parameters,
+        ),
+        field_type if is_query_type(field_type) => {
+          let rules = field_rules(field);
+          let is_required = matches!(
+            rules.and_then(|rules| get_value(rules, "required")),
+            Some(OptionValue::Bool(true))
+          );
+
+          let mut parameter = json!({
+            "name": json_path,
+            "in": "query",
+            "schema": self.exporter.field_schema(field, rules),
+          });
+
+          if is_required && let Json::Object(parameter) = &mut parameter {
+            parameter.insert("required".into(), true.into());
+          }
+
+          parameters.push(parameter);
+        }
+        _ => {}
+      }
+    }
+
+    visited.pop();
+  }
+}
@@ -7,9 +7,9 @@ pub use crate::field_type::{Duration, Timestamp};
 /// A struct representing a protobuf option.
 /// The [`proto_option`] helper makes building these much easier.
 /// For building [`OptionValue`]s for options with a message type, try using the [`message_option`](crate::message_option) macro or the [`message_value`] helper. For lists, use the [`list_value`] helper. For options that have enum values, you can use the [`enum_option`](crate::enum_option) macro or the [`enum_values_list`] helper.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ProtoOption {
-  pub name: &'static str,
+  pub name: Arc<str>,
   pub value: Arc<OptionValue>,
 }
 
@@ -17,14 +17,14 @@ pub struct ProtoOption {
 /// For building [`OptionValue`]s for options with a message type, try using the [`message_option`](crate::message_option) macro or the [`message_value`] helper. For lists, use the [`list_value`] helper. For options that have enum values, you can use the [`enum_option`](crate::enum_option) macro or the [`enum_values_list`] helper.
 pub fn proto_option<T: Into<OptionValue>>(name: &'static str, value: T) -> ProtoOption {
   ProtoOption {
-    name,
+    name: name.into(),
     value: Arc::new(value.into()),
   }
 }
 
 /// An enum representing values for protobuf options.
 /// For building [`OptionValue`]s for options with a message type, try using the [`message_option`](crate::message_option) macro or the [`message_value`] helper. For lists, use the [`list_value`] helper. For options that have enum values, you can use the [`enum_option`](crate::enum_option) macro or the [`enum_values_list`] helper.
-#[derive(Clone, Debug, Template)]
+#[derive(Clone, Debug, PartialEq, Template)]
 #[template(path = "option_value.proto.j2")]
 pub enum OptionValue {
   Bool(bool),
@@ -105,7 +105,64 @@ option_value_conversion!(u32, Uint, as u64);
 option_value_conversion!(f64, Float);
 option_value_conversion!(f32, Float, as f64);
 
+/// Adds the given options to a list of options, following the merge semantics described in [`OptionValue::merge`].
+/// Identical options are only added once, and options with the same name and a message value are merged into a single option.
+/// If two options with the same name cannot be merged, they are both kept and reported as a conflict by [`Package::check`](crate::Package::check), unless the option is declared with [`Package::allow_repeated_option`](crate::Package::allow_repeated_option).
+pub(crate) fn merge_options<I>(list: &mut Vec<ProtoOption>, options: I)
+where
+  I: IntoIterator<Item = ProtoOption>,
+{
+  for option in options {
+    if let Some(existing) = list.iter_mut().find(|o| o.name == option.name)
+      && let Some(merged) = existing.value.merge(&option.value)
+    {
+      existing.value = Arc::new(merged);
+      continue;
+    }
+
+    list.push(option);
+  }
+}
+
 impl OptionValue {
+  /// Merges two option values, or returns `None` if they are in conflict.
+  ///
+  /// Identical values are kept as they are, message values are merged key by key (recursively), and lists are concatenated, skipping the items that are already present in the first list.
+  /// Unlike the protobuf merge, where the last scalar value wins and repeated values are always appended, two different scalar values (or values of different kinds) for the same key are a conflict.
+  pub fn merge(&self, other: &OptionValue) -> Option<OptionValue> {
+    if self == other {
+      return Some(self.clone());
+    }
+
+    match (self, other) {
+      (Self::Message(current), Self::Message(new)) => {
+        let mut merged: Vec<(Box<str>, OptionValue)> = current.to_vec();
+
+        for (name, value) in new.iter() {
+          if let Some((_, existing)) = merged.iter_mut().find(|(n, _)| n == name) {
+            *existing = existing.merge(value)?;
+          } else {
+            merged.push((name.clone(), value.clone()));
+          }
+        }
+
+        Some(Self::Message(merged.into_boxed_slice()))
+      }
+      (Self::List(current), Self::List(new)) => {
+        let mut merged: Vec<OptionValue> = current.to_vec();
+
+        for item in new.iter() {
+          if !merged.contains(item) {
+            merged.push(item.clone());
+          }
+        }
+
+        Some(Self::List(merged.into_boxed_slice()))
+      }
+      _ => None,
+    }
+  }
+
   pub(crate) fn is_short(&self) -> bool {
     match self {
       Self::List(list) => list.len() <= 5 && list.iter().all(OptionValue::is_short),
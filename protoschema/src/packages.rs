@@ -8,12 +8,24 @@ use std::{
 
 use askama::Template;
 
+#[cfg(feature = "serde")]
+use crate::{
+  errors::{EvaluationError, ExampleError},
+  evaluation::{evaluate_message, Violation},
+  examples::{generate_examples, Examples},
+  jsonschema::json_schemas,
+  openapi::openapi_document,
+};
+
 use crate::{
   enums::EnumData,
-  errors::TemplatingError,
+  errors::{DerivationError, SchemaError, SnapshotError, TemplatingError},
+  fields::FieldData,
   files::{FileBuilder, FileData},
   messages::MessageData,
-  rendering::FileTemplate,
+  passes::{Pass, Pipeline},
+  rendering::{ConstantsTemplate, FileTemplate},
+  schema::Schema,
   services::ServiceData,
 };
 
@@ -21,20 +33,66 @@ use crate::{
 pub(crate) type Arena = Rc<RefCell<PackageData>>;
 
 #[doc(hidden)]
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub(crate) struct PackageData {
   pub(crate) name: Arc<str>,
   pub(crate) files: Vec<FileData>,
   pub(crate) messages: Vec<MessageData>,
   pub(crate) enums: Vec<EnumData>,
   pub(crate) services: Vec<ServiceData>,
+  pub(crate) enforce_required_rules: bool,
+  pub(crate) repeated_options: Vec<Box<str>>,
+  // The passes of the package, shared by all of its builders
+  pub(crate) passes: Rc<RefCell<Pipeline>>,
+}
+
+impl PackageData {
+  // Applies the passes to a copy of the items of the package, and returns it with the violations that they reported
+  pub(crate) fn processed(&self) -> (PackageData, Vec<SchemaError>) {
+    let mut data = self.clone();
+    let violations = self.passes.borrow_mut().run(&mut data);
+
+    (data, violations)
+  }
+
+  pub(crate) fn build_templates(&self) -> Vec<FileTemplate> {
+    self.files.iter().map(|f| f.build_template(self)).collect()
+  }
+
+  /// Returns the index of the message with the given fully qualified name
+  pub(crate) fn message_id(&self, full_name_with_package: &str) -> Option<usize> {
+    self
+      .messages
+      .iter()
+      .position(|msg| msg.import_path.full_name_with_package.as_ref() == full_name_with_package)
+  }
+
+  /// Returns the index of a message, given its fully qualified name or its name within the package
+  #[cfg(feature = "serde")]
+  pub(crate) fn resolve_message(&self, name: &str) -> Option<usize> {
+    self
+      .message_id(name)
+      .or_else(|| self.message_id(&format!("{}.{}", self.name, name)))
+  }
+
+  /// Returns a field of a message (including the fields inside of oneofs) by its name
+  pub(crate) fn find_field(&self, msg_id: usize, name: &str) -> Option<&FieldData> {
+    let msg = &self.messages[msg_id];
+
+    msg
+      .fields
+      .iter()
+      .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()))
+      .map(|(_, field)| field)
+      .find(|field| field.name.as_ref() == name)
+  }
 }
 
 /// A struct representing a protobuf package.
 #[derive(Clone)]
 pub struct Package {
   path: Box<str>,
-  data: Arena,
+  pub(crate) data: Arena,
 }
 
 impl Package {
@@ -54,6 +112,58 @@ impl Package {
     }
   }
 
+  /// Makes [`check`](Package::check) report the fields marked as `REQUIRED` with the `(google.api.field_behavior)` option which do not also have the `required` validation rule, so that the requirement is enforced by protovalidate as well.
+  pub fn enforce_required_rules(self) -> Self {
+    self.data.borrow_mut().enforce_required_rules = true;
+    self
+  }
+
+  /// Declares a custom option whose type is a repeated field, so that it can be defined multiple times on the same item with different values.
+  /// Otherwise, [`check`](Package::check) reports the options with the same name that cannot be merged as conflicting.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use protoschema::{message, proto_option, string, Package};
+  ///
+  /// let package = Package::new("myapp.v1").allow_repeated_option("(myapp.tags)");
+  /// let file = package.new_file("user");
+  ///
+  /// message!(
+  ///   file.new_message("User"),
+  ///   1 => string!("name")
+  ///     .add_option(proto_option("(myapp.tags)", "a"))
+  ///     .add_option(proto_option("(myapp.tags)", "b")),
+  /// );
+  ///
+  /// assert!(package.check().is_empty());
+  /// ```
+  pub fn allow_repeated_option<T: AsRef<str>>(self, name: T) -> Self {
+    self
+      .data
+      .borrow_mut()
+      .repeated_options
+      .push(name.as_ref().into());
+    self
+  }
+
+  /// Adds a [`Pass`] at the end of the pipeline of this package.
+  /// The passes run in order before the templates are built or the package is checked, so the options that they add are part of the generated files and the violations that they report are returned by [`check`](Package::check).
+  /// The passes are applied to a copy of the items each time, so the items added after the package was checked are visited as well.
+  pub fn with_pass<P: Pass + 'static>(self, pass: P) -> Self {
+    self.data.borrow().passes.borrow_mut().push(Box::new(pass));
+    self
+  }
+
+  /// Returns a read-only [`Schema`] with the items of this package after the passes were applied to them, which can be used to inspect their results.
+  /// The items of the package itself are not modified, and the violations reported by the passes are returned by [`check`](Package::check).
+  pub fn run_passes(&self) -> Schema {
+    self.processed_data().0.schema()
+  }
+
+  pub(crate) fn processed_data(&self) -> (PackageData, Vec<SchemaError>) {
+    self.data.borrow().processed()
+  }
+
   /// Creates a new file belonging to this package.
   /// The ".proto" suffix is added automatically to the name being given.
   pub fn new_file<T: AsRef<str>>(&self, name: T) -> FileBuilder {
@@ -74,19 +184,169 @@ impl Package {
   /// This is only useful if you want to manually process the template's data.
   /// To write the templates directly, use [`render_templates`](crate::packages::Package::render_templates)
   pub fn build_templates(&self) -> Vec<FileTemplate> {
-    let arena = self.data.borrow_mut();
-    let templates: Vec<FileTemplate> = arena
-      .files
-      .iter()
-      .map(|f| f.build_template(&arena))
-      .collect();
-    templates
+    self.processed_data().0.build_templates()
+  }
+
+  /// Checks the definitions in this package for issues that would cause the generated files to be rejected by protoc or protovalidate, such as options defined multiple times with conflicting values, and returns the list of diagnostics.
+  /// This is automatically called by [`render_templates`](crate::packages::Package::render_templates).
+  ///
+  /// The passes of the package are applied first, and the violations that they report are included in the list.
+  pub fn check(&self) -> Vec<SchemaError> {
+    let (data, mut errors) = self.processed_data();
+    errors.extend(data.check());
+    errors
+  }
+
+  // Applies the passes once, and builds the templates from their result if it does not contain any of the issues reported by `check`
+  pub(crate) fn checked_templates(&self) -> Result<Vec<FileTemplate>, Vec<SchemaError>> {
+    let (data, mut errors) = self.processed_data();
+    errors.extend(data.check());
+
+    if !errors.is_empty() {
+      return Err(errors);
+    }
+
+    Ok(data.build_templates())
+  }
+
+  /// Returns a read-only [`Schema`] with the current items of this package, which can be used to inspect them without building the templates.
+  /// The schema is a snapshot, so it does not reflect the items added to the package afterwards.
+  pub fn schema(&self) -> Schema {
+    self.data.borrow().schema()
+  }
+
+  /// Rebuilds a package from a [`Schema`], such as a snapshot that was serialized with the `serde` feature and loaded again.
+  ///
+  /// The rebuilt package renders the same files as the package that the schema was taken from, and it can be extended like any other package.
+  /// Only the resulting items are part of a schema, so the lineage of the derived messages, the mixins and the generic messages that were used to build them are not restored.
+  ///
+  /// An error is returned if an item refers to another one that is not defined in the schema, or if an item does not belong to the package of the schema.
+  pub fn from_schema(schema: &Schema) -> Result<Self, SnapshotError> {
+    let package = Package::new(&schema.package);
+    *package.data.borrow_mut() = schema.package_data()?;
+
+    Ok(package)
+  }
+
+  /// Applies the derivations of the messages created with [`derive_from`](crate::messages::MessageBuilder::derive_from) again, if their source has changed since then, so that they reflect the current fields of their source.
+  /// The fields added to a derived message separately, such as with [`add_fields`](crate::messages::MessageBuilder::add_fields), are kept as they are.
+  pub fn propagate_derivations(&self) -> Result<(), DerivationError> {
+    self.data.borrow_mut().propagate_derivations()
+  }
+
+  /// Validates an instance of a message, given in the proto3 JSON format, against all of the validation rules defined for it (and for the messages it contains), and returns the list of violations, in the same shape used by protovalidate.
+  ///
+  /// The message can be referred to with its fully qualified name (`myapp.v1.User`) or with its name within the package (`User`, `User.Address`).
+  /// This makes it possible to test the validation rules of a schema without a protovalidate runtime.
+  ///
+  /// The standard rules (such as `string.min_len` or `repeated.unique`), the Cel rules and the predefined rules defined in this package are all evaluated, including the ones added by the passes of the package.
+  /// An error is returned if the instance does not match the definition of the message, or if a Cel rule cannot be evaluated.
+  #[cfg(feature = "serde")]
+  pub fn evaluate(
+    &self,
+    message: &str,
+    instance: &serde_json::Value,
+  ) -> Result<Vec<Violation>, EvaluationError> {
+    let (package, _) = self.processed_data();
+
+    let msg_id = package
+      .resolve_message(message)
+      .ok_or_else(|| EvaluationError::UnknownMessage(message.into()))?;
+
+    evaluate_message(&package, msg_id, instance)
+  }
+
+  /// Generates example instances of a message, in the proto3 JSON format, from its validation rules.
+  ///
+  /// The result contains an instance that satisfies all the rules of the message, and a list of instances that violate exactly one rule each, together with the violation that they produce.
+  /// These can be used as golden fixtures, or as inputs for the tests of the services that use this schema. With the `proptest` feature, the [`examples`](crate::examples) module also provides strategies that generate many different instances.
+  ///
+  /// All the instances are checked with [`evaluate`](Package::evaluate). Cel rules are not used to generate the values, so an error is returned if the generated instance does not satisfy them.
+  #[cfg(feature = "serde")]
+  pub fn examples(&self, message: &str) -> Result<Examples, ExampleError> {
+    let (package, _) = self.processed_data();
+
+    let msg_id = package
+      .resolve_message(message)
+      .ok_or_else(|| ExampleError::UnknownMessage(message.into()))?;
+
+    generate_examples(&package, msg_id, 0)
+  }
+
+  /// Builds a JSON Schema document (draft 2020-12) for each top level message and enum of this package, describing their instances in the proto3 JSON format.
+  ///
+  /// 64-bit integers are represented as strings, timestamps as `date-time` strings, oneofs with `oneOf` and maps with `additionalProperties`. The validation rules that have a JSON Schema equivalent, such as `min_len` or `unique`, are translated as well.
+  /// See [`json_schemas`](crate::jsonschema::json_schemas) for more details.
+  #[cfg(feature = "serde")]
+  pub fn json_schemas(&self) -> Vec<serde_json::Value> {
+    json_schemas(&self.build_templates())
+  }
+
+  /// Builds an OpenAPI 3.1 document for the services of this package, where each handler with a `(google.api.http)` option becomes an operation.
+  ///
+  /// The path and query parameters are derived from the binding and from the request message, and the schemas of the messages (including the constraints from their validation rules) are defined in the components of the document.
+  /// See [`openapi_document`](crate::openapi::openapi_document) for more details.
+  #[cfg(feature = "serde")]
+  pub fn openapi(&self, title: &str, version: &str) -> serde_json::Value {
+    openapi_document(&self.build_templates(), title, version)
+  }
+
+  /// Builds the template for a Rust module with the constants of this package, which can be used to refer to its items from the application code without repeating their names and numbers.
+  /// To write it directly, use [`render_constants`](crate::packages::Package::render_constants).
+  pub fn build_constants_template(&self) -> ConstantsTemplate {
+    self.processed_data().0.build_constants_template()
+  }
+
+  /// Writes a Rust module with the constants of this package to the given path, such as a file in the `OUT_DIR` of a build script.
+  ///
+  /// Each message becomes a module (named after it in snake case) with its fully qualified name and the names, numbers and JSON names of its fields, in the `fields`, `numbers` and `json_names` modules. Its nested messages and enums are defined inside of it.
+  /// Each enum becomes a module with its fully qualified name and the number of each of its values.
+  ///
+  /// The file only contains the items, so it can be placed in a module with `include!`.
+  ///
+  /// # Examples
+  /// ```rust
+  /// use protoschema::{message, string, uint64, Package};
+  ///
+  /// let package = Package::new("myapp.v1");
+  /// let file = package.new_file("user");
+  ///
+  /// message!(file.new_message("User"), 1 => uint64!("id"), 2 => string!("display_name"));
+  ///
+  /// let constants = package.build_constants_template().to_string();
+  ///
+  /// assert!(constants.contains(r#"pub const FULL_NAME: &str = "myapp.v1.User";"#));
+  /// assert!(constants.contains("pub const DISPLAY_NAME: u32 = 2;"));
+  /// assert!(constants.contains(r#"pub const DISPLAY_NAME: &str = "displayName";"#));
+  /// ```
+  pub fn render_constants(&self, path: &Path) -> Result<(), TemplatingError> {
+    let template = self.build_constants_template();
+
+    if let Some(parent) = path.parent() {
+      create_dir_all(parent).map_err(|e| TemplatingError::DirCreationFailure {
+        dir: parent.to_path_buf(),
+        source: e,
+      })?;
+    }
+
+    let mut file = File::create(path).map_err(|e| TemplatingError::FileCreationFailure {
+      file: path.to_path_buf(),
+      source: e,
+    })?;
+
+    template
+      .write_into(&mut file)
+      .map_err(|e| TemplatingError::TemplateWritingFailure {
+        file: path.to_path_buf(),
+        source: e,
+      })
   }
 
   /// Writes the protobuf files defined in this Package schema.
   ///
   /// The only argument it accepts is the proto_root, namely the root directory for the protobuf project.
   /// It will write the files by joining the root to the file names.
+  /// If [`check`](crate::packages::Package::check) finds any issues in the schema, no file is written and a [`TemplatingError::InvalidSchema`] is returned.
   ///
   /// # Examples
   /// With this input:
@@ -99,7 +359,9 @@ impl Package {
   #[doc = include_str!("../proto/myapp/v1/user.proto")]
   /// ```
   pub fn render_templates(&self, proto_root: &Path) -> Result<(), TemplatingError> {
-    let templates = self.build_templates();
+    let templates = self
+      .checked_templates()
+      .map_err(TemplatingError::InvalidSchema)?;
 
     for template in templates {
       let path = proto_root.join(template.name.as_ref());
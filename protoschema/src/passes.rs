@@ -0,0 +1,641 @@
+use std::{fmt, sync::Arc};
+
+use convert_case::{Case, Casing};
+
+use crate::{
+  common::deprecated,
+  errors::SchemaError,
+  field_type::ImportedItemPath,
+  fields::{FieldData, FieldKind},
+  options::merge_options,
+  packages::PackageData,
+  FieldType, OptionValue, ProtoOption,
+};
+
+/// A pass that inspects and modifies the items of a package before its templates are built, used to apply organization-wide policies.
+///
+/// Each method is called once for each item of the corresponding kind, and does nothing by default.
+/// The passes configured with [`Package::with_pass`](crate::Package::with_pass) are applied to a copy of the items of the package each time that the templates are built or the package is checked, so the items defined by the package are never modified by them.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{message, passes::{Node, NodeKind, Pass}, string, Package};
+///
+/// // Requires validation rules on every string field
+/// struct StringRules;
+///
+/// impl Pass for StringRules {
+///   fn visit_field(&mut self, field: &mut Node) {
+///     if field.field_type() == Some(&protoschema::FieldType::String) && !field.has_option("(buf.validate.field)") {
+///       field.report("string fields must have validation rules");
+///     }
+///   }
+/// }
+///
+/// let my_pkg = Package::new("my_pkg").with_pass(StringRules);
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// message!(my_file.new_message("User"), 1 => string!("name"), 2 => string!("email", |v| v.email()));
+///
+/// let errors = my_pkg.check();
+///
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].to_string(), "my_pkg.User.name: string fields must have validation rules");
+/// ```
+pub trait Pass {
+  /// Visits a file
+  fn visit_file(&mut self, _file: &mut Node) {}
+  /// Visits a message, including the nested ones
+  fn visit_message(&mut self, _message: &mut Node) {}
+  /// Visits a field of a message, including the fields inside of oneofs
+  fn visit_field(&mut self, _field: &mut Node) {}
+  /// Visits an enum, including the nested ones
+  fn visit_enum(&mut self, _enum: &mut Node) {}
+  /// Visits a variant of an enum
+  fn visit_variant(&mut self, _variant: &mut Node) {}
+  /// Visits a service
+  fn visit_service(&mut self, _service: &mut Node) {}
+  /// Visits a handler of a service
+  fn visit_handler(&mut self, _handler: &mut Node) {}
+}
+
+/// The kind of item visited by a [`Pass`], with the details that are specific to it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind<'a> {
+  File,
+  Message,
+  Field {
+    tag: u32,
+    field_type: &'a FieldType,
+    cardinality: FieldKind,
+    /// The name of the oneof that contains the field, if there is one
+    oneof: Option<&'a str>,
+  },
+  Enum,
+  Variant {
+    number: i32,
+  },
+  Service,
+  Handler {
+    /// The fully qualified name of the request message
+    request: &'a str,
+    /// The fully qualified name of the response message
+    response: &'a str,
+  },
+}
+
+// The state shared by the nodes visited during a pass
+struct Context {
+  package: Arc<str>,
+  imports: Vec<Arc<str>>,
+  violations: Vec<SchemaError>,
+}
+
+/// An item visited by a [`Pass`], whose options can be modified
+pub struct Node<'a> {
+  pub kind: NodeKind<'a>,
+  /// The name of the item, as it appears in the file. The names of enum variants include the prefix with the name of the enum.
+  pub name: &'a str,
+  /// The fully qualified name of the item (such as `myapp.v1.User.email`), or the path of the file for files
+  pub full_name: &'a str,
+  /// The path of the file that contains the item
+  pub file: &'a str,
+  options: &'a mut Vec<ProtoOption>,
+  context: &'a mut Context,
+}
+
+impl Node<'_> {
+  /// Returns the name of the package
+  pub fn package(&self) -> &str {
+    &self.context.package
+  }
+
+  /// Returns the type of the field, if this node is a field
+  pub fn field_type(&self) -> Option<&FieldType> {
+    match self.kind {
+      NodeKind::Field { field_type, .. } => Some(field_type),
+      _ => None,
+    }
+  }
+
+  /// Checks if the name of the item matches a pattern, where `*` matches any sequence of characters (such as `*password*`)
+  pub fn name_matches(&self, pattern: &str) -> bool {
+    wildcard_match(pattern, self.name)
+  }
+
+  /// Returns the options of the item
+  pub fn options(&self) -> &[ProtoOption] {
+    self.options
+  }
+
+  /// Returns the value of an option, if it is defined for this item
+  pub fn option(&self, name: &str) -> Option<&OptionValue> {
+    self
+      .options
+      .iter()
+      .find(|option| &*option.name == name)
+      .map(|option| option.value.as_ref())
+  }
+
+  /// Checks if an option is defined for this item
+  pub fn has_option(&self, name: &str) -> bool {
+    self.option(name).is_some()
+  }
+
+  /// Adds some options to the item. Options that are already defined are merged with the new values.
+  pub fn add_options<I>(&mut self, options: I)
+  where
+    I: IntoIterator<Item = ProtoOption>,
+  {
+    merge_options(self.options, options);
+  }
+
+  /// Removes all of the definitions of an option from the item
+  pub fn remove_option(&mut self, name: &str) {
+    self.options.retain(|option| &*option.name != name);
+  }
+
+  /// Adds an import to the file that contains the item, such as the file that defines an option that was added
+  pub fn add_import<T: AsRef<str>>(&mut self, import: T) {
+    self.context.imports.push(import.as_ref().into());
+  }
+
+  /// Reports a violation of a policy for this item, which is returned by [`Package::check`](crate::Package::check)
+  pub fn report<T: AsRef<str>>(&mut self, message: T) {
+    self.context.violations.push(SchemaError::PolicyViolation {
+      item: self.full_name.into(),
+      message: message.as_ref().into(),
+    });
+  }
+}
+
+// Matches a name against a pattern where `*` stands for any sequence of characters
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+  let parts: Vec<&str> = pattern.split('*').collect();
+
+  if parts.len() == 1 {
+    return pattern == name;
+  }
+
+  let (first, last) = (parts[0], parts[parts.len() - 1]);
+
+  if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+    return false;
+  }
+
+  let mut rest = &name[first.len()..name.len() - last.len()];
+
+  for part in &parts[1..parts.len() - 1] {
+    match rest.find(part) {
+      Some(index) => rest = &rest[index + part.len()..],
+      None => return false,
+    }
+  }
+
+  true
+}
+
+/// A list of passes that are applied in order.
+///
+/// A pipeline is also a [`Pass`], so pipelines can be composed. When it is used as a pass, its passes are applied to each item in turn.
+#[derive(Default)]
+pub struct Pipeline {
+  passes: Vec<Box<dyn Pass>>,
+}
+
+impl fmt::Debug for Pipeline {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Pipeline")
+      .field("passes", &self.passes.len())
+      .finish()
+  }
+}
+
+impl Pipeline {
+  /// Creates a new empty pipeline
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a pass at the end of this pipeline
+  pub fn pass<P: Pass + 'static>(mut self, pass: P) -> Self {
+    self.passes.push(Box::new(pass));
+    self
+  }
+
+  /// Returns the number of passes in this pipeline
+  pub fn len(&self) -> usize {
+    self.passes.len()
+  }
+
+  /// Checks if this pipeline has no passes
+  pub fn is_empty(&self) -> bool {
+    self.passes.is_empty()
+  }
+
+  pub(crate) fn push(&mut self, pass: Box<dyn Pass>) {
+    self.passes.push(pass);
+  }
+
+  // Runs each pass over the whole package, so that every pass sees the changes made by the previous ones
+  pub(crate) fn run(&mut self, package: &mut PackageData) -> Vec<SchemaError> {
+    self
+      .passes
+      .iter_mut()
+      .flat_map(|pass| package.run_pass(pass.as_mut()))
+      .collect()
+  }
+}
+
+impl Pass for Pipeline {
+  fn visit_file(&mut self, file: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_file(file));
+  }
+
+  fn visit_message(&mut self, message: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_message(message));
+  }
+
+  fn visit_field(&mut self, field: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_field(field));
+  }
+
+  fn visit_enum(&mut self, enum_: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_enum(enum_));
+  }
+
+  fn visit_variant(&mut self, variant: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_variant(variant));
+  }
+
+  fn visit_service(&mut self, service: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_service(service));
+  }
+
+  fn visit_handler(&mut self, handler: &mut Node) {
+    self
+      .passes
+      .iter_mut()
+      .for_each(|pass| pass.visit_handler(handler));
+  }
+}
+
+type NodeFilter = Box<dyn Fn(&Node) -> bool>;
+
+/// A pass that adds some options to all of the items selected by a filter.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{common::debug_redact, message, passes::{AddOptions, NodeKind}, string, Package};
+///
+/// let my_pkg = Package::new("my_pkg").with_pass(AddOptions::new(
+///   |node| matches!(node.kind, NodeKind::Field { .. }) && node.name_matches("*password*"),
+///   [debug_redact()],
+/// ));
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// message!(my_file.new_message("User"), 1 => string!("password"), 2 => string!("repeated_password"));
+///
+/// let schema = my_pkg.run_passes();
+/// assert!(schema.find_message("User").unwrap().field("repeated_password").unwrap().option("debug_redact").is_some());
+/// ```
+pub struct AddOptions {
+  filter: NodeFilter,
+  options: Vec<ProtoOption>,
+}
+
+impl AddOptions {
+  /// Creates a new pass that adds the given options to the items for which the filter returns true
+  pub fn new<F, I>(filter: F, options: I) -> Self
+  where
+    F: Fn(&Node) -> bool + 'static,
+    I: IntoIterator<Item = ProtoOption>,
+  {
+    AddOptions {
+      filter: Box::new(filter),
+      options: options.into_iter().collect(),
+    }
+  }
+
+  fn apply(&self, node: &mut Node) {
+    if (self.filter)(node) {
+      node.add_options(self.options.clone());
+    }
+  }
+}
+
+impl Pass for AddOptions {
+  fn visit_file(&mut self, file: &mut Node) {
+    self.apply(file);
+  }
+
+  fn visit_message(&mut self, message: &mut Node) {
+    self.apply(message);
+  }
+
+  fn visit_field(&mut self, field: &mut Node) {
+    self.apply(field);
+  }
+
+  fn visit_enum(&mut self, enum_: &mut Node) {
+    self.apply(enum_);
+  }
+
+  fn visit_variant(&mut self, variant: &mut Node) {
+    self.apply(variant);
+  }
+
+  fn visit_service(&mut self, service: &mut Node) {
+    self.apply(service);
+  }
+
+  fn visit_handler(&mut self, handler: &mut Node) {
+    self.apply(handler);
+  }
+}
+
+/// A pass that reports the fields selected by a filter which do not have any validation rules, such as all of the string fields.
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{message, passes::RequireRules, string, FieldType, Package};
+///
+/// let my_pkg = Package::new("my_pkg")
+///   .with_pass(RequireRules::new(|field| field.field_type() == Some(&FieldType::String)));
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// message!(my_file.new_message("User"), 1 => string!("name"));
+///
+/// assert_eq!(my_pkg.check()[0].to_string(), "my_pkg.User.name: the field does not have any validation rules");
+/// ```
+pub struct RequireRules {
+  filter: NodeFilter,
+}
+
+impl RequireRules {
+  /// Creates a new pass that reports the fields without validation rules for which the filter returns true
+  pub fn new<F>(filter: F) -> Self
+  where
+    F: Fn(&Node) -> bool + 'static,
+  {
+    RequireRules {
+      filter: Box::new(filter),
+    }
+  }
+}
+
+impl Pass for RequireRules {
+  fn visit_field(&mut self, field: &mut Node) {
+    if (self.filter)(field) && !field.has_option("(buf.validate.field)") {
+      field.report("the field does not have any validation rules");
+    }
+  }
+}
+
+/// A pass that marks every item of the package as deprecated, with the `deprecated` option
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deprecate;
+
+impl Pass for Deprecate {
+  fn visit_file(&mut self, file: &mut Node) {
+    file.add_options([deprecated()]);
+  }
+
+  fn visit_message(&mut self, message: &mut Node) {
+    message.add_options([deprecated()]);
+  }
+
+  fn visit_field(&mut self, field: &mut Node) {
+    field.add_options([deprecated()]);
+  }
+
+  fn visit_enum(&mut self, enum_: &mut Node) {
+    enum_.add_options([deprecated()]);
+  }
+
+  fn visit_variant(&mut self, variant: &mut Node) {
+    variant.add_options([deprecated()]);
+  }
+
+  fn visit_service(&mut self, service: &mut Node) {
+    service.add_options([deprecated()]);
+  }
+
+  fn visit_handler(&mut self, handler: &mut Node) {
+    handler.add_options([deprecated()]);
+  }
+}
+
+// Visits a field, whose options are moved into a list for the duration of the visit
+fn visit_field(
+  pass: &mut dyn Pass,
+  context: &mut Context,
+  message: &ImportedItemPath,
+  tag: u32,
+  field: &mut FieldData,
+  oneof: Option<&str>,
+) {
+  let full_name = format!("{}.{}", message.full_name_with_package, field.name);
+  let mut options = std::mem::take(&mut field.options).into_vec();
+
+  pass.visit_field(&mut Node {
+    kind: NodeKind::Field {
+      tag,
+      field_type: &field.field_type,
+      cardinality: field.kind,
+      oneof,
+    },
+    name: &field.name,
+    full_name: &full_name,
+    file: &message.file,
+    options: &mut options,
+    context,
+  });
+
+  field.options = options.into_boxed_slice();
+}
+
+impl PackageData {
+  // Applies a pass to all of the items of the package, and returns the violations that it reported
+  pub(crate) fn run_pass(&mut self, pass: &mut dyn Pass) -> Vec<SchemaError> {
+    let mut context = Context {
+      package: self.name.clone(),
+      imports: Vec::new(),
+      violations: Vec::new(),
+    };
+
+    // The derived messages that are up to date before the pass are kept that way,
+    // since the pass is applied to their fields as well
+    let synced_derivations: Vec<usize> = self
+      .messages
+      .iter()
+      .enumerate()
+      .filter(|(_, msg)| {
+        msg
+          .lineage
+          .as_ref()
+          .is_some_and(|lineage| self.messages[lineage.source_id].fields == lineage.snapshot)
+      })
+      .map(|(msg_id, _)| msg_id)
+      .collect();
+
+    let mut imports: Vec<(Arc<str>, Vec<Arc<str>>)> = Vec::new();
+
+    for file in self.files.iter_mut() {
+      let name = file.name.clone();
+
+      pass.visit_file(&mut Node {
+        kind: NodeKind::File,
+        name: &name,
+        full_name: &name,
+        file: &name,
+        options: &mut file.options,
+        context: &mut context,
+      });
+
+      imports.push((name, std::mem::take(&mut context.imports)));
+    }
+
+    for msg in self.messages.iter_mut() {
+      let path = msg.import_path.clone();
+
+      pass.visit_message(&mut Node {
+        kind: NodeKind::Message,
+        name: &msg.name,
+        full_name: &path.full_name_with_package,
+        file: &path.file,
+        options: &mut msg.options,
+        context: &mut context,
+      });
+
+      for (tag, field) in msg.fields.iter_mut() {
+        visit_field(pass, &mut context, &path, *tag, field, None);
+      }
+
+      for oneof in msg.oneofs.iter_mut() {
+        for (tag, field) in oneof.fields.iter_mut() {
+          visit_field(pass, &mut context, &path, *tag, field, Some(&oneof.name));
+        }
+      }
+
+      imports.push((path.file.clone(), std::mem::take(&mut context.imports)));
+    }
+
+    for enum_ in self.enums.iter_mut() {
+      let path = enum_.import_path.clone();
+      let mut options = std::mem::take(&mut enum_.options).into_vec();
+
+      pass.visit_enum(&mut Node {
+        kind: NodeKind::Enum,
+        name: &enum_.name,
+        full_name: &path.full_name_with_package,
+        file: &path.file,
+        options: &mut options,
+        context: &mut context,
+      });
+
+      enum_.options = options.into_boxed_slice();
+
+      let prefix = enum_.name.to_case(Case::UpperSnake);
+
+      for (number, variant) in enum_.variants.iter_mut() {
+        let name = format!("{}_{}", prefix, variant.name);
+        let full_name = format!("{}.{}", path.full_name_with_package, name);
+        let mut options = variant.options.to_vec();
+
+        pass.visit_variant(&mut Node {
+          kind: NodeKind::Variant { number: *number },
+          name: &name,
+          full_name: &full_name,
+          file: &path.file,
+          options: &mut options,
+          context: &mut context,
+        });
+
+        variant.options = options.into();
+      }
+
+      imports.push((path.file.clone(), std::mem::take(&mut context.imports)));
+    }
+
+    for (service_id, service) in self.services.iter_mut().enumerate() {
+      let file = self
+        .files
+        .iter()
+        .find(|file| file.services.contains(&service_id))
+        .map(|file| file.name.clone())
+        .unwrap_or_default();
+      let full_name = format!("{}.{}", self.name, service.name);
+      let mut options = std::mem::take(&mut service.options).into_vec();
+
+      pass.visit_service(&mut Node {
+        kind: NodeKind::Service,
+        name: &service.name,
+        full_name: &full_name,
+        file: &file,
+        options: &mut options,
+        context: &mut context,
+      });
+
+      service.options = options.into_boxed_slice();
+
+      for handler in service.handlers.iter_mut() {
+        let handler_name = format!("{}.{}", full_name, handler.name);
+        let mut options = std::mem::take(&mut handler.options).into_vec();
+
+        pass.visit_handler(&mut Node {
+          kind: NodeKind::Handler {
+            request: &handler.request.full_name_with_package,
+            response: &handler.response.full_name_with_package,
+          },
+          name: &handler.name,
+          full_name: &handler_name,
+          file: &file,
+          options: &mut options,
+          context: &mut context,
+        });
+
+        handler.options = options.into_boxed_slice();
+      }
+
+      imports.push((file, std::mem::take(&mut context.imports)));
+    }
+
+    for (file_name, file_imports) in imports {
+      if let Some(file) = self.files.iter_mut().find(|file| file.name == file_name) {
+        for import in file_imports.iter() {
+          file.conditionally_add_import(import);
+        }
+      }
+    }
+
+    for msg_id in synced_derivations {
+      let source_id = self.messages[msg_id].lineage.as_ref().unwrap().source_id;
+      let snapshot = self.messages[source_id].fields.clone();
+
+      if let Some(lineage) = self.messages[msg_id].lineage.as_mut() {
+        lineage.snapshot = snapshot;
+      }
+    }
+
+    context.violations
+  }
+}
@@ -1,4 +1,4 @@
-use std::{ops::Range, sync::Arc};
+use std::{collections::HashSet, ops::Range, sync::Arc};
 
 use askama::Template;
 use convert_case::{Case, Casing};
@@ -6,13 +6,13 @@ use convert_case::{Case, Casing};
 use crate::{
   enums::{EnumData, EnumVariant},
   extensions::ExtensionData,
-  fields::FieldData,
+  fields::{field_json_name, FieldData},
   files::FileData,
   messages::MessageData,
   oneofs::OneofData,
   packages::PackageData,
   services::ServiceData,
-  ProtoOption,
+  OptionValue, ProtoOption,
 };
 
 /// The struct containing all of the processed data for a protobuf file template
@@ -58,6 +58,49 @@ pub struct EnumTemplate {
   pub options: Box<[ProtoOption]>,
 }
 
+/// The struct containing the data for the Rust module with the constants of a package, which is rendered with [`Package::render_constants`](crate::Package::render_constants).
+///
+/// Each top level message and enum of the package becomes a module, named after it in snake case.
+#[derive(Clone, Debug, Default, Template)]
+#[template(path = "constants.rs.j2")]
+pub struct ConstantsTemplate {
+  pub package: Arc<str>,
+  pub messages: Vec<MessageConstants>,
+  pub enums: Vec<EnumConstants>,
+}
+
+/// The constants for a message, rendered as a module containing its fully qualified name, the `fields`, `numbers` and `json_names` modules with the constants for its fields, and a module for each of its nested messages and enums.
+/// The nested items named like the modules for the fields get a module with a trailing underscore, such as `fields_`.
+#[derive(Clone, Debug, Default, Template)]
+#[template(path = "message_constants.rs.j2")]
+pub struct MessageConstants {
+  pub module: Box<str>,
+  pub full_name: Arc<str>,
+  pub fields: Vec<FieldConstants>,
+  pub messages: Vec<MessageConstants>,
+  pub enums: Vec<EnumConstants>,
+}
+
+/// The constants for a field of a message, including the fields in its oneofs
+#[derive(Clone, Debug, Default)]
+pub struct FieldConstants {
+  /// The name of the constants for this field, in upper snake case
+  pub constant: Box<str>,
+  pub name: Arc<str>,
+  pub number: u32,
+  /// The name of the field in the JSON format, taken from the `json_name` option when it is set
+  pub json_name: Box<str>,
+}
+
+/// The constants for an enum, rendered as a module containing its fully qualified name and the number of each of its values
+#[derive(Clone, Debug, Default, Template)]
+#[template(path = "enum_constants.rs.j2")]
+pub struct EnumConstants {
+  pub module: Box<str>,
+  pub full_name: Arc<str>,
+  pub variants: Vec<(Box<str>, i32)>,
+}
+
 impl From<EnumData> for EnumTemplate {
   fn from(mut value: EnumData) -> Self {
     value.variants.sort_by_key(|t| t.0);
@@ -112,6 +155,29 @@ impl FileData {
       .map(|id| package.services[*id].clone())
       .collect();
 
+    let mut predefined_rules: HashSet<Box<str>> = HashSet::new();
+
+    for id in &self.messages {
+      collect_predefined_rules(&package.messages[*id], package, &mut predefined_rules);
+    }
+
+    if !predefined_rules.is_empty() {
+      for file in &package.files {
+        let defines_used_rule = file
+          .extensions
+          .iter()
+          .filter(|ext| ext.kind.is_validate_rules())
+          .flat_map(|ext| ext.fields.iter())
+          .any(|(_, field)| {
+            predefined_rules.contains(format!("{}.{}", package.name, field.name).as_str())
+          });
+
+        if defines_used_rule && file.name != self.name {
+          imports.insert(file.name.clone());
+        }
+      }
+    }
+
     let mut sorted_imports: Vec<Arc<str>> = imports.iter().cloned().collect();
     sorted_imports.sort();
 
@@ -161,3 +227,161 @@ impl MessageData {
     }
   }
 }
+
+// Collects the names of the predefined rules used in the fields of a message and its nested messages
+fn collect_predefined_rules(
+  msg: &MessageData,
+  package: &PackageData,
+  names: &mut HashSet<Box<str>>,
+) {
+  let fields = msg
+    .fields
+    .iter()
+    .chain(msg.oneofs.iter().flat_map(|oneof| oneof.fields.iter()));
+
+  for (_, field) in fields {
+    for option in field.options.iter() {
+      collect_rule_keys(&option.value, names);
+    }
+  }
+
+  for id in &msg.messages {
+    collect_predefined_rules(&package.messages[*id], package, names);
+  }
+}
+
+fn collect_rule_keys(value: &OptionValue, names: &mut HashSet<Box<str>>) {
+  match value {
+    OptionValue::Message(values) => {
+      for (key, value) in values.iter() {
+        if let Some(name) = key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+          names.insert(name.into());
+        }
+
+        collect_rule_keys(value, names);
+      }
+    }
+    OptionValue::List(items) => {
+      for item in items.iter() {
+        collect_rule_keys(item, names);
+      }
+    }
+    _ => {}
+  }
+}
+
+// The keywords that can be used as raw identifiers
+const RUST_KEYWORDS: [&str; 48] = [
+  "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+  "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+  "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+  "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+  "virtual", "where", "while", "yield",
+];
+
+// The name of the module for a message or enum, which is made a raw identifier if it is a keyword
+fn module_name(name: &str) -> Box<str> {
+  let module = name.to_case(Case::Snake);
+
+  match module.as_str() {
+    // These cannot be raw identifiers
+    "self" | "super" | "crate" => format!("{module}_").into(),
+    m if RUST_KEYWORDS.contains(&m) => format!("r#{module}").into(),
+    _ => module.into(),
+  }
+}
+
+// The modules generated for the fields of a message
+const FIELD_MODULES: [&str; 3] = ["fields", "numbers", "json_names"];
+
+impl PackageData {
+  pub(crate) fn build_constants_template(&self) -> ConstantsTemplate {
+    let top_level_messages = self
+      .files
+      .iter()
+      .flat_map(|file| file.messages.iter())
+      .map(|id| self.messages[*id].build_constants(self))
+      .collect();
+
+    let top_level_enums = self
+      .files
+      .iter()
+      .flat_map(|file| file.enums.iter())
+      .map(|id| self.enums[*id].build_constants())
+      .collect();
+
+    ConstantsTemplate {
+      package: self.name.clone(),
+      messages: top_level_messages,
+      enums: top_level_enums,
+    }
+  }
+}
+
+impl MessageData {
+  fn build_constants(&self, package: &PackageData) -> MessageConstants {
+    let fields: Vec<FieldConstants> = self
+      .fields
+      .iter()
+      .chain(self.oneofs.iter().flat_map(|oneof| oneof.fields.iter()))
+      .map(|(tag, field)| FieldConstants {
+        constant: field.name.to_case(Case::UpperSnake).into(),
+        name: field.name.clone(),
+        number: *tag,
+        json_name: field_json_name(field).into(),
+      })
+      .collect();
+
+    let mut messages: Vec<MessageConstants> = self
+      .messages
+      .iter()
+      .map(|id| package.messages[*id].build_constants(package))
+      .collect();
+    let mut enums: Vec<EnumConstants> = self
+      .enums
+      .iter()
+      .map(|id| package.enums[*id].build_constants())
+      .collect();
+
+    // The nested items cannot share the names of the modules for the fields
+    if !fields.is_empty() {
+      let modules = messages
+        .iter_mut()
+        .map(|msg| &mut msg.module)
+        .chain(enums.iter_mut().map(|e| &mut e.module));
+
+      for module in modules {
+        if FIELD_MODULES.contains(&module.as_ref()) {
+          *module = format!("{module}_").into();
+        }
+      }
+    }
+
+    MessageConstants {
+      module: module_name(&self.name),
+      full_name: self.import_path.full_name_with_package.clone(),
+      fields,
+      messages,
+      enums,
+    }
+  }
+}
+
+impl EnumData {
+  fn build_constants(&self) -> EnumConstants {
+    let prefix = self.name.to_case(Case::UpperSnake);
+    let mut variants: Vec<(Box<str>, i32)> = self
+      .variants
+      .iter()
+      .map(|(number, variant)| (format!("{prefix}_{}", variant.name).into(), *number))
+      .collect();
+
+    variants.sort_by_key(|(_, number)| *number);
+
+    EnumConstants {
+      module: module_name(&self.name),
+      full_name: self.import_path.full_name_with_package.clone(),
+      variants,
+    }
+  }
+}
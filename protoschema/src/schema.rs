@@ -0,0 +1,905 @@
+use std::{ops::Range, sync::Arc};
+
+use convert_case::{Case, Casing};
+
+use crate::{
+  checks::{find_option, get_value},
+  diff::{diff_schemas, Change},
+  enums::{EnumData, EnumVariant},
+  errors::SnapshotError,
+  extensions::{ExtensionData, ExtensionKind},
+  field_type::ImportedItemPath,
+  fields::{FieldData, FieldKind},
+  files::FileData,
+  messages::MessageData,
+  oneofs::OneofData,
+  packages::PackageData,
+  rendering::EnumTemplate,
+  services::{ServiceData, ServiceHandler},
+  validators::cel::CelRule,
+  FieldType, OptionValue, ProtoOption,
+};
+
+/// A read-only snapshot of the items of a [`Package`](crate::Package), returned by [`Package::schema`](crate::Package::schema).
+///
+/// It can be used to write custom generators and checks, without going through the templates.
+/// The items are referred to with their fully qualified name (such as `myapp.v1.User.Address`), or with their name within the package (`User.Address`).
+///
+/// With the `serde` feature, the schema can be serialized (for example as JSON or YAML), to be used for golden tests, caching, diffing or as the input of external tools.
+/// A package can be rebuilt from a schema with [`Package::from_schema`](crate::Package::from_schema).
+///
+/// # Examples
+/// ```rust
+/// use protoschema::{message, msg_field, string, Package};
+///
+/// let my_pkg = Package::new("my_pkg");
+/// let my_file = my_pkg.new_file("my_file");
+///
+/// let address_msg = message!(my_file.new_message("Address"), 1 => string!("city", |v| v.min_len(2)));
+/// message!(my_file.new_message("User"), 1 => msg_field!(address_msg, "address"));
+///
+/// let schema = my_pkg.schema();
+///
+/// let city_field = schema.find_message("Address").unwrap().field("city").unwrap();
+/// assert_eq!(city_field.rules().unwrap().type_rules.unwrap().kind.as_ref(), "string");
+///
+/// assert_eq!(schema.usages("Address").len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schema {
+  /// The name of the package
+  pub package: Arc<str>,
+  /// The files of the package
+  pub files: Vec<FileInfo>,
+  /// All of the messages of the package, including the nested ones
+  pub messages: Vec<MessageInfo>,
+  /// All of the enums of the package, including the nested ones
+  pub enums: Vec<EnumInfo>,
+  /// The services of the package
+  pub services: Vec<ServiceInfo>,
+}
+
+/// A file in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileInfo {
+  /// The path of the file, such as `myapp/v1/user.proto`
+  pub name: Arc<str>,
+  /// The imports of the file, in alphabetical order
+  pub imports: Vec<Arc<str>>,
+  /// The fully qualified names of the top level messages of the file
+  pub messages: Vec<Arc<str>>,
+  /// The fully qualified names of the top level enums of the file
+  pub enums: Vec<Arc<str>>,
+  /// The fully qualified names of the services of the file
+  pub services: Vec<Arc<str>>,
+  pub extensions: Vec<ExtensionInfo>,
+  pub options: Vec<ProtoOption>,
+}
+
+/// An extension defined in a file of a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtensionInfo {
+  pub kind: ExtensionKind,
+  /// The fields of the extension, in order of tag
+  pub fields: Vec<FieldInfo>,
+}
+
+/// A message in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageInfo {
+  pub name: Arc<str>,
+  /// The fully qualified name of the message, including the package
+  pub full_name: Arc<str>,
+  /// The file where the message is defined
+  pub file: Arc<str>,
+  /// The fully qualified name of the message that contains this one, if it is nested
+  pub parent: Option<Arc<str>>,
+  /// The fields of the message (including those inside of its oneofs), in order of tag
+  pub fields: Vec<FieldInfo>,
+  pub oneofs: Vec<OneofInfo>,
+  pub options: Vec<ProtoOption>,
+  pub reserved_numbers: Vec<u32>,
+  /// The reserved ranges, which are inclusive as in protobuf
+  pub reserved_ranges: Vec<Range<u32>>,
+  pub reserved_names: Vec<Box<str>>,
+  /// The fully qualified names of the messages nested in this one
+  pub messages: Vec<Arc<str>>,
+  /// The fully qualified names of the enums nested in this one
+  pub enums: Vec<Arc<str>>,
+}
+
+impl MessageInfo {
+  /// Returns the field with the given name
+  pub fn field(&self, name: &str) -> Option<&FieldInfo> {
+    self.fields.iter().find(|field| field.name.as_ref() == name)
+  }
+
+  /// Returns the value of the option with the given name, such as `(buf.validate.message)`
+  pub fn option(&self, name: &str) -> Option<&OptionValue> {
+    find_option(&self.options, name)
+  }
+
+  /// Returns the message-level validation rules, from the `(buf.validate.message)` option
+  pub fn rules(&self) -> Option<MessageRulesInfo> {
+    let rules = self.option("(buf.validate.message)")?;
+
+    let oneof = list(get_value(rules, "oneof"))
+      .iter()
+      .map(|rule| OneofRuleInfo {
+        fields: list(get_value(rule, "fields"))
+          .iter()
+          .filter_map(|field| match field {
+            OptionValue::String(name) => Some(name.clone()),
+            _ => None,
+          })
+          .collect(),
+        required: get_value(rule, "required") == Some(&OptionValue::Bool(true)),
+      })
+      .collect();
+
+    Some(MessageRulesInfo {
+      disabled: get_value(rules, "disabled") == Some(&OptionValue::Bool(true)),
+      cel: cel_rules(rules),
+      oneof,
+    })
+  }
+}
+
+/// A field in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldInfo {
+  pub name: Arc<str>,
+  pub tag: u32,
+  pub kind: FieldKind,
+  pub field_type: FieldType,
+  /// The fully qualified name of the type of the field, such as `string`, `google.protobuf.Timestamp`, `myapp.v1.User` or `map<string, myapp.v1.User>`
+  pub type_name: Arc<str>,
+  /// The name of the oneof that contains this field, if there is one
+  pub oneof: Option<Arc<str>>,
+  pub options: Vec<ProtoOption>,
+}
+
+impl FieldInfo {
+  /// Returns the value of the option with the given name, such as `(buf.validate.field)`
+  pub fn option(&self, name: &str) -> Option<&OptionValue> {
+    find_option(&self.options, name)
+  }
+
+  /// Returns the validation rules of this field, from the `(buf.validate.field)` option
+  pub fn rules(&self) -> Option<FieldRules> {
+    self.option("(buf.validate.field)").map(field_rules)
+  }
+}
+
+/// The validation rules of a field, extracted from its `(buf.validate.field)` option
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRules {
+  pub required: bool,
+  /// The value of the `ignore` setting, such as `IGNORE_IF_ZERO_VALUE`
+  pub ignore: Option<Box<str>>,
+  /// The rules that are specific to the type of the field, such as the `string` rules
+  pub type_rules: Option<TypeRules>,
+  pub cel: Vec<CelRule>,
+}
+
+/// The rules for a specific type of field, such as `string` or `repeated`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeRules {
+  /// The type of the rules, such as `string` or `repeated`
+  pub kind: Box<str>,
+  /// The standard rules, such as `min_len`, with their values
+  pub rules: Vec<(Box<str>, OptionValue)>,
+  /// The predefined rules, without the surrounding brackets, with their values
+  pub predefined: Vec<(Box<str>, OptionValue)>,
+  /// The Cel rules defined for this type of field, as in the `cel` rules of a predefined rule
+  pub cel: Vec<CelRule>,
+}
+
+/// The validation rules of a message, extracted from its `(buf.validate.message)` option
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageRulesInfo {
+  pub disabled: bool,
+  pub cel: Vec<CelRule>,
+  pub oneof: Vec<OneofRuleInfo>,
+}
+
+/// A message oneof rule, which allows only one of the given fields to be set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OneofRuleInfo {
+  pub fields: Vec<Box<str>>,
+  /// Whether one of the fields must be set
+  pub required: bool,
+}
+
+/// A oneof in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OneofInfo {
+  pub name: Arc<str>,
+  /// The names of the fields of the oneof
+  pub fields: Vec<Arc<str>>,
+  pub options: Vec<ProtoOption>,
+}
+
+/// An enum in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumInfo {
+  pub name: Arc<str>,
+  /// The fully qualified name of the enum, including the package
+  pub full_name: Arc<str>,
+  /// The file where the enum is defined
+  pub file: Arc<str>,
+  /// The fully qualified name of the message that contains this enum, if it is nested
+  pub parent: Option<Arc<str>>,
+  /// The variants of the enum, in order of number, with their full names (such as `STATUS_ACTIVE`)
+  pub variants: Vec<(i32, Arc<str>)>,
+  /// The options of the variants that have any, by number
+  pub variant_options: Vec<(i32, Vec<ProtoOption>)>,
+  pub options: Vec<ProtoOption>,
+  pub reserved_numbers: Vec<i32>,
+  /// The reserved ranges, which are inclusive as in protobuf
+  pub reserved_ranges: Vec<Range<i32>>,
+  pub reserved_names: Vec<Box<str>>,
+}
+
+/// A service in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceInfo {
+  pub name: Arc<str>,
+  /// The fully qualified name of the service, including the package
+  pub full_name: Arc<str>,
+  /// The file where the service is defined
+  pub file: Arc<str>,
+  pub handlers: Vec<HandlerInfo>,
+  pub options: Vec<ProtoOption>,
+}
+
+/// A handler of a service in a [`Schema`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandlerInfo {
+  pub name: Arc<str>,
+  /// The path of the request message, which can also come from another package (such as `google.protobuf.Empty`)
+  pub request: Arc<ImportedItemPath>,
+  /// The path of the response message, which can also come from another package
+  pub response: Arc<ImportedItemPath>,
+  pub options: Vec<ProtoOption>,
+}
+
+/// An item of a [`Schema`], returned by [`Schema::find`]
+#[derive(Debug, Clone, Copy)]
+pub enum Item<'a> {
+  Message(&'a MessageInfo),
+  Enum(&'a EnumInfo),
+  Service(&'a ServiceInfo),
+}
+
+/// A place where a message or an enum is used, returned by [`Schema::usages`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Usage {
+  /// The type of a field (including repeated fields and the values of maps)
+  Field { message: Arc<str>, field: Arc<str> },
+  /// The request of a handler
+  Request {
+    service: Arc<str>,
+    handler: Arc<str>,
+  },
+  /// The response of a handler
+  Response {
+    service: Arc<str>,
+    handler: Arc<str>,
+  },
+}
+
+impl Schema {
+  // Returns the fully qualified version of a name, if it is given relative to the package
+  fn qualify(&self, name: &str) -> String {
+    if name.starts_with(&format!("{}.", self.package)) {
+      name.to_string()
+    } else {
+      format!("{}.{}", self.package, name)
+    }
+  }
+
+  /// Returns the file with the given name, such as `myapp/v1/user.proto`
+  pub fn find_file(&self, name: &str) -> Option<&FileInfo> {
+    self.files.iter().find(|file| file.name.as_ref() == name)
+  }
+
+  /// Returns the message with the given name
+  pub fn find_message(&self, name: &str) -> Option<&MessageInfo> {
+    let name = self.qualify(name);
+
+    self.messages.iter().find(|msg| *msg.full_name == *name)
+  }
+
+  /// Returns the enum with the given name
+  pub fn find_enum(&self, name: &str) -> Option<&EnumInfo> {
+    let name = self.qualify(name);
+
+    self.enums.iter().find(|e| *e.full_name == *name)
+  }
+
+  /// Returns the service with the given name
+  pub fn find_service(&self, name: &str) -> Option<&ServiceInfo> {
+    let name = self.qualify(name);
+
+    self.services.iter().find(|s| *s.full_name == *name)
+  }
+
+  /// Returns the message, enum or service with the given name
+  pub fn find(&self, name: &str) -> Option<Item<'_>> {
+    self
+      .find_message(name)
+      .map(Item::Message)
+      .or_else(|| self.find_enum(name).map(Item::Enum))
+      .or_else(|| self.find_service(name).map(Item::Service))
+  }
+
+  /// Returns the changes from an older version of this schema to this one.
+  /// See [`diff_schemas`](crate::diff::diff_schemas) for more details.
+  pub fn diff(&self, old: &Schema) -> Vec<Change> {
+    diff_schemas(old, self)
+  }
+
+  /// Returns all of the places where the message or enum with the given name is used, in the fields of the messages and in the handlers of the services
+  pub fn usages(&self, name: &str) -> Vec<Usage> {
+    let name = self.qualify(name);
+    let mut usages = Vec::new();
+
+    for msg in self.messages.iter() {
+      for field in msg.fields.iter() {
+        if refers_to(&field.field_type, &name) {
+          usages.push(Usage::Field {
+            message: msg.full_name.clone(),
+            field: field.name.clone(),
+          });
+        }
+      }
+    }
+
+    for service in self.services.iter() {
+      for handler in service.handlers.iter() {
+        if *handler.request.full_name_with_package == *name {
+          usages.push(Usage::Request {
+            service: service.full_name.clone(),
+            handler: handler.name.clone(),
+          });
+        }
+
+        if *handler.response.full_name_with_package == *name {
+          usages.push(Usage::Response {
+            service: service.full_name.clone(),
+            handler: handler.name.clone(),
+          });
+        }
+      }
+    }
+
+    usages
+  }
+}
+
+fn refers_to(field_type: &FieldType, full_name: &str) -> bool {
+  match field_type {
+    FieldType::Message(path) | FieldType::Enum(path) => {
+      path.full_name_with_package.as_ref() == full_name
+    }
+    FieldType::Map(_, value) => refers_to(value, full_name),
+    _ => false,
+  }
+}
+
+fn type_name(field_type: &FieldType) -> Arc<str> {
+  match field_type {
+    FieldType::Message(path) | FieldType::Enum(path) => path.full_name_with_package.clone(),
+    FieldType::Map(key, value) => format!("map<{}, {}>", key, type_name(value)).into(),
+    other => other.name().into(),
+  }
+}
+
+fn list(value: Option<&OptionValue>) -> &[OptionValue] {
+  match value {
+    Some(OptionValue::List(items)) => items,
+    _ => &[],
+  }
+}
+
+fn cel_rules(rules: &OptionValue) -> Vec<CelRule> {
+  list(get_value(rules, "cel"))
+    .iter()
+    .map(|rule| {
+      let get_string = |key: &str| match get_value(rule, key) {
+        Some(OptionValue::String(value)) => value.clone(),
+        _ => "".into(),
+      };
+
+      CelRule {
+        id: get_string("id"),
+        message: get_string("message"),
+        expression: get_string("expression"),
+      }
+    })
+    .collect()
+}
+
+fn field_rules(rules: &OptionValue) -> FieldRules {
+  let type_rules = match rules {
+    OptionValue::Message(entries) => entries
+      .iter()
+      .find(|(name, _)| !matches!(name.as_ref(), "required" | "ignore" | "cel"))
+      .map(|(kind, value)| {
+        let entries = match value {
+          OptionValue::Message(entries) => entries.as_ref(),
+          _ => &[],
+        };
+
+        let (predefined, rules): (Vec<_>, Vec<_>) = entries
+          .iter()
+          .filter(|(name, _)| name.as_ref() != "cel")
+          .cloned()
+          .partition(|(name, _)| name.starts_with('['));
+
+        TypeRules {
+          kind: kind.clone(),
+          rules,
+          predefined: predefined
+            .into_iter()
+            .map(|(name, value)| (name.trim_matches(['[', ']']).into(), value))
+            .collect(),
+          cel: cel_rules(value),
+        }
+      }),
+    _ => None,
+  };
+
+  FieldRules {
+    required: get_value(rules, "required") == Some(&OptionValue::Bool(true)),
+    ignore: match get_value(rules, "ignore") {
+      Some(OptionValue::Enum(value)) => Some(value.clone()),
+      _ => None,
+    },
+    type_rules,
+    cel: cel_rules(rules),
+  }
+}
+
+fn field_info(tag: u32, field: &FieldData, oneof: Option<&Arc<str>>) -> FieldInfo {
+  FieldInfo {
+    name: field.name.clone(),
+    tag,
+    kind: field.kind,
+    field_type: field.field_type.clone(),
+    type_name: type_name(&field.field_type),
+    oneof: oneof.cloned(),
+    options: field.options.to_vec(),
+  }
+}
+
+// Returns the fully qualified name of the message that contains an item, if it is nested
+fn parent(package: &str, full_name: &str) -> Option<Arc<str>> {
+  full_name
+    .rsplit_once('.')
+    .map(|(parent, _)| format!("{package}.{parent}").into())
+}
+
+impl PackageData {
+  pub(crate) fn schema(&self) -> Schema {
+    let messages = self
+      .messages
+      .iter()
+      .map(|msg| {
+        let mut fields: Vec<FieldInfo> = msg
+          .fields
+          .iter()
+          .map(|(tag, field)| field_info(*tag, field, None))
+          .chain(msg.oneofs.iter().flat_map(|oneof| {
+            oneof
+              .fields
+              .iter()
+              .map(|(tag, field)| field_info(*tag, field, Some(&oneof.name)))
+          }))
+          .collect();
+
+        fields.sort_by_key(|field| field.tag);
+
+        MessageInfo {
+          name: msg.name.clone(),
+          full_name: msg.import_path.full_name_with_package.clone(),
+          file: msg.import_path.file.clone(),
+          parent: parent(&self.name, &msg.import_path.full_name),
+          fields,
+          oneofs: msg
+            .oneofs
+            .iter()
+            .map(|oneof| OneofInfo {
+              name: oneof.name.clone(),
+              fields: oneof.fields.iter().map(|(_, f)| f.name.clone()).collect(),
+              options: oneof.options.to_vec(),
+            })
+            .collect(),
+          options: msg.options.clone(),
+          reserved_numbers: msg.reserved_numbers.to_vec(),
+          reserved_ranges: msg.reserved_ranges.to_vec(),
+          reserved_names: msg.reserved_names.to_vec(),
+          messages: msg
+            .messages
+            .iter()
+            .map(|id| {
+              self.messages[*id]
+                .import_path
+                .full_name_with_package
+                .clone()
+            })
+            .collect(),
+          enums: msg
+            .enums
+            .iter()
+            .map(|id| self.enums[*id].import_path.full_name_with_package.clone())
+            .collect(),
+        }
+      })
+      .collect();
+
+    let enums = self
+      .enums
+      .iter()
+      .map(|enum_data| {
+        let template = EnumTemplate::from(enum_data.clone());
+
+        EnumInfo {
+          name: enum_data.name.clone(),
+          full_name: enum_data.import_path.full_name_with_package.clone(),
+          file: enum_data.import_path.file.clone(),
+          parent: parent(&self.name, &enum_data.import_path.full_name),
+          variants: template
+            .variants
+            .iter()
+            .map(|(number, variant)| (*number, variant.name.clone()))
+            .collect(),
+          variant_options: template
+            .variants
+            .iter()
+            .filter(|(_, variant)| !variant.options.is_empty())
+            .map(|(number, variant)| (*number, variant.options.to_vec()))
+            .collect(),
+          options: template.options.to_vec(),
+          reserved_numbers: template.reserved_numbers.to_vec(),
+          reserved_ranges: template.reserved_ranges.to_vec(),
+          reserved_names: template.reserved_names.to_vec(),
+        }
+      })
+      .collect();
+
+    let mut services = Vec::new();
+    let mut files = Vec::new();
+
+    for file in self.files.iter() {
+      let service_names: Vec<Arc<str>> = file
+        .services
+        .iter()
+        .map(|id| {
+          let service = &self.services[*id];
+          let full_name: Arc<str> = format!("{}.{}", self.name, service.name).into();
+
+          services.push(ServiceInfo {
+            name: service.name.as_ref().into(),
+            full_name: full_name.clone(),
+            file: file.name.clone(),
+            handlers: service
+              .handlers
+              .iter()
+              .map(|handler| HandlerInfo {
+                name: handler.name.as_ref().into(),
+                request: handler.request.clone(),
+                response: handler.response.clone(),
+                options: handler.options.to_vec(),
+              })
+              .collect(),
+            options: service.options.to_vec(),
+          });
+
+          full_name
+        })
+        .collect();
+
+      // The imports of the top level messages are added to the file when it is rendered
+      let mut imports: Vec<Arc<str>> = file
+        .imports
+        .iter()
+        .chain(
+          file
+            .messages
+            .iter()
+            .flat_map(|id| self.messages[*id].imports.iter()),
+        )
+        .filter(|import| **import != file.name)
+        .cloned()
+        .collect();
+      imports.sort();
+      imports.dedup();
+
+      files.push(FileInfo {
+        name: file.name.clone(),
+        imports,
+        messages: file
+          .messages
+          .iter()
+          .map(|id| {
+            self.messages[*id]
+              .import_path
+              .full_name_with_package
+              .clone()
+          })
+          .collect(),
+        enums: file
+          .enums
+          .iter()
+          .map(|id| self.enums[*id].import_path.full_name_with_package.clone())
+          .collect(),
+        services: service_names,
+        extensions: file
+          .extensions
+          .iter()
+          .map(|ext| {
+            let mut fields: Vec<FieldInfo> = ext
+              .fields
+              .iter()
+              .map(|(tag, field)| field_info(*tag, field, None))
+              .collect();
+            fields.sort_by_key(|field| field.tag);
+
+            ExtensionInfo {
+              kind: ext.kind,
+              fields,
+            }
+          })
+          .collect(),
+        options: file.options.clone(),
+      });
+    }
+
+    Schema {
+      package: self.name.clone(),
+      files,
+      messages,
+      enums,
+      services,
+    }
+  }
+}
+
+fn field_data(field: &FieldInfo) -> (u32, FieldData) {
+  (
+    field.tag,
+    FieldData {
+      options: field.options.clone().into_boxed_slice(),
+      kind: field.kind,
+      field_type: field.field_type.clone(),
+      name: field.name.clone(),
+    },
+  )
+}
+
+impl Schema {
+  // Returns the name of an item within the package, such as `User.Address`
+  fn relative_name(&self, full_name: &str) -> Result<Arc<str>, SnapshotError> {
+    full_name
+      .strip_prefix(&format!("{}.", self.package))
+      .map(Arc::from)
+      .ok_or_else(|| SnapshotError::ForeignItem {
+        item: full_name.into(),
+        package: self.package.as_ref().into(),
+      })
+  }
+
+  fn item_path(
+    &self,
+    full_name: &Arc<str>,
+    file: &Arc<str>,
+  ) -> Result<Arc<ImportedItemPath>, SnapshotError> {
+    Ok(Arc::new(ImportedItemPath {
+      full_name: self.relative_name(full_name)?,
+      full_name_with_package: full_name.clone(),
+      file: file.clone(),
+      package: self.package.clone(),
+    }))
+  }
+
+  fn message_id(&self, item: &str, name: &str) -> Result<usize, SnapshotError> {
+    self
+      .messages
+      .iter()
+      .position(|msg| *msg.full_name == *name)
+      .ok_or_else(|| SnapshotError::UnknownItem {
+        item: item.into(),
+        name: name.into(),
+      })
+  }
+
+  fn enum_id(&self, item: &str, name: &str) -> Result<usize, SnapshotError> {
+    self
+      .enums
+      .iter()
+      .position(|e| *e.full_name == *name)
+      .ok_or_else(|| SnapshotError::UnknownItem {
+        item: item.into(),
+        name: name.into(),
+      })
+  }
+
+  // Rebuilds the data of a package from this snapshot, keeping the order of the items
+  pub(crate) fn package_data(&self) -> Result<PackageData, SnapshotError> {
+    let mut messages = Vec::new();
+
+    for msg in &self.messages {
+      if let Some(field) = msg.fields.iter().find(|field| {
+        field
+          .oneof
+          .as_ref()
+          .is_some_and(|name| !msg.oneofs.iter().any(|oneof| oneof.name == *name))
+      }) {
+        return Err(SnapshotError::UnknownItem {
+          item: format!("{}.{}", msg.full_name, field.name).into(),
+          name: field.oneof.as_deref().unwrap_or_default().into(),
+        });
+      }
+
+      messages.push(MessageData {
+        name: msg.name.clone(),
+        import_path: self.item_path(&msg.full_name, &msg.file)?,
+        fields: msg
+          .fields
+          .iter()
+          .filter(|field| field.oneof.is_none())
+          .map(field_data)
+          .collect(),
+        oneofs: msg
+          .oneofs
+          .iter()
+          .map(|oneof| OneofData {
+            name: oneof.name.clone(),
+            fields: msg
+              .fields
+              .iter()
+              .filter(|field| field.oneof.as_ref() == Some(&oneof.name))
+              .map(field_data)
+              .collect(),
+            options: oneof.options.clone().into_boxed_slice(),
+          })
+          .collect(),
+        reserved_numbers: msg.reserved_numbers.clone().into_boxed_slice(),
+        reserved_ranges: msg.reserved_ranges.clone().into_boxed_slice(),
+        reserved_names: msg.reserved_names.clone().into_boxed_slice(),
+        options: msg.options.clone(),
+        enums: msg
+          .enums
+          .iter()
+          .map(|name| self.enum_id(&msg.full_name, name))
+          .collect::<Result<_, _>>()?,
+        messages: msg
+          .messages
+          .iter()
+          .map(|name| self.message_id(&msg.full_name, name))
+          .collect::<Result<_, _>>()?,
+        ..Default::default()
+      });
+    }
+
+    let mut enums = Vec::new();
+
+    for enum_info in &self.enums {
+      // The variants are stored without the prefix, which is added when they are rendered
+      let prefix = format!("{}_", enum_info.name.to_case(Case::UpperSnake));
+
+      enums.push(EnumData {
+        name: enum_info.name.clone(),
+        variants: enum_info
+          .variants
+          .iter()
+          .map(|(number, name)| {
+            let options = enum_info
+              .variant_options
+              .iter()
+              .find(|(n, _)| n == number)
+              .map(|(_, options)| options.as_slice())
+              .unwrap_or_default();
+
+            (
+              *number,
+              EnumVariant {
+                name: name.strip_prefix(&prefix).unwrap_or(&**name).into(),
+                options: options.into(),
+              },
+            )
+          })
+          .collect(),
+        import_path: self.item_path(&enum_info.full_name, &enum_info.file)?,
+        reserved_numbers: enum_info.reserved_numbers.clone().into_boxed_slice(),
+        reserved_ranges: enum_info.reserved_ranges.clone().into_boxed_slice(),
+        reserved_names: enum_info.reserved_names.clone().into_boxed_slice(),
+        options: enum_info.options.clone().into_boxed_slice(),
+      });
+    }
+
+    let mut services = Vec::new();
+
+    for service in &self.services {
+      let mut handlers = Vec::new();
+
+      // The paths are restored as they are, since the messages can come from other packages
+      for handler in &service.handlers {
+        handlers.push(ServiceHandler {
+          name: handler.name.as_ref().into(),
+          options: handler.options.clone().into_boxed_slice(),
+          request: handler.request.clone(),
+          response: handler.response.clone(),
+        });
+      }
+
+      services.push(ServiceData {
+        name: service.name.as_ref().into(),
+        handlers: handlers.into_boxed_slice(),
+        options: service.options.clone().into_boxed_slice(),
+      });
+    }
+
+    let mut files = Vec::new();
+
+    for file in &self.files {
+      files.push(FileData {
+        name: file.name.clone(),
+        messages: file
+          .messages
+          .iter()
+          .map(|name| self.message_id(&file.name, name))
+          .collect::<Result<_, _>>()?,
+        enums: file
+          .enums
+          .iter()
+          .map(|name| self.enum_id(&file.name, name))
+          .collect::<Result<_, _>>()?,
+        imports: file.imports.iter().cloned().collect(),
+        services: file
+          .services
+          .iter()
+          .map(|name| {
+            self
+              .services
+              .iter()
+              .position(|service| service.full_name == *name)
+              .ok_or_else(|| SnapshotError::UnknownItem {
+                item: file.name.as_ref().into(),
+                name: name.as_ref().into(),
+              })
+          })
+          .collect::<Result<_, _>>()?,
+        extensions: file
+          .extensions
+          .iter()
+          .map(|ext| ExtensionData {
+            kind: ext.kind,
+            fields: ext.fields.iter().map(field_data).collect(),
+          })
+          .collect(),
+        options: file.options.clone(),
+      });
+    }
+
+    Ok(PackageData {
+      name: self.package.clone(),
+      files,
+      messages,
+      enums,
+      services,
+      ..Default::default()
+    })
+  }
+}
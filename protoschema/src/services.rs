@@ -3,7 +3,10 @@ use std::{marker::PhantomData, sync::Arc};
 use bon::Builder;
 
 use crate::{
+  common::ANNOTATIONS_PROTO_FILE,
+  errors::ConflictError,
   field_type::{get_shortest_item_name, ImportedItemPath},
+  http::is_http_option,
   messages::{MessageBuilder, MessageState},
   packages::Arena,
   sealed, Empty, IsUnset, ProtoOption, Set, Unset,
@@ -29,9 +32,9 @@ pub struct ServiceHandler {
   #[builder(setters(vis = "", name = options_internal))]
   #[builder(default)]
   pub(crate) options: Box<[ProtoOption]>,
-  #[builder(setters(vis = "", name = request_internal))]
+  #[builder(setters(vis = "pub(crate)", name = request_internal))]
   pub(crate) request: Arc<ImportedItemPath>,
-  #[builder(setters(vis = "", name = response_internal))]
+  #[builder(setters(vis = "pub(crate)", name = response_internal))]
   pub(crate) response: Arc<ImportedItemPath>,
 }
 
@@ -116,23 +119,16 @@ impl<S: ServiceState> ServiceBuilder<S> {
   }
 
   /// Sets the handlers for this service. Consumes the original builder and returns a new one.
+  ///
+  /// # Panics
+  /// If a handler has the same name as another handler of this service, including those added with [`add_handlers`](ServiceBuilder::add_handlers).
+  #[track_caller]
   pub fn handlers<I>(self, handlers: I) -> ServiceBuilder<SetHandlers<S>>
   where
     S::Handlers: IsUnset,
     I: IntoIterator<Item = ServiceHandler>,
   {
-    {
-      let mut arena = self.arena.borrow_mut();
-      let file = &mut arena.files[self.file_id];
-
-      arena.services[self.id].handlers = handlers
-        .into_iter()
-        .inspect(|h| {
-          file.conditionally_add_import(&h.request.file);
-          file.conditionally_add_import(&h.response.file);
-        })
-        .collect();
-    }
+    self.store_handlers(handlers);
 
     ServiceBuilder {
       id: self.id,
@@ -142,6 +138,68 @@ impl<S: ServiceState> ServiceBuilder<S> {
     }
   }
 
+  /// Adds handlers to this service, in addition to the existing ones.
+  /// Unlike [`handlers`](ServiceBuilder::handlers), this can be called any number of times, so that separate modules can contribute handlers to the same service.
+  ///
+  /// # Panics
+  /// If a handler has the same name as another handler of this service.
+  #[track_caller]
+  pub fn add_handlers<I>(self, handlers: I) -> ServiceBuilder<S>
+  where
+    I: IntoIterator<Item = ServiceHandler>,
+  {
+    self.store_handlers(handlers);
+    self
+  }
+
+  // Checks that the names of the new handlers are unique, then adds them to the existing ones along with their imports
+  #[track_caller]
+  fn store_handlers<I>(&self, handlers: I)
+  where
+    I: IntoIterator<Item = ServiceHandler>,
+  {
+    let handlers: Vec<ServiceHandler> = handlers.into_iter().collect();
+
+    {
+      let arena = self.arena.borrow();
+      let service = &arena.services[self.id];
+      let mut names: Vec<&str> = service.handlers.iter().map(|h| h.name.as_ref()).collect();
+
+      for handler in handlers.iter() {
+        if names.contains(&handler.name.as_ref()) {
+          panic!(
+            "{}",
+            ConflictError::DuplicateName {
+              item: format!("{}.{}", arena.name, service.name).into(),
+              name: handler.name.clone(),
+            }
+          )
+        }
+
+        names.push(&handler.name);
+      }
+    }
+
+    let mut arena = self.arena.borrow_mut();
+    let file = &mut arena.files[self.file_id];
+
+    let handlers: Vec<ServiceHandler> = handlers
+      .into_iter()
+      .inspect(|h| {
+        file.conditionally_add_import(&h.request.file);
+        file.conditionally_add_import(&h.response.file);
+
+        if h.options.iter().any(is_http_option) {
+          file.conditionally_add_import(&ANNOTATIONS_PROTO_FILE);
+        }
+      })
+      .collect();
+
+    let service = &mut arena.services[self.id];
+
+    service.handlers = [service.handlers.as_ref(), &handlers].concat().into();
+  }
+
   /// Sets the options for this service. Consumes the original builder and returns a new one.
   pub fn options<I>(self, options: I) -> ServiceBuilder<SetOptions<S>>
   where
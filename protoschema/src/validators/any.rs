@@ -8,6 +8,9 @@ use crate::{
 /// Used by the [`any`](crate::any) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct AnyValidator<'a> {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Only the type_urls defined in this list will be considered valid for this field.
   #[builder(into)]
   pub in_: Option<&'a [&'a str]>,
@@ -25,6 +28,7 @@ pub struct AnyValidator<'a> {
 }
 
 impl_ignore!(AnyValidatorBuilder);
+impl_predefined!(AnyValidatorBuilder, AnyRules);
 
 impl<'a, S: any_validator_builder::State> From<AnyValidatorBuilder<'a, S>> for ProtoOption {
   #[track_caller]
@@ -50,6 +54,8 @@ impl<'a> From<AnyValidator<'a>> for ProtoOption {
     insert_option!(validator, values, in_, [string]);
     insert_option!(validator, values, not_in, [string]);
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "any".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -57,9 +63,10 @@ impl<'a> From<AnyValidator<'a>> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
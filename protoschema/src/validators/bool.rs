@@ -5,6 +5,9 @@ use crate::{validators::OptionValueList, OptionValue, ProtoOption};
 /// Used by the [`bool`](crate::bool) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct BoolValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Only this specific value will be considered valid for this field.
   pub const_: Option<bool>,
   #[builder(with = || true)]
@@ -12,6 +15,8 @@ pub struct BoolValidator {
   pub required: Option<bool>,
 }
 
+impl_predefined!(no_lifetime, BoolValidatorBuilder, BoolRules);
+
 impl<S: bool_validator_builder::State> From<BoolValidatorBuilder<S>> for ProtoOption {
   #[track_caller]
   fn from(value: BoolValidatorBuilder<S>) -> Self {
@@ -30,6 +35,8 @@ impl From<BoolValidator> for ProtoOption {
       values.push(("const".into(), OptionValue::Bool(const_val)));
     }
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "bool".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -38,7 +45,7 @@ impl From<BoolValidator> for ProtoOption {
     insert_option!(validator, option_value, required, bool);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
@@ -34,6 +34,9 @@ macro_rules! insert_bytes_option {
 /// Used by the [`bytes`](crate::bytes) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct BytesValidator<'a> {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Specifies the exact length for this bytes field to be considered valid.
   pub len: Option<u64>,
   /// The minimum length for this field in order to be considered valid.
@@ -69,6 +72,7 @@ pub struct BytesValidator<'a> {
 }
 
 impl_ignore!(BytesValidatorBuilder);
+impl_predefined!(BytesValidatorBuilder, BytesRules);
 
 impl<'a, S: bytes_validator_builder::State> From<BytesValidatorBuilder<'a, S>> for ProtoOption {
   #[track_caller]
@@ -124,6 +128,8 @@ impl<'a> From<BytesValidator<'a>> for ProtoOption {
       v.to_option(&mut values)
     }
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "bytes".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -131,9 +137,10 @@ impl<'a> From<BytesValidator<'a>> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
@@ -152,6 +159,8 @@ where
 }
 
 #[derive(Clone, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum WellKnown {
   Ip,
   Ipv4,
@@ -1,4 +1,10 @@
-use crate::OptionValue;
+use std::{
+  cell::Cell,
+  fmt::Display,
+  ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Rem, Sub},
+};
+
+use crate::{fields::FieldReference, Duration, OptionValue};
 
 /// The structure of a custom Cel rule used to define validation logic with protovalidate.
 /// The id should be a unique identifier for the given rule, which will appear in error messages.
@@ -6,7 +12,8 @@ use crate::OptionValue;
 /// The expression is a [Cel](https://cel.dev/) expression that will be executed on validation by protovalidate-compatible libraries such as [protocheck](https://github.com/Rick-Phoenix/protocheck) (for rust) or [protovalidate-es](https://github.com/bufbuild/protovalidate-es) (for javascript).
 /// <br/>
 /// You can use the [`cel_rule`](crate::cel_rule) macro to build them with a shorter syntax.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CelRule {
   pub id: Box<str>,
   pub message: Box<str>,
@@ -25,3 +32,497 @@ impl From<CelRule> for OptionValue {
     )
   }
 }
+
+/// A [Cel](https://cel.dev/) expression, built with a typed API instead of a raw string.
+///
+/// Field references are created with [`field`], which accepts either the name of the field or the [`FieldBuilder`](crate::fields::FieldBuilder) used to define it, so that the expressions follow the changes in the message definition.
+/// The fields referenced as `this.<field>` are checked against the message's fields, along with the types of the expression, when the templates are rendered.
+///
+/// The `&`, `|` and `!` operators produce the logical `&&`, `||` and `!` operations, and the arithmetic operators produce their Cel equivalents. Rust values can be used as literals by converting them with [`lit`] or by passing them directly to the methods that accept `Into<CelExpr>`.
+/// A [`CelExpr`] can be converted into a `Box<str>`, so it can be used directly in the [`cel_rule`](crate::cel_rule) macro.
+/// # Examples
+/// ```
+/// use protoschema::{cel_rule, validators::cel::{field, has}};
+///
+/// let rule = cel_rule!(
+///   id = "passwords_match",
+///   msg = "the passwords do not match",
+///   expr = field("password").eq(field("repeated_password"))
+/// );
+///
+/// assert_eq!(rule.expression.as_ref(), "this.password == this.repeated_password");
+///
+/// let expr = !has("nickname") | field("nickname").size().gte(3);
+/// assert_eq!(expr.to_string(), "!has(this.nickname) || size(this.nickname) >= 3");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CelExpr {
+  text: Box<str>,
+  precedence: Precedence,
+}
+
+thread_local! {
+  // The number of comprehensions whose predicate is being built, used to give a distinct variable to the nested ones
+  static COMPREHENSION_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Increments [`COMPREHENSION_DEPTH`] while it is alive, and restores it when dropped (even if the predicate being built panics)
+struct DepthGuard(usize);
+
+impl DepthGuard {
+  fn enter() -> Self {
+    let depth = COMPREHENSION_DEPTH.get();
+    COMPREHENSION_DEPTH.set(depth + 1);
+    DepthGuard(depth)
+  }
+}
+
+impl Drop for DepthGuard {
+  fn drop(&mut self) {
+    COMPREHENSION_DEPTH.set(self.0);
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+  Conditional,
+  Or,
+  And,
+  Relation,
+  Addition,
+  Multiplication,
+  Unary,
+  Primary,
+}
+
+impl Display for CelExpr {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.text)
+  }
+}
+
+impl From<CelExpr> for Box<str> {
+  fn from(value: CelExpr) -> Self {
+    value.text
+  }
+}
+
+impl From<CelExpr> for String {
+  fn from(value: CelExpr) -> Self {
+    value.text.into()
+  }
+}
+
+/// Returns a reference to the value being validated (`this`).
+/// For message-level rules, this is the message itself, while for field-level rules it is the value of the field.
+pub fn this() -> CelExpr {
+  CelExpr::primary("this")
+}
+
+/// Returns a reference to the value of a predefined rule (`rule`), to be used in the Cel rules of a [`PredefinedRule`](crate::validators::predefined::PredefinedRule).
+pub fn rule() -> CelExpr {
+  CelExpr::primary("rule")
+}
+
+/// Returns the current time (`now`).
+pub fn now() -> CelExpr {
+  CelExpr::primary("now")
+}
+
+/// Returns a reference to a field of the message being validated (`this.<field>`).
+/// The field can be referred to by name or with its [`FieldBuilder`](crate::fields::FieldBuilder).
+pub fn field<F: FieldReference>(field: F) -> CelExpr {
+  this().member(field.field_name())
+}
+
+/// Checks if a field of the message being validated is set (`has(this.<field>)`).
+pub fn has<F: FieldReference>(field: F) -> CelExpr {
+  CelExpr::primary(format!("has(this.{})", field.field_name()))
+}
+
+/// Converts a value into a literal Cel expression.
+pub fn lit<T: Into<CelExpr>>(value: T) -> CelExpr {
+  value.into()
+}
+
+/// Creates a conditional expression (`condition ? if_true : if_false`)
+pub fn cond<C, T, F>(condition: C, if_true: T, if_false: F) -> CelExpr
+where
+  C: Into<CelExpr>,
+  T: Into<CelExpr>,
+  F: Into<CelExpr>,
+{
+  let condition = condition.into().wrap(Precedence::Or);
+  let if_true = if_true.into().wrap(Precedence::Or);
+  let if_false = if_false.into().wrap(Precedence::Conditional);
+
+  CelExpr {
+    text: format!("{condition} ? {if_true} : {if_false}").into(),
+    precedence: Precedence::Conditional,
+  }
+}
+
+/// Creates a list literal (`[a, b, c]`)
+pub fn list<I, T>(items: I) -> CelExpr
+where
+  I: IntoIterator<Item = T>,
+  T: Into<CelExpr>,
+{
+  let items: Vec<String> = items
+    .into_iter()
+    .map(|item| item.into().text.into())
+    .collect();
+
+  CelExpr::primary(format!("[{}]", items.join(", ")))
+}
+
+macro_rules! binary_op {
+  ($(#[$meta:meta])* $name:ident, $op:literal, $precedence:ident) => {
+    $(#[$meta])*
+    pub fn $name<T: Into<CelExpr>>(self, other: T) -> CelExpr {
+      self.binary($op, other.into(), Precedence::$precedence)
+    }
+  };
+}
+
+macro_rules! method_call {
+  ($(#[$meta:meta])* $name:ident, $cel_name:literal) => {
+    $(#[$meta])*
+    pub fn $name(self) -> CelExpr {
+      self.call($cel_name, &[])
+    }
+  };
+
+  ($(#[$meta:meta])* $name:ident, $cel_name:literal, $arg:ident) => {
+    $(#[$meta])*
+    pub fn $name<T: Into<CelExpr>>(self, $arg: T) -> CelExpr {
+      self.call($cel_name, &[$arg.into()])
+    }
+  };
+}
+
+impl CelExpr {
+  /// Creates an expression from a raw string, which is used as is.
+  pub fn raw<T: AsRef<str>>(expression: T) -> CelExpr {
+    CelExpr {
+      text: expression.as_ref().into(),
+      precedence: Precedence::Conditional,
+    }
+  }
+
+  fn primary<T: AsRef<str>>(text: T) -> CelExpr {
+    CelExpr {
+      text: text.as_ref().into(),
+      precedence: Precedence::Primary,
+    }
+  }
+
+  fn wrap(self, min_precedence: Precedence) -> Box<str> {
+    if self.precedence < min_precedence {
+      format!("({})", self.text).into()
+    } else {
+      self.text
+    }
+  }
+
+  fn binary(self, op: &str, other: CelExpr, precedence: Precedence) -> CelExpr {
+    let left = self.wrap(precedence);
+    // Operators are left-associative, so the right side needs parentheses if it has the same precedence
+    let right = if other.precedence <= precedence {
+      format!("({})", other.text).into()
+    } else {
+      other.text
+    };
+
+    CelExpr {
+      text: format!("{left} {op} {right}").into(),
+      precedence,
+    }
+  }
+
+  fn call(self, name: &str, args: &[CelExpr]) -> CelExpr {
+    let target = self.wrap(Precedence::Primary);
+    let args: Vec<&str> = args.iter().map(|a| a.text.as_ref()).collect();
+
+    CelExpr::primary(format!("{target}.{name}({})", args.join(", ")))
+  }
+
+  /// Accesses a member of this value (`self.<name>`)
+  pub fn member<T: AsRef<str>>(self, name: T) -> CelExpr {
+    let target = self.wrap(Precedence::Primary);
+    CelExpr::primary(format!("{target}.{}", name.as_ref()))
+  }
+
+  /// Accesses an element of a list or map (`self[index]`)
+  pub fn index<T: Into<CelExpr>>(self, index: T) -> CelExpr {
+    let target = self.wrap(Precedence::Primary);
+    CelExpr::primary(format!("{target}[{}]", index.into().text))
+  }
+
+  /// Returns the size of a string, bytes, list or map (`size(self)`)
+  pub fn size(self) -> CelExpr {
+    CelExpr::primary(format!("size({})", self.text))
+  }
+
+  /// Logical and (`self && other`). The `&` operator can also be used.
+  pub fn and<T: Into<CelExpr>>(self, other: T) -> CelExpr {
+    let other = other.into();
+    // && is associative, so there is no need for parentheses on the right side
+    let right = other.wrap(Precedence::And);
+    let left = self.wrap(Precedence::And);
+
+    CelExpr {
+      text: format!("{left} && {right}").into(),
+      precedence: Precedence::And,
+    }
+  }
+
+  /// Logical or (`self || other`). The `|` operator can also be used.
+  pub fn or<T: Into<CelExpr>>(self, other: T) -> CelExpr {
+    let other = other.into();
+    let right = other.wrap(Precedence::Or);
+    let left = self.wrap(Precedence::Or);
+
+    CelExpr {
+      text: format!("{left} || {right}").into(),
+      precedence: Precedence::Or,
+    }
+  }
+
+  binary_op!(
+    /// Equality (`self == other`)
+    eq, "==", Relation
+  );
+  binary_op!(
+    /// Inequality (`self != other`)
+    ne, "!=", Relation
+  );
+  binary_op!(
+    /// Smaller than (`self < other`)
+    lt, "<", Relation
+  );
+  binary_op!(
+    /// Smaller than or equal to (`self <= other`)
+    lte, "<=", Relation
+  );
+  binary_op!(
+    /// Greater than (`self > other`)
+    gt, ">", Relation
+  );
+  binary_op!(
+    /// Greater than or equal to (`self >= other`)
+    gte, ">=", Relation
+  );
+  binary_op!(
+    /// Membership in a list or map (`self in other`)
+    in_, "in", Relation
+  );
+
+  method_call!(
+    /// Checks if this string contains the given substring (`self.contains(substring)`)
+    contains, "contains", substring
+  );
+  method_call!(
+    /// Checks if this string starts with the given prefix (`self.startsWith(prefix)`)
+    starts_with, "startsWith", prefix
+  );
+  method_call!(
+    /// Checks if this string ends with the given suffix (`self.endsWith(suffix)`)
+    ends_with, "endsWith", suffix
+  );
+  method_call!(
+    /// Checks if this string matches the given RE2 regex (`self.matches(regex)`)
+    matches, "matches", regex
+  );
+  method_call!(
+    /// Checks if this string is a valid email address (`self.isEmail()`)
+    is_email, "isEmail"
+  );
+  method_call!(
+    /// Checks if this string is a valid hostname (`self.isHostname()`)
+    is_hostname, "isHostname"
+  );
+  method_call!(
+    /// Checks if this string is a valid IP address (`self.isIp()`)
+    is_ip, "isIp"
+  );
+  method_call!(
+    /// Checks if this string is a valid IP prefix (`self.isIpPrefix()`)
+    is_ip_prefix, "isIpPrefix"
+  );
+  method_call!(
+    /// Checks if this string is a valid absolute URI (`self.isUri()`)
+    is_uri, "isUri"
+  );
+  method_call!(
+    /// Checks if this string is a valid URI reference (`self.isUriRef()`)
+    is_uri_ref, "isUriRef"
+  );
+  method_call!(
+    /// Checks if this string is a valid host and port pair (`self.isHostAndPort(true)`, with the port being required)
+    is_host_and_port, "isHostAndPort", port_required
+  );
+  method_call!(
+    /// Checks if all the items in this list are unique (`self.unique()`)
+    unique, "unique"
+  );
+  method_call!(
+    /// Checks if this number is NaN (`self.isNan()`)
+    is_nan, "isNan"
+  );
+  method_call!(
+    /// Checks if this number is infinite (`self.isInf()`)
+    is_inf, "isInf"
+  );
+
+  /// Checks if all the items in this list (or all the keys in this map) satisfy the given predicate (`self.all(item, <predicate>)`).
+  /// The closure receives a reference to the item. The comprehensions created inside of it use a different variable for their items (`item1`, `item2` and so on), so that the outer items can still be referenced.
+  pub fn all<F: FnOnce(CelExpr) -> CelExpr>(self, predicate: F) -> CelExpr {
+    self.comprehension("all", predicate)
+  }
+
+  /// Checks if at least one of the items in this list (or the keys in this map) satisfies the given predicate (`self.exists(item, <predicate>)`).
+  /// The closure receives a reference to the item, like in [`all`](CelExpr::all).
+  pub fn exists<F: FnOnce(CelExpr) -> CelExpr>(self, predicate: F) -> CelExpr {
+    self.comprehension("exists", predicate)
+  }
+
+  fn comprehension<F: FnOnce(CelExpr) -> CelExpr>(self, name: &str, predicate: F) -> CelExpr {
+    let target = self.wrap(Precedence::Primary);
+    let depth = DepthGuard::enter();
+    let variable = if depth.0 == 0 {
+      "item".to_string()
+    } else {
+      format!("item{}", depth.0)
+    };
+
+    let predicate = predicate(CelExpr::primary(variable.clone()));
+    drop(depth);
+
+    CelExpr::primary(format!("{target}.{name}({variable}, {})", predicate.text))
+  }
+}
+
+impl Not for CelExpr {
+  type Output = CelExpr;
+
+  fn not(self) -> Self::Output {
+    CelExpr {
+      text: format!("!{}", self.wrap(Precedence::Unary)).into(),
+      precedence: Precedence::Unary,
+    }
+  }
+}
+
+impl Neg for CelExpr {
+  type Output = CelExpr;
+
+  fn neg(self) -> Self::Output {
+    CelExpr {
+      text: format!("-{}", self.wrap(Precedence::Unary)).into(),
+      precedence: Precedence::Unary,
+    }
+  }
+}
+
+impl<T: Into<CelExpr>> BitAnd<T> for CelExpr {
+  type Output = CelExpr;
+
+  fn bitand(self, rhs: T) -> Self::Output {
+    self.and(rhs)
+  }
+}
+
+impl<T: Into<CelExpr>> BitOr<T> for CelExpr {
+  type Output = CelExpr;
+
+  fn bitor(self, rhs: T) -> Self::Output {
+    self.or(rhs)
+  }
+}
+
+macro_rules! impl_arithmetic_op {
+  ($trait:ident, $method:ident, $op:literal, $precedence:ident) => {
+    impl<T: Into<CelExpr>> $trait<T> for CelExpr {
+      type Output = CelExpr;
+
+      fn $method(self, rhs: T) -> Self::Output {
+        self.binary($op, rhs.into(), Precedence::$precedence)
+      }
+    }
+  };
+}
+
+impl_arithmetic_op!(Add, add, "+", Addition);
+impl_arithmetic_op!(Sub, sub, "-", Addition);
+impl_arithmetic_op!(Mul, mul, "*", Multiplication);
+impl_arithmetic_op!(Div, div, "/", Multiplication);
+impl_arithmetic_op!(Rem, rem, "%", Multiplication);
+
+macro_rules! impl_literal {
+  ($type:ty, |$val:ident| $fmt:expr) => {
+    impl From<$type> for CelExpr {
+      fn from($val: $type) -> Self {
+        CelExpr::primary($fmt)
+      }
+    }
+  };
+}
+
+impl_literal!(bool, |v| v.to_string());
+impl_literal!(i32, |v| v.to_string());
+impl_literal!(i64, |v| v.to_string());
+impl_literal!(u32, |v| format!("{v}u"));
+impl_literal!(u64, |v| format!("{v}u"));
+impl_literal!(f32, |v| format_float(v as f64));
+impl_literal!(f64, |v| format_float(v));
+impl_literal!(&str, |v| quote_string(v));
+impl_literal!(String, |v| quote_string(&v));
+
+impl From<Duration> for CelExpr {
+  fn from(value: Duration) -> Self {
+    CelExpr::primary(format!("duration('{}')", format_duration(&value)))
+  }
+}
+
+fn format_float(value: f64) -> String {
+  // CEL has no literals for the non-finite values, so they are created by converting a string
+  if value.is_nan() {
+    "double('NaN')".to_string()
+  } else if value.is_infinite() {
+    format!("double('{}Infinity')", if value < 0.0 { "-" } else { "" })
+  } else if value.fract() == 0.0 {
+    format!("{value:.1}")
+  } else {
+    value.to_string()
+  }
+}
+
+fn quote_string(value: &str) -> String {
+  let mut output = String::with_capacity(value.len() + 2);
+  output.push('\'');
+
+  for char in value.chars() {
+    match char {
+      '\\' => output.push_str("\\\\"),
+      '\'' => output.push_str("\\'"),
+      '\n' => output.push_str("\\n"),
+      '\r' => output.push_str("\\r"),
+      '\t' => output.push_str("\\t"),
+      c => output.push(c),
+    }
+  }
+
+  output.push('\'');
+  output
+}
+
+fn format_duration(duration: &Duration) -> String {
+  let total_nanos = duration.seconds as i128 * 1_000_000_000 + duration.nanos as i128;
+
+  if total_nanos % 1_000_000_000 == 0 {
+    format!("{}s", total_nanos / 1_000_000_000)
+  } else {
+    format!("{}ns", total_nanos)
+  }
+}
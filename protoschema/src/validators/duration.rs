@@ -9,6 +9,9 @@ use crate::{
 /// Used by the [`duration`](crate::duration) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct DurationValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Only the values in this list will be considered valid for this field.
   #[builder(into)]
   pub in_: Option<Box<[Duration]>>,
@@ -36,6 +39,7 @@ pub struct DurationValidator {
 }
 
 impl_ignore!(no_lifetime, DurationValidatorBuilder);
+impl_predefined!(no_lifetime, DurationValidatorBuilder, DurationRules);
 
 impl<S: duration_validator_builder::State> From<DurationValidatorBuilder<S>> for ProtoOption {
   #[track_caller]
@@ -72,6 +76,8 @@ impl From<DurationValidator> for ProtoOption {
     insert_option!(validator, values, in_, [duration]);
     insert_option!(validator, values, not_in, [duration]);
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "duration".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -79,9 +85,10 @@ impl From<DurationValidator> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
@@ -8,6 +8,9 @@ use crate::{
 /// Used by the [`enum_field`](crate::enum_field) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct EnumValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Only the values in this list will be considered valid for this field.
   #[builder(into)]
   pub in_: Option<Box<[i32]>>,
@@ -29,6 +32,7 @@ pub struct EnumValidator {
 }
 
 impl_ignore!(no_lifetime, EnumValidatorBuilder);
+impl_predefined!(no_lifetime, EnumValidatorBuilder, EnumRules);
 
 use enum_validator_builder::State;
 
@@ -63,6 +67,8 @@ impl From<EnumValidator> for ProtoOption {
     insert_option!(validator, values, in_, [i32]);
     insert_option!(validator, values, not_in, [i32]);
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "enum".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -70,9 +76,10 @@ impl From<EnumValidator> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
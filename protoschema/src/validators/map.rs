@@ -20,6 +20,9 @@ use crate::{
 /// Used to define validation rules for map fields by the [`map`](crate::map), [`enum_map`](crate::enum_map) and [`msg_map`](crate::msg_map) macros.
 #[derive(Clone, Debug, Builder)]
 pub struct MapValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   #[builder(into)]
   /// The options that will apply to this map's keys.
   /// This is mostly useful when calling the map definition macros, which will automatically convert validators into the option to use here.
@@ -44,6 +47,7 @@ pub struct MapValidator {
 }
 
 impl_ignore!(no_lifetime, MapValidatorBuilder);
+impl_predefined!(no_lifetime, MapValidatorBuilder, MapRules);
 
 impl<S: map_validator_builder::State> From<MapValidatorBuilder<S>> for ProtoOption {
   #[track_caller]
@@ -70,6 +74,8 @@ impl From<MapValidator> for ProtoOption {
       values.push(("values".into(), (*values_option.value).clone()));
     }
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "map".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -77,9 +83,10 @@ impl From<MapValidator> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
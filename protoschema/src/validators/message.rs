@@ -36,9 +36,10 @@ impl From<MessageValidator> for ProtoOption {
 
     insert_cel_rule!(validator, values);
     insert_option!(validator, values, required, bool);
+    insert_option!(validator, values, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(values.into_boxed_slice()).into(),
     }
   }
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use bon::Builder;
+pub(crate) use message_rules_builder::*;
+
+use crate::{
+  fields::FieldReference,
+  validators::{cel::CelRule, OptionValueList},
+  OptionValue, ProtoOption,
+};
+
+/// The protovalidate rules that apply to a message as a whole, which are rendered as the `(buf.validate.message)` option.
+/// They can be set with [`MessageBuilder::validate`](crate::messages::MessageBuilder::validate) or with the `validate` keyword in the [`message`](crate::message) macro.
+#[derive(Debug, Clone, Builder)]
+pub struct MessageRules {
+  /// The rules that make a group of fields mutually exclusive.
+  #[builder(field)]
+  pub oneof: Vec<MessageOneofRule>,
+  /// Adds custom validation using one or more [`CelRule`]s to this message.
+  #[builder(into)]
+  pub cel: Option<Box<[CelRule]>>,
+  /// Disables all validation rules for this message, including the rules defined on its fields.
+  #[builder(with = || true)]
+  pub disabled: Option<bool>,
+}
+
+/// A protovalidate rule which ensures that at most one of the given fields is set (or exactly one, if `required` is true).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageOneofRule {
+  pub fields: Box<[Arc<str>]>,
+  pub required: bool,
+}
+
+impl<S: message_rules_builder::State> MessageRulesBuilder<S> {
+  /// Adds a oneof rule for the given fields, which can be referred to by name or with their [`FieldBuilder`](crate::fields::FieldBuilder).
+  pub fn oneof<I, F>(mut self, fields: I, required: bool) -> Self
+  where
+    I: IntoIterator<Item = F>,
+    F: FieldReference,
+  {
+    self.oneof.push(MessageOneofRule {
+      fields: fields.into_iter().map(|f| f.field_name()).collect(),
+      required,
+    });
+    self
+  }
+}
+
+impl From<MessageOneofRule> for OptionValue {
+  fn from(value: MessageOneofRule) -> Self {
+    let fields: Vec<OptionValue> = value
+      .fields
+      .iter()
+      .map(|f| OptionValue::String(f.as_ref().into()))
+      .collect();
+
+    let mut values: OptionValueList = vec![(
+      "fields".into(),
+      OptionValue::List(fields.into_boxed_slice()),
+    )];
+
+    if value.required {
+      values.push(("required".into(), OptionValue::Bool(true)));
+    }
+
+    OptionValue::Message(values.into_boxed_slice())
+  }
+}
+
+impl<S: message_rules_builder::State> From<MessageRulesBuilder<S>> for ProtoOption {
+  #[track_caller]
+  fn from(value: MessageRulesBuilder<S>) -> Self {
+    value.build().into()
+  }
+}
+
+impl From<MessageRules> for ProtoOption {
+  #[track_caller]
+  fn from(rules: MessageRules) -> Self {
+    let name = "(buf.validate.message)";
+
+    let mut values: OptionValueList = Vec::new();
+
+    insert_option!(rules, values, disabled, bool);
+    insert_cel_rule!(rules, values);
+
+    if !rules.oneof.is_empty() {
+      let oneof_rules: Vec<OptionValue> = rules.oneof.into_iter().map(OptionValue::from).collect();
+      values.push((
+        "oneof".into(),
+        OptionValue::List(oneof_rules.into_boxed_slice()),
+      ));
+    }
+
+    ProtoOption {
+      name: name.into(),
+      value: OptionValue::Message(values.into_boxed_slice()).into(),
+    }
+  }
+}
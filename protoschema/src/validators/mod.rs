@@ -48,42 +48,105 @@ macro_rules! impl_ignore {
   };
 }
 
+macro_rules! impl_predefined {
+  (no_lifetime, $builder:ident, $kind:ident) => {
+    $crate::paste! {
+      impl<S: [< $builder:snake >]::State> $builder<S> {
+        /// Attaches a [`PredefinedRule`](crate::validators::predefined::PredefinedRule) to this field, with the given value.
+        /// Panics if the rule extends a different type of rules, or if the value does not match the type of the rule.
+        #[track_caller]
+        pub fn predefined<T: Into<$crate::OptionValue>>(
+          mut self,
+          rule: &$crate::validators::predefined::PredefinedRule,
+          value: T,
+        ) -> Self {
+          self.predefined.push(rule.entry($crate::extensions::ExtensionKind::$kind, value.into()));
+          self
+        }
+      }
+    }
+  };
+
+  ($builder:ident, $kind:ident) => {
+    $crate::paste! {
+      impl<'a, S: [< $builder:snake >]::State> $builder<'a, S> {
+        /// Attaches a [`PredefinedRule`](crate::validators::predefined::PredefinedRule) to this field, with the given value.
+        /// Panics if the rule extends a different type of rules, or if the value does not match the type of the rule.
+        #[track_caller]
+        pub fn predefined<T: Into<$crate::OptionValue>>(
+          mut self,
+          rule: &$crate::validators::predefined::PredefinedRule,
+          value: T,
+        ) -> Self {
+          self.predefined.push(rule.entry($crate::extensions::ExtensionKind::$kind, value.into()));
+          self
+        }
+      }
+    }
+  };
+}
+
 #[track_caller]
 fn validate_comparables<T>(lt: Option<T>, lte: Option<T>, gt: Option<T>, gte: Option<T>)
+where
+  T: Copy + PartialEq + PartialOrd,
+{
+  if let Err(error) = check_comparables(lt, lte, gt, gte) {
+    panic!("{error}")
+  }
+}
+
+// Checks that the lt/lte/gt/gte rules can be used together, and that they describe a valid range
+pub(crate) fn check_comparables<T>(
+  lt: Option<T>,
+  lte: Option<T>,
+  gt: Option<T>,
+  gte: Option<T>,
+) -> Result<(), &'static str>
 where
   T: Copy + PartialEq + PartialOrd,
 {
   if lt.is_some() && lte.is_some() {
-    panic!("Cannot use lt and lte together")
+    return Err("Cannot use lt and lte together");
   }
 
   if gt.is_some() && gte.is_some() {
-    panic!("Cannot use gt and gte together")
+    return Err("Cannot use gt and gte together");
   }
 
   if let Some(lt_val) = lt {
-    if let Some(gt_val) = gt && lt_val <= gt_val {
-      panic!("Lt cannot be smaller than or equal to gt")
+    if let Some(gt_val) = gt
+      && lt_val <= gt_val
+    {
+      return Err("Lt cannot be smaller than or equal to gt");
     }
 
-    if let Some(gte_val) = gte && lt_val < gte_val {
-      panic!("Lt cannot be smaller than gte")
+    if let Some(gte_val) = gte
+      && lt_val < gte_val
+    {
+      return Err("Lt cannot be smaller than gte");
     }
   }
 
   if let Some(lte_val) = lte {
-    if let Some(gt_val) = gt && lte_val < gt_val {
-      panic!("Lte cannot be smaller than to gt")
+    if let Some(gt_val) = gt
+      && lte_val < gt_val
+    {
+      return Err("Lte cannot be smaller than to gt");
     }
 
-    if let Some(gte_val) = gte && lte_val < gte_val {
-      panic!("Lte cannot be smaller than gte")
+    if let Some(gte_val) = gte
+      && lte_val < gte_val
+    {
+      return Err("Lte cannot be smaller than gte");
     }
   }
+
+  Ok(())
 }
 
 #[track_caller]
-fn validate_lists<'a, T>(
+pub(crate) fn validate_lists<'a, T>(
   in_list: Option<&'a [T]>,
   not_in_list: Option<&'a [T]>,
 ) -> Result<(), Vec<T>>
@@ -113,6 +176,16 @@ where
   }
 }
 
+fn ignore_value(ignore: Ignore) -> OptionValue {
+  let name = match ignore {
+    Ignore::Unspecified => "IGNORE_UNSPECIFIED",
+    Ignore::IfZeroValue => "IGNORE_IF_ZERO_VALUE",
+    Ignore::Always => "IGNORE_ALWAYS",
+  };
+
+  OptionValue::Enum(name.into())
+}
+
 fn get_option_name(raw_name: &str) -> Box<str> {
   if raw_name == "const_" {
     "const".into()
@@ -175,6 +248,9 @@ mod macros {
     ($val:ident, string) => {
       OptionValue::String($val.into())
     };
+    ($val:ident, ignore) => {
+      super::ignore_value($val)
+    };
     ($val:ident, $val_type:ident) => {
       paste::paste! {
         OptionValue::from($val)
@@ -191,7 +267,9 @@ pub mod duration;
 pub mod enums;
 pub mod map;
 pub mod message;
+pub mod message_rules;
 pub mod numeric;
+pub mod predefined;
 pub mod repeated;
 pub mod string;
 pub mod timestamp;
@@ -59,6 +59,9 @@ macro_rules! get_fields {
     /// A struct that can be used to generate a [`ProtoOption`] containing protovalidate rules for a protobuf 'double' field.
     #[derive(Clone, Debug, Builder)]
     pub struct DoubleValidator {
+      /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+      #[builder(field)]
+      pub predefined: Vec<(Box<str>, OptionValue)>,
       /// Only this specific value will be considered valid for this field.
       pub const_: Option<f64>,
       /// This field's value will be valid only if it is smaller than the specified amount
@@ -93,6 +96,9 @@ macro_rules! get_fields {
     /// A struct that can be used to generate a [`ProtoOption`] containing protovalidate rules for a protobuf 'float' field.
     #[derive(Clone, Debug, Builder)]
     pub struct FloatValidator {
+      /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+      #[builder(field)]
+      pub predefined: Vec<(Box<str>, OptionValue)>,
       /// Only this specific value will be considered valid for this field.
       pub const_: Option<f32>,
       /// This field's value will be valid only if it is smaller than the specified amount
@@ -130,6 +136,9 @@ macro_rules! get_fields {
       #[doc = "` field."]
       #[derive(Clone, Debug, Builder)]
         pub struct [< $proto_type:camel Validator >] {
+        /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+        #[builder(field)]
+        pub predefined: Vec<(Box<str>, OptionValue)>,
         /// Only this specific value will be considered valid for this field.
         pub const_: Option<$rust_type>,
         /// This field's value will be valid only if it is smaller than the specified amount
@@ -180,11 +189,12 @@ macro_rules! get_options {
 }
 
 macro_rules! numeric_validator {
-  ($proto_type:ident, $rust_type:ty, $option_value_variant:ident) => {
+  ($proto_type:ident, $rust_type:ty, $option_value_variant:ident, $rules_kind:ident) => {
     paste::paste! {
       get_fields!($rust_type, $proto_type);
 
       impl_ignore!(no_lifetime, [< $proto_type:camel ValidatorBuilder >]);
+      impl_predefined!(no_lifetime, [< $proto_type:camel ValidatorBuilder >], $rules_kind);
 
       impl<S: [< $proto_type _validator_builder >]::State> From<[< $proto_type:camel ValidatorBuilder >]<S>> for ProtoOption {
         #[doc(hidden)]
@@ -209,6 +219,7 @@ macro_rules! numeric_validator {
           super::validate_comparables(validator.lt, validator.lte, validator.gt, validator.gte);
           get_list_check!($rust_type, validator.in_.as_deref(), validator.not_in.as_deref());
           get_options!($option_value_variant, validator, values);
+          values.extend(validator.predefined);
 
           let mut option_value: OptionValueList = vec! [
             (stringify!($proto_type).into(), OptionValue::Message(values.into_boxed_slice()))
@@ -216,6 +227,7 @@ macro_rules! numeric_validator {
 
           insert_cel_rule!(validator, option_value);
           insert_option!(validator, option_value, required, bool);
+          insert_option!(validator, option_value, ignore, ignore);
 
           ProtoOption {
             name: name.into(),
@@ -239,15 +251,15 @@ macro_rules! numeric_validator {
   };
 }
 
-numeric_validator!(int64, i64, Int);
-numeric_validator!(int32, i32, Int);
-numeric_validator!(sint64, i64, Int);
-numeric_validator!(sint32, i32, Int);
-numeric_validator!(sfixed64, i64, Int);
-numeric_validator!(sfixed32, i32, Int);
-numeric_validator!(uint64, u64, Uint);
-numeric_validator!(uint32, u32, Uint);
-numeric_validator!(fixed64, u64, Uint);
-numeric_validator!(fixed32, u32, Uint);
-numeric_validator!(float, f32, Float);
-numeric_validator!(double, f64, Float);
+numeric_validator!(int64, i64, Int, Int64Rules);
+numeric_validator!(int32, i32, Int, Int32Rules);
+numeric_validator!(sint64, i64, Int, SInt64Rules);
+numeric_validator!(sint32, i32, Int, SInt32Rules);
+numeric_validator!(sfixed64, i64, Int, SFixed64Rules);
+numeric_validator!(sfixed32, i32, Int, SFixed32Rules);
+numeric_validator!(uint64, u64, Uint, UInt64Rules);
+numeric_validator!(uint32, u32, Uint, UInt32Rules);
+numeric_validator!(fixed64, u64, Uint, Fixed64Rules);
+numeric_validator!(fixed32, u32, Uint, Fixed32Rules);
+numeric_validator!(float, f32, Float, FloatRules);
+numeric_validator!(double, f64, Float, DoubleRules);
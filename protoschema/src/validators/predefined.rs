@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use crate::{
+  extensions::ExtensionKind, fields::FieldKind, validators::cel::CelRule, FieldType, OptionValue,
+  ProtoOption,
+};
+
+/// A reusable protovalidate rule, defined as an extension of one of protovalidate's rules messages (like `buf.validate.StringRules`) which carries its own Cel expression.
+///
+/// It is created with [`FileBuilder::new_predefined_rule`](crate::files::FileBuilder::new_predefined_rule) or with the [`predefined_rule`](crate::predefined_rule) macro, and it can then be attached to any field by calling `predefined` on the validator builder of the matching type.
+/// The rule's value is rendered as `[package.rule_name]` within the validator's rules, as in `(buf.validate.field).string.(myrules.slug) = true`.
+///
+/// The file where the rule is defined is automatically imported by the files of the same package that use it. When using the rule from another package, the file must be imported manually with [`add_imports`](crate::files::FileBuilder::add_imports).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PredefinedRule {
+  /// The rules message being extended
+  pub kind: ExtensionKind,
+  /// The fully qualified name of the extension field
+  pub name: Arc<str>,
+  /// The file where the rule is defined
+  pub file: Arc<str>,
+  pub field_type: FieldType,
+  pub field_kind: FieldKind,
+}
+
+impl PredefinedRule {
+  /// Returns the key and the value for this rule, to be placed inside the rules of a validator.
+  /// Panics if the rule does not extend the given rules message or if the value does not match the type of the rule.
+  #[track_caller]
+  pub(crate) fn entry(&self, target: ExtensionKind, value: OptionValue) -> (Box<str>, OptionValue) {
+    if self.kind != target {
+      panic!(
+        "The predefined rule '{}' extends {} and cannot be used with {}",
+        self.name,
+        self.kind.get_target(),
+        target.get_target()
+      )
+    }
+
+    let is_valid = match (&self.field_kind, &value) {
+      (FieldKind::Repeated, OptionValue::List(items)) => items
+        .iter()
+        .all(|item| value_matches_type(&self.field_type, item)),
+      (FieldKind::Repeated, _) => false,
+      (_, value) => value_matches_type(&self.field_type, value),
+    };
+
+    if !is_valid {
+      panic!(
+        "The value {:?} does not match the type of the predefined rule '{}'",
+        value, self.name
+      )
+    }
+
+    (format!("[{}]", self.name).into(), value)
+  }
+}
+
+/// Builds the `(buf.validate.predefined)` option that holds the Cel rules for a predefined rule.
+pub(crate) fn predefined_rule_option<I>(rules: I) -> ProtoOption
+where
+  I: IntoIterator<Item = CelRule>,
+{
+  let rules: Vec<OptionValue> = rules.into_iter().map(OptionValue::from).collect();
+
+  ProtoOption {
+    name: "(buf.validate.predefined)".into(),
+    value: OptionValue::Message(
+      vec![("cel".into(), OptionValue::List(rules.into_boxed_slice()))].into_boxed_slice(),
+    )
+    .into(),
+  }
+}
+
+fn value_matches_type(field_type: &FieldType, value: &OptionValue) -> bool {
+  match field_type {
+    FieldType::Bool => matches!(value, OptionValue::Bool(_)),
+    FieldType::String | FieldType::Bytes => matches!(value, OptionValue::String(_)),
+    FieldType::Int32
+    | FieldType::Int64
+    | FieldType::Sint32
+    | FieldType::Sint64
+    | FieldType::Sfixed32
+    | FieldType::Sfixed64 => matches!(value, OptionValue::Int(_) | OptionValue::Uint(_)),
+    FieldType::Uint32 | FieldType::Uint64 | FieldType::Fixed32 | FieldType::Fixed64 => {
+      matches!(value, OptionValue::Uint(_)) || matches!(value, OptionValue::Int(v) if *v >= 0)
+    }
+    FieldType::Float | FieldType::Double => matches!(
+      value,
+      OptionValue::Float(_) | OptionValue::Int(_) | OptionValue::Uint(_)
+    ),
+    FieldType::Enum(_) => matches!(value, OptionValue::Enum(_)),
+    FieldType::Duration => matches!(value, OptionValue::Duration(_)),
+    FieldType::Timestamp => matches!(value, OptionValue::Timestamp(_)),
+    FieldType::Map(_, _) => false,
+    _ => matches!(value, OptionValue::Message(_)),
+  }
+}
@@ -21,6 +21,9 @@ use crate::{
 /// Used by the various kinds of field macros such as [`string`](crate::string) or [`int64`](crate::int64) to define validation rules when the field is marked as repeated.
 #[derive(Clone, Debug, Builder)]
 pub struct RepeatedValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   #[builder(into)]
   /// The rules to apply to the individual items in this field's list. Usually defined via the various field macros, which automatically convert field validator instances into the correct [`ProtoOption`] to place here.
   pub items: Option<ProtoOption>,
@@ -43,6 +46,7 @@ pub struct RepeatedValidator {
 }
 
 impl_ignore!(no_lifetime, RepeatedValidatorBuilder);
+impl_predefined!(no_lifetime, RepeatedValidatorBuilder, RepeatedRules);
 
 impl<S: repeated_validator_builder::State> From<RepeatedValidatorBuilder<S>> for ProtoOption {
   #[track_caller]
@@ -66,6 +70,8 @@ impl From<RepeatedValidator> for ProtoOption {
       values.push(("items".into(), (*items_option.value).clone()));
     }
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "repeated".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -73,9 +79,10 @@ impl From<RepeatedValidator> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
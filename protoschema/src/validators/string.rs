@@ -10,6 +10,9 @@ use crate::{
 #[derive(Clone, Debug, Builder)]
 #[builder(derive(Clone))]
 pub struct StringValidator<'a> {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// The exact character length that this field's value must have in order to be considered valid.
   pub len: Option<u64>,
   /// The minimum character length for this field's value to be considered valid.
@@ -111,6 +114,8 @@ impl<'a> From<StringValidator<'a>> for ProtoOption {
       v.to_option(&mut values)
     }
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "string".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -118,9 +123,10 @@ impl<'a> From<StringValidator<'a>> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
@@ -140,6 +146,8 @@ where
 
 /// All of the variants for protovalidate's well known string rules
 #[derive(Clone, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum WellKnown {
   Email,
   Hostname,
@@ -183,6 +191,7 @@ macro_rules! well_known_impl {
 }
 
 impl_ignore!(StringValidatorBuilder);
+impl_predefined!(StringValidatorBuilder, StringRules);
 
 impl<'a, S: State> StringValidatorBuilder<'a, S> {
   well_known_impl!(Email);
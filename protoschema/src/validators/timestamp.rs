@@ -9,6 +9,9 @@ use crate::{
 /// Used by the [`timestamp`](crate::timestamp) macro to define validation rules.
 #[derive(Clone, Debug, Builder)]
 pub struct TimestampValidator {
+  /// The [`PredefinedRule`](crate::validators::predefined::PredefinedRule)s attached to this field, with their values.
+  #[builder(field)]
+  pub predefined: Vec<(Box<str>, OptionValue)>,
   /// Only this specific value will be considered valid for this field.
   pub const_: Option<Timestamp>,
   /// This field's value will be valid only if it is smaller than the specified amount.
@@ -38,6 +41,7 @@ pub struct TimestampValidator {
 }
 
 impl_ignore!(no_lifetime, TimestampValidatorBuilder);
+impl_predefined!(no_lifetime, TimestampValidatorBuilder, TimestampRules);
 
 impl<S: timestamp_validator_builder::State> From<TimestampValidatorBuilder<S>> for ProtoOption {
   #[track_caller]
@@ -59,30 +63,8 @@ impl From<TimestampValidator> for ProtoOption {
 
     validate_comparables(validator.lt, validator.lte, validator.gt, validator.gte);
 
-    if let Some(true) = validator.lt_now {
-      if validator.lt.is_some() || validator.lte.is_some() {
-        panic!("Cannot use lt_now with lt or lte")
-      }
-
-      if let Some(gt) = validator.gt && gt.is_future() {
-      panic!("Gt cannot be in the future if lt_now is true")
-    }
-      if let Some(gte) = validator.gte && gte.is_future() {
-      panic!("Gte cannot be in the future if lt_now is true")
-    }
-    }
-
-    if let Some(true) = validator.gt_now {
-      if validator.gt.is_some() || validator.gte.is_some() {
-        panic!("Cannot use gt_now with gt or gte")
-      }
-
-      if let Some(lt) = validator.lt && lt.is_past() {
-      panic!("Lt cannot be in the past if gt_now is true")
-    }
-      if let Some(lte) = validator.lte && lte.is_past() {
-      panic!("Lte cannot be in the past if gt_now is true")
-    }
+    if let Err(error) = check_now_rules(&validator) {
+      panic!("{error}")
     }
 
     insert_option!(validator, values, lt, timestamp);
@@ -93,6 +75,8 @@ impl From<TimestampValidator> for ProtoOption {
     insert_option!(validator, values, gt_now, bool);
     insert_option!(validator, values, within, duration);
 
+    values.extend(validator.predefined);
+
     let mut option_value: OptionValueList = vec![(
       "timestamp".into(),
       OptionValue::Message(values.into_boxed_slice()),
@@ -100,14 +84,48 @@ impl From<TimestampValidator> for ProtoOption {
 
     insert_cel_rule!(validator, option_value);
     insert_option!(validator, option_value, required, bool);
+    insert_option!(validator, option_value, ignore, ignore);
 
     ProtoOption {
-      name,
+      name: name.into(),
       value: OptionValue::Message(option_value.into_boxed_slice()).into(),
     }
   }
 }
 
+// Checks that the lt_now and gt_now rules are compatible with the other comparison rules
+pub(crate) fn check_now_rules(validator: &TimestampValidator) -> Result<(), &'static str> {
+  if let Some(true) = validator.lt_now {
+    if validator.lt.is_some() || validator.lte.is_some() {
+      return Err("Cannot use lt_now with lt or lte");
+    }
+
+    if let Some(gt) = validator.gt && gt.is_future() {
+      return Err("Gt cannot be in the future if lt_now is true");
+    }
+
+    if let Some(gte) = validator.gte && gte.is_future() {
+      return Err("Gte cannot be in the future if lt_now is true");
+    }
+  }
+
+  if let Some(true) = validator.gt_now {
+    if validator.gt.is_some() || validator.gte.is_some() {
+      return Err("Cannot use gt_now with gt or gte");
+    }
+
+    if let Some(lt) = validator.lt && lt.is_past() {
+      return Err("Lt cannot be in the past if gt_now is true");
+    }
+
+    if let Some(lte) = validator.lte && lte.is_past() {
+      return Err("Lte cannot be in the past if gt_now is true");
+    }
+  }
+
+  Ok(())
+}
+
 #[doc(hidden)]
 #[track_caller]
 pub fn build_timestamp_validator_option<F, S>(config_fn: F) -> ProtoOption
@@ -0,0 +1,219 @@
+use protoschema::{
+  aip::{ResourceConfig, StandardMethod},
+  message, message_option,
+  messages::{MessageBuilder, SetFields},
+  proto_option,
+  rendering::MessageTemplate,
+  string, OptionValue, Package,
+};
+
+fn field_names(msg: MessageBuilder<SetFields>) -> Vec<String> {
+  template_field_names(&msg.get_data())
+}
+
+fn template_field_names(msg: &MessageTemplate) -> Vec<String> {
+  msg
+    .fields
+    .iter()
+    .map(|(_, field)| field.name.to_string())
+    .collect()
+}
+
+#[test]
+fn standard_methods() {
+  let package = Package::new("library.v1");
+  let file = package.new_file("library");
+
+  let book_msg = message!(
+    file.new_message("Book"),
+    1 => string!("name"),
+    2 => string!("title"),
+  );
+
+  let books = file.resource_service(
+    &book_msg,
+    ResourceConfig::new("publishers/{publisher}/books/{book}")
+      .resource_type("library.example.com/Book"),
+  );
+
+  assert_eq!(field_names(books.get_request.unwrap()), vec!["name"]);
+  assert_eq!(
+    field_names(books.list_request.unwrap()),
+    vec!["parent", "page_size", "page_token"]
+  );
+  assert_eq!(
+    field_names(books.list_response.unwrap()),
+    vec!["books", "next_page_token"]
+  );
+  assert_eq!(
+    field_names(books.create_request.unwrap()),
+    vec!["parent", "book", "book_id"]
+  );
+  assert_eq!(
+    field_names(books.update_request.unwrap()),
+    vec!["book", "update_mask"]
+  );
+
+  let delete_request = books.delete_request.unwrap().get_data();
+
+  assert_eq!(template_field_names(&delete_request), vec!["name", "etag"]);
+
+  assert_eq!(
+    book_msg.get_data().options.to_vec(),
+    vec![proto_option(
+      "(google.api.resource)",
+      message_option!(
+        "type" => "library.example.com/Book",
+        "pattern" => "publishers/{publisher}/books/{book}",
+        "plural" => "books",
+        "singular" => "book"
+      )
+    )]
+  );
+
+  let (_, name_field) = &delete_request.fields[0];
+
+  assert_eq!(
+    name_field
+      .options
+      .iter()
+      .map(|option| &*option.name)
+      .collect::<Vec<_>>(),
+    vec![
+      "(google.api.field_behavior)",
+      "(google.api.resource_reference)",
+      "(buf.validate.field)"
+    ]
+  );
+  assert_eq!(
+    *name_field.options[0].value,
+    OptionValue::Enum("REQUIRED".into())
+  );
+  assert_eq!(
+    *name_field.options[1].value,
+    message_option!("type" => "library.example.com/Book")
+  );
+
+  let service = books.service.get_data();
+
+  assert_eq!(service.name.as_ref(), "BookService");
+
+  let imports = file.get_data().imports;
+
+  for import in [
+    "google/api/annotations.proto",
+    "google/api/field_behavior.proto",
+    "google/api/resource.proto",
+    "google/protobuf/empty.proto",
+    "google/protobuf/field_mask.proto",
+    "buf/validate/validate.proto",
+  ] {
+    assert!(imports.contains(&import.into()), "missing import {import}");
+  }
+
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn top_level_resources() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let shelf_msg = message!(file.new_message("Shelf"), 1 => string!("name"));
+
+  let shelves = file.resource_service(
+    &shelf_msg,
+    ResourceConfig::new("shelves/{shelf}")
+      .plural("shelves")
+      .service_name("Library")
+      .methods([StandardMethod::List, StandardMethod::Create]),
+  );
+
+  assert!(shelves.get_request.is_none());
+  assert_eq!(
+    field_names(shelves.list_request.unwrap()),
+    vec!["page_size", "page_token"]
+  );
+  assert_eq!(
+    field_names(shelves.create_request.unwrap()),
+    vec!["shelf", "shelf_id"]
+  );
+
+  let service = shelves.service.get_data();
+
+  assert_eq!(service.name.as_ref(), "Library");
+  assert_eq!(service.handlers.len(), 2);
+  assert!(package.check().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "Invalid resource pattern")]
+fn invalid_pattern() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let book_msg = message!(file.new_message("Book"), 1 => string!("name"));
+
+  file.resource_service(&book_msg, "books/{book}/versions");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn http_bindings_and_rules() {
+  use serde_json::json;
+
+  let package = Package::new("library.v1");
+  let file = package.new_file("library");
+
+  let book_msg = message!(file.new_message("Book"), 1 => string!("name"));
+
+  file.resource_service(
+    &book_msg,
+    ResourceConfig::new("publishers/{publisher}/books/{book}").max_page_size(100),
+  );
+
+  let document = package.openapi("Library", "1.0.0");
+  let paths = &document["paths"];
+
+  assert_eq!(
+    paths["/v1/{name}"]["get"]["parameters"][0]["schema"],
+    json!({ "type": "string", "pattern": "^publishers/[^/]+/books/[^/]+$" })
+  );
+  assert_eq!(
+    paths["/v1/{parent}/books"]["get"]["parameters"][1],
+    json!({
+      "name": "pageSize",
+      "in": "query",
+      "schema": { "type": "integer", "minimum": 0, "maximum": 100 },
+    })
+  );
+  assert_eq!(
+    paths["/v1/{parent}/books"]["post"]["requestBody"]["content"]["application/json"]["schema"],
+    json!({ "$ref": "#/components/schemas/library.v1.Book" })
+  );
+  assert!(paths["/v1/{book.name}"]["patch"].is_object());
+  assert_eq!(
+    paths["/v1/{name}"]["delete"]["responses"]["200"]["content"]["application/json"]["schema"],
+    json!({ "type": "object", "additionalProperties": false })
+  );
+
+  let violations = package
+    .evaluate(
+      "library.v1.CreateBookRequest",
+      &json!({ "parent": "publishers/acme", "book": {}, "bookId": "Not-Valid" }),
+    )
+    .unwrap();
+
+  assert_eq!(violations.len(), 1);
+  assert_eq!(violations[0].field_path.as_ref(), "book_id");
+  assert_eq!(violations[0].rule_id.as_ref(), "string.pattern");
+
+  // The id is optional
+  assert!(package
+    .evaluate(
+      "library.v1.CreateBookRequest",
+      &json!({ "parent": "publishers/acme", "book": {} }),
+    )
+    .unwrap()
+    .is_empty());
+}
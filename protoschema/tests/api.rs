@@ -0,0 +1,104 @@
+#![cfg(feature = "api_types")]
+
+use protoschema::{
+  api::{FieldBehavior, ResourceDescriptor, ResourceReference},
+  errors::SchemaError,
+  message, message_option, proto_option, string, OptionValue, Package,
+};
+
+#[test]
+fn typed_options() {
+  let package = Package::new("library.v1");
+  let file = package.new_file("library");
+
+  let book_msg = message!(
+    file.new_message("Book"),
+    1 => string!("name").field_behavior(FieldBehavior::Identifier),
+    2 => string!("shelf")
+      .field_behavior(FieldBehavior::Required)
+      .field_behavior(FieldBehavior::Immutable)
+      .resource_reference(ResourceReference::Type("library.example.com/Shelf".into())),
+  )
+  .resource(
+    ResourceDescriptor::new("library.example.com/Book")
+      .pattern("shelves/{shelf}/books/{book}")
+      .pattern("books/{book}")
+      .plural("books"),
+  );
+
+  let data = book_msg.get_data();
+
+  assert_eq!(
+    data.options.to_vec(),
+    vec![proto_option(
+      "(google.api.resource)",
+      message_option!(
+        "type" => "library.example.com/Book",
+        "pattern" => "shelves/{shelf}/books/{book}",
+        "pattern" => "books/{book}",
+        "plural" => "books"
+      )
+    )]
+  );
+
+  let (_, shelf_field) = &data.fields[1];
+
+  assert_eq!(
+    shelf_field.options.to_vec(),
+    vec![
+      proto_option(
+        "(google.api.field_behavior)",
+        OptionValue::Enum("REQUIRED".into())
+      ),
+      proto_option(
+        "(google.api.field_behavior)",
+        OptionValue::Enum("IMMUTABLE".into())
+      ),
+      proto_option(
+        "(google.api.resource_reference)",
+        message_option!("type" => "library.example.com/Shelf")
+      ),
+    ]
+  );
+
+  let imports = file.get_data().imports;
+
+  assert!(imports.contains(&"google/api/field_behavior.proto".into()));
+  assert!(imports.contains(&"google/api/resource.proto".into()));
+
+  // Repeated options are not reported as conflicts
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn required_rules() {
+  let package = Package::new("mypkg").enforce_required_rules();
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("CreateUserRequest"),
+    1 => string!("name", |v| v.required()).field_behavior(FieldBehavior::Required),
+    2 => string!("email", |v| v.email()).field_behavior(FieldBehavior::Required),
+    3 => string!("nickname").field_behavior(FieldBehavior::Optional),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::MissingRequiredRule {
+      item: "mypkg.CreateUserRequest.email".into()
+    }]
+  );
+}
+
+#[test]
+fn required_rules_are_opt_in() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("CreateUserRequest"),
+    1 => string!("email").field_behavior(FieldBehavior::Required),
+  );
+
+  assert!(package.check().is_empty());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::{fs, io, path::PathBuf};
+
+use protoschema::{build, errors::BuildError, message, string, Package};
+
+use common::user_package;
+
+fn test_dir(name: &str) -> PathBuf {
+  let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("build_{name}"));
+
+  let _ = fs::remove_dir_all(&dir);
+  dir
+}
+
+#[test]
+fn render_and_compile() {
+  let package = user_package();
+  let proto_root = test_dir("compile");
+  let mut received = None;
+
+  let rendered = build::configure(&package)
+    .out_dir(&proto_root)
+    .include("proto_deps")
+    .watch("schema.yaml")
+    .compile(|files, includes| {
+      received = Some((files.to_vec(), includes.to_vec()));
+      Ok::<(), io::Error>(())
+    })
+    .unwrap();
+
+  let user_file = proto_root.join("mypkg/myfile.proto");
+
+  assert_eq!(rendered.files, vec![user_file.clone()]);
+  assert_eq!(
+    rendered.includes,
+    vec![proto_root.clone(), PathBuf::from("proto_deps")]
+  );
+  assert_eq!(
+    rendered.rerun_paths,
+    vec![PathBuf::from("schema.yaml"), PathBuf::from("proto_deps")]
+  );
+  assert_eq!(received, Some((rendered.files, rendered.includes)));
+  assert!(fs::read_to_string(&user_file)
+    .unwrap()
+    .contains("message User"));
+
+  // The files that did not change are not written again
+  let modified = fs::metadata(&user_file).unwrap().modified().unwrap();
+
+  build::configure(&package)
+    .out_dir(&proto_root)
+    .render()
+    .unwrap();
+
+  assert_eq!(
+    fs::metadata(&user_file).unwrap().modified().unwrap(),
+    modified
+  );
+}
+
+#[test]
+fn build_errors() {
+  let package = user_package();
+
+  let result = build::configure(&package)
+    .out_dir(test_dir("errors"))
+    .compile(|_, _| Err("protoc not found"));
+
+  assert_eq!(
+    result.unwrap_err().to_string(),
+    "The code generation failed: protoc not found"
+  );
+
+  let rendered = build::configure(&package)
+    .out_dir(test_dir("default_rerun"))
+    .render()
+    .unwrap();
+
+  let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+  assert_eq!(rendered.rerun_paths, vec![manifest_dir.join("build.rs")]);
+
+  let rendered = build::configure(&package)
+    .out_dir(test_dir("custom_build_script"))
+    .build_script("build/main.rs")
+    .render()
+    .unwrap();
+
+  assert_eq!(
+    rendered.rerun_paths,
+    vec![manifest_dir.join("build/main.rs")]
+  );
+
+  let invalid = Package::new("myapp.v1");
+  let file = invalid.new_file("user");
+
+  message!(
+    file.new_message("User"),
+    1 => string!("name", |v| v.cel([protoschema::cel_rule!(id = "x", msg = "m", expr = "this >")])),
+  );
+
+  assert!(matches!(
+    build::configure(&invalid)
+      .out_dir(test_dir("invalid"))
+      .render(),
+    Err(BuildError::Templating(_))
+  ));
+}
@@ -0,0 +1,196 @@
+use protoschema::{
+  cel_rule,
+  errors::SchemaError,
+  message,
+  messages::MessageBuilder,
+  string, uint32,
+  validators::cel::{cond, field, has, lit, this},
+  Package,
+};
+
+#[test]
+fn cel_expressions() {
+  let password = string!("password");
+
+  assert_eq!(
+    field(&password).eq(field("repeated_password")).to_string(),
+    "this.password == this.repeated_password"
+  );
+
+  assert_eq!(
+    (!(has("a") & has("b")) | field("c").size().gt(2u64)).to_string(),
+    "!(has(this.a) && has(this.b)) || size(this.c) > 2u"
+  );
+
+  assert_eq!(
+    ((field("a") + 1) * 2)
+      .lt(field("b") - (field("c") - 3))
+      .to_string(),
+    "(this.a + 1) * 2 < this.b - (this.c - 3)"
+  );
+
+  assert_eq!(
+    field("emails").all(|e| e.is_email()).to_string(),
+    "this.emails.all(item, item.isEmail())"
+  );
+
+  assert_eq!(
+    field("groups")
+      .all(|group| {
+        group
+          .clone()
+          .member("members")
+          .exists(|member| member.eq(group.member("owner")))
+      })
+      .to_string(),
+    "this.groups.all(item, item.members.exists(item1, item1 == item.owner))"
+  );
+
+  assert_eq!(
+    cond(this().member("x").starts_with("it's"), lit(1.0), 0.5).to_string(),
+    "this.x.startsWith('it\\'s') ? 1.0 : 0.5"
+  );
+
+  assert_eq!(
+    (field("a").eq(f64::NAN) | field("b").lt(f64::NEG_INFINITY)).to_string(),
+    "this.a == double('NaN') || this.b < double('-Infinity')"
+  );
+
+  // The depth of the comprehensions is restored even when building a predicate panics
+  let result = std::panic::catch_unwind(|| field("a").all(|_| panic!("Failed predicate")));
+  assert!(result.is_err());
+  assert_eq!(
+    field("a").all(|item| item.gt(1)).to_string(),
+    "this.a.all(item, item > 1)"
+  );
+}
+
+fn message_with_rule(package: &Package, expr: &str) -> MessageBuilder {
+  let file = package.new_file("myfile");
+
+  let rule = cel_rule!(id = "my_rule", msg = "invalid", expr = expr);
+
+  file.new_message("MyMessage").cel_rules([rule])
+}
+
+#[test]
+fn reports_unknown_cel_fields() {
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(&package, "this.name != 'this.other' && this.nickname.size() > 0"),
+    1 => string!("name"),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::UnknownField {
+      item: "mypkg.MyMessage".into(),
+      field: "nickname".into(),
+      rule: "the cel rule 'my_rule'".into(),
+    }]
+  );
+}
+
+#[test]
+fn type_checks_cel_rules() {
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(&package, "this.age == 'ten'"),
+    1 => uint32!("age"),
+    2 => string!("tags", |v| v.cel([cel_rule!(id = "tag_rule", msg = "invalid", expr = "this.startsWith(1)")])),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![
+      SchemaError::InvalidCelRule {
+        item: "mypkg.MyMessage".into(),
+        rule_id: "my_rule".into(),
+        error: "no matching overload for '==' applied to (uint, string)".into(),
+      },
+      SchemaError::InvalidCelRule {
+        item: "mypkg.MyMessage.tags".into(),
+        rule_id: "tag_rule".into(),
+        error: "'startsWith' expects an argument of type string, found int".into(),
+      },
+    ]
+  );
+}
+
+#[test]
+fn accepts_extension_functions_and_qualified_names() {
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(
+      &package,
+      "this.age > math.greatest(this.limits) ? 'age must be at most %d, found %s'.format([math.greatest(this.limits), this.age]) : ''",
+    ),
+    1 => uint32!("age"),
+    2 => uint32!(repeated "limits"),
+    3 => string!("status", |v| v.cel([
+      cel_rule!(id = "status_rule", msg = "invalid", expr = "this != mypkg.Status.UNKNOWN.name() && this.reverse().lastIndexOf('x') == -1"),
+    ])),
+  );
+
+  assert_eq!(package.check(), vec![]);
+
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(&package, "this.age.format([1]) == '' && this.age.myFunction(this.unknown)"),
+    1 => uint32!("age"),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::InvalidCelRule {
+      item: "mypkg.MyMessage".into(),
+      rule_id: "my_rule".into(),
+      error: "no matching overload for 'format' applied to uint".into(),
+    }]
+  );
+}
+
+#[test]
+fn reports_undeclared_references() {
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(&package, "ths.name == ''"),
+    1 => string!("name", |v| v.cel([cel_rule!(id = "name_rule", msg = "invalid", expr = "myFunction(this)")])),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![
+      SchemaError::InvalidCelRule {
+        item: "mypkg.MyMessage".into(),
+        rule_id: "my_rule".into(),
+        error: "undeclared reference to 'ths'".into(),
+      },
+      SchemaError::InvalidCelRule {
+        item: "mypkg.MyMessage.name".into(),
+        rule_id: "name_rule".into(),
+        error: "undeclared reference to the function 'myFunction'".into(),
+      },
+    ]
+  );
+}
+
+#[test]
+fn accepts_message_construction() {
+  let package = Package::new("mypkg");
+
+  message!(
+    message_with_rule(
+      &package,
+      "this.name != '' || MyMessage{name: 'default'} != google.protobuf.Empty{}",
+    ),
+    1 => string!("name"),
+  );
+
+  assert_eq!(package.check(), vec![]);
+}
@@ -0,0 +1,226 @@
+#![cfg(feature = "cli")]
+
+use std::{fs, path::PathBuf, process::Command};
+
+use serde_json::Value;
+
+const SCHEMA: &str = r#"
+package: myapp.v1
+files:
+  - name: user
+    messages:
+      - name: User
+        fields:
+          - { tag: 1, name: id, type: uint64 }
+          - tag: 2
+            name: email
+            type: string
+            rules: { string: { well_known: email } }
+"#;
+
+// A directory for the files of a single test, which is emptied before it runs
+fn test_dir(name: &str) -> PathBuf {
+  let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("cli_{name}"));
+
+  let _ = fs::remove_dir_all(&dir);
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+// Runs the binary with the JSON output, and returns the exit code with the report
+fn run(args: &[&str]) -> (i32, Value) {
+  let output = Command::new(env!("CARGO_BIN_EXE_protoschema"))
+    .arg("--format=json")
+    .args(args)
+    .output()
+    .unwrap();
+
+  let report = serde_json::from_slice(&output.stdout).unwrap();
+
+  (output.status.code().unwrap(), report)
+}
+
+#[test]
+fn render_and_check() {
+  let dir = test_dir("render");
+  let schema = dir.join("schema.yaml");
+  let out = dir.join("proto");
+
+  fs::write(&schema, SCHEMA).unwrap();
+
+  let args = |command: &'static str| {
+    [
+      command,
+      schema.to_str().unwrap(),
+      "--out",
+      out.to_str().unwrap(),
+    ]
+  };
+
+  let (code, report) = run(&args("check"));
+
+  assert_eq!(code, 1);
+  assert_eq!(report["ok"], false);
+  assert_eq!(report["missing"].as_array().unwrap().len(), 1);
+
+  let (code, report) = run(&args("render"));
+  let user_file = out.join("myapp/v1/user.proto");
+
+  assert_eq!(code, 0);
+  assert_eq!(report["files"][0], user_file.to_str().unwrap());
+  assert!(fs::read_to_string(&user_file)
+    .unwrap()
+    .contains("message User"));
+
+  let (code, report) = run(&args("check"));
+
+  assert_eq!(code, 0);
+  assert_eq!(report["ok"], true);
+
+  fs::write(&user_file, "// edited by hand").unwrap();
+
+  let (code, report) = run(&args("check"));
+
+  assert_eq!(code, 1);
+  assert_eq!(report["stale"][0], user_file.to_str().unwrap());
+}
+
+#[test]
+fn lint_and_errors() {
+  let dir = test_dir("lint");
+  let schema = dir.join("schema.yaml");
+
+  fs::write(
+    &schema,
+    SCHEMA.replace(
+      "{ string: { well_known: email } }",
+      "{ cel: [{ id: email, message: invalid, expression: 'this >' }] }",
+    ),
+  )
+  .unwrap();
+
+  let (code, report) = run(&["lint", schema.to_str().unwrap()]);
+
+  assert_eq!(code, 1);
+  assert_eq!(report["command"], "lint");
+  assert_eq!(report["errors"].as_array().unwrap().len(), 1);
+
+  let invalid = dir.join("invalid.yaml");
+
+  fs::write(&invalid, SCHEMA.replace("type: uint64", "type: User2")).unwrap();
+
+  let (code, report) = run(&["lint", invalid.to_str().unwrap()]);
+
+  assert_eq!(code, 2);
+  assert_eq!(report["error"]["line"], 8);
+  assert_eq!(report["error"]["column"], 29);
+}
+
+#[test]
+fn snapshots_and_breaking_changes() {
+  let dir = test_dir("breaking");
+  let schema = dir.join("schema.yaml");
+  let old_snapshot = dir.join("old.json");
+
+  fs::write(&schema, SCHEMA).unwrap();
+
+  let (code, _) = run(&[
+    "snapshot",
+    schema.to_str().unwrap(),
+    "--output",
+    old_snapshot.to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 0);
+
+  let new_schema = dir.join("new.yaml");
+
+  fs::write(
+    &new_schema,
+    SCHEMA.replace("name: id, type: uint64", "name: id, type: string"),
+  )
+  .unwrap();
+
+  let (code, report) = run(&[
+    "diff",
+    old_snapshot.to_str().unwrap(),
+    new_schema.to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 0);
+  assert_eq!(report["changes"][0]["item"], "myapp.v1.User.id");
+  assert_eq!(report["changes"][0]["kind"], "field_type_changed");
+  assert_eq!(report["changes"][0]["breaking"], true);
+
+  let (code, report) = run(&[
+    "breaking",
+    old_snapshot.to_str().unwrap(),
+    new_schema.to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 1);
+  assert_eq!(report["changes"].as_array().unwrap().len(), 1);
+
+  let (code, report) = run(&[
+    "breaking",
+    old_snapshot.to_str().unwrap(),
+    schema.to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 0);
+  assert_eq!(report["changes"], Value::Array(vec![]));
+}
+
+#[test]
+fn exports() {
+  let dir = test_dir("exports");
+  let schema = dir.join("schema.yaml");
+  let out = dir.join("jsonschema");
+
+  fs::write(&schema, SCHEMA).unwrap();
+
+  let (code, _) = run(&[
+    "export",
+    "json-schema",
+    schema.to_str().unwrap(),
+    "--out",
+    out.to_str().unwrap(),
+  ]);
+
+  let document: Value =
+    serde_json::from_str(&fs::read_to_string(out.join("myapp.v1.User.schema.json")).unwrap())
+      .unwrap();
+
+  assert_eq!(code, 0);
+  assert_eq!(document["properties"]["email"]["format"], "email");
+
+  let constants = dir.join("constants.rs");
+  let (code, _) = run(&[
+    "export",
+    "rust-constants",
+    schema.to_str().unwrap(),
+    "--output",
+    constants.to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 0);
+  assert!(fs::read_to_string(&constants)
+    .unwrap()
+    .contains("pub const EMAIL: u32 = 2;"));
+
+  let (code, report) = run(&[
+    "export",
+    "descriptor-set",
+    schema.to_str().unwrap(),
+    "--output",
+    dir.join("descriptors.bin").to_str().unwrap(),
+    "--protoc",
+    dir.join("missing-protoc").to_str().unwrap(),
+  ]);
+
+  assert_eq!(code, 2);
+  assert!(report["error"]["message"]
+    .as_str()
+    .unwrap()
+    .starts_with("could not run protoc"));
+}
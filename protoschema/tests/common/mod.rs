@@ -0,0 +1,59 @@
+use protoschema::{
+  bytes, double, enum_field, int64, map, message, msg_field, proto_enum, string, timestamp, uint32,
+  Package,
+};
+use regex::Regex;
+
+// The package shared by the tests of the evaluation, the examples, the JSON schemas and the build scripts
+pub fn user_package() -> Package {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let status_enum = proto_enum!(
+    file.new_enum("status"),
+    0 => "UNSPECIFIED",
+    1 => "ACTIVE",
+  );
+
+  let user = file.new_message("User");
+
+  let address_msg = message!(
+    user.new_message("Address"),
+    1 => string!("city", |v| v.min_len(2).max_len(20)),
+    2 => string!("zip_code", |v| v.pattern(Regex::new(r"^\d{5}$").unwrap())),
+  );
+
+  message!(
+    user,
+    cel = [
+      {
+        id = "passwords_match",
+        msg = "the passwords do not match",
+        expr = "this.password == this.repeated_password"
+      }
+    ],
+
+    1 => string!("email", |v| v.email()),
+    2 => string!("username", |v| v.min_len(8).prefix("user_")),
+    3 => uint32!("age", |v| v.gt(17).lt(130)),
+    4 => int64!("score", |v| v.not_in([1, 2, 3])),
+    5 => double!("ratio", |v| v.gte(0.0).lte(1.0)),
+    6 => string!(repeated "tags", |r, v| r.min_items(1).max_items(2).unique().items(v.min_len(1))),
+    7 => string!("country", |v| v.in_(["IT", "FR"])),
+    8 => map!("labels", <string, string>, |m, k, v| m.max_pairs(3).keys(k.min_len(2)).values(v.max_len(4))),
+    9 => enum_field!(status_enum, "status", |v| v.defined_only()),
+    10 => msg_field!(address_msg, "home_address", |v| v.required()),
+    11 => timestamp!("deleted_at", |v| v.lt_now()),
+    12 => bytes!("hash", |v| v.len(4)),
+    13 => string!("password"),
+    14 => string!("repeated_password"),
+
+    oneof "contact" {
+      required,
+      15 => string!("phone", |v| v.pattern(Regex::new(r"^\+[0-9]{6,12}$").unwrap())),
+      16 => string!("website", |v| v.uri()),
+    }
+  );
+
+  package
+}
@@ -0,0 +1,220 @@
+use protoschema::{
+  common::allow_alias,
+  enums::EnumVariant,
+  errors::{ConflictError, SchemaError},
+  message, oneof, proto_enum,
+  services::ServiceHandler,
+  string, uint64, Package,
+};
+
+#[test]
+fn incremental_fields() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  // Each module contributes some fields to the same message
+  let user_msg = file
+    .new_message("User")
+    .add_fields([(3, string!("email"))])
+    .add_reserved([4])
+    .add_reserved_names(["password"]);
+
+  let user_msg = message!(user_msg, 1 => uint64!("id"), 2 => string!("name"))
+    .add_fields([(5, string!("nickname"))]);
+
+  let data = user_msg.get_data();
+
+  assert_eq!(
+    data
+      .fields
+      .iter()
+      .map(|(tag, field)| (*tag, field.name.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![(1, "id"), (2, "name"), (3, "email"), (5, "nickname")]
+  );
+  assert_eq!(data.reserved_numbers.as_ref(), &[4]);
+  assert_eq!(data.reserved_names.as_ref(), &["password".into()]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.User: the number 1 is already used by 'id'")]
+fn duplicate_tags() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(file.new_message("User"), 1 => uint64!("id")).add_fields([(1, string!("name"))]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.User: the name 'id' is already in use")]
+fn duplicate_names_across_calls() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = file.new_message("User").add_fields([(1, uint64!("id"))]);
+
+  message!(user_msg, 2 => uint64!("id"));
+}
+
+#[test]
+#[should_panic(expected = "mypkg.User: the number 3 is reserved, but it is used by 'email'")]
+fn reserved_tags() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  file
+    .new_message("User")
+    .add_reserved_ranges([2..4, 10..12])
+    .add_fields([(3, string!("email"))]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.User: the name 'id' is already in use")]
+fn oneof_collisions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  file
+    .new_message("User")
+    .add_fields([(1, uint64!("id"))])
+    .add_oneofs([oneof!("choice", 2 => string!("id"))]);
+}
+
+#[test]
+fn reported_collisions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  // The reservations set with `reserved_numbers` are not checked against the existing fields
+  file
+    .new_message("User")
+    .add_fields([(1, uint64!("id"))])
+    .reserved_numbers([1]);
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::MemberConflict(ConflictError::ReservedNumber {
+      item: "mypkg.User".into(),
+      number: 1,
+      name: "id".into(),
+    })]
+  );
+}
+
+#[test]
+fn incremental_variants() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let status_enum = proto_enum!(file.new_enum("Status"), 0 => "UNSPECIFIED")
+    .add_variants([(1, EnumVariant::builder().name("ACTIVE").build())])
+    .add_reserved([2]);
+
+  // The names are prefixed with the name of the enum in the template
+  let data = status_enum.get_data();
+
+  assert_eq!(
+    data
+      .variants
+      .iter()
+      .map(|(number, variant)| (*number, variant.name.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![(0, "STATUS_UNSPECIFIED"), (1, "STATUS_ACTIVE")]
+  );
+  assert_eq!(data.reserved_numbers.as_ref(), &[2]);
+
+  // Aliases are allowed with the allow_alias option
+  proto_enum!(
+    file.new_enum("Aliased"),
+    options = [allow_alias()],
+    0 => "UNSPECIFIED",
+    1 => "STARTED"
+  )
+  .add_variants([(1, EnumVariant::builder().name("RUNNING").build())]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.Status: the number 0 is already used by 'UNSPECIFIED'")]
+fn duplicate_variants() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  proto_enum!(file.new_enum("Status"), 0 => "UNSPECIFIED")
+    .add_variants([(0, EnumVariant::builder().name("UNKNOWN").build())]);
+}
+
+#[test]
+fn incremental_handlers() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = message!(file.new_message("User"), 1 => uint64!("id"));
+
+  let handler = |name: &str| {
+    ServiceHandler::new(name.into())
+      .request(&user_msg)
+      .response(&user_msg)
+      .build()
+  };
+
+  let service = file
+    .new_service("UserService")
+    .add_handlers([handler("GetUser")])
+    .handlers([handler("UpdateUser")]);
+
+  assert_eq!(service.get_data().handlers.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.UserService: the name 'GetUser' is already in use")]
+fn duplicate_handlers() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = message!(file.new_message("User"), 1 => uint64!("id"));
+
+  let handler = |name: &str| {
+    ServiceHandler::new(name.into())
+      .request(&user_msg)
+      .response(&user_msg)
+      .build()
+  };
+
+  file
+    .new_service("UserService")
+    .add_handlers([handler("GetUser"), handler("GetUser")]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.UserService: the name 'GetUser' is already in use")]
+fn duplicate_handlers_across_calls() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = message!(file.new_message("User"), 1 => uint64!("id"));
+
+  let handler = |name: &str| {
+    ServiceHandler::new(name.into())
+      .request(&user_msg)
+      .response(&user_msg)
+      .build()
+  };
+
+  file
+    .new_service("UserService")
+    .add_handlers([handler("GetUser")])
+    .handlers([handler("GetUser")]);
+}
+
+#[test]
+#[should_panic(expected = "mypkg.Status: the name 'UNSPECIFIED' is already in use")]
+fn duplicate_variants_across_calls() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let status_enum = file
+    .new_enum("Status")
+    .add_variants([(0, EnumVariant::builder().name("UNSPECIFIED").build())]);
+
+  proto_enum!(status_enum, 1 => "UNSPECIFIED");
+}
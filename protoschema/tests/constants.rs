@@ -0,0 +1,64 @@
+use std::{fs, path::PathBuf};
+
+use protoschema::{message, proto_enum, proto_option, string, uint64, Package};
+
+fn constants_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+
+  let user_msg = file.new_message("User");
+  let user_msg = message!(
+    user_msg,
+    1 => uint64!("id"),
+    2 => string!("display_name"),
+    3 => string!("nickname").add_option(proto_option("json_name", "alias")),
+
+    enum "status" {
+      0 => "UNSPECIFIED",
+      1 => "ACTIVE",
+    }
+
+    oneof "contact" {
+      10 => string!("phone_number"),
+      11 => string!("email"),
+    }
+  );
+
+  message!(user_msg.new_message("Type"), 1 => string!("name"));
+  proto_enum!(user_msg.new_enum("Fields"), 0 => "UNSPECIFIED");
+  file.new_message("Empty");
+
+  proto_enum!(file.new_enum("Role"), 0 => "UNSPECIFIED", 1 => "ADMIN");
+
+  package
+}
+
+// The module rendered for the package above, which is included to check that it compiles
+#[allow(dead_code)]
+mod generated {
+  include!("fixtures/constants.rs");
+}
+
+#[test]
+fn rust_constants() {
+  let package = constants_package();
+  let rendered = package.build_constants_template().to_string();
+
+  assert_eq!(rendered, include_str!("fixtures/constants.rs"));
+
+  assert_eq!(generated::user::FULL_NAME, "myapp.v1.User");
+  assert_eq!(generated::user::fields::DISPLAY_NAME, "display_name");
+  assert_eq!(generated::user::numbers::EMAIL, 11);
+  assert_eq!(generated::user::json_names::PHONE_NUMBER, "phoneNumber");
+  assert_eq!(generated::user::json_names::NICKNAME, "alias");
+  assert_eq!(generated::user::fields_::FIELDS_UNSPECIFIED, 0);
+  assert_eq!(generated::user::status::STATUS_ACTIVE, 1);
+  assert_eq!(generated::user::r#type::FULL_NAME, "myapp.v1.User.Type");
+  assert_eq!(generated::role::ROLE_ADMIN, 1);
+
+  let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("constants/myapp.rs");
+
+  package.render_constants(&path).unwrap();
+
+  assert_eq!(fs::read_to_string(&path).unwrap(), rendered);
+}
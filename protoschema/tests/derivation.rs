@@ -0,0 +1,243 @@
+use protoschema::{
+  derivation::Derivation, errors::SchemaError, message, message_option, msg_field, proto_option,
+  string, timestamp, uint32, validators::string::StringValidator, Package,
+};
+
+#[test]
+fn derived_fields() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+  let requests_file = package.new_file("requests");
+
+  let user_msg = message!(
+    file.new_message("User"),
+    1 => uint32!("id"),
+    2 => string!("name", |v| v.required().min_len(2)),
+    3 => string!("email", |v| v.required()),
+    4 => timestamp!("created_at"),
+    5 => string!("bio", |v| v.max_len(100)),
+  );
+
+  let create_user_msg = requests_file.new_message("CreateUserRequest").derive_from(
+    &user_msg,
+    Derivation::new()
+      .omit(["id", "created_at"])
+      .rename("email", "contact_email")
+      .without_required(["email"])
+      .validator("bio", StringValidator::builder().max_len(50))
+      .renumber()
+      .tag("bio", 10)
+      .add_field(4, string!("request_id")),
+  );
+
+  let lineage = create_user_msg.get_lineage().unwrap();
+
+  assert_eq!(lineage.source.full_name_with_package.as_ref(), "mypkg.User");
+  assert_eq!(
+    lineage.fields,
+    vec![
+      ("name".into(), "name".into()),
+      ("contact_email".into(), "email".into()),
+      ("bio".into(), "bio".into()),
+    ]
+  );
+
+  let data = create_user_msg.get_data();
+  let fields: Vec<(u32, &str)> = data
+    .fields
+    .iter()
+    .map(|(tag, field)| (*tag, field.name.as_ref()))
+    .collect();
+
+  assert_eq!(
+    fields,
+    vec![
+      (1, "name"),
+      (2, "contact_email"),
+      (4, "request_id"),
+      (10, "bio")
+    ]
+  );
+
+  assert_eq!(
+    data.fields[0].1.options.to_vec(),
+    vec![proto_option(
+      "(buf.validate.field)",
+      message_option!("string" => message_option!("min_len" => 2u64), "required" => true)
+    )]
+  );
+  // The validator only contained the required rule
+  assert!(data.fields[1].1.options.is_empty());
+  assert_eq!(
+    data.fields[3].1.options.to_vec(),
+    vec![proto_option(
+      "(buf.validate.field)",
+      message_option!("string" => message_option!("max_len" => 50u64))
+    )]
+  );
+
+  assert!(requests_file
+    .get_data()
+    .imports
+    .contains(&"buf/validate/validate.proto".into()));
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn picked_fields_and_imports() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+  let summaries_file = package.new_file("summaries");
+
+  let address_msg = message!(file.new_message("Address"), 1 => string!("city"));
+
+  let user_msg = message!(
+    file.new_message("User"),
+    1 => uint32!("id"),
+    2 => string!("name"),
+    3 => msg_field!(address_msg, "address"),
+    4 => timestamp!("created_at"),
+  );
+
+  let summary_msg = summaries_file
+    .new_message("UserSummary")
+    .derive_from(&user_msg, Derivation::new().pick(["id", "address"]));
+
+  let data = summary_msg.get_data();
+
+  assert_eq!(
+    data
+      .fields
+      .iter()
+      .map(|(tag, field)| (*tag, field.name.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![(1, "id"), (3, "address")]
+  );
+
+  let imports = summaries_file.get_data().imports;
+
+  assert!(imports.contains(&"mypkg/myfile.proto".into()));
+  assert!(!imports.contains(&"google/protobuf/timestamp.proto".into()));
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.UserSummary: the field 'age' does not exist in the source message 'mypkg.User'"
+)]
+fn unknown_fields() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = message!(file.new_message("User"), 1 => string!("name"));
+
+  file
+    .new_message("UserSummary")
+    .derive_from(&user_msg, Derivation::new().omit(["age"]));
+}
+
+#[test]
+#[should_panic(expected = "mypkg.UserSummary: the tag 1 is used by more than one field")]
+fn duplicate_tags() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = message!(file.new_message("User"), 1 => string!("name"));
+
+  file.new_message("UserSummary").derive_from(
+    &user_msg,
+    Derivation::new().add_field(1, string!("nickname")),
+  );
+}
+
+#[test]
+fn outdated_derivations() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  // The source gets its fields after the derivation
+  let user_msg = file.new_message("User");
+
+  let summary_msg = file
+    .new_message("UserSummary")
+    .derive_from(&user_msg, Derivation::new());
+
+  let summary_copy_msg = file
+    .new_message("UserSummaryCopy")
+    .derive_from(&summary_msg, Derivation::new().renumber());
+
+  message!(
+    user_msg,
+    3 => string!("name"),
+    4 => string!("email"),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::OutdatedDerivation {
+      item: "mypkg.UserSummary".into(),
+      source_message: "mypkg.User".into(),
+    }]
+  );
+
+  package.propagate_derivations().unwrap();
+
+  assert!(package.check().is_empty());
+  assert_eq!(
+    summary_copy_msg
+      .get_data()
+      .fields
+      .iter()
+      .map(|(tag, field)| (*tag, field.name.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![(1, "name"), (2, "email")]
+  );
+}
+
+#[test]
+fn propagation_keeps_added_fields() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let user_msg = file.new_message("User");
+
+  let summary_msg = file
+    .new_message("UserSummary")
+    .derive_from(
+      &user_msg,
+      Derivation::new().add_field(4, string!("nickname")),
+    )
+    .add_fields([(5, string!("extra"))]);
+
+  message!(user_msg, 1 => string!("name"));
+
+  package.propagate_derivations().unwrap();
+
+  assert_eq!(
+    summary_msg
+      .get_data()
+      .fields
+      .iter()
+      .map(|(tag, field)| (*tag, field.name.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![(1, "name"), (4, "nickname"), (5, "extra")]
+  );
+}
+
+#[test]
+#[should_panic(
+  expected = "The source of a derivation must belong to the same package as the derived message"
+)]
+fn foreign_sources() {
+  let package = Package::new("mypkg");
+  let other_package = Package::new("otherpkg");
+
+  let user_msg = message!(
+    other_package.new_file("myfile").new_message("User"),
+    1 => string!("name"),
+  );
+
+  package
+    .new_file("myfile")
+    .new_message("UserSummary")
+    .derive_from(&user_msg, Derivation::new());
+}
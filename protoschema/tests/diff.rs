@@ -0,0 +1,154 @@
+use protoschema::{
+  diff::{Change, ChangeKind},
+  fields::FieldKind,
+  message, proto_enum, proto_option, services, string, uint64, Package,
+};
+
+fn old_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+
+  let user_msg = message!(
+    file.new_message("User"),
+    1 => uint64!("id"),
+    2 => string!("name"),
+    3 => string!("email"),
+    4 => string!("nickname"),
+  );
+
+  message!(file.new_message("Legacy"), 1 => string!("note"));
+
+  proto_enum!(
+    file.new_enum("Status"),
+    0 => "UNSPECIFIED",
+    1 => "ACTIVE",
+    2 => "BANNED",
+  );
+
+  services!(
+    file,
+    UserService {
+      GetUser(user_msg => user_msg),
+      DeleteUser(user_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+fn new_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+
+  let request_msg = message!(file.new_message("GetUserRequest"), 1 => uint64!("id"));
+
+  let user_msg = message!(
+    file.new_message("User"),
+    reserved = [ 3 ],
+
+    1 => uint64!("id"),
+    2 => string!("full_name", |v| v.min_len(1)),
+    4 => string!(repeated "nickname"),
+    5 => string!("phone"),
+  );
+
+  proto_enum!(
+    file.new_enum("Status"),
+    reserved = [ 2 ]
+    0 => "UNSPECIFIED",
+    1 => "ACTIVE" { [proto_option("deprecated", true)] },
+  );
+
+  services!(
+    file,
+    UserService {
+      GetUser(request_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+fn change(item: &str, kind: ChangeKind) -> Change {
+  Change {
+    item: item.into(),
+    kind,
+  }
+}
+
+#[test]
+fn schema_changes() {
+  let changes = new_package().schema().diff(&old_package().schema());
+
+  assert_eq!(
+    changes,
+    vec![
+      change("myapp.v1.GetUserRequest", ChangeKind::MessageAdded),
+      change(
+        "myapp.v1.User.full_name",
+        ChangeKind::FieldRenamed {
+          tag: 2,
+          old: "name".into()
+        }
+      ),
+      change("myapp.v1.User.full_name", ChangeKind::OptionsChanged),
+      change(
+        "myapp.v1.User.nickname",
+        ChangeKind::FieldKindChanged {
+          old: FieldKind::Normal,
+          new: FieldKind::Repeated
+        }
+      ),
+      change("myapp.v1.User.phone", ChangeKind::FieldAdded { tag: 5 }),
+      change(
+        "myapp.v1.User.email",
+        ChangeKind::FieldRemoved {
+          tag: 3,
+          reserved: true
+        }
+      ),
+      change("myapp.v1.Legacy", ChangeKind::MessageRemoved),
+      change("myapp.v1.Status.STATUS_ACTIVE", ChangeKind::OptionsChanged),
+      change(
+        "myapp.v1.Status.STATUS_BANNED",
+        ChangeKind::VariantRemoved {
+          number: 2,
+          reserved: true
+        }
+      ),
+      change(
+        "myapp.v1.UserService.GetUser",
+        ChangeKind::RequestChanged {
+          old: "myapp.v1.User".into(),
+          new: "myapp.v1.GetUserRequest".into()
+        }
+      ),
+      change(
+        "myapp.v1.UserService.DeleteUser",
+        ChangeKind::HandlerRemoved
+      ),
+    ]
+  );
+
+  let breaking: Vec<String> = changes
+    .iter()
+    .filter(|change| change.is_breaking())
+    .map(|change| change.to_string())
+    .collect();
+
+  assert_eq!(
+    breaking,
+    vec![
+      "myapp.v1.User.full_name: the field with the tag 2 was renamed from 'name'",
+      "myapp.v1.User.nickname: the cardinality of the field changed from 'singular' to 'repeated'",
+      "myapp.v1.Legacy: the message was removed",
+      "myapp.v1.UserService.GetUser: the request changed from 'myapp.v1.User' to 'myapp.v1.GetUserRequest'",
+      "myapp.v1.UserService.DeleteUser: the handler was removed",
+    ]
+  );
+
+  assert!(old_package()
+    .schema()
+    .diff(&old_package().schema())
+    .is_empty());
+}
@@ -0,0 +1,347 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use protoschema::{
+  bytes, cel_rule, errors::EvaluationError, evaluation::Violation, map, message,
+  options::proto_option, string, Package,
+};
+use serde_json::json;
+
+use common::user_package;
+
+fn violation(field_path: &str, rule_id: &str, message: &str) -> Violation {
+  Violation {
+    field_path: field_path.into(),
+    rule_id: rule_id.into(),
+    message: message.into(),
+    for_key: false,
+  }
+}
+
+fn key_violation(field_path: &str, rule_id: &str, message: &str) -> Violation {
+  Violation {
+    for_key: true,
+    ..violation(field_path, rule_id, message)
+  }
+}
+
+#[test]
+fn valid_instance() {
+  let package = user_package();
+
+  let violations = package
+    .evaluate(
+      "User",
+      &json!({
+        "email": "me@example.com",
+        "username": "user_name",
+        "age": 30,
+        "score": "4",
+        "ratio": 0.5,
+        "tags": ["a", "b"],
+        "country": "IT",
+        "labels": { "ab": "cd" },
+        "status": "ACTIVE",
+        "homeAddress": { "city": "Rome", "zipCode": "00100" },
+        "hash": "AAAAAA==",
+        "password": "secret",
+        "repeatedPassword": "secret",
+        "deletedAt": "2020-01-01T00:00:00Z",
+        "website": "https://example.com",
+      }),
+    )
+    .unwrap();
+
+  assert!(violations.is_empty(), "{violations:#?}");
+}
+
+#[test]
+fn reports_violations() {
+  let package = user_package();
+
+  let violations = package
+    .evaluate(
+      "mypkg.User",
+      &json!({
+        "email": "not an email",
+        "username": "user_name",
+        "age": 12,
+        "tags": ["a", "a", ""],
+        "country": "FR",
+        "labels": { "a": "too long" },
+        "status": 5,
+        "home_address": { "city": "R", "zip_code": "00100" },
+        "hash": "AAAAAA==",
+        "password": "secret",
+        "repeated_password": "other",
+        "deleted_at": "2999-01-01T00:00:00Z",
+      }),
+    )
+    .unwrap();
+
+  assert_eq!(
+    violations,
+    vec![
+      violation("", "passwords_match", "the passwords do not match"),
+      violation(
+        "contact",
+        "required",
+        "exactly one field is required in oneof"
+      ),
+      violation(
+        "email",
+        "string.email",
+        "value must be a valid email address"
+      ),
+      violation(
+        "age",
+        "uint32.gt_lt",
+        "value must be greater than 17 and less than 130"
+      ),
+      violation(
+        "tags",
+        "repeated.unique",
+        "repeated value must contain unique items"
+      ),
+      violation(
+        "tags",
+        "repeated.max_items",
+        "value must contain no more than 2 item(s)"
+      ),
+      violation(
+        "tags[2]",
+        "string.min_len",
+        "value length must be at least 1 characters"
+      ),
+      key_violation(
+        "labels[\"a\"]",
+        "string.min_len",
+        "value length must be at least 2 characters"
+      ),
+      violation(
+        "labels[\"a\"]",
+        "string.max_len",
+        "value length must be at most 4 characters"
+      ),
+      violation(
+        "status",
+        "enum.defined_only",
+        "value must be one of the defined enum values"
+      ),
+      violation(
+        "home_address.city",
+        "string.min_len",
+        "value length must be at least 2 characters"
+      ),
+      violation(
+        "deleted_at",
+        "timestamp.lt_now",
+        "value must be less than now"
+      ),
+    ]
+  );
+}
+
+#[test]
+fn field_cel_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let msg = message!(
+    file.new_message("MyMessage"),
+    1 => string!("name", |v| v.cel([
+      cel_rule!(id = "no_spaces", msg = "", expr = "!this.contains(' ')"),
+      cel_rule!(id = "not_admin", msg = "", expr = "this == 'admin' ? 'reserved name' : ''"),
+    ])),
+  );
+
+  assert_eq!(
+    msg.evaluate(&json!({ "name": "admin user" })).unwrap(),
+    vec![violation(
+      "name",
+      "no_spaces",
+      "\"!this.contains(' ')\" returned false"
+    )]
+  );
+
+  assert_eq!(
+    msg.evaluate(&json!({ "name": "admin" })).unwrap(),
+    vec![violation("name", "not_admin", "reserved name")]
+  );
+}
+
+#[test]
+fn cel_string_functions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let msg = message!(
+    file.new_message("MyMessage"),
+    1 => string!("name", |v| v.cel([
+      cel_rule!(id = "format", msg = "", expr = "'%s: %d%% %.2f %e %x %X %o %b %x'.format([this, -3, 1.5, 1234.5, 255, 255u, 8, 5, 'hi'])"),
+      cel_rule!(id = "reverse", msg = "", expr = "this.reverse()"),
+      cel_rule!(id = "invalid", msg = "", expr = "size(this) > 5 ? '%d'.format([this]) : ''"),
+    ])),
+  );
+
+  assert_eq!(
+    msg.evaluate(&json!({ "name": "abc" })).unwrap(),
+    vec![
+      violation(
+        "name",
+        "format",
+        "abc: -3% 1.50 1.234500e+03 ff FF 10 101 6869"
+      ),
+      violation("name", "reverse", "cba"),
+    ]
+  );
+
+  assert_eq!(
+    msg.evaluate(&json!({ "name": "abcdef" })),
+    Err(EvaluationError::RuleEvaluationFailure {
+      field_path: "name".into(),
+      rule_id: "invalid".into(),
+      error: "the format verb '%d' cannot be applied to string".into(),
+    })
+  );
+}
+
+#[test]
+fn ignored_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let msg = message!(
+    file.new_message("MyMessage"),
+    1 => string!("always", |v| v.min_len(3).ignore_always()),
+    2 => string!("if_zero", |v| v.min_len(3).ignore_if_zero_value()),
+    3 => string!(repeated "tags", |r, v| r.items(v.min_len(3).ignore_if_zero_value())),
+  );
+
+  assert_eq!(msg.evaluate(&json!({})).unwrap(), vec![]);
+
+  assert_eq!(
+    msg
+      .evaluate(&json!({ "always": "x", "if_zero": "", "tags": ["", "abc"] }))
+      .unwrap(),
+    vec![]
+  );
+
+  let violations = msg
+    .evaluate(&json!({ "always": "x", "if_zero": "x", "tags": ["x"] }))
+    .unwrap();
+
+  assert_eq!(
+    violations
+      .iter()
+      .map(|v| (v.field_path.as_ref(), v.rule_id.as_ref()))
+      .collect::<Vec<_>>(),
+    vec![("if_zero", "string.min_len"), ("tags[0]", "string.min_len")]
+  );
+}
+
+#[test]
+fn invalid_instances() {
+  let package = user_package();
+
+  assert_eq!(
+    package.evaluate("Unknown", &json!({})),
+    Err(EvaluationError::UnknownMessage("Unknown".into()))
+  );
+
+  assert!(matches!(
+    package.evaluate("User", &json!({ "nickname": "me" })),
+    Err(EvaluationError::InvalidInstance { .. })
+  ));
+
+  assert!(matches!(
+    package.evaluate("User", &json!({ "age": "ten" })),
+    Err(EvaluationError::InvalidInstance { field_path, .. }) if field_path.as_ref() == "age"
+  ));
+
+  assert!(matches!(
+    package.evaluate(
+      "User",
+      &json!({ "phone": "123", "website": "https://example.com" })
+    ),
+    Err(EvaluationError::InvalidInstance { .. })
+  ));
+}
+
+#[test]
+fn bytes_literals() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Blob"),
+    1 => bytes!("data", |v| v.cel([
+      cel_rule!(id = "octets", msg = "invalid", expr = "this == b'\\xff\\101' && size(this) == 2"),
+    ])),
+  );
+
+  assert_eq!(
+    package.evaluate("Blob", &json!({ "data": "/0E=" })),
+    Ok(vec![])
+  );
+}
+
+#[test]
+fn explicit_json_names() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Account"),
+    1 => string!("user_id", |v| v.min_len(3)).add_option(proto_option("json_name", "uid")),
+  );
+
+  for key in ["uid", "user_id"] {
+    assert_eq!(
+      package.evaluate("Account", &json!({ key: "ab" })),
+      Ok(vec![violation(
+        "user_id",
+        "string.min_len",
+        "value length must be at least 3 characters"
+      )])
+    );
+  }
+
+  assert!(matches!(
+    package.evaluate("Account", &json!({ "userId": "abc" })),
+    Err(EvaluationError::InvalidInstance { .. })
+  ));
+}
+
+#[test]
+fn map_key_violations() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Stats"),
+    1 => map!("counts", <int32, int32>, |m, k, v| m.keys(k.gt(0)).values(v.gt(0))),
+  );
+
+  let violations = package
+    .evaluate("Stats", &json!({ "counts": { "-1": -1 } }))
+    .unwrap();
+
+  assert_eq!(
+    violations,
+    vec![
+      key_violation("counts[-1]", "int32.gt", "value must be greater than 0"),
+      violation("counts[-1]", "int32.gt", "value must be greater than 0"),
+    ]
+  );
+
+  assert_eq!(
+    violations[0].to_string(),
+    "counts[-1] (key): [int32.gt] value must be greater than 0"
+  );
+  assert_eq!(
+    violations[1].to_string(),
+    "counts[-1]: [int32.gt] value must be greater than 0"
+  );
+}
@@ -0,0 +1,208 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use protoschema::{errors::ExampleError, message, options::proto_option, string, Package};
+use serde_json::json;
+
+use common::user_package;
+
+#[test]
+fn valid_example() {
+  let package = user_package();
+  let examples = package.examples("User").unwrap();
+
+  assert!(package
+    .evaluate("User", &examples.valid)
+    .unwrap()
+    .is_empty());
+
+  let valid = &examples.valid;
+
+  assert_eq!(valid["email"], json!("user@example.com"));
+  assert_eq!(valid["username"], json!("user_value"));
+  assert_eq!(valid["age"], json!(73));
+  assert_eq!(valid["score"], json!("4"));
+  assert_eq!(valid["tags"], json!(["value"]));
+  assert_eq!(valid["country"], json!("IT"));
+  assert_eq!(valid["status"], json!("STATUS_ACTIVE"));
+  assert_eq!(valid["homeAddress"]["zipCode"], json!("00000"));
+  assert_eq!(valid["hash"], json!("YWx1ZQ=="));
+  assert_eq!(valid["phone"], json!("+000000"));
+}
+
+#[test]
+fn invalid_examples() {
+  let package = user_package();
+  let examples = package.examples("mypkg.User").unwrap();
+
+  let rules: Vec<(&str, &str)> = examples
+    .invalid
+    .iter()
+    .map(|example| {
+      (
+        example.violation.field_path.as_ref(),
+        example.violation.rule_id.as_ref(),
+      )
+    })
+    .collect();
+
+  for expected in [
+    ("email", "string.email"),
+    ("email", "string.email_empty"),
+    ("username", "string.min_len"),
+    ("username", "string.prefix"),
+    ("age", "uint32.gt_lt"),
+    ("score", "int64.not_in"),
+    ("ratio", "double.gte_lte"),
+    ("tags", "repeated.min_items"),
+    ("tags", "repeated.max_items"),
+    ("tags", "repeated.unique"),
+    ("tags[0]", "string.min_len"),
+    ("country", "string.in"),
+    ("labels", "map.max_pairs"),
+    ("status", "enum.defined_only"),
+    ("home_address", "required"),
+    ("home_address.city", "string.min_len"),
+    ("home_address.city", "string.max_len"),
+    ("home_address.zip_code", "string.pattern"),
+    ("deleted_at", "timestamp.lt_now"),
+    ("hash", "bytes.len"),
+    ("contact", "required"),
+    ("phone", "string.pattern"),
+  ] {
+    assert!(
+      rules.contains(&expected),
+      "missing {expected:?} in {rules:#?}"
+    );
+  }
+
+  for example in &examples.invalid {
+    assert_eq!(
+      package.evaluate("User", &example.instance).unwrap(),
+      vec![example.violation.clone()]
+    );
+  }
+}
+
+#[test]
+fn ignored_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("MyMessage"),
+    1 => string!("always", |v| v.min_len(3).ignore_always()),
+    2 => string!("if_zero", |v| v.min_len(1).max_len(3).ignore_if_zero_value()),
+    3 => string!(repeated "tags", |r, _| r.min_items(1).max_items(2).ignore_if_zero_value()),
+  );
+
+  let examples = package.examples("MyMessage").unwrap();
+
+  let mut rules: Vec<(&str, &str)> = examples
+    .invalid
+    .iter()
+    .map(|example| {
+      (
+        example.violation.field_path.as_ref(),
+        example.violation.rule_id.as_ref(),
+      )
+    })
+    .collect();
+
+  rules.sort();
+
+  assert_eq!(
+    rules,
+    vec![
+      ("if_zero", "string.max_len"),
+      ("tags", "repeated.max_items")
+    ]
+  );
+}
+
+#[test]
+fn unsatisfied_cel_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("MyMessage"),
+    cel = [
+      {
+        id = "impossible",
+        msg = "the name cannot be 'value'",
+        expr = "this.name != 'value'"
+      }
+    ],
+
+    1 => string!("name"),
+  );
+
+  assert!(matches!(
+    package.examples("MyMessage"),
+    Err(ExampleError::UnsatisfiedRules(violations)) if violations[0].rule_id.as_ref() == "impossible"
+  ));
+
+  assert_eq!(
+    package.examples("Unknown"),
+    Err(ExampleError::UnknownMessage("Unknown".into()))
+  );
+}
+
+#[cfg(feature = "proptest")]
+mod strategies {
+  use proptest::{
+    prelude::*,
+    test_runner::{Config, TestRunner},
+  };
+  use protoschema::examples::{invalid_instances, valid_instances};
+
+  use super::user_package;
+
+  #[test]
+  fn valid_instances_strategy() {
+    let package = user_package();
+    let strategy = valid_instances(&package, "User").unwrap();
+
+    TestRunner::new(Config::with_cases(32))
+      .run(&strategy, |instance| {
+        prop_assert!(package.evaluate("User", &instance).unwrap().is_empty());
+        Ok(())
+      })
+      .unwrap();
+  }
+
+  #[test]
+  fn invalid_instances_strategy() {
+    let package = user_package();
+    let strategy = invalid_instances(&package, "User").unwrap();
+
+    TestRunner::new(Config::with_cases(32))
+      .run(&strategy, |example| {
+        prop_assert_eq!(
+          package.evaluate("User", &example.instance).unwrap(),
+          vec![example.violation]
+        );
+        Ok(())
+      })
+      .unwrap();
+  }
+}
+
+#[test]
+fn explicit_json_names() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Account"),
+    1 => string!("user_id", |v| v.min_len(3)).add_option(proto_option("json_name", "uid")),
+  );
+
+  let examples = package.examples("Account").unwrap();
+
+  assert_eq!(examples.valid, json!({ "uid": "value" }));
+  assert_eq!(examples.invalid.len(), 1);
+  assert_eq!(examples.invalid[0].instance, json!({ "uid": "va" }));
+}
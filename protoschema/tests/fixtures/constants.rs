@@ -0,0 +1,87 @@
+// The constants for the `myapp.v1` package, generated by protoschema. Do not edit.
+
+/// The constants for the `myapp.v1.Role` enum
+pub mod role {
+  /// The fully qualified name of the enum
+  pub const FULL_NAME: &str = "myapp.v1.Role";
+
+  pub const ROLE_UNSPECIFIED: i32 = 0;
+  pub const ROLE_ADMIN: i32 = 1;
+}
+
+/// The constants for the `myapp.v1.User` message
+pub mod user {
+  /// The fully qualified name of the message
+  pub const FULL_NAME: &str = "myapp.v1.User";
+
+  /// The names of the fields
+  pub mod fields {
+    pub const ID: &str = "id";
+    pub const DISPLAY_NAME: &str = "display_name";
+    pub const NICKNAME: &str = "nickname";
+    pub const PHONE_NUMBER: &str = "phone_number";
+    pub const EMAIL: &str = "email";
+  }
+
+  /// The numbers of the fields
+  pub mod numbers {
+    pub const ID: u32 = 1;
+    pub const DISPLAY_NAME: u32 = 2;
+    pub const NICKNAME: u32 = 3;
+    pub const PHONE_NUMBER: u32 = 10;
+    pub const EMAIL: u32 = 11;
+  }
+
+  /// The names of the fields in the JSON format
+  pub mod json_names {
+    pub const ID: &str = "id";
+    pub const DISPLAY_NAME: &str = "displayName";
+    pub const NICKNAME: &str = "alias";
+    pub const PHONE_NUMBER: &str = "phoneNumber";
+    pub const EMAIL: &str = "email";
+  }
+
+  /// The constants for the `myapp.v1.User.status` enum
+  pub mod status {
+    /// The fully qualified name of the enum
+    pub const FULL_NAME: &str = "myapp.v1.User.status";
+
+    pub const STATUS_UNSPECIFIED: i32 = 0;
+    pub const STATUS_ACTIVE: i32 = 1;
+  }
+
+  /// The constants for the `myapp.v1.User.Fields` enum
+  pub mod fields_ {
+    /// The fully qualified name of the enum
+    pub const FULL_NAME: &str = "myapp.v1.User.Fields";
+
+    pub const FIELDS_UNSPECIFIED: i32 = 0;
+  }
+
+  /// The constants for the `myapp.v1.User.Type` message
+  pub mod r#type {
+    /// The fully qualified name of the message
+    pub const FULL_NAME: &str = "myapp.v1.User.Type";
+
+    /// The names of the fields
+    pub mod fields {
+      pub const NAME: &str = "name";
+    }
+
+    /// The numbers of the fields
+    pub mod numbers {
+      pub const NAME: u32 = 1;
+    }
+
+    /// The names of the fields in the JSON format
+    pub mod json_names {
+      pub const NAME: &str = "name";
+    }
+  }
+}
+
+/// The constants for the `myapp.v1.Empty` message
+pub mod empty {
+  /// The fully qualified name of the message
+  pub const FULL_NAME: &str = "myapp.v1.Empty";
+}
@@ -0,0 +1,121 @@
+use protoschema::{generics::GenericMessage, message, proto_enum, string, FieldType, Package};
+
+fn page() -> GenericMessage {
+  GenericMessage::new("Page", ["T"])
+    .nested(|page| {
+      proto_enum!(page.message().new_enum("Order"), 0 => "ASC", 1 => "DESC");
+    })
+    .field(1, |page| page.field("T", "items").repeated())
+    .field(2, |_| string!("next_page_token"))
+    .field(3, |page| page.nested_field("Order", "order"))
+}
+
+#[test]
+fn instances() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+  let pages_file = package.new_file("pages");
+
+  let user_msg = message!(file.new_message("User"), 1 => string!("name"));
+
+  let page = page();
+
+  let user_page_msg = page.instantiate(&pages_file, [user_msg.get_type()]);
+  let timestamp_page_msg = page.instantiate(&pages_file, [FieldType::Timestamp]);
+
+  assert_eq!(user_page_msg.get_full_name().as_ref(), "UserPage");
+  assert_eq!(timestamp_page_msg.get_full_name().as_ref(), "TimestampPage");
+
+  let data = user_page_msg.get_data();
+
+  assert_eq!(data.fields[0].1.field_type, user_msg.get_type());
+  assert_eq!(data.fields[2].1.field_type.name(), "UserPage.Order");
+
+  let imports = pages_file.get_data().imports;
+
+  assert!(imports.contains(&"mypkg/myfile.proto".into()));
+  assert!(imports.contains(&"google/protobuf/timestamp.proto".into()));
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn repeated_instances() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let pair = GenericMessage::new("Pair", ["K", "V"])
+    .field(1, |pair| pair.field("K", "key"))
+    .field(2, |pair| pair.field("V", "value"));
+
+  let first = pair.instantiate(&file, [FieldType::String, FieldType::Int32]);
+  let second = pair.instantiate(&file, [FieldType::String, FieldType::Int32]);
+  let custom = pair.instantiate_as(&file, "Entry", [FieldType::String, FieldType::Int32]);
+
+  assert_eq!(first.get_full_name().as_ref(), "StringInt32Pair");
+  assert_eq!(first.get_full_name(), second.get_full_name());
+  assert_eq!(custom.get_full_name().as_ref(), "Entry");
+  assert_eq!(file.get_data().messages.len(), 2);
+}
+
+#[test]
+#[should_panic(
+  expected = "The generic message 'Page' expects 1 type argument(s), but 2 were given"
+)]
+fn wrong_arguments() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  page().instantiate(&file, [FieldType::String, FieldType::Bytes]);
+}
+
+#[test]
+#[should_panic(expected = "The package already contains a message named 'mypkg.StringPage'")]
+fn name_collisions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(file.new_message("StringPage"), 1 => string!("name"));
+
+  page().instantiate(&file, [FieldType::String]);
+}
+
+#[test]
+#[should_panic(expected = "The package already contains a message named 'mypkg.StringPage'")]
+fn templates_with_the_same_name() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let other_page = GenericMessage::new("Page", ["T"]).field(1, |page| page.field("T", "value"));
+
+  page().instantiate(&file, [FieldType::String]);
+  other_page.instantiate(&file, [FieldType::String]);
+}
+
+#[test]
+fn modified_clones() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let page = page();
+  let extended_page = page.clone().field(4, |_| string!("filter"));
+
+  page.instantiate(&file, [FieldType::String]);
+  let custom = extended_page.instantiate_as(&file, "FilteredStringPage", [FieldType::String]);
+
+  assert_eq!(custom.get_data().fields.len(), 4);
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.StringInt32Pair: the name 'value' is used by both the field 'value' and the field 'value'"
+)]
+fn duplicate_fields() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let pair = GenericMessage::new("Pair", ["K", "V"])
+    .field(1, |pair| pair.field("K", "value"))
+    .field(2, |pair| pair.field("V", "value"));
+
+  pair.instantiate(&file, [FieldType::String, FieldType::Int32]);
+}
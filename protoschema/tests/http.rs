@@ -0,0 +1,115 @@
+use protoschema::{
+  errors::SchemaError, http::HttpRule, message, message_option, msg_field, options::OptionValue,
+  services, string, Package, ProtoOption,
+};
+
+#[test]
+fn http_rule_option() {
+  let option: ProtoOption = HttpRule::patch("/v1/{book.name=shelves/*/books/*}")
+    .body("book")
+    .additional_binding(HttpRule::custom("HEAD", "/v1/books/{book.name}"))
+    .into();
+
+  assert_eq!(&*option.name, "(google.api.http)");
+  assert_eq!(
+    *option.value,
+    message_option!(
+      "patch" => "/v1/{book.name=shelves/*/books/*}",
+      "body" => "book",
+      "additional_bindings" => message_option!(
+        "custom" => message_option!("kind" => "HEAD", "path" => "/v1/books/{book.name}")
+      )
+    )
+  );
+}
+
+#[test]
+#[should_panic(expected = "An additional binding cannot contain other additional bindings")]
+fn nested_additional_bindings() {
+  let _: OptionValue = HttpRule::get("/v1/books")
+    .additional_binding(
+      HttpRule::get("/v1/shelves/books").additional_binding(HttpRule::get("/v1/all/books")),
+    )
+    .build()
+    .into();
+}
+
+#[test]
+fn imports_annotations() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let book_msg = message!(file.new_message("Book"), 1 => string!("name"));
+  let get_book_msg = message!(file.new_message("GetBookRequest"), 1 => string!("name"));
+
+  services!(
+    file,
+    Library {
+      GetBook(get_book_msg => book_msg) { [ HttpRule::get("/v1/{name=books/*}").into() ] },
+    };
+  );
+
+  assert!(file
+    .get_data()
+    .imports
+    .contains(&"google/api/annotations.proto".into()));
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn reports_invalid_http_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let book_msg = message!(file.new_message("Book"), 1 => string!("name"));
+  let update_book_msg = message!(
+    file.new_message("UpdateBookRequest"),
+    1 => msg_field!(book_msg, "book"),
+  );
+
+  services!(
+    file,
+    Library {
+      UpdateBook(update_book_msg => book_msg) {
+        [
+          HttpRule::patch("/v1/{book.title}/{shelf=shelves/**/books}")
+            .body("books")
+            .response_body("name")
+            .additional_binding(HttpRule::get("/v1/{book.name}").body("*"))
+            .into()
+        ]
+      },
+    };
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![
+      SchemaError::InvalidHttpRule {
+        item: "mypkg.Library.UpdateBook".into(),
+        rule: "/v1/{book.title}/{shelf=shelves/**/books}".into(),
+        error: "the '**' wildcard can only be used as the last segment of a variable".into(),
+      },
+      SchemaError::UnknownField {
+        item: "mypkg.Library.UpdateBook".into(),
+        field: "book.title".into(),
+        rule: "the http rule '/v1/{book.title}/{shelf=shelves/**/books}'".into(),
+      },
+      SchemaError::UnknownField {
+        item: "mypkg.Library.UpdateBook".into(),
+        field: "shelf".into(),
+        rule: "the http rule '/v1/{book.title}/{shelf=shelves/**/books}'".into(),
+      },
+      SchemaError::UnknownField {
+        item: "mypkg.Library.UpdateBook".into(),
+        field: "books".into(),
+        rule: "the http rule '/v1/{book.title}/{shelf=shelves/**/books}'".into(),
+      },
+      SchemaError::InvalidHttpRule {
+        item: "mypkg.Library.UpdateBook".into(),
+        rule: "/v1/{book.name}".into(),
+        error: "a GET binding cannot have a body".into(),
+      },
+    ]
+  );
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "serde")]
+
+use protoschema::{
+  enum_option, message_option,
+  options::{list_value, proto_option, Duration, OptionValue, Timestamp},
+  ProtoOption,
+};
+use serde_json::json;
+
+#[test]
+fn option_values_roundtrip() {
+  let value = message_option!(
+    "int" => -5,
+    "uint" => 5u64,
+    "big_uint" => u64::MAX,
+    "float" => 1.5,
+    "nan" => f64::NAN,
+    "neg_inf" => f64::NEG_INFINITY,
+    "enum" => enum_option!("MY_VALUE"),
+    "duration" => Duration { seconds: -3, nanos: -500_000_000 },
+    "timestamp" => Timestamp { seconds: 1_700_000_000, nanos: 1_000 },
+    "list" => list_value(["a", "b"]),
+  );
+
+  let json = value.to_json();
+
+  assert_eq!(json["uint"], json!({ "$uint": 5 }));
+  assert_eq!(json["neg_inf"], json!({ "$float": "-Infinity" }));
+  assert_eq!(json["big_uint"], json!(u64::MAX));
+  assert_eq!(json["duration"], json!({ "$duration": "-3.500s" }));
+  assert_eq!(
+    json["timestamp"],
+    json!({ "$timestamp": "2023-11-14T22:13:20.000001Z" })
+  );
+
+  let OptionValue::Message(converted) = OptionValue::from_json(json).unwrap() else {
+    panic!("Expected a message value");
+  };
+  let OptionValue::Message(original) = value else {
+    unreachable!()
+  };
+
+  assert_eq!(converted.len(), original.len());
+
+  for (name, original) in original.iter() {
+    let (_, converted) = converted.iter().find(|(n, _)| n == name).unwrap();
+
+    if name.as_ref() == "nan" {
+      assert!(matches!(converted, OptionValue::Float(f) if f.is_nan()));
+    } else {
+      assert_eq!(converted, original);
+    }
+  }
+}
+
+#[test]
+fn plain_json_values() {
+  let value: OptionValue = serde_json::from_str(
+    r#"{ "summary": "Get a user", "retries": 3, "timeout": { "$duration": "1.5s" } }"#,
+  )
+  .unwrap();
+
+  assert_eq!(
+    value,
+    message_option!(
+      "summary" => "Get a user",
+      "retries" => 3i64,
+      "timeout" => Duration { seconds: 1, nanos: 500_000_000 },
+    )
+  );
+
+  assert!(OptionValue::from_json(json!(null)).is_err());
+  assert!(OptionValue::from_json(json!({ "$duration": "abc" })).is_err());
+
+  for duration in ["--3s", "+3s", "-+3s", "-3.-5s"] {
+    assert!(OptionValue::from_json(json!({ "$duration": duration })).is_err());
+  }
+}
+
+#[test]
+fn proto_option_serde() {
+  let option = proto_option("(my.option)", message_option!("enabled" => true));
+  let serialized = serde_json::to_string(&option).unwrap();
+
+  assert_eq!(
+    serialized,
+    r#"{"name":"(my.option)","value":{"enabled":true}}"#
+  );
+
+  let deserialized: ProtoOption = serde_json::from_str(&serialized).unwrap();
+
+  assert_eq!(deserialized, option);
+}
+
+#[test]
+fn serde_keeps_the_order_of_the_fields() {
+  let value = message_option!(
+    "zeta" => 1i64,
+    "alpha" => message_option!("$not_a_tag" => true, "beta" => false),
+  );
+  let serialized = serde_json::to_string(&value).unwrap();
+
+  assert_eq!(
+    serialized,
+    r#"{"zeta":1,"alpha":{"$not_a_tag":true,"beta":false}}"#
+  );
+  assert_eq!(
+    serde_json::from_str::<OptionValue>(&serialized).unwrap(),
+    value
+  );
+
+  let option: ProtoOption =
+    serde_json::from_str(r#"{ "name": "(my.runtime_option)", "value": { "$enum": "A" } }"#)
+      .unwrap();
+
+  assert_eq!(
+    option,
+    proto_option("(my.runtime_option)", enum_option!("A"))
+  );
+}
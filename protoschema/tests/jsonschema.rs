@@ -0,0 +1,252 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use protoschema::{
+  double, enum_field, int64, message, options::proto_option, proto_enum, string, Package,
+};
+use regex::Regex;
+use serde_json::{json, Value};
+
+use common::user_package;
+
+fn find_schema<'a>(schemas: &'a [Value], id: &str) -> &'a Value {
+  schemas
+    .iter()
+    .find(|schema| schema["$id"] == id)
+    .unwrap_or_else(|| panic!("missing schema {id}"))
+}
+
+#[test]
+fn message_schema() {
+  let schemas = user_package().json_schemas();
+  let user = find_schema(&schemas, "mypkg.User.schema.json");
+  let properties = &user["properties"];
+
+  assert_eq!(
+    user["$schema"],
+    json!("https://json-schema.org/draft/2020-12/schema")
+  );
+  assert_eq!(user["required"], Value::Null);
+  assert_eq!(user["additionalProperties"], json!(false));
+
+  assert_eq!(
+    properties["email"],
+    json!({ "type": "string", "format": "email" })
+  );
+  assert_eq!(
+    properties["username"],
+    json!({ "type": "string", "minLength": 8, "pattern": "^user_" })
+  );
+  assert_eq!(
+    properties["age"],
+    json!({
+      "type": "integer",
+      "minimum": 0,
+      "maximum": u32::MAX,
+      "exclusiveMinimum": 17,
+      "exclusiveMaximum": 130,
+    })
+  );
+  assert_eq!(
+    properties["score"],
+    json!({
+      "type": "string",
+      "pattern": "^-?[0-9]+$",
+      "not": { "enum": ["1", "2", "3"] },
+    })
+  );
+  assert_eq!(
+    properties["ratio"],
+    json!({ "type": "number", "minimum": 0.0, "maximum": 1.0 })
+  );
+  assert_eq!(
+    properties["tags"],
+    json!({
+      "type": "array",
+      "items": { "type": "string", "minLength": 1 },
+      "minItems": 1,
+      "maxItems": 2,
+      "uniqueItems": true,
+    })
+  );
+  assert_eq!(
+    properties["labels"],
+    json!({
+      "type": "object",
+      "propertyNames": { "minLength": 2 },
+      "additionalProperties": { "type": "string", "maxLength": 4 },
+      "maxProperties": 3,
+    })
+  );
+  assert_eq!(
+    properties["status"],
+    json!({ "$ref": "mypkg.status.schema.json" })
+  );
+  assert_eq!(
+    properties["homeAddress"],
+    json!({ "$ref": "mypkg.User.Address.schema.json" })
+  );
+  assert_eq!(
+    properties["deletedAt"],
+    json!({ "type": "string", "format": "date-time" })
+  );
+  // The original names of the fields are accepted as well
+  assert_eq!(properties["home_address"], properties["homeAddress"]);
+  assert_eq!(
+    user["allOf"],
+    json!([
+      { "anyOf": [{ "required": ["homeAddress"] }, { "required": ["home_address"] }] },
+      { "oneOf": [{ "required": ["phone"] }, { "required": ["website"] }] },
+    ])
+  );
+
+  assert_eq!(
+    user["$defs"]["Address"]["properties"]["city"],
+    json!({ "type": "string", "minLength": 2, "maxLength": 20 })
+  );
+  assert_eq!(
+    user["$defs"]["Address"]["$id"],
+    json!("mypkg.User.Address.schema.json")
+  );
+}
+
+#[test]
+fn enum_schema() {
+  let schemas = user_package().json_schemas();
+
+  assert_eq!(
+    find_schema(&schemas, "mypkg.status.schema.json"),
+    &json!({
+      "$schema": "https://json-schema.org/draft/2020-12/schema",
+      "$id": "mypkg.status.schema.json",
+      "title": "status",
+      "type": ["string", "integer"],
+      "enum": ["STATUS_UNSPECIFIED", "STATUS_ACTIVE", 0, 1],
+    })
+  );
+}
+
+#[test]
+fn optional_oneofs_and_open_enums() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let color_enum = proto_enum!(
+    file.new_enum("color"),
+    0 => "UNSPECIFIED",
+    1 => "RED",
+    2 => "BLUE",
+  );
+
+  message!(
+    file.new_message("Shape"),
+    1 => enum_field!(color_enum, "color", |v| v.in_([1])),
+
+    oneof "size" {
+      2 => double!("radius"),
+      3 => double!("side"),
+    }
+  );
+
+  let schemas = package.json_schemas();
+  let shape = find_schema(&schemas, "mypkg.Shape.schema.json");
+
+  assert_eq!(
+    shape["properties"]["color"],
+    json!({
+      "anyOf": [{ "$ref": "mypkg.color.schema.json" }, { "type": "integer" }],
+      "enum": ["COLOR_RED", 1],
+    })
+  );
+  assert_eq!(
+    shape["oneOf"],
+    json!([
+      { "required": ["radius"] },
+      { "required": ["side"] },
+      { "not": { "anyOf": [{ "required": ["radius"] }, { "required": ["side"] }] } },
+    ])
+  );
+}
+
+#[test]
+fn int64_bounds_and_ignored_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Item"),
+    1 => int64!("count", |v| v.gt(0).lte(100)),
+    2 => string!("code", |v| v.min_len(3).required().ignore_always()),
+    3 => string!(repeated "tags", |r, v| r.items(v.min_len(3).ignore_always())),
+  );
+
+  let schemas = package.json_schemas();
+  let item = find_schema(&schemas, "mypkg.Item.schema.json");
+
+  assert_eq!(
+    item["properties"]["count"],
+    json!({
+      "type": ["string", "integer"],
+      "pattern": "^-?[0-9]+$",
+      "exclusiveMinimum": 0,
+      "maximum": 100,
+    })
+  );
+  assert_eq!(item["properties"]["code"], json!({ "type": "string" }));
+  assert_eq!(
+    item["properties"]["tags"],
+    json!({ "type": "array", "items": { "type": "string" } })
+  );
+  assert_eq!(item.get("required"), None);
+}
+
+#[test]
+fn combined_patterns() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Account"),
+    1 => string!("username", |v| v.pattern(Regex::new("^[a-z_]+$").unwrap()).prefix("user_")),
+  );
+
+  let schemas = package.json_schemas();
+  let account = find_schema(&schemas, "mypkg.Account.schema.json");
+
+  assert_eq!(
+    account["properties"]["username"],
+    json!({
+      "type": "string",
+      "allOf": [{ "pattern": "^[a-z_]+$" }, { "pattern": "^user_" }],
+    })
+  );
+}
+
+#[test]
+fn explicit_json_names() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("Account"),
+    1 => string!("user_id", |v| v.required()).add_option(proto_option("json_name", "uid")),
+    2 => string!("name"),
+  );
+
+  let schemas = package.json_schemas();
+  let account = find_schema(&schemas, "mypkg.Account.schema.json");
+
+  assert_eq!(
+    account["properties"],
+    json!({
+      "uid": { "type": "string" },
+      "user_id": { "type": "string" },
+      "name": { "type": "string" },
+    })
+  );
+  assert_eq!(
+    account["anyOf"],
+    json!([{ "required": ["uid"] }, { "required": ["user_id"] }])
+  );
+}
@@ -0,0 +1,322 @@
+#![cfg(all(feature = "yaml", feature = "toml"))]
+
+use askama::Template;
+use protoschema::{
+  enum_field,
+  errors::LoadError,
+  loader::{from_json, from_toml, from_yaml},
+  message, mixin, msg_field, proto_enum, proto_option, services, string, timestamp, uint32, uint64,
+  FieldType, Package,
+};
+
+const USER_SCHEMA: &str = r#"
+package: myapp.v1
+blocks:
+  - name: timestamps
+    fields:
+      - { tag: 0, name: created_at, type: google.protobuf.Timestamp }
+      - { tag: 1, name: updated_at, type: google.protobuf.Timestamp }
+files:
+  - name: user
+    options:
+      java_multiple_files: true
+    messages:
+      - name: User
+        options:
+          deprecated: true
+        reserved: [20, "30..35"]
+        reserved_names: [password]
+        include: [timestamps]
+        rules:
+          cel:
+            - id: names
+              message: the name must not be the email
+              expression: this.name != this.email
+        fields:
+          - { tag: 1, name: id, type: uint64 }
+          - tag: 2
+            name: name
+            type: string
+            rules:
+              string: { min_len: 1, max_len: 50 }
+          - tag: 3
+            name: email
+            type: string
+            rules:
+              required: true
+              string: { well_known: email }
+          - tag: 4
+            name: tags
+            type: string
+            repeated: true
+            rules:
+              repeated:
+                max_items: 10
+                unique: true
+                items:
+                  string: { min_len: 2 }
+          - { tag: 5, name: role, type: Role }
+          - { tag: 6, name: address, type: Address, optional: true }
+        oneofs:
+          - name: contact
+            required: true
+            fields:
+              - { tag: 10, name: phone, type: string }
+              - { tag: 11, name: fax, type: string }
+        messages:
+          - name: Address
+            fields:
+              - tag: 1
+                name: zip
+                type: uint32
+                rules:
+                  uint32: { gt: 0, lt: 100000 }
+    enums:
+      - name: Role
+        reserved: ["5..7"]
+        variants:
+          - { number: 0, name: UNSPECIFIED }
+          - number: 1
+            name: ADMIN
+            options:
+              deprecated: true
+  - name: services
+    messages:
+      - name: GetUserRequest
+        fields:
+          - { tag: 1, name: id, type: uint64 }
+    services:
+      - name: UserService
+        handlers:
+          - { name: GetUser, request: GetUserRequest, response: myapp.v1.User }
+"#;
+
+// The same package as USER_SCHEMA, defined with the macros
+fn user_schema_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+  let services_file = package.new_file("services");
+
+  file.add_options([proto_option("java_multiple_files", true)]);
+
+  let timestamps = mixin!("timestamps",
+    0 => timestamp!("created_at"),
+    1 => timestamp!("updated_at"),
+  );
+
+  let user_msg = file.new_message("User");
+  let address_msg = message!(
+    user_msg.new_message("Address"),
+    1 => uint32!("zip", |v| v.gt(0).lt(100000)),
+  );
+  let role_enum = proto_enum!(
+    file.new_enum("Role"),
+    reserved = [ 5..7 ]
+    0 => "UNSPECIFIED",
+    1 => "ADMIN" { [proto_option("deprecated", true)] },
+  );
+
+  let user_msg = message!(
+    user_msg
+      .add_options([proto_option("deprecated", true)])
+      .mixin(&timestamps, protoschema::mixins::MixinPlacement::Auto),
+    cel = [
+      {
+        id = "names",
+        msg = "the name must not be the email",
+        expr = "this.name != this.email"
+      }
+    ],
+    reserved_names = [ "password" ],
+    reserved = [ 20, 30..35 ],
+
+    1 => uint64!("id"),
+    2 => string!("name", |v| v.min_len(1).max_len(50)),
+    3 => string!("email", |v| v.required().email()),
+    4 => string!(repeated "tags", |r, i| r.max_items(10).unique().items(i.min_len(2))),
+    5 => enum_field!(role_enum, "role"),
+    6 => msg_field!(address_msg, "address").optional(),
+
+    oneof "contact" {
+      required,
+      10 => string!("phone"),
+      11 => string!("fax"),
+    }
+  );
+
+  let request_msg = message!(services_file.new_message("GetUserRequest"), 1 => uint64!("id"));
+
+  services!(
+    services_file,
+    UserService {
+      GetUser(request_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+fn render(package: &Package) -> Vec<String> {
+  package
+    .build_templates()
+    .iter()
+    .map(|template| template.render().unwrap())
+    .collect()
+}
+
+#[test]
+fn yaml_documents() {
+  let package = from_yaml(USER_SCHEMA).unwrap();
+
+  assert_eq!(render(&package), render(&user_schema_package()));
+}
+
+#[test]
+fn toml_and_json_documents() {
+  let toml_package = from_toml(
+    r#"
+package = "myapp.v1"
+
+[[files]]
+name = "user"
+
+[[files.messages]]
+name = "User"
+fields = [
+  { tag = 1, name = "id", type = "uint64" },
+  { tag = 2, name = "labels", type = "map<string, int32>", rules = { map = { max_pairs = 5 } } },
+]
+"#,
+  )
+  .unwrap();
+
+  let json_package = from_json(
+    r#"{
+  "package": "myapp.v1",
+  "files": [
+    {
+      "name": "user",
+      "messages": [
+        {
+          "name": "User",
+          "fields": [
+            { "tag": 1, "name": "id", "type": "uint64" },
+            { "tag": 2, "name": "labels", "type": "map<string, int32>", "rules": { "map": { "max_pairs": 5 } } }
+          ]
+        }
+      ]
+    }
+  ]
+}"#,
+  )
+  .unwrap();
+
+  let schema = toml_package.schema();
+  let user_msg = schema.find_message("User").unwrap();
+
+  assert_eq!(
+    user_msg.fields[1].field_type,
+    FieldType::Map(
+      protoschema::field_type::MapKey::String,
+      Box::new(FieldType::Int32)
+    )
+  );
+  assert!(user_msg.fields[1].option("(buf.validate.field)").is_some());
+  assert_eq!(render(&toml_package), render(&json_package));
+}
+
+fn load_error(source: &str) -> LoadError {
+  from_yaml(source).err().unwrap()
+}
+
+#[test]
+fn located_errors() {
+  let unknown_type = load_error(
+    r#"
+package: myapp.v1
+files:
+  - name: user
+    messages:
+      - name: User
+        fields:
+          - { tag: 1, name: id, type: uint64 }
+          - { tag: 2, name: role, type: Role }
+"#,
+  );
+
+  assert_eq!(
+    unknown_type.to_string(),
+    "line 9, column 29: files[0].messages[0].fields[1].name: the type 'Role' is not defined in the package"
+  );
+
+  let unknown_key = load_error(
+    r#"
+package: myapp.v1
+files:
+  - name: user
+    mesages: []
+"#,
+  );
+
+  assert!(matches!(unknown_key, LoadError::Invalid { line: 5, .. }));
+  assert!(unknown_key.to_string().contains("unknown field `mesages`"));
+
+  let wrong_rules = load_error(
+    r#"
+package: myapp.v1
+files:
+  - name: user
+    messages:
+      - name: User
+        fields:
+          - tag: 1
+            name: id
+            type: uint64
+            rules:
+              string: { min_len: 1 }
+"#,
+  );
+
+  assert!(matches!(
+    wrong_rules,
+    LoadError::Invalid {
+      line: 9,
+      column: 19,
+      ..
+    }
+  ));
+
+  let duplicate_tag = load_error(
+    r#"
+package: myapp.v1
+files:
+  - name: user
+    messages:
+      - name: User
+        fields:
+          - { tag: 1, name: id, type: uint64 }
+          - { tag: 1, name: name, type: string }
+"#,
+  );
+
+  assert_eq!(
+    duplicate_tag.to_string(),
+    "line 9, column 29: files[0].messages[0].fields[1].name: myapp.v1.User: the number 1 is already used by 'id'"
+  );
+
+  let unknown_block = load_error(
+    r#"
+package: myapp.v1
+files:
+  - name: user
+    messages:
+      - name: User
+        include: [{ block: timestamps, at: 100 }]
+"#,
+  );
+
+  assert_eq!(
+    unknown_block.to_string(),
+    "line 7, column 28: files[0].messages[0].include[0].block: the block 'timestamps' is not defined"
+  );
+}
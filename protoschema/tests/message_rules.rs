@@ -0,0 +1,121 @@
+use protoschema::{errors::SchemaError, message, options::OptionValue, string, Package};
+
+#[test]
+fn message_oneof_rule() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let email = string!("email");
+
+  let msg = message!(
+    file.new_message("MyMessage"),
+    oneof_rule(required) = [ email, "phone" ],
+
+    1 => email.clone(),
+    2 => string!("phone"),
+  );
+
+  let data = msg.get_data();
+  let option = data
+    .options
+    .iter()
+    .find(|o| &*o.name == "(buf.validate.message)")
+    .unwrap();
+
+  assert_eq!(
+    *option.value,
+    OptionValue::Message(
+      vec![(
+        "oneof".into(),
+        OptionValue::List(
+          vec![OptionValue::Message(
+            vec![
+              (
+                "fields".into(),
+                OptionValue::List(
+                  vec![
+                    OptionValue::String("email".into()),
+                    OptionValue::String("phone".into())
+                  ]
+                  .into_boxed_slice()
+                )
+              ),
+              ("required".into(), OptionValue::Bool(true)),
+            ]
+            .into_boxed_slice()
+          )]
+          .into_boxed_slice()
+        )
+      )]
+      .into_boxed_slice()
+    )
+  );
+
+  assert!(package.check().is_empty());
+}
+
+#[test]
+fn reports_invalid_message_oneof_rules() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("MyMessage"),
+    oneof_rule = [ "email", "email", "phone" ],
+
+    1 => string!("email"),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![
+      SchemaError::DuplicateOneofRuleField {
+        item: "mypkg.MyMessage".into(),
+        field: "email".into(),
+      },
+      SchemaError::UnknownField {
+        item: "mypkg.MyMessage".into(),
+        field: "phone".into(),
+        rule: "a message oneof rule".into(),
+      },
+    ]
+  );
+}
+
+#[test]
+fn merges_message_rules_into_a_single_option() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let msg = message!(
+    file.new_message("MyMessage"),
+    cel = [
+      {
+        id = "passwords_match",
+        msg = "the passwords do not match",
+        expr = "this.password == this.repeated_password"
+      }
+    ],
+    oneof_rule = [ "password", "repeated_password" ],
+    validate = |v| v.disabled(),
+
+    1 => string!("password"),
+    2 => string!("repeated_password"),
+  );
+
+  let data = msg.get_data();
+  let message_options: Vec<_> = data
+    .options
+    .iter()
+    .filter(|o| &*o.name == "(buf.validate.message)")
+    .collect();
+
+  assert_eq!(message_options.len(), 1);
+
+  let OptionValue::Message(values) = message_options[0].value.as_ref() else {
+    panic!("Expected a message value");
+  };
+
+  let keys: Vec<&str> = values.iter().map(|(k, _)| k.as_ref()).collect();
+  assert_eq!(keys, ["cel", "oneof", "disabled"]);
+}
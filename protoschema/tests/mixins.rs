@@ -0,0 +1,237 @@
+use protoschema::{
+  message, mixin,
+  mixins::{AppliedMixin, MixinPlacement, MAX_FIELD_NUMBER},
+  oneof, string, timestamp, uint64, Package,
+};
+
+#[test]
+fn placements() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let timestamps =
+    mixin!("timestamps", 0 => timestamp!("created_at"), 1 => timestamp!("updated_at"));
+  let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+  let etag = mixin!("etag", 0 => string!("etag"));
+
+  let user_msg = message!(
+    file
+      .new_message("User")
+      .mixin_band(50)
+      .mixin(&audit, MixinPlacement::Auto)
+      .mixin(&timestamps, 51)
+      .mixin(&etag, MixinPlacement::Auto),
+    1 => uint64!("id"),
+    50 => string!("name"),
+  );
+
+  assert_eq!(
+    user_msg.get_mixins(),
+    vec![
+      AppliedMixin {
+        mixin: "audit".into(),
+        base: 53,
+        fields: vec![(53, "created_by".into()), (54, "updated_by".into())],
+      },
+      AppliedMixin {
+        mixin: "timestamps".into(),
+        base: 51,
+        fields: vec![(51, "created_at".into()), (52, "updated_at".into())],
+      },
+      AppliedMixin {
+        mixin: "etag".into(),
+        base: 55,
+        fields: vec![(55, "etag".into())],
+      },
+    ]
+  );
+
+  let tags: Vec<u32> = user_msg
+    .get_data()
+    .fields
+    .iter()
+    .map(|(tag, _)| *tag)
+    .collect();
+
+  assert_eq!(tags, vec![1, 50, 51, 52, 53, 54, 55]);
+  assert!(file
+    .get_data()
+    .imports
+    .contains(&"google/protobuf/timestamp.proto".into()));
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.User: the tag 101 is used by both the field 'updated_at' (from the mixin 'timestamps') and the field 'deleted_at' (from the mixin 'deletion')"
+)]
+fn tag_collisions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let timestamps =
+    mixin!("timestamps", 0 => timestamp!("created_at"), 1 => timestamp!("updated_at"));
+  let deletion = mixin!("deletion", 0 => timestamp!("deleted_at"));
+
+  message!(
+    file
+      .new_message("User")
+      .mixin(&timestamps, 100)
+      .mixin(&deletion, 101),
+    1 => uint64!("id"),
+  );
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.User: the name 'created_at' is used by both the field 'created_at' and the field 'created_at' (from the mixin 'timestamps')"
+)]
+fn name_collisions() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let timestamps = mixin!("timestamps", 0 => timestamp!("created_at"));
+
+  message!(
+    file.new_message("User").mixin(&timestamps, MixinPlacement::Auto),
+    1 => timestamp!("created_at"),
+  );
+}
+
+#[test]
+fn placements_avoid_reservations_and_oneofs() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+  let etag = mixin!("etag", 0 => string!("etag"));
+
+  let user_msg = file
+    .new_message("User")
+    .add_reserved([1000])
+    .add_reserved_ranges([1002..1004, 2000..2001])
+    .add_oneofs([oneof!("contact", 1005 => string!("email"))])
+    .mixin(&audit, MixinPlacement::Auto)
+    .mixin(&etag, 100)
+    .add_fields([(1, uint64!("id"))]);
+
+  assert_eq!(
+    user_msg.get_mixins(),
+    vec![
+      AppliedMixin {
+        mixin: "audit".into(),
+        base: 1006,
+        fields: vec![(1006, "created_by".into()), (1007, "updated_by".into())],
+      },
+      AppliedMixin {
+        mixin: "etag".into(),
+        base: 100,
+        fields: vec![(100, "etag".into())],
+      },
+    ]
+  );
+}
+
+#[test]
+fn macro_placements_avoid_reservations_and_oneofs() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let etag = mixin!("etag", 0 => string!("etag"));
+
+  let user_msg = message!(
+    file
+      .new_message("User")
+      .mixin_band(5)
+      .mixin(&etag, MixinPlacement::Auto),
+    1 => uint64!("id"),
+
+    oneof "choice" {
+      5 => string!("a"),
+    }
+  );
+
+  let post_msg = message!(
+    file
+      .new_message("Post")
+      .mixin_band(6)
+      .mixin(&etag, MixinPlacement::Auto),
+    reserved = [6],
+    1 => uint64!("id"),
+  );
+
+  assert_eq!(user_msg.get_mixins()[0].base, 6);
+  assert_eq!(post_msg.get_mixins()[0].base, 7);
+  assert!(package.check().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "mypkg.User: the number 2 is already used by 'a'")]
+fn macro_collisions_with_oneofs() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let etag = mixin!("etag", 0 => string!("etag"));
+
+  message!(
+    file.new_message("User").mixin(&etag, 2),
+    1 => uint64!("id"),
+
+    oneof "choice" {
+      2 => string!("a"),
+    }
+  );
+}
+
+#[test]
+fn placements_skip_the_implementation_range() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+
+  let user_msg = message!(
+    file
+      .new_message("User")
+      .mixin_band(18999)
+      .mixin(&audit, MixinPlacement::Auto),
+    1 => uint64!("id"),
+  );
+
+  assert_eq!(user_msg.get_mixins()[0].base, 20000);
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.User: the mixin 'audit' cannot be placed at the base tag 536870911, since one of its tags would be 0, above the maximum field number or reserved by the implementation (19000-19999)"
+)]
+fn placements_above_the_maximum_field_number() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+
+  message!(
+    file.new_message("User").mixin(&audit, MAX_FIELD_NUMBER),
+    1 => uint64!("id"),
+  );
+}
+
+#[test]
+#[should_panic(
+  expected = "mypkg.User: there are no free tags for the mixin 'audit' from the tag 536870910 up to the maximum field number"
+)]
+fn no_free_tags() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let audit = mixin!("audit", 0 => string!("created_by"), 1 => string!("updated_by"));
+
+  message!(
+    file
+      .new_message("User")
+      .mixin_band(MAX_FIELD_NUMBER - 1)
+      .mixin(&audit, MixinPlacement::Auto),
+    1 => uint64!("id"),
+    536870911 => uint64!("last"),
+  );
+}
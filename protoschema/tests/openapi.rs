@@ -0,0 +1,193 @@
+#![cfg(feature = "serde")]
+
+use protoschema::{
+  message, message_option, msg_field, proto_option, services, string, uint32, Package,
+};
+use serde_json::json;
+
+fn library_package() -> Package {
+  let package = Package::new("library.v1");
+  let file = package.new_file("library");
+
+  let book_msg = message!(
+    file.new_message("Book"),
+    1 => string!("name"),
+    2 => string!("title", |v| v.min_len(1).max_len(100)),
+  );
+
+  let get_book_msg = message!(
+    file.new_message("GetBookRequest"),
+    1 => string!("name", |v| v.required()),
+    2 => string!("view_mode", |v| v.in_(["BASIC", "FULL"])),
+  );
+
+  let page_msg = message!(
+    file.new_message("Page"),
+    1 => uint32!("page_size", |v| v.lte(50)),
+    2 => string!("page_token").add_option(proto_option("json_name", "cursor")),
+  );
+
+  let list_books_msg = message!(
+    file.new_message("ListBooksRequest"),
+    1 => string!("parent"),
+    2 => msg_field!(page_msg, "page"),
+  );
+
+  let list_books_response_msg = message!(
+    file.new_message("ListBooksResponse"),
+    1 => msg_field!(repeated book_msg, "books"),
+  );
+
+  let update_book_msg = message!(
+    file.new_message("UpdateBookRequest"),
+    1 => msg_field!(book_msg, "book"),
+    2 => string!("update_mask"),
+  );
+
+  let get_rule = proto_option(
+    "(google.api.http)",
+    message_option!(
+      "get" => "/v1/{name=shelves/*/books/*}",
+      "additional_bindings" => message_option!("get" => "/v1/books/{name}")
+    ),
+  );
+
+  let list_rule = proto_option(
+    "(google.api.http)",
+    message_option!(
+      "get" => "/v1/{parent=shelves/*}/books",
+      "response_body" => "books"
+    ),
+  );
+
+  let update_rule = proto_option(
+    "(google.api.http)",
+    message_option!("patch" => "/v1/{book.name=shelves/*/books/*}", "body" => "book"),
+  );
+
+  services!(
+    file,
+    Library {
+      GetBook(get_book_msg => book_msg) { [ get_rule ] },
+      ListBooks(list_books_msg => list_books_response_msg) { [ list_rule ] },
+      UpdateBook(update_book_msg => book_msg) { [ update_rule ] },
+      DeleteBook(get_book_msg => book_msg),
+    };
+  );
+
+  package
+}
+
+#[test]
+fn operations() {
+  let document = library_package().openapi("Library", "1.0.0");
+  let paths = &document["paths"];
+
+  assert_eq!(document["openapi"], json!("3.1.0"));
+  assert_eq!(
+    document["info"],
+    json!({ "title": "Library", "version": "1.0.0" })
+  );
+
+  assert_eq!(
+    paths["/v1/{name}"]["get"],
+    json!({
+      "operationId": "Library_GetBook",
+      "tags": ["Library"],
+      "parameters": [
+        {
+          "name": "name",
+          "in": "path",
+          "required": true,
+          "schema": { "type": "string", "pattern": "^shelves/[^/]+/books/[^/]+$" },
+        },
+        {
+          "name": "viewMode",
+          "in": "query",
+          "schema": { "type": "string", "enum": ["BASIC", "FULL"] },
+        },
+      ],
+      "responses": {
+        "200": {
+          "description": "A successful response.",
+          "content": {
+            "application/json": { "schema": { "$ref": "#/components/schemas/library.v1.Book" } },
+          },
+        },
+      },
+    })
+  );
+
+  assert_eq!(
+    paths["/v1/books/{name}"]["get"]["operationId"],
+    json!("Library_GetBook1")
+  );
+
+  // Handlers without an http rule are not exposed
+  assert_eq!(paths.as_object().unwrap().len(), 4);
+}
+
+#[test]
+fn query_parameters_and_bodies() {
+  let document = library_package().openapi("Library", "1.0.0");
+  let paths = &document["paths"];
+
+  let list_books = &paths["/v1/{parent}/books"]["get"];
+
+  assert_eq!(
+    list_books["parameters"][1],
+    json!({
+      "name": "page.pageSize",
+      "in": "query",
+      "schema": { "type": "integer", "minimum": 0, "maximum": 50 },
+    })
+  );
+  // The json_name option replaces the default name of the field
+  assert_eq!(list_books["parameters"][2]["name"], json!("page.cursor"));
+  assert_eq!(
+    list_books["responses"]["200"]["content"]["application/json"]["schema"],
+    json!({
+      "type": "array",
+      "items": { "$ref": "#/components/schemas/library.v1.Book" },
+    })
+  );
+
+  let update_book = &paths["/v1/{book.name}"]["patch"];
+
+  assert_eq!(
+    update_book["requestBody"]["content"]["application/json"]["schema"],
+    json!({ "$ref": "#/components/schemas/library.v1.Book" })
+  );
+  assert_eq!(
+    update_book["parameters"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|parameter| parameter["name"].clone())
+      .collect::<Vec<_>>(),
+    vec![json!("book.name"), json!("updateMask")]
+  );
+}
+
+#[test]
+fn component_schemas() {
+  let document = library_package().openapi("Library", "1.0.0");
+  let schemas = &document["components"]["schemas"];
+
+  assert_eq!(
+    schemas["library.v1.Book"],
+    json!({
+      "title": "Book",
+      "type": "object",
+      "properties": {
+        "name": { "type": "string" },
+        "title": { "type": "string", "minLength": 1, "maxLength": 100 },
+      },
+      "additionalProperties": false,
+    })
+  );
+  assert_eq!(
+    schemas["library.v1.GetBookRequest"]["required"],
+    json!(["name"])
+  );
+}
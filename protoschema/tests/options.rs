@@ -0,0 +1,91 @@
+use protoschema::{
+  errors::{SchemaError, TemplatingError},
+  message,
+  options::{proto_option, OptionValue},
+  string,
+  validators::string::StringValidator,
+  Package,
+};
+
+#[test]
+fn merges_validators_into_a_single_option() {
+  let field = string!("name", |v| v.min_len(3))
+    .add_option(StringValidator::builder().max_len(10).into())
+    .build();
+
+  assert_eq!(field.options.len(), 1);
+  assert_eq!(
+    *field.options[0].value,
+    OptionValue::Message(
+      vec![(
+        "string".into(),
+        OptionValue::Message(
+          vec![
+            ("min_len".into(), OptionValue::Uint(3)),
+            ("max_len".into(), OptionValue::Uint(10)),
+          ]
+          .into_boxed_slice()
+        )
+      )]
+      .into_boxed_slice()
+    )
+  );
+}
+
+#[test]
+fn deduplicates_identical_options() {
+  let validator = StringValidator::builder().min_len(3).build();
+  let field = string!("name")
+    .add_option(validator.clone().into())
+    .add_option(validator.into())
+    .build();
+
+  assert_eq!(field.options.len(), 1);
+}
+
+#[test]
+fn reports_conflicting_options() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  message!(
+    file.new_message("MyMessage"),
+    1 => string!("name", |v| v.min_len(3)).add_option(StringValidator::builder().min_len(5).into()),
+  );
+
+  assert_eq!(
+    package.check(),
+    vec![SchemaError::ConflictingOptions {
+      item: "mypkg.MyMessage.name".into(),
+      option: "(buf.validate.field)".into(),
+    }]
+  );
+}
+
+#[test]
+fn allows_declared_repeated_options() {
+  let package = Package::new("mypkg");
+  let file = package.new_file("myfile");
+
+  let tagged_field = || {
+    string!("name")
+      .add_option(proto_option("(mypkg.tag)", "a"))
+      .add_option(proto_option("(mypkg.tag)", "b"))
+  };
+
+  message!(file.new_message("MyMessage"), 1 => tagged_field());
+
+  let errors = package.check();
+
+  assert_eq!(
+    TemplatingError::InvalidSchema(errors).to_string(),
+    "The schema contains 1 error(s): mypkg.MyMessage.name: the option '(mypkg.tag)' is defined multiple times with conflicting values"
+  );
+
+  let package = Package::new("mypkg").allow_repeated_option("(mypkg.tag)");
+  let file = package.new_file("myfile");
+
+  message!(file.new_message("MyMessage"), 1 => tagged_field());
+
+  assert_eq!(package.check(), vec![]);
+}
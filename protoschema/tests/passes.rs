@@ -0,0 +1,261 @@
+use protoschema::{
+  common::{debug_redact, deprecated},
+  derivation::Derivation,
+  message,
+  passes::{AddOptions, Deprecate, Node, NodeKind, Pass, Pipeline, RequireRules},
+  proto_enum, proto_option, services, string, uint64, FieldType, OptionValue, Package,
+};
+
+fn policy_package(package: Package) -> Package {
+  let file = package.new_file("user");
+
+  let user_msg = message!(
+    file.new_message("User"),
+    1 => uint64!("id"),
+    2 => string!("email", |v| v.email()),
+    3 => string!("password"),
+
+    oneof "secret" {
+      4 => string!("api_password"),
+      5 => string!("token"),
+    }
+  );
+
+  proto_enum!(file.new_enum("Status"), 0 => "UNSPECIFIED", 1 => "ACTIVE");
+
+  services!(
+    file,
+    UserService {
+      GetUser(user_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+#[test]
+fn field_options() {
+  let package = policy_package(Package::new("myapp.v1").with_pass(AddOptions::new(
+    |node| matches!(node.kind, NodeKind::Field { .. }) && node.name_matches("*password*"),
+    [debug_redact()],
+  )));
+
+  let schema = package.run_passes();
+  let user_msg = schema.find_message("User").unwrap();
+
+  let redacted: Vec<&str> = user_msg
+    .fields
+    .iter()
+    .filter(|field| field.option("debug_redact").is_some())
+    .map(|field| field.name.as_ref())
+    .collect();
+
+  assert_eq!(redacted, vec!["password", "api_password"]);
+
+  // The passes run again when the templates are built, without duplicating the options
+  let templates = package.build_templates();
+  let user_template = &templates[0].messages[0];
+  let (_, password_field) = user_template
+    .fields
+    .iter()
+    .find(|(_, field)| field.name.as_ref() == "password")
+    .unwrap();
+
+  assert_eq!(*password_field.options, [debug_redact()]);
+}
+
+#[test]
+fn required_rules() {
+  let package = policy_package(
+    Package::new("myapp.v1").with_pass(RequireRules::new(|field| {
+      field.field_type() == Some(&FieldType::String)
+    })),
+  );
+
+  let errors: Vec<String> = package.check().iter().map(|e| e.to_string()).collect();
+
+  assert_eq!(
+    errors,
+    vec![
+      "myapp.v1.User.password: the field does not have any validation rules",
+      "myapp.v1.User.api_password: the field does not have any validation rules",
+      "myapp.v1.User.token: the field does not have any validation rules",
+    ]
+  );
+}
+
+#[test]
+fn deprecation() {
+  let package = policy_package(Package::new("myapp.v1").with_pass(Deprecate));
+
+  let schema = package.run_passes();
+  let is_deprecated = |options: &[protoschema::ProtoOption]| options.contains(&deprecated());
+
+  assert!(is_deprecated(&schema.files[0].options));
+
+  let user_msg = schema.find_message("User").unwrap();
+
+  assert!(is_deprecated(&user_msg.options));
+  assert!(user_msg
+    .fields
+    .iter()
+    .all(|field| is_deprecated(&field.options)));
+
+  assert!(is_deprecated(&schema.find_enum("Status").unwrap().options));
+
+  let user_service = schema.find_service("UserService").unwrap();
+
+  assert!(is_deprecated(&user_service.options));
+  assert!(is_deprecated(&user_service.handlers[0].options));
+}
+
+// Records the items that it visits, and marks the enum variants with a custom option
+#[derive(Default)]
+struct Recorder {
+  visited: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl Pass for Recorder {
+  fn visit_variant(&mut self, variant: &mut Node) {
+    self
+      .visited
+      .borrow_mut()
+      .push(variant.full_name.to_string());
+    variant.add_options([proto_option("(myapp.v1.label)", "status")]);
+    variant.add_import("myapp/v1/labels.proto");
+  }
+
+  fn visit_handler(&mut self, handler: &mut Node) {
+    if let NodeKind::Handler { request, response } = handler.kind {
+      self
+        .visited
+        .borrow_mut()
+        .push(format!("{}({request} => {response})", handler.full_name));
+    }
+  }
+}
+
+#[test]
+fn pipelines() {
+  let recorder = Recorder::default();
+  let visited = recorder.visited.clone();
+
+  let package = policy_package(
+    Package::new("myapp.v1").with_pass(Pipeline::new().pass(recorder).pass(Deprecate)),
+  );
+
+  let schema = package.run_passes();
+
+  assert_eq!(
+    *visited.borrow(),
+    vec![
+      "myapp.v1.Status.STATUS_UNSPECIFIED",
+      "myapp.v1.Status.STATUS_ACTIVE",
+      "myapp.v1.UserService.GetUser(myapp.v1.User => myapp.v1.User)",
+    ]
+  );
+
+  let status_enum = schema.find_enum("Status").unwrap();
+
+  assert!(schema.files[0]
+    .imports
+    .contains(&"myapp/v1/labels.proto".into()));
+  assert_eq!(status_enum.variants.len(), 2);
+  assert_eq!(
+    *package.build_templates()[0].enums[0].variants[1].1.options,
+    [proto_option("(myapp.v1.label)", "status"), deprecated()]
+  );
+}
+
+#[test]
+fn items_added_later() {
+  let recorder = Recorder::default();
+  let visited = recorder.visited.clone();
+
+  let package = policy_package(Package::new("myapp.v1").with_pass(recorder).with_pass(
+    RequireRules::new(|field| field.field_type() == Some(&FieldType::String)),
+  ));
+
+  assert_eq!(package.check().len(), 3);
+
+  let file = package.new_file("post");
+
+  message!(file.new_message("Post"), 1 => string!("title"));
+
+  let errors: Vec<String> = package.check().iter().map(|e| e.to_string()).collect();
+
+  assert_eq!(errors.len(), 4);
+  assert_eq!(
+    errors[3],
+    "myapp.v1.Post.title: the field does not have any validation rules"
+  );
+
+  // The items of the package are not modified, so the passes do not add their options twice
+  package.build_templates();
+
+  assert_eq!(visited.borrow().len(), 9);
+  assert_eq!(
+    *package.build_templates()[0].enums[0].variants[1].1.options,
+    [proto_option("(myapp.v1.label)", "status")]
+  );
+}
+
+#[test]
+fn passes_run_once_per_render() {
+  let recorder = Recorder::default();
+  let visited = recorder.visited.clone();
+
+  let package = policy_package(Package::new("myapp.v1").with_pass(recorder));
+
+  package
+    .render_templates(&std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("passes"))
+    .unwrap();
+
+  assert_eq!(visited.borrow().len(), 3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn evaluation() {
+  use protoschema::validators::string::StringValidator;
+
+  let package = Package::new("myapp.v1").with_pass(AddOptions::new(
+    |node| matches!(node.kind, NodeKind::Field { .. }),
+    [StringValidator::builder().min_len(3).into()],
+  ));
+  let file = package.new_file("user");
+
+  let user_msg = message!(file.new_message("User"), 1 => string!("name"));
+  let instance = serde_json::json!({ "name": "ab" });
+
+  // The rules added by the passes are evaluated both from the package and from the builder of the message
+  let violations = package.evaluate("User", &instance).unwrap();
+
+  assert_eq!(violations.len(), 1);
+  assert_eq!(user_msg.evaluate(&instance).unwrap(), violations);
+  assert_eq!(user_msg.examples().unwrap().invalid.len(), 1);
+}
+
+#[test]
+fn derived_messages() {
+  let package = Package::new("myapp.v1").with_pass(Deprecate);
+  let file = package.new_file("user");
+
+  let user_msg = message!(file.new_message("User"), 1 => uint64!("id"), 2 => string!("name"));
+
+  file
+    .new_message("UserSummary")
+    .derive_from(&user_msg, Derivation::new().pick(["id"]));
+
+  // The derived messages stay in sync with their source, since the passes are applied to both
+  assert!(package.check().is_empty());
+  assert_eq!(
+    package
+      .run_passes()
+      .find_message("UserSummary")
+      .unwrap()
+      .fields[0]
+      .option("deprecated"),
+    Some(&OptionValue::Bool(true))
+  );
+}
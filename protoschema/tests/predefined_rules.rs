@@ -0,0 +1,83 @@
+use protoschema::{
+  bool, extensions::ExtensionKind, message, options::OptionValue, predefined_rule, string, uint32,
+  Package,
+};
+
+#[test]
+fn predefined_rules() {
+  let package = Package::new("mypkg");
+  let rules_file = package.new_file("rules");
+
+  let slug = predefined_rule!(
+    rules_file,
+    StringRules,
+    1161 => bool!("slug"),
+    cel = [
+      {
+        id = "string.slug",
+        msg = "value must be a slug",
+        expr = "!rule || this.matches('^[a-z0-9]+(?:-[a-z0-9]+)*$')"
+      }
+    ]
+  );
+
+  assert_eq!(slug.kind, ExtensionKind::StringRules);
+  assert_eq!(slug.name.as_ref(), "mypkg.slug");
+
+  let file = package.new_file("myfile");
+
+  let msg = message!(
+    file.new_message("Post"),
+    1 => string!("url_slug", |v| v.min_len(1).predefined(&slug, true)),
+  );
+
+  let data = msg.get_data();
+  let (_, field) = &data.fields[0];
+
+  assert_eq!(
+    *field.options[0].value,
+    OptionValue::Message(
+      vec![(
+        "string".into(),
+        OptionValue::Message(
+          vec![
+            ("min_len".into(), OptionValue::Uint(1)),
+            ("[mypkg.slug]".into(), OptionValue::Bool(true)),
+          ]
+          .into_boxed_slice()
+        )
+      )]
+      .into_boxed_slice()
+    )
+  );
+
+  assert!(file
+    .get_data()
+    .imports
+    .iter()
+    .any(|i| i.as_ref() == "mypkg/rules.proto"));
+}
+
+#[test]
+#[should_panic(
+  expected = "The predefined rule 'mypkg.slug' extends buf.validate.StringRules and cannot be used with buf.validate.UInt32Rules"
+)]
+fn predefined_rules_type_check() {
+  let package = Package::new("mypkg");
+  let rules_file = package.new_file("rules");
+
+  let slug = predefined_rule!(
+    rules_file,
+    StringRules,
+    1161 => bool!("slug"),
+    cel = [
+      {
+        id = "string.slug",
+        msg = "value must be a slug",
+        expr = "!rule || this.matches('^[a-z0-9]+(?:-[a-z0-9]+)*$')"
+      }
+    ]
+  );
+
+  let _ = uint32!("id", |v| v.predefined(&slug, true));
+}
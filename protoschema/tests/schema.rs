@@ -0,0 +1,235 @@
+use protoschema::{
+  map, message, msg_field,
+  schema::{Item, OneofRuleInfo, Usage},
+  services, string, timestamp, uint32,
+  validators::cel::CelRule,
+  FieldType, OptionValue, Package,
+};
+
+fn schema_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+  let services_file = package.new_file("services");
+
+  let address_msg = message!(
+    file.new_message("Address"),
+    1 => string!("city", |v| v.min_len(2)),
+  );
+
+  let user_msg = file.new_message("User");
+
+  let user_msg = message!(
+    user_msg,
+    cel = [
+      {
+        id = "passwords_match",
+        msg = "the passwords do not match",
+        expr = "this.password == this.repeated_password"
+      }
+    ],
+    oneof_rule = [ "password", "token" ],
+
+    1 => string!("email", |v| v.required().email()),
+    2 => uint32!("age", |v| v.gt(17)),
+    3 => map!("labels", <string, string>, |m, _k, _v| m.max_pairs(2)),
+    4 => timestamp!("created_at"),
+    5 => string!("password"),
+    6 => string!("repeated_password"),
+    7 => string!("token"),
+
+    enum "status" {
+      0 => "UNSPECIFIED",
+      1 => "ACTIVE",
+    }
+
+    oneof "contact" {
+      10 => string!("phone"),
+      11 => msg_field!(address_msg, "home"),
+    }
+  );
+
+  services!(
+    services_file,
+    UserService {
+      GetUser(address_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+#[test]
+fn items() {
+  let package = schema_package();
+  let schema = package.schema();
+
+  assert_eq!(schema.package.as_ref(), "myapp.v1");
+  assert_eq!(schema.files.len(), 2);
+
+  let user_file = schema.find_file("myapp/v1/user.proto").unwrap();
+
+  assert_eq!(
+    user_file.messages,
+    vec!["myapp.v1.Address".into(), "myapp.v1.User".into()]
+  );
+  assert!(user_file
+    .imports
+    .contains(&"google/protobuf/timestamp.proto".into()));
+
+  let user_msg = schema.find_message("myapp.v1.User").unwrap();
+
+  assert_eq!(
+    user_msg
+      .fields
+      .iter()
+      .map(|field| (field.tag, field.name.as_ref(), field.oneof.as_deref()))
+      .collect::<Vec<_>>(),
+    vec![
+      (1, "email", None),
+      (2, "age", None),
+      (3, "labels", None),
+      (4, "created_at", None),
+      (5, "password", None),
+      (6, "repeated_password", None),
+      (7, "token", None),
+      (10, "phone", Some("contact")),
+      (11, "home", Some("contact")),
+    ]
+  );
+
+  let status_enum = schema.find_enum("User.status").unwrap();
+
+  assert_eq!(status_enum.parent.as_deref(), Some("myapp.v1.User"));
+  assert_eq!(
+    status_enum.variants,
+    vec![
+      (0, "STATUS_UNSPECIFIED".into()),
+      (1, "STATUS_ACTIVE".into())
+    ]
+  );
+  assert_eq!(user_msg.enums, vec!["myapp.v1.User.status".into()]);
+
+  let user_service = schema.find_service("UserService").unwrap();
+
+  assert_eq!(user_service.file.as_ref(), "myapp/v1/services.proto");
+  assert_eq!(
+    user_service.handlers[0]
+      .request
+      .full_name_with_package
+      .as_ref(),
+    "myapp.v1.Address"
+  );
+
+  assert!(matches!(schema.find("User"), Some(Item::Message(_))));
+  assert!(matches!(schema.find("User.status"), Some(Item::Enum(_))));
+  assert!(matches!(schema.find("UserService"), Some(Item::Service(_))));
+  assert!(schema.find("Unknown").is_none());
+}
+
+#[test]
+fn field_types() {
+  let schema = schema_package().schema();
+  let user_msg = schema.find_message("User").unwrap();
+
+  let type_names: Vec<&str> = user_msg
+    .fields
+    .iter()
+    .map(|field| field.type_name.as_ref())
+    .collect();
+
+  assert_eq!(
+    type_names[..4],
+    [
+      "string",
+      "uint32",
+      "map<string, string>",
+      "google.protobuf.Timestamp"
+    ][..]
+  );
+
+  let home_field = user_msg.field("home").unwrap();
+
+  assert_eq!(home_field.type_name.as_ref(), "myapp.v1.Address");
+  assert!(matches!(home_field.field_type, FieldType::Message(_)));
+}
+
+#[test]
+fn usages() {
+  let schema = schema_package().schema();
+
+  assert_eq!(
+    schema.usages("Address"),
+    vec![
+      Usage::Field {
+        message: "myapp.v1.User".into(),
+        field: "home".into(),
+      },
+      Usage::Request {
+        service: "myapp.v1.UserService".into(),
+        handler: "GetUser".into(),
+      },
+    ]
+  );
+  assert_eq!(
+    schema.usages("myapp.v1.User"),
+    vec![Usage::Response {
+      service: "myapp.v1.UserService".into(),
+      handler: "GetUser".into(),
+    }]
+  );
+}
+
+#[test]
+fn validation_rules() {
+  let schema = schema_package().schema();
+  let user_msg = schema.find_message("User").unwrap();
+
+  let email_rules = user_msg.field("email").unwrap().rules().unwrap();
+  let email_type_rules = email_rules.type_rules.unwrap();
+
+  assert!(email_rules.required);
+  assert_eq!(email_type_rules.kind.as_ref(), "string");
+  assert_eq!(
+    email_type_rules.rules,
+    vec![("email".into(), OptionValue::Bool(true))]
+  );
+
+  let age_rules = user_msg.field("age").unwrap().rules().unwrap();
+
+  assert!(!age_rules.required);
+  assert_eq!(
+    age_rules.type_rules.unwrap().rules,
+    vec![("gt".into(), OptionValue::Uint(17))]
+  );
+
+  assert!(user_msg.field("created_at").unwrap().rules().is_none());
+
+  let message_rules = user_msg.rules().unwrap();
+
+  assert_eq!(
+    message_rules.cel,
+    vec![CelRule {
+      id: "passwords_match".into(),
+      message: "the passwords do not match".into(),
+      expression: "this.password == this.repeated_password".into(),
+    }]
+  );
+  assert_eq!(
+    message_rules.oneof,
+    vec![OneofRuleInfo {
+      fields: vec!["password".into(), "token".into()],
+      required: false,
+    }]
+  );
+}
+
+#[test]
+fn snapshots() {
+  let package = schema_package();
+  let schema = package.schema();
+
+  package.new_file("other").new_message("Other");
+
+  assert!(schema.find_message("Other").is_none());
+  assert!(package.schema().find_message("Other").is_some());
+}
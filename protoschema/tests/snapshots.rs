@@ -0,0 +1,156 @@
+#![cfg(feature = "serde")]
+
+use askama::Template;
+use protoschema::{
+  errors::SnapshotError, extension, message, msg_field, proto_enum, proto_option, schema::Schema,
+  services, string, timestamp, uint64, Package,
+};
+
+fn snapshot_package() -> Package {
+  let package = Package::new("myapp.v1");
+  let file = package.new_file("user");
+  let services_file = package.new_file("services");
+
+  extension!(file, FieldOptions {
+    5000 => string!("label"),
+  });
+
+  let user_msg = file
+    .new_message("User")
+    .add_options([proto_option("deprecated", true)]);
+
+  let user_msg = message!(
+    user_msg,
+    cel = [
+      {
+        id = "names",
+        msg = "the name must not be the email",
+        expr = "this.name != this.email"
+      }
+    ],
+    reserved = [ 20, 21 ],
+
+    1 => uint64!("id"),
+    2 => string!("name", |v| v.min_len(1).max_len(50)),
+    3 => string!("email", |v| v.required().email()),
+    4 => timestamp!("created_at"),
+
+    enum "status" {
+      0 => "UNSPECIFIED",
+      1 => "ACTIVE",
+    }
+
+    oneof "contact" {
+      10 => string!("phone"),
+      11 => string!("fax"),
+    }
+  );
+
+  message!(user_msg.new_message("Address"), 1 => string!("city"));
+
+  proto_enum!(
+    file.new_enum("Role"),
+    reserved = [ 5..7 ]
+    0 => "UNSPECIFIED",
+    1 => "ADMIN" { [proto_option("deprecated", true)] },
+  );
+
+  let request_msg = message!(services_file.new_message("GetUserRequest"), 1 => uint64!("id"));
+
+  message!(
+    services_file.new_message("GetUserResponse"),
+    1 => msg_field!(user_msg, "user"),
+  );
+
+  services!(
+    services_file,
+    UserService {
+      GetUser(request_msg => user_msg),
+    };
+  );
+
+  package
+}
+
+fn render(package: &Package) -> Vec<String> {
+  package
+    .build_templates()
+    .iter()
+    .map(|template| template.render().unwrap())
+    .collect()
+}
+
+#[test]
+fn round_trips() {
+  let package = snapshot_package();
+
+  let json = serde_json::to_string_pretty(&package.schema()).unwrap();
+  let schema: Schema = serde_json::from_str(&json).unwrap();
+
+  let rebuilt = Package::from_schema(&schema).unwrap();
+
+  assert_eq!(render(&rebuilt), render(&package));
+  assert_eq!(
+    serde_json::to_string_pretty(&rebuilt.schema()).unwrap(),
+    json
+  );
+
+  // The rebuilt package can be extended like any other package
+  let file = rebuilt.new_file("extra");
+  message!(file.new_message("Extra"), 1 => string!("note"));
+
+  assert!(rebuilt.schema().find_message("Extra").is_some());
+}
+
+#[test]
+fn handlers_with_imported_types() {
+  let package = Package::new("library.v1");
+  let file = package.new_file("library");
+
+  let book_msg = message!(file.new_message("Book"), 1 => string!("name"));
+
+  // The Delete method returns google.protobuf.Empty
+  file.resource_service(&book_msg, "books/{book}");
+
+  let rebuilt = Package::from_schema(&package.schema()).unwrap();
+
+  assert_eq!(render(&rebuilt), render(&package));
+}
+
+#[test]
+fn snapshot_format() {
+  let schema = snapshot_package().schema();
+  let value = serde_json::to_value(&schema).unwrap();
+
+  let email_field = &value["messages"][0]["fields"][2];
+
+  assert_eq!(email_field["name"], "email");
+  assert_eq!(email_field["field_type"], "String");
+  assert_eq!(email_field["type_name"], "string");
+  assert_eq!(
+    email_field["options"][0]["value"],
+    serde_json::json!({ "required": true, "string": { "email": true } })
+  );
+}
+
+#[test]
+fn invalid_snapshots() {
+  let mut schema = snapshot_package().schema();
+  schema.files[0].messages.push("myapp.v1.Unknown".into());
+
+  assert_eq!(
+    Package::from_schema(&schema).err(),
+    Some(SnapshotError::UnknownItem {
+      item: "myapp/v1/user.proto".into(),
+      name: "myapp.v1.Unknown".into(),
+    })
+  );
+
+  let mut schema = snapshot_package().schema();
+  schema.messages.last_mut().unwrap().full_name = "otherpkg.GetUserResponse".into();
+
+  assert_eq!(
+    Package::from_schema(&schema).err().unwrap().to_string(),
+    "otherpkg.GetUserResponse: the item does not belong to the package 'myapp.v1'"
+  );
+}